@@ -1,15 +1,28 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::fs;
 use std::io::{self, IsTerminal};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
 use console::style;
 use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
 
-use crate::clear_store::{self, ClearManifest};
-use crate::conan::{self, ConanProvider};
-use crate::files;
+use crate::audit;
+use crate::clear_store::{self, ClearManifest, InstallEntry};
+use crate::conan::ConanProvider;
+use crate::elf;
+use crate::files::{self, BuildSystem};
+use crate::licenses;
 use crate::mode::{self, ProjectMode};
-use crate::model::{ConanRef, PackageCppInfo, ProjectMetadata};
+use crate::model::{
+    self, AppliedOverride, ConanRef, HostTool, Linkage, LockedBinary, LockedPackage,
+    PackageCppInfo, PackageLicense, ProjectMetadata, ResolvedGraph, SpecConfig, TargetAssignment,
+    VendoredPackage,
+};
+use crate::policy::{self, ForcedVersion, ResolutionPolicy};
+use crate::recipe::{self, RecipeProfile};
+use crate::sbom::{self, SbomFormat, SbomSubject};
+use crate::validate;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CliCommand {
@@ -18,10 +31,20 @@ pub enum CliCommand {
     Add {
         dependency: String,
         version: Option<String>,
+        targets: Vec<TargetAssignment>,
+        /// Линковка для clear-режима; `None` оставляет сохранённую.
+        linkage: Option<Linkage>,
+        /// Ставить как инструмент сборки под архитектуру хоста (clear-режим).
+        tool: bool,
     },
     Remove {
         dependency: String,
     },
+    /// `version: None` снимает переопределение.
+    Override {
+        package: String,
+        version: Option<String>,
+    },
     Search {
         dependency: String,
     },
@@ -33,18 +56,68 @@ pub enum CliCommand {
         dependency: String,
         version: String,
     },
+    Validate {
+        allowed_libs: Option<PathBuf>,
+    },
+    Licenses,
+    Waive {
+        package: String,
+        reason: String,
+    },
+    Unwaive {
+        package: String,
+    },
+    Sbom {
+        format: SbomFormat,
+        output: Option<PathBuf>,
+    },
+    Audit {
+        database: Option<PathBuf>,
+    },
 }
 
-pub fn run(provider: &dyn ConanProvider, project_root: &Path, command: CliCommand) -> Result<()> {
+/// Выполняет команду; `specs` — явно выбранные через `--spec` файлы.
+pub fn run(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    specs: &[PathBuf],
+    command: CliCommand,
+) -> Result<()> {
     match command {
-        CliCommand::Init => init_conan_mode(project_root)?,
-        CliCommand::InitClear => init_clear_mode(project_root)?,
+        CliCommand::Init => init_conan_mode(project_root, specs)?,
+        CliCommand::InitClear => init_clear_mode(project_root, specs)?,
         CliCommand::Add {
             dependency,
             version,
-        } => add_dependency(provider, project_root, &dependency, version.as_deref())?,
+            targets,
+            linkage,
+            tool,
+        } => {
+            if tool {
+                add_tool(
+                    provider,
+                    project_root,
+                    specs,
+                    &dependency,
+                    version.as_deref(),
+                )?
+            } else {
+                add_dependency(
+                    provider,
+                    project_root,
+                    specs,
+                    &dependency,
+                    version.as_deref(),
+                    &targets,
+                    linkage,
+                )?
+            }
+        }
         CliCommand::Remove { dependency } => {
-            remove_dependency(provider, project_root, &dependency)?
+            remove_dependency(provider, project_root, specs, &dependency)?
+        }
+        CliCommand::Override { package, version } => {
+            set_override(provider, project_root, specs, &package, version.as_deref())?
         }
         CliCommand::Search { dependency } => {
             let matches = provider.search_dependencies(&dependency)?;
@@ -66,35 +139,61 @@ pub fn run(provider: &dyn ConanProvider, project_root: &Path, command: CliComman
             dependency,
             version,
         } => {
-            let dependencies =
-                provider.resolve_dependencies_without_conan(&dependency, &version)?;
-            for reference in dependencies {
-                println!("{}", reference.to_ref_string());
+            let policy = policy::load_org_policy(project_root)?;
+            let resolved =
+                provider.resolve_dependencies_without_conan(&dependency, &version, &policy)?;
+            for reference in resolved.packages {
+                match resolved
+                    .overrides
+                    .iter()
+                    .find(|item| item.name == reference.name)
+                {
+                    Some(item) => {
+                        println!("{}\t{}", reference.to_ref_string(), describe_override(item))
+                    }
+                    None => println!("{}", reference.to_ref_string()),
+                }
             }
         }
+        CliCommand::Validate { allowed_libs } => {
+            validate_project(project_root, specs, allowed_libs.as_deref())?
+        }
+        CliCommand::Licenses => list_licenses(provider, project_root)?,
+        CliCommand::Waive { package, reason } => {
+            set_license_waiver(project_root, &package, Some(reason))?
+        }
+        CliCommand::Unwaive { package } => set_license_waiver(project_root, &package, None)?,
+        CliCommand::Sbom { format, output } => {
+            export_sbom(provider, project_root, specs, format, output.as_deref())?
+        }
+        CliCommand::Audit { database } => audit_project(provider, project_root, database)?,
     }
 
     Ok(())
 }
 
-fn init_conan_mode(project_root: &Path) -> Result<()> {
-    ensure_project_files_exist(project_root)?;
+fn init_conan_mode(project_root: &Path, explicit_specs: &[PathBuf]) -> Result<()> {
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
+    check_spec_layouts(project_root, &specs, ProjectMode::Conan)?;
     mode::save_mode(project_root, ProjectMode::Conan)?;
-    files::write_conanfile(project_root, &[])?;
-    apply_conan_changes(
+    save_explicit_specs(project_root, explicit_specs, &specs)?;
+    files::write_conanfile(
         project_root,
-        &ProjectMetadata {
-            direct_pkg_modules: Vec::new(),
-            shared_lib_patterns: Vec::new(),
-            system_libs: Vec::new(),
-        },
+        &[],
+        &[],
+        &[],
+        &BTreeMap::new(),
+        &BTreeMap::new(),
     )?;
+    apply_conan_changes(project_root, &specs, &ProjectMetadata::default())?;
     Ok(())
 }
 
-fn init_clear_mode(project_root: &Path) -> Result<()> {
-    ensure_project_files_exist(project_root)?;
+fn init_clear_mode(project_root: &Path, explicit_specs: &[PathBuf]) -> Result<()> {
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
+    check_spec_layouts(project_root, &specs, ProjectMode::Clear)?;
     mode::save_mode(project_root, ProjectMode::Clear)?;
+    save_explicit_specs(project_root, explicit_specs, &specs)?;
     clear_store::ensure_layout(project_root)?;
     clear_store::save_manifest(project_root, &ClearManifest::default())?;
 
@@ -104,32 +203,131 @@ fn init_clear_mode(project_root: &Path) -> Result<()> {
             .with_context(|| format!("Не удалось удалить {}", conanfile.display()))?;
     }
 
-    apply_clear_changes(
-        project_root,
-        &ProjectMetadata {
-            direct_pkg_modules: Vec::new(),
-            shared_lib_patterns: Vec::new(),
-            system_libs: Vec::new(),
-        },
-    )?;
+    apply_clear_changes(project_root, &specs, &ProjectMetadata::default())?;
     Ok(())
 }
 
+/// `init --spec` задаёт список .spec проекта; подмножества пакетов для тех же путей сохраняются.
+fn save_explicit_specs(
+    project_root: &Path,
+    explicit_specs: &[PathBuf],
+    specs: &[SpecConfig],
+) -> Result<()> {
+    if explicit_specs.is_empty() {
+        return Ok(());
+    }
+    mode::save_specs(project_root, specs)
+}
+
+/// Настройки .spec после `add --spec`: пакет записывается в `packages` указанных .spec.
+/// .spec с пустым списком и так упаковывают все зависимости и не меняются.
+fn specs_with_package(
+    project_root: &Path,
+    explicit_specs: &[PathBuf],
+    package: &str,
+) -> Result<Vec<SpecConfig>> {
+    let mut configured = mode::load_specs(project_root)?;
+    let explicit = files::resolve_specs(project_root, explicit_specs, &configured)?;
+    if configured.is_empty() {
+        // Без настроек проект обновлял единственный .spec: он и дальше получает все зависимости
+        let others: Vec<PathBuf> = files::list_spec_files(project_root)?
+            .into_iter()
+            .map(|path| {
+                path.strip_prefix(project_root)
+                    .unwrap_or(&path)
+                    .to_path_buf()
+            })
+            .filter(|path| !explicit.iter().any(|spec| &spec.path == path))
+            .collect();
+        match others.as_slice() {
+            [] => {}
+            [single] => configured.push(SpecConfig {
+                path: single.clone(),
+                packages: Vec::new(),
+            }),
+            _ => {
+                return Err(anyhow!(
+                    "Найдено несколько .spec файлов в rpm/. Перечислите их в specs файла {} перед add --spec",
+                    mode::mode_path(project_root).display()
+                ));
+            }
+        }
+    }
+
+    for spec in explicit {
+        match configured.iter_mut().find(|item| item.path == spec.path) {
+            Some(existing) => {
+                if !existing.packages.is_empty()
+                    && !existing.packages.iter().any(|name| name == package)
+                {
+                    existing.packages.push(package.to_string());
+                }
+            }
+            None => configured.push(SpecConfig {
+                path: spec.path,
+                packages: vec![package.to_string()],
+            }),
+        }
+    }
+    Ok(configured)
+}
+
+/// Убирает удалённый пакет из `packages` настроенных .spec. .spec без оставшихся пакетов
+/// убирается из настроек: пустой список означал бы все зависимости.
+fn forget_spec_package(project_root: &Path, package: &str) -> Result<()> {
+    let configured = mode::load_specs(project_root)?;
+    if !configured
+        .iter()
+        .any(|spec| spec.packages.iter().any(|name| name == package))
+    {
+        return Ok(());
+    }
+
+    let remaining: Vec<SpecConfig> = configured
+        .into_iter()
+        .filter_map(|mut spec| {
+            if spec.packages.is_empty() {
+                return Some(spec);
+            }
+            spec.packages.retain(|name| name != package);
+            (!spec.packages.is_empty()).then_some(spec)
+        })
+        .collect();
+    mode::save_specs(project_root, &remaining)
+}
+
 fn add_dependency(
     provider: &dyn ConanProvider,
     project_root: &Path,
+    explicit_specs: &[PathBuf],
     dependency: &str,
     version: Option<&str>,
+    targets: &[TargetAssignment],
+    linkage: Option<Linkage>,
 ) -> Result<()> {
     let progress = create_progress_bar(4, format!("add {}{}", dependency, version_suffix(version)));
     progress_step(&progress, "Validating project structure");
-    ensure_project_files_exist(project_root)?;
+    let mut specs = ensure_project_files_exist(project_root, explicit_specs)?;
 
     progress_step(&progress, "Detecting project mode");
     let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    check_spec_layouts(project_root, &specs, mode)?;
+    if linkage.is_some() && mode == ProjectMode::Conan {
+        return Err(anyhow!(
+            "--linkage поддерживается только в clear-режиме; в conan-режиме задайте опцию shared в conanfile.py"
+        ));
+    }
 
     progress_step(&progress, "Resolving dependency version");
     let resolved = provider.resolve_direct_dependency(project_root, dependency, version)?;
+    let recorded_specs = if explicit_specs.is_empty() {
+        None
+    } else {
+        // Новый пакет попадает и в .spec без списка пакетов, поэтому обновляем все настроенные
+        let configured = specs_with_package(project_root, explicit_specs, &resolved.name)?;
+        specs = files::resolve_specs(project_root, &[], &configured)?;
+        Some(configured)
+    };
     log_info(
         Some(&progress),
         &format!(
@@ -143,23 +341,64 @@ fn add_dependency(
     match mode {
         ProjectMode::Conan => {
             let mut current = files::read_requires(project_root)?;
+            let mut assignments = files::read_targets(project_root)?;
+            if !targets.is_empty() {
+                assignments.insert(resolved.name.clone(), targets.to_vec());
+            }
             upsert_reference(&mut current, resolved);
-            files::write_conanfile(project_root, &current)?;
-            let metadata = provider.resolve_project_metadata(project_root, &current)?;
-            apply_conan_changes(project_root, &metadata)?;
-        }
-        ProjectMode::Clear => {
-            let mut manifest = clear_store::load_manifest(project_root)?;
-            upsert_reference(&mut manifest.direct_requires, resolved);
-            clear_store::save_manifest(project_root, &manifest)?;
-            sync_clear_mode(
+            let overrides = files::read_overrides(project_root)?;
+            let waivers = files::read_license_waivers(project_root)?;
+            let policy = resolution_policy(project_root, mode, &overrides)?;
+            // conanfile.py пишем только после того, как граф собрался по политике
+            let resolved = build_full_dependency_set(provider, &current, &policy)?;
+            log_overrides(&resolved.overrides, Some(&progress));
+            enforce_license_policy(
                 provider,
                 project_root,
-                &manifest.direct_requires,
+                &current,
+                &policy,
+                &waivers,
                 Some(&progress),
             )?;
+            let mut metadata =
+                provider.resolve_project_metadata(project_root, &current, &policy)?;
+            files::write_conanfile(
+                project_root,
+                &current,
+                &overrides,
+                &policy_overrides(&policy),
+                &assignments,
+                &waivers,
+            )?;
+            metadata.target_assignments = assignments;
+            apply_conan_changes(project_root, &specs, &metadata)?;
+        }
+        ProjectMode::Clear => {
+            let mut manifest = clear_store::load_manifest(project_root)?;
+            if !targets.is_empty() {
+                manifest
+                    .targets
+                    .insert(resolved.name.clone(), targets.to_vec());
+            }
+            match linkage {
+                Some(Linkage::Static) => {
+                    manifest
+                        .linkage
+                        .insert(resolved.name.clone(), Linkage::Static);
+                }
+                Some(Linkage::Shared) => {
+                    manifest.linkage.remove(&resolved.name);
+                }
+                None => {}
+            }
+            upsert_reference(&mut manifest.direct_requires, resolved);
+            sync_clear_mode(provider, project_root, &specs, &manifest, Some(&progress))?;
         }
     }
+    if let Some(configured) = recorded_specs {
+        mode::save_mode(project_root, mode)?;
+        mode::save_specs(project_root, &configured)?;
+    }
 
     progress.finish_with_message(format!(
         "{} add completed: {}{}",
@@ -171,17 +410,64 @@ fn add_dependency(
     Ok(())
 }
 
+/// Добавляет tool-пакет: его `bin/` под архитектуру хоста ставится в `thirdparty/aurora/host`.
+fn add_tool(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    explicit_specs: &[PathBuf],
+    dependency: &str,
+    version: Option<&str>,
+) -> Result<()> {
+    let progress = create_progress_bar(
+        4,
+        format!("add --tool {}{}", dependency, version_suffix(version)),
+    );
+    progress_step(&progress, "Validating project structure");
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
+
+    progress_step(&progress, "Detecting project mode");
+    let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    check_spec_layouts(project_root, &specs, mode)?;
+    if mode == ProjectMode::Conan {
+        return Err(anyhow!(
+            "--tool поддерживается только в clear-режиме; в conan-режиме используйте tool_requires в conanfile.py"
+        ));
+    }
+
+    progress_step(&progress, "Resolving tool version");
+    let resolved = provider.resolve_direct_dependency(project_root, dependency, version)?;
+    log_info(
+        Some(&progress),
+        &format!("Applying host tool {}", resolved.to_ref_string()),
+    );
+
+    progress_step(&progress, "Applying project changes");
+    let mut manifest = clear_store::load_manifest(project_root)?;
+    upsert_reference(&mut manifest.tools, resolved);
+    sync_clear_mode(provider, project_root, &specs, &manifest, Some(&progress))?;
+
+    progress.finish_with_message(format!(
+        "{} add completed: {} (tool)",
+        style("✔").green(),
+        dependency
+    ));
+    log_success(Some(&progress), "Tool added successfully");
+    Ok(())
+}
+
 fn remove_dependency(
     provider: &dyn ConanProvider,
     project_root: &Path,
+    explicit_specs: &[PathBuf],
     dependency: &str,
 ) -> Result<()> {
     let progress = create_progress_bar(4, format!("remove {}", dependency));
     progress_step(&progress, "Validating project structure");
-    ensure_project_files_exist(project_root)?;
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
 
     progress_step(&progress, "Detecting project mode");
     let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    check_spec_layouts(project_root, &specs, mode)?;
     log_info(
         Some(&progress),
         &format!(
@@ -204,40 +490,55 @@ fn remove_dependency(
                 ));
             }
 
-            files::write_conanfile(project_root, &current)?;
-            let metadata = if current.is_empty() {
-                ProjectMetadata {
-                    direct_pkg_modules: Vec::new(),
-                    shared_lib_patterns: Vec::new(),
-                    system_libs: Vec::new(),
-                }
+            let mut assignments = files::read_targets(project_root)?;
+            assignments.remove(dependency);
+            let overrides = files::read_overrides(project_root)?;
+            let waivers = files::read_license_waivers(project_root)?;
+            let policy = resolution_policy(project_root, mode, &overrides)?;
+            enforce_license_policy(
+                provider,
+                project_root,
+                &current,
+                &policy,
+                &waivers,
+                Some(&progress),
+            )?;
+            let mut metadata = if current.is_empty() {
+                ProjectMetadata::default()
             } else {
-                provider.resolve_project_metadata(project_root, &current)?
+                provider.resolve_project_metadata(project_root, &current, &policy)?
             };
-            apply_conan_changes(project_root, &metadata)?;
+            files::write_conanfile(
+                project_root,
+                &current,
+                &overrides,
+                &policy_overrides(&policy),
+                &assignments,
+                &waivers,
+            )?;
+            metadata.target_assignments = assignments;
+            apply_conan_changes(project_root, &specs, &metadata)?;
         }
         ProjectMode::Clear => {
             let mut manifest = clear_store::load_manifest(project_root)?;
-            let before = manifest.direct_requires.len();
+            let before = manifest.direct_requires.len() + manifest.tools.len();
             manifest
                 .direct_requires
                 .retain(|item| item.name != dependency);
-            if manifest.direct_requires.len() == before {
+            manifest.tools.retain(|item| item.name != dependency);
+            if manifest.direct_requires.len() + manifest.tools.len() == before {
                 return Err(anyhow!(
                     "Зависимость {} не найдена в thirdparty manifest",
                     dependency
                 ));
             }
 
-            clear_store::save_manifest(project_root, &manifest)?;
-            sync_clear_mode(
-                provider,
-                project_root,
-                &manifest.direct_requires,
-                Some(&progress),
-            )?;
+            manifest.targets.remove(dependency);
+            manifest.linkage.remove(dependency);
+            sync_clear_mode(provider, project_root, &specs, &manifest, Some(&progress))?;
         }
     }
+    forget_spec_package(project_root, dependency)?;
 
     progress_step(&progress, "Finalizing");
     progress.finish_with_message(format!(
@@ -249,29 +550,188 @@ fn remove_dependency(
     Ok(())
 }
 
-fn sync_clear_mode(
+/// Закрепляет версию пакета графа (`override=True` в conanfile.py или `overrides` в манифесте)
+/// и пересобирает проект; `version: None` снимает переопределение.
+fn set_override(
     provider: &dyn ConanProvider,
     project_root: &Path,
+    explicit_specs: &[PathBuf],
+    package: &str,
+    version: Option<&str>,
+) -> Result<()> {
+    let progress = create_progress_bar(
+        4,
+        format!("override {}{}", package, version_suffix(version)),
+    );
+    progress_step(&progress, "Validating project structure");
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
+
+    progress_step(&progress, "Detecting project mode");
+    let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    check_spec_layouts(project_root, &specs, mode)?;
+
+    progress_step(&progress, "Resolving override version");
+    let resolved = match version {
+        Some(version) => {
+            Some(provider.resolve_direct_dependency(project_root, package, Some(version))?)
+        }
+        None => None,
+    };
+
+    progress_step(&progress, "Applying project changes");
+    match mode {
+        ProjectMode::Conan => {
+            let current = files::read_requires(project_root)?;
+            let mut overrides = files::read_overrides(project_root)?;
+            update_overrides(&current, &mut overrides, package, resolved)?;
+            let policy = resolution_policy(project_root, mode, &overrides)?;
+            // Conan сам применяет override=True, граф строим, чтобы предупредить о нарушенных ограничениях
+            if !current.is_empty() {
+                let resolved = build_full_dependency_set(provider, &current, &policy)?;
+                log_overrides(&resolved.overrides, Some(&progress));
+            }
+            let waivers = files::read_license_waivers(project_root)?;
+            enforce_license_policy(
+                provider,
+                project_root,
+                &current,
+                &policy,
+                &waivers,
+                Some(&progress),
+            )?;
+            let assignments = files::read_targets(project_root)?;
+            let mut metadata = if current.is_empty() {
+                ProjectMetadata::default()
+            } else {
+                provider.resolve_project_metadata(project_root, &current, &policy)?
+            };
+            files::write_conanfile(
+                project_root,
+                &current,
+                &overrides,
+                &policy_overrides(&policy),
+                &assignments,
+                &waivers,
+            )?;
+            metadata.target_assignments = assignments;
+            apply_conan_changes(project_root, &specs, &metadata)?;
+        }
+        ProjectMode::Clear => {
+            let mut manifest = clear_store::load_manifest(project_root)?;
+            update_overrides(
+                &manifest.direct_requires,
+                &mut manifest.overrides,
+                package,
+                resolved,
+            )?;
+            // Манифест сохраняет синхронизация: переопределение, с которым граф не собрался,
+            // в него не попадает
+            sync_clear_mode(provider, project_root, &specs, &manifest, Some(&progress))?;
+        }
+    }
+
+    progress.finish_with_message(format!(
+        "{} override completed: {}{}",
+        style("✔").green(),
+        package,
+        version_suffix(version)
+    ));
+    log_success(Some(&progress), "Override applied successfully");
+    Ok(())
+}
+
+fn update_overrides(
     direct_refs: &[ConanRef],
+    overrides: &mut Vec<ConanRef>,
+    package: &str,
+    resolved: Option<ConanRef>,
+) -> Result<()> {
+    match resolved {
+        Some(reference) => {
+            if let Some(direct) = direct_refs.iter().find(|item| item.name == reference.name) {
+                return Err(anyhow!(
+                    "{} — прямая зависимость проекта ({}), её версию меняет add",
+                    direct.name,
+                    direct.to_ref_string()
+                ));
+            }
+            upsert_reference(overrides, reference);
+        }
+        None => {
+            let before = overrides.len();
+            overrides.retain(|item| item.name != package);
+            if overrides.len() == before {
+                return Err(anyhow!("Переопределение для {} не найдено", package));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sync_clear_mode(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    specs: &[SpecConfig],
+    manifest: &ClearManifest,
     main_progress: Option<&ProgressBar>,
 ) -> Result<()> {
+    let direct_refs = manifest.direct_requires.as_slice();
     log_info(main_progress, "Syncing clear package store");
     let (target_arches, strict_arch_mode) = clear_store::resolve_target_arches()?;
     log_info(
         main_progress,
         &format!("Target architectures: {}", target_arches.join(", ")),
     );
+
+    // Граф, рецепты и лицензионную политику проверяем до того, как трогать clear-store
+    let policy = resolution_policy(project_root, ProjectMode::Clear, &manifest.overrides)?;
+    let all_refs = if direct_refs.is_empty() {
+        Vec::new()
+    } else {
+        log_info(main_progress, "Building full dependency graph");
+        let resolved = build_full_dependency_set(provider, direct_refs, &policy)?;
+        log_overrides(&resolved.overrides, main_progress);
+        log_info(
+            main_progress,
+            &format!(
+                "Resolved package count (direct + transitive): {}",
+                resolved.packages.len()
+            ),
+        );
+        resolved.packages
+    };
+    let recipes = fetch_recipes(provider, &all_refs, main_progress);
+    let package_licenses = collect_package_licenses(&all_refs, &recipes);
+    check_license_policy(
+        provider,
+        project_root,
+        &manifest.license_waivers,
+        direct_refs,
+        &policy,
+        &package_licenses,
+        main_progress,
+    )?;
+
     for arch in &target_arches {
         clear_store::reset_arch_layout(project_root, arch)?;
     }
+    let host_tools = sync_host_tools(provider, project_root, manifest, main_progress)?;
 
     if direct_refs.is_empty() {
+        write_install_manifests(project_root, &target_arches, main_progress)?;
         apply_clear_changes(
             project_root,
+            specs,
             &ProjectMetadata {
-                direct_pkg_modules: Vec::new(),
-                shared_lib_patterns: Vec::new(),
-                system_libs: Vec::new(),
+                host_tools,
+                ..Default::default()
+            },
+        )?;
+        clear_store::save_manifest(
+            project_root,
+            &ClearManifest {
+                packages: Vec::new(),
+                ..manifest.clone()
             },
         )?;
         log_info(
@@ -281,18 +741,20 @@ fn sync_clear_mode(
         return Ok(());
     }
 
-    log_info(main_progress, "Building full dependency graph");
-    let all_refs = build_full_dependency_set(provider, direct_refs)?;
-    log_info(
-        main_progress,
-        &format!(
-            "Resolved package count (direct + transitive): {}",
-            all_refs.len()
-        ),
-    );
     let mut lib_patterns = Vec::new();
+    let mut bundled_sonames = BTreeSet::new();
+    let mut needed_by: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
     let mut all_system_libs = Vec::new();
+    let mut package_system_libs: BTreeMap<String, Vec<String>> = BTreeMap::new();
     let mut pkg_modules = Vec::new();
+    let mut package_modules: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut vendored_packages = Vec::new();
+    let mut soname_owners: BTreeMap<String, String> = BTreeMap::new();
+    let mut package_needed: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut package_lib_patterns: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut recipe_warnings = BTreeSet::new();
+    let mut requires_by_name = package_requires(provider, &all_refs, &recipes, &policy);
+    let mut locked_packages = Vec::new();
     let arch_ops_total = (all_refs.len() * target_arches.len()).max(1) as u64;
     let package_progress =
         create_progress_bar(arch_ops_total, "Downloading and extracting packages");
@@ -303,27 +765,29 @@ fn sync_clear_mode(
             &format!("Processing {}", reference.to_ref_string()),
         );
 
-        // Получаем cpp_info из conanfile.py
-        let cpp_info = match conan::fetch_cpp_info_from_artifactory(&reference.name, &reference.version) {
-            Ok(info) => info,
-            Err(_) => {
-                // Fallback к пустому cpp_info если не удалось получить
-                PackageCppInfo {
-                    package_name: reference.name.clone(),
-                    ..Default::default()
-                }
-            }
-        };
+        // conanfile.py разбираем отдельно для каждой архитектуры: условия зависят от профиля
+        let conanfile = recipes.get(&reference.name);
 
         let artifacts = provider.download_dependency_archives(
             &reference.name,
             &reference.version,
             project_root,
         )?;
+        let linkage = manifest
+            .linkage
+            .get(&reference.name)
+            .copied()
+            .unwrap_or_default();
         let mut installed_any = false;
+        let mut vendored_modules: Vec<String> = Vec::new();
+        // Каталоги и библиотеки для .pri: .pri выбирает архитектуру сам, поэтому объединяем
+        let mut vendored_include_dirs: Vec<String> = Vec::new();
+        let mut vendored_lib_dirs: Vec<String> = Vec::new();
+        let mut vendored_libs: Vec<String> = Vec::new();
+        let mut binaries = Vec::new();
         for arch in &target_arches {
             package_progress.set_message(format!("{} -> {}", reference.to_ref_string(), arch));
-            let selected = match clear_store::choose_artifact(&artifacts, arch) {
+            let selected = match clear_store::choose_artifact(&artifacts, arch, linkage) {
                 Ok(item) => item,
                 Err(error) => {
                     if strict_arch_mode {
@@ -339,10 +803,67 @@ fn sync_clear_mode(
                 }
             };
 
+            if let Some(shared) = selected.shared
+                && shared != linkage.shared_option()
+            {
+                log_warning(
+                    Some(&package_progress),
+                    &format!(
+                        "{} ({}): no {} binary published, using {}",
+                        reference.to_ref_string(),
+                        arch,
+                        if linkage.shared_option() {
+                            "shared"
+                        } else {
+                            "static"
+                        },
+                        if shared { "shared" } else { "static" }
+                    ),
+                );
+            }
+
             installed_any = true;
+            binaries.push(LockedBinary {
+                arch: arch.clone(),
+                shared: selected.shared,
+                sha256: clear_store::sha256_file(&selected.path)?,
+                origin: selected.origin.clone(),
+            });
+            let cpp_info = match &conanfile {
+                Some(conanfile) => {
+                    // Условия по self.options.shared вычисляем для выбранного бинарника
+                    let shared = selected.shared.unwrap_or(linkage.shared_option());
+                    let recipe = recipe::analyze_recipe(
+                        &reference.name,
+                        conanfile,
+                        &RecipeProfile::aurora(Some(arch)).with_shared(shared),
+                    );
+                    recipe_warnings.extend(recipe.unsupported.iter().map(|warning| {
+                        format!("{}/{}: {}", reference.name, reference.version, warning)
+                    }));
+                    recipe.cpp_info
+                }
+                // Fallback к пустому cpp_info если не удалось получить
+                None => PackageCppInfo {
+                    package_name: reference.name.clone(),
+                    ..Default::default()
+                },
+            };
             let package_dir =
                 clear_store::package_root(project_root, arch, &reference.name, &reference.version);
             clear_store::extract_tgz(&selected.path, &package_dir)?;
+            if let Err(error) = elf::verify_package_arch(&package_dir, arch) {
+                // Не оставляем в хранилище пакет, который нельзя использовать
+                let _ = fs::remove_dir_all(&package_dir);
+                return Err(error).with_context(|| {
+                    format!(
+                        "Пакет {} содержит бинарники чужой архитектуры (артефакт '{}', каталог {})",
+                        reference.to_ref_string(),
+                        selected.arch,
+                        arch
+                    )
+                });
+            }
 
             // Обнаруживаем библиотеки из файловой системы как fallback
             let discovered_libs = clear_store::discover_lib_names(&package_dir)?;
@@ -355,77 +876,228 @@ fn sync_clear_mode(
                 }
             }
 
-            for lib in &combined_libs {
-                let pattern = format!("lib{}.*", lib);
-                if !lib_patterns.iter().any(|item| item == &pattern) {
+            // SONAME и DT_NEEDED берём из самих ELF-файлов; угадываем по именам,
+            // только если в пакете нет читаемых ELF-библиотек.
+            let shared_libraries = elf::scan_shared_libraries(&package_dir)?;
+            let own_patterns = package_lib_patterns
+                .entry(reference.name.clone())
+                .or_default();
+            if shared_libraries.is_empty() {
+                // Статические архивы линкуются в приложение и в RPM не попадают
+                for lib in combined_libs
+                    .iter()
+                    .filter(|lib| !clear_store::is_static_only_library(&package_dir, lib))
+                {
+                    let pattern = format!("lib{}.*", lib);
+                    if !own_patterns.contains(&pattern) {
+                        own_patterns.push(pattern.clone());
+                    }
+                    if !lib_patterns.iter().any(|item| item == &pattern) {
+                        lib_patterns.push(pattern);
+                    }
+                }
+            }
+            for library in &shared_libraries {
+                let soname = library.provided_name();
+                let pattern = files::soname_requires_pattern(&soname);
+                if !own_patterns.contains(&pattern) {
+                    own_patterns.push(pattern.clone());
+                }
+                if !lib_patterns.contains(&pattern) {
                     lib_patterns.push(pattern);
                 }
+                bundled_sonames.insert(soname.clone());
+                soname_owners
+                    .entry(soname.clone())
+                    .or_insert_with(|| reference.name.clone());
+                for needed in &library.info.needed {
+                    package_needed
+                        .entry(reference.name.clone())
+                        .or_default()
+                        .insert(needed.clone());
+                    needed_by
+                        .entry(needed.clone())
+                        .or_default()
+                        .insert(format!("{} ({}, {})", soname, reference.name, arch));
+                }
+            }
+
+            // Создаём cpp_info с объединёнными libs для записи .pc и CMake-конфига
+            let pkg_cpp_info = PackageCppInfo {
+                libs: combined_libs,
+                ..cpp_info.clone()
+            };
+            for (target, items) in [
+                (&mut vendored_include_dirs, pkg_cpp_info.all_includedirs()),
+                (&mut vendored_lib_dirs, pkg_cpp_info.all_libdirs()),
+                (&mut vendored_libs, pkg_cpp_info.all_libs()),
+            ] {
+                for item in items {
+                    if !target.contains(&item) {
+                        target.push(item);
+                    }
+                }
+            }
+            let dependencies = required_cpp_infos(&pkg_cpp_info, &recipes, arch, &manifest.linkage);
+            // Источники .pc и CMake-конфигов по убыванию приоритета: файлы из архива пакета,
+            // cpp_info рецепта, библиотеки, найденные в каталоге пакета. Сначала ставим
+            // поставляемые файлы, затем генерируем только те, которых пакет не содержит.
+            let label = format!("{} ({})", reference.to_ref_string(), arch);
+            let generated_from = if conanfile.is_some() {
+                "recipe cpp_info"
+            } else {
+                "discovered libraries"
+            };
+
+            let mut packaged_cmake_names = Vec::new();
+            for config in clear_store::find_packaged_cmake_configs(&package_dir)? {
+                if clear_store::packaged_cmake_config_name(&config)
+                    .is_some_and(|name| packaged_cmake_names.contains(&name))
+                {
+                    continue;
+                }
+                let name = clear_store::install_packaged_cmake_config(
+                    project_root,
+                    arch,
+                    reference,
+                    &config,
+                )?;
+                log_info(
+                    Some(&package_progress),
+                    &format!(
+                        "{label}: CMake config {} from packaged {}",
+                        name,
+                        display_relative(&package_dir, &config)
+                    ),
+                );
+                packaged_cmake_names.push(name);
+            }
+            let cmake_name = clear_store::cmake_file_name(&reference.name, &pkg_cpp_info);
+            // Без рецепта поставляемые конфиги полностью заменяют угаданный по каталогу
+            if !packaged_cmake_names.contains(&cmake_name)
+                && (conanfile.is_some() || packaged_cmake_names.is_empty())
+            {
+                clear_store::write_cmake_config(
+                    project_root,
+                    arch,
+                    reference,
+                    &pkg_cpp_info,
+                    &dependencies,
+                )?;
+                log_info(
+                    Some(&package_progress),
+                    &format!("{label}: CMake config {cmake_name} generated from {generated_from}"),
+                );
+            }
+
+            let mut packaged_names = Vec::new();
+            for source in &clear_store::find_packaged_pkg_configs(&package_dir)? {
+                // lib/pkgconfig раньше share/pkgconfig: первый .pc с таким именем и ставим
+                if source
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| packaged_names.iter().any(|name| name == stem))
+                {
+                    continue;
+                }
+                let name = clear_store::install_packaged_pkg_config(
+                    project_root,
+                    arch,
+                    reference,
+                    source,
+                )?;
+                log_info(
+                    Some(&package_progress),
+                    &format!(
+                        "{label}: {name}.pc from packaged {}",
+                        display_relative(&package_dir, source)
+                    ),
+                );
+                packaged_names.push(name);
             }
 
-            // Генерируем .pc файлы
-            if !cpp_info.components.is_empty() {
+            // Недостающие .pc всё равно генерируем: на них ссылаются Requires зависимых пакетов
+            let mut generated_names = Vec::new();
+            if conanfile.is_none() && !packaged_names.is_empty() {
+                // Без рецепта поставляемые .pc полностью заменяют угаданные по каталогу
+            } else if !cpp_info.components.is_empty() {
                 // Пакет с компонентами - генерируем .pc для каждого компонента
                 for component in &cpp_info.components {
-                    // Определяем sibling .pc имена для Requires
-                    let sibling_pc_names: Vec<String> = component
-                        .requires
-                        .iter()
-                        .filter_map(|req| {
-                            // Находим компонент с таким именем и берём его pkg_config_name
-                            cpp_info.components.iter().find_map(|c| {
-                                if &c.name == req {
-                                    Some(
-                                        c.pkg_config_name
-                                            .clone()
-                                            .unwrap_or_else(|| c.name.clone()),
-                                    )
-                                } else {
-                                    None
-                                }
-                            })
-                        })
-                        .collect();
-
+                    let pc_name = clear_store::component_pc_name(component);
+                    if packaged_names.contains(&pc_name) {
+                        continue;
+                    }
                     clear_store::write_component_pkg_config(
                         project_root,
                         arch,
                         reference,
+                        &cpp_info,
                         component,
-                        &sibling_pc_names,
+                        &dependencies,
                     )?;
+                    generated_names.push(pc_name);
                 }
-
-                // Собираем pkg_config_name для модулей из компонентов
-                let is_direct = direct_refs.iter().any(|d| d.name == reference.name);
-                if is_direct {
-                    // Для прямых зависимостей с компонентами используем имена компонентов
-                    for component in &cpp_info.components {
-                        let pc_name = component
-                            .pkg_config_name
-                            .clone()
-                            .unwrap_or_else(|| component.name.clone());
-                        if !pkg_modules.contains(&pc_name) {
-                            pkg_modules.push(pc_name);
-                        }
-                    }
+            } else {
+                let pc_name = cpp_info
+                    .pkg_config_name
+                    .clone()
+                    .unwrap_or_else(|| reference.name.clone());
+                if !packaged_names.contains(&pc_name) {
+                    // Пакет без компонентов - один .pc файл, Requires - пакеты из графа зависимостей
+                    let requires = required_pc_names(
+                        requires_by_name
+                            .get(&reference.name)
+                            .map(Vec::as_slice)
+                            .unwrap_or_default(),
+                        &recipes,
+                        arch,
+                        &manifest.linkage,
+                    );
+                    clear_store::write_pkg_config(
+                        project_root,
+                        arch,
+                        reference,
+                        &pkg_cpp_info,
+                        &requires,
+                    )?;
+                    generated_names.push(pc_name);
                 }
+            }
+            if !generated_names.is_empty() {
+                log_info(
+                    Some(&package_progress),
+                    &format!(
+                        "{label}: {} generated from {generated_from}",
+                        generated_names
+                            .iter()
+                            .map(|name| format!("{name}.pc"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                );
+            }
+
+            // Линкуем поставляемые .pc, если они есть, иначе сгенерированные
+            let pc_names = if packaged_names.is_empty() {
+                generated_names
             } else {
-                // Пакет без компонентов - один .pc файл
-                // Создаём cpp_info с объединёнными libs для записи .pc
-                let pkg_cpp_info = PackageCppInfo {
-                    libs: combined_libs,
-                    system_libs: cpp_info.system_libs.clone(),
-                    ..cpp_info.clone()
-                };
-                clear_store::write_pkg_config(project_root, arch, reference, &pkg_cpp_info, &[])?;
-
-                // Добавляем pkg_config_name в модули для прямых зависимостей
-                let is_direct = direct_refs.iter().any(|d| d.name == reference.name);
-                if is_direct {
-                    let pc_name = cpp_info
-                        .pkg_config_name
-                        .clone()
-                        .unwrap_or_else(|| reference.name.clone());
+                packaged_names
+            };
+
+            for pc_name in &pc_names {
+                if !vendored_modules.contains(pc_name) {
+                    vendored_modules.push(pc_name.clone());
+                }
+            }
+
+            // Добавляем pkg_config_name в модули для прямых зависимостей
+            let is_direct = direct_refs.iter().any(|d| d.name == reference.name);
+            if is_direct {
+                let modules = package_modules.entry(reference.name.clone()).or_default();
+                for pc_name in pc_names {
+                    if !modules.contains(&pc_name) {
+                        modules.push(pc_name.clone());
+                    }
                     if !pkg_modules.contains(&pc_name) {
                         pkg_modules.push(pc_name);
                     }
@@ -433,16 +1105,15 @@ fn sync_clear_mode(
             }
 
             // Собираем системные библиотеки
-            for sys_lib in &cpp_info.system_libs {
-                if !all_system_libs.contains(sys_lib) {
-                    all_system_libs.push(sys_lib.clone());
+            let own_system_libs = package_system_libs
+                .entry(reference.name.clone())
+                .or_default();
+            for sys_lib in cpp_info.all_system_libs() {
+                if !own_system_libs.contains(&sys_lib) {
+                    own_system_libs.push(sys_lib.clone());
                 }
-            }
-            for component in &cpp_info.components {
-                for sys_lib in &component.system_libs {
-                    if !all_system_libs.contains(sys_lib) {
-                        all_system_libs.push(sys_lib.clone());
-                    }
+                if !all_system_libs.contains(&sys_lib) {
+                    all_system_libs.push(sys_lib);
                 }
             }
 
@@ -456,9 +1127,60 @@ fn sync_clear_mode(
                 reference.version
             ));
         }
+
+        locked_packages.push(LockedPackage {
+            name: reference.name.clone(),
+            version: reference.version.clone(),
+            user: reference.user.clone(),
+            license: package_licenses
+                .iter()
+                .find(|package| package.name == reference.name)
+                .map(|package| package.spdx.clone())
+                .unwrap_or_else(|| licenses::NOASSERTION.to_string()),
+            requires: requires_by_name.remove(&reference.name).unwrap_or_default(),
+            binaries,
+        });
+        vendored_packages.push(VendoredPackage {
+            name: reference.name.clone(),
+            version: reference.version.clone(),
+            pkg_modules: vendored_modules,
+            include_dirs: vendored_include_dirs,
+            lib_dirs: vendored_lib_dirs,
+            libs: vendored_libs,
+        });
     }
     package_progress.finish_with_message(format!("{} packages synced", style("✔").green()));
 
+    // Пакет зависит от другого, если его библиотеки требуют SONAME, которые тот поставляет
+    let package_dependencies: BTreeMap<String, BTreeSet<String>> = package_needed
+        .iter()
+        .map(|(package, needed)| {
+            let owners = needed
+                .iter()
+                .filter_map(|soname| soname_owners.get(soname))
+                .filter(|owner| *owner != package)
+                .cloned()
+                .collect();
+            (package.clone(), owners)
+        })
+        .collect();
+    let vendored_packages = order_dependents_first(vendored_packages, &package_dependencies);
+    write_install_manifests(project_root, &target_arches, main_progress)?;
+
+    for (needed, consumers) in &needed_by {
+        if bundled_sonames.contains(needed) || elf::is_system_library(needed) {
+            continue;
+        }
+        log_warning(
+            main_progress,
+            &format!(
+                "Unresolved DT_NEEDED {}: required by {}",
+                needed,
+                consumers.iter().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        );
+    }
+
     lib_patterns.sort();
     lib_patterns.dedup();
     all_system_libs.sort();
@@ -472,375 +1194,1652 @@ fn sync_clear_mode(
 
     apply_clear_changes(
         project_root,
+        specs,
         &ProjectMetadata {
             direct_pkg_modules: pkg_modules,
             shared_lib_patterns: lib_patterns,
             system_libs: all_system_libs,
+            vendored_packages,
+            package_modules,
+            target_assignments: manifest.targets.clone(),
+            package_lib_patterns,
+            package_system_libs,
+            package_dependencies: model::package_dependencies(&locked_packages),
+            recipe_warnings: recipe_warnings.into_iter().collect(),
+            host_tools,
+            package_licenses,
+        },
+    )?;
+    // Манифест сохраняем только после успешной синхронизации
+    clear_store::save_manifest(
+        project_root,
+        &ClearManifest {
+            packages: locked_packages,
+            ..manifest.clone()
         },
     )?;
     log_info(
         main_progress,
         &format!(
-            "Updated CMake/spec: modules={}, shared_lib_patterns={}, system_libs={}",
+            "Updated build files/spec: modules={}, shared_lib_patterns={}, system_libs={}",
             module_count, pattern_count, system_lib_count
         ),
     );
     Ok(())
 }
 
-fn build_full_dependency_set(
-    provider: &dyn ConanProvider,
-    direct_refs: &[ConanRef],
-) -> Result<Vec<ConanRef>> {
-    let mut all = direct_refs.to_vec();
+/// Пишет манифесты установки, по которым `%install` копирует библиотеки.
+fn write_install_manifests(
+    project_root: &Path,
+    target_arches: &[String],
+    main_progress: Option<&ProgressBar>,
+) -> Result<()> {
+    for arch in target_arches {
+        let entries = clear_store::write_install_manifest(project_root, arch)?;
+        log_info(
+            main_progress,
+            &format!(
+                "Install manifest ({}): {} files, {} symlinks",
+                arch,
+                entries
+                    .iter()
+                    .filter(|entry| matches!(entry, InstallEntry::File { .. }))
+                    .count(),
+                entries
+                    .iter()
+                    .filter(|entry| matches!(entry, InstallEntry::Link { .. }))
+                    .count()
+            ),
+        );
+    }
+    Ok(())
+}
 
-    for direct in direct_refs {
-        let resolved =
-            provider.resolve_dependencies_without_conan(&direct.name, &direct.version)?;
-        for item in resolved {
-            if item.version == "error" {
-                return Err(anyhow!(
-                    "Не удалось определить версию транзитивной зависимости {}",
-                    item.name
-                ));
-            }
+/// Ставит tool-пакеты под архитектуру хоста и возвращает их исполняемые файлы.
+fn sync_host_tools(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    manifest: &ClearManifest,
+    main_progress: Option<&ProgressBar>,
+) -> Result<Vec<HostTool>> {
+    clear_store::reset_host_layout(project_root)?;
+    if manifest.tools.is_empty() {
+        return Ok(Vec::new());
+    }
 
-            if let Some(existing) = all.iter().find(|ref_item| ref_item.name == item.name) {
-                if existing.version != item.version {
-                    return Err(anyhow!(
-                        "Конфликт версий зависимости '{}': {} и {}",
-                        item.name,
-                        existing.version,
-                        item.version
-                    ));
-                }
+    let host_arch = clear_store::host_arch()?;
+    let mut tools: Vec<HostTool> = Vec::new();
+    for reference in &manifest.tools {
+        let artifacts = provider.download_dependency_archives(
+            &reference.name,
+            &reference.version,
+            project_root,
+        )?;
+        // Статически собранный инструмент запускается без библиотек пакета
+        let selected = clear_store::choose_artifact(&artifacts, &host_arch, Linkage::Static)
+            .with_context(|| {
+                format!(
+                    "Не найден артефакт инструмента {} для машины сборки {}",
+                    reference.to_ref_string(),
+                    host_arch
+                )
+            })?;
+        let package_dir =
+            clear_store::host_package_root(project_root, &reference.name, &reference.version);
+        clear_store::extract_tgz(&selected.path, &package_dir)?;
+        if let Err(error) = elf::verify_package_arch(&package_dir, &host_arch) {
+            let _ = fs::remove_dir_all(&package_dir);
+            return Err(error).with_context(|| {
+                format!(
+                    "Инструмент {} содержит бинарники чужой архитектуры (машина сборки {})",
+                    reference.to_ref_string(),
+                    host_arch
+                )
+            });
+        }
+
+        let executables = clear_store::discover_executables(&package_dir)?;
+        if executables.is_empty() {
+            log_warning(
+                main_progress,
+                &format!(
+                    "Tool package {} has no executables in bin/",
+                    reference.to_ref_string()
+                ),
+            );
+        }
+        for executable in executables {
+            elf::verify_executable_arch(&executable, &host_arch)?;
+            let name = executable
+                .file_name()
+                .map(|value| value.to_string_lossy().to_string())
+                .unwrap_or_default();
+            if let Some(existing) = tools.iter().find(|tool| tool.name == name) {
+                log_warning(
+                    main_progress,
+                    &format!(
+                        "Tool {} of {} is shadowed by {}",
+                        name,
+                        reference.to_ref_string(),
+                        existing.package
+                    ),
+                );
                 continue;
             }
-            all.push(item);
+            let path = display_relative(project_root, &executable).replace('\\', "/");
+            log_info(
+                main_progress,
+                &format!("Host tool {} ({}): {}", name, host_arch, path),
+            );
+            tools.push(HostTool {
+                name,
+                package: reference.name.clone(),
+                path,
+            });
         }
     }
-
-    all.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
-    Ok(all)
+    Ok(tools)
 }
 
-fn ensure_project_files_exist(project_root: &Path) -> Result<()> {
-    let cmake = project_root.join(files::CMAKE_FILE);
-    if !cmake.exists() {
-        return Err(anyhow!("Не найден {}", cmake.display()));
+fn validate_project(
+    project_root: &Path,
+    explicit_specs: &[PathBuf],
+    allowed_libs: Option<&Path>,
+) -> Result<()> {
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
+    let allowed = validate::load_allowed_libraries(project_root, allowed_libs)?;
+    let dependencies =
+        model::package_dependencies(&clear_store::load_manifest(project_root)?.packages);
+    let violations = validate::validate_project(project_root, &specs, &dependencies, &allowed)?;
+    if violations.is_empty() {
+        log_success(None, "Bundled libraries pass store validation");
+        return Ok(());
     }
 
-    files::find_spec_file(project_root)?;
-    Ok(())
+    for violation in &violations {
+        match &violation.arch {
+            Some(arch) => println!("[{}] {}", arch, violation.message),
+            None => println!("{}", violation.message),
+        }
+    }
+    Err(anyhow!(
+        "Найдено нарушений правил валидатора: {}",
+        violations.len()
+    ))
 }
 
-fn upsert_reference(references: &mut Vec<ConanRef>, new_ref: ConanRef) {
-    if let Some(existing) = references.iter_mut().find(|item| item.name == new_ref.name) {
-        *existing = new_ref;
-    } else {
-        references.push(new_ref);
-    }
+/// Пишет SBOM: в clear-режиме — по `packages` манифеста, иначе по графу, разрешённому как в `deps`.
+fn export_sbom(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    explicit_specs: &[PathBuf],
+    format: SbomFormat,
+    output: Option<&Path>,
+) -> Result<()> {
+    let specs = ensure_project_files_exist(project_root, explicit_specs)?;
+    let spec_path = specs.first().map(|spec| project_root.join(&spec.path));
+    let spec_tag = |tag: &str| -> Result<Option<String>> {
+        match &spec_path {
+            Some(path) => files::read_spec_tag(path, tag),
+            None => Ok(None),
+        }
+    };
+    let name = match spec_tag("Name")? {
+        Some(name) => name,
+        None => project_root
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| "application".to_string()),
+    };
+    let version = spec_tag("Version")?.unwrap_or_else(|| "0".to_string());
 
-    references.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
-}
+    let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    let (direct_refs, locked, linkage) = match mode {
+        ProjectMode::Conan => (
+            files::read_requires(project_root)?,
+            Vec::new(),
+            BTreeMap::new(),
+        ),
+        ProjectMode::Clear => {
+            let manifest = clear_store::load_manifest(project_root)?;
+            (
+                manifest.direct_requires,
+                manifest.packages,
+                manifest.linkage,
+            )
+        }
+    };
+    let packages = if !locked.is_empty() || direct_refs.is_empty() {
+        locked
+    } else {
+        let policy =
+            resolution_policy(project_root, mode, &project_overrides(project_root, mode)?)?;
+        let all_refs = build_full_dependency_set(provider, &direct_refs, &policy)?.packages;
+        let recipes = fetch_recipes(provider, &all_refs, None);
+        let mut requires = package_requires(provider, &all_refs, &recipes, &policy);
+        let (target_arches, strict_arch_mode) = clear_store::resolve_target_arches()?;
+        let download_root = tempfile::tempdir()
+            .context("Не удалось создать временный каталог для загрузки бинарников")?;
+        let mut packages = Vec::new();
+        for (license, reference) in collect_package_licenses(&all_refs, &recipes)
+            .into_iter()
+            .zip(&all_refs)
+        {
+            let binaries = locked_binaries(
+                provider,
+                reference,
+                &target_arches,
+                strict_arch_mode,
+                linkage.get(&reference.name).copied().unwrap_or_default(),
+                download_root.path(),
+            )
+            .with_context(|| {
+                format!(
+                    "Не удалось получить бинарники пакета {} для SBOM",
+                    reference.to_ref_string()
+                )
+            })?;
+            packages.push(LockedPackage {
+                name: reference.name.clone(),
+                version: reference.version.clone(),
+                user: reference.user.clone(),
+                license: license.spdx,
+                requires: requires.remove(&reference.name).unwrap_or_default(),
+                binaries,
+            });
+        }
+        packages
+    };
 
-fn apply_conan_changes(project_root: &Path, metadata: &ProjectMetadata) -> Result<()> {
-    files::update_cmake(project_root, metadata)?;
-    files::update_spec(project_root, metadata)?;
+    let subject = SbomSubject {
+        name,
+        version,
+        requires: direct_refs
+            .iter()
+            .map(|reference| format!("{}/{}", reference.name, reference.version))
+            .collect(),
+    };
+    let document = sbom::render(format, &subject, &packages, &sbom::creation_timestamp());
+    match output {
+        Some(path) => {
+            fs::write(path, document)
+                .with_context(|| format!("Не удалось записать {}", path.display()))?;
+            log_info(
+                None,
+                &format!(
+                    "SBOM with {} packages written to {}",
+                    packages.len(),
+                    path.display()
+                ),
+            );
+        }
+        None => print!("{document}"),
+    }
     Ok(())
 }
 
-fn apply_clear_changes(project_root: &Path, metadata: &ProjectMetadata) -> Result<()> {
-    files::update_cmake_clear(project_root, metadata)?;
-    files::update_spec_clear(project_root, metadata)?;
-    Ok(())
+/// Скачивает архивы пакета в `download_root` и выбирает бинарник для каждой архитектуры
+/// так же, как синхронизация clear-режима, не распаковывая его.
+fn locked_binaries(
+    provider: &dyn ConanProvider,
+    reference: &ConanRef,
+    target_arches: &[String],
+    strict_arch_mode: bool,
+    linkage: Linkage,
+    download_root: &Path,
+) -> Result<Vec<LockedBinary>> {
+    let artifacts = provider.download_dependency_archives(
+        &reference.name,
+        &reference.version,
+        download_root,
+    )?;
+    let mut binaries = Vec::new();
+    for arch in target_arches {
+        let selected = match clear_store::choose_artifact(&artifacts, arch, linkage) {
+            Ok(item) => item,
+            Err(error) if strict_arch_mode => {
+                return Err(error).with_context(|| {
+                    format!(
+                        "Не найден артефакт пакета {}/{} для архитектуры {}",
+                        reference.name, reference.version, arch
+                    )
+                });
+            }
+            Err(_) => continue,
+        };
+        binaries.push(LockedBinary {
+            arch: arch.clone(),
+            shared: selected.shared,
+            sha256: clear_store::sha256_file(&selected.path)?,
+            origin: selected.origin.clone(),
+        });
+    }
+    Ok(binaries)
 }
 
-fn create_progress_bar(total: u64, message: impl Into<String>) -> ProgressBar {
-    let progress = ProgressBar::new(total);
-    if !io::stderr().is_terminal() {
-        progress.set_draw_target(ProgressDrawTarget::hidden());
+/// Сверяет пакеты графа с локальной базой уязвимостей в формате OSV; найденные уязвимости
+/// завершают команду ошибкой, чтобы её можно было использовать как проверку в CI.
+fn audit_project(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    database: Option<PathBuf>,
+) -> Result<()> {
+    let database = database
+        .or_else(|| std::env::var_os("AURORA_CONAN_OSV_DIR").map(PathBuf::from))
+        .ok_or_else(|| {
+            anyhow!("Укажите каталог базы уязвимостей: --db <path> или AURORA_CONAN_OSV_DIR")
+        })?;
+    let advisories = audit::load_advisories(&project_root.join(database))?;
+
+    let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    let (direct_refs, locked) = match mode {
+        ProjectMode::Conan => (files::read_requires(project_root)?, Vec::new()),
+        ProjectMode::Clear => {
+            let manifest = clear_store::load_manifest(project_root)?;
+            (manifest.direct_requires, manifest.packages)
+        }
+    };
+    // Синхронизированный манифест позволяет проверить граф без обращения к удалённому репозиторию
+    let policy = if locked.is_empty() && !direct_refs.is_empty() {
+        resolution_policy(project_root, mode, &project_overrides(project_root, mode)?)?
+    } else {
+        ResolutionPolicy::default()
+    };
+    let all_refs: Vec<ConanRef> = if !locked.is_empty() || direct_refs.is_empty() {
+        locked
+            .iter()
+            .map(|package| ConanRef {
+                name: package.name.clone(),
+                version: package.version.clone(),
+                user: package.user.clone(),
+            })
+            .collect()
+    } else {
+        build_full_dependency_set(provider, &direct_refs, &policy)?.packages
+    };
+
+    let mut closures = HashMap::new();
+    let mut remote_versions: BTreeMap<String, Option<Vec<String>>> = BTreeMap::new();
+    let mut findings = 0;
+    for reference in &all_refs {
+        for advisory in advisories
+            .iter()
+            .filter(|advisory| advisory.affects(&reference.name, &reference.version))
+        {
+            findings += 1;
+            let path = if locked.is_empty() {
+                dependency_path(
+                    provider,
+                    &direct_refs,
+                    &policy,
+                    &reference.name,
+                    &mut closures,
+                )
+            } else {
+                locked_dependency_path(&direct_refs, &locked, &reference.name)
+            };
+            let available = remote_versions
+                .entry(reference.name.clone())
+                .or_insert_with(|| match provider.list_dependency_versions(&reference.name) {
+                    Ok(versions) => Some(versions),
+                    Err(error) => {
+                        log_warning(
+                            None,
+                            &format!(
+                                "Versions of {} are unavailable ({:#}): fixed releases on the remote are not listed",
+                                reference.name, error
+                            ),
+                        );
+                        None
+                    }
+                });
+            let mut upgrades: Vec<String> = available
+                .iter()
+                .flatten()
+                .filter(|version| {
+                    audit::compare_versions(version, &reference.version).is_gt()
+                        && !advisory.affects(&reference.name, version)
+                })
+                .cloned()
+                .collect();
+            upgrades.sort_by(|a, b| audit::compare_versions(a, b));
+
+            let mut title = advisory.id.clone();
+            if !advisory.aliases.is_empty() {
+                title.push_str(&format!(" ({})", advisory.aliases.join(", ")));
+            }
+            println!(
+                "{} {} {}/{}: {}",
+                title, advisory.severity, reference.name, reference.version, advisory.summary
+            );
+            println!("  path: {}", path.join(" -> "));
+            let fixed = advisory.fixed_versions(&reference.name);
+            println!(
+                "  fixed in: {}",
+                if fixed.is_empty() {
+                    "-".to_string()
+                } else {
+                    fixed.join(", ")
+                }
+            );
+            if available.is_some() {
+                println!(
+                    "  available on remote: {}",
+                    if upgrades.is_empty() {
+                        "-".to_string()
+                    } else {
+                        upgrades.join(", ")
+                    }
+                );
+            }
+        }
     }
-    let style = ProgressStyle::with_template(
-        "[{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>3}/{len:3} {msg}",
-    )
-    .unwrap_or_else(|_| ProgressStyle::default_bar())
-    .progress_chars("=>-");
-    progress.set_style(style);
-    progress.set_message(message.into());
-    progress
+
+    if findings == 0 {
+        log_success(
+            None,
+            &format!(
+                "No known vulnerabilities in {} packages ({} advisories)",
+                all_refs.len(),
+                advisories.len()
+            ),
+        );
+        return Ok(());
+    }
+    Err(audit::FindingsError { count: findings }.into())
 }
 
-fn progress_step(progress: &ProgressBar, message: &str) {
-    progress.inc(1);
-    progress.set_message(message.to_string());
-    log_info(Some(progress), message);
+/// Цепочка `name/version` до пакета по рёбрам `requires` из манифеста.
+fn locked_dependency_path(
+    direct_refs: &[ConanRef],
+    packages: &[LockedPackage],
+    target: &str,
+) -> Vec<String> {
+    let label = |package: &LockedPackage| format!("{}/{}", package.name, package.version);
+    let by_label: BTreeMap<String, &LockedPackage> = packages
+        .iter()
+        .map(|package| (label(package), package))
+        .collect();
+
+    let mut parents: BTreeMap<String, Option<String>> = BTreeMap::new();
+    let mut queue: VecDeque<String> = direct_refs
+        .iter()
+        .map(|reference| format!("{}/{}", reference.name, reference.version))
+        .collect();
+    for start in &queue {
+        parents.insert(start.clone(), None);
+    }
+    while let Some(current) = queue.pop_front() {
+        let Some(package) = by_label.get(&current) else {
+            continue;
+        };
+        if package.name == target {
+            let mut path = vec![current.clone()];
+            let mut cursor = parents.get(&current).cloned().flatten();
+            while let Some(parent) = cursor {
+                cursor = parents.get(&parent).cloned().flatten();
+                path.push(parent);
+            }
+            path.reverse();
+            return path;
+        }
+        for child in &package.requires {
+            if !parents.contains_key(child) {
+                parents.insert(child.clone(), Some(current.clone()));
+                queue.push_back(child.clone());
+            }
+        }
+    }
+    packages
+        .iter()
+        .find(|package| package.name == target)
+        .map(|package| vec![label(package)])
+        .unwrap_or_else(|| vec![target.to_string()])
 }
 
-fn mode_label(mode: ProjectMode) -> &'static str {
+/// Записывает или снимает исключение пакета из лицензионной политики: `aurora_license_waivers`
+/// в conanfile.py или `license_waivers` в манифесте.
+fn set_license_waiver(project_root: &Path, package: &str, reason: Option<String>) -> Result<()> {
+    let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    let mut waivers = match mode {
+        ProjectMode::Conan => files::read_license_waivers(project_root)?,
+        ProjectMode::Clear => clear_store::load_manifest(project_root)?.license_waivers,
+    };
+    match reason {
+        Some(reason) => {
+            if reason.trim().is_empty() {
+                return Err(anyhow!("Укажите обоснование исключения для {}", package));
+            }
+            waivers.insert(package.to_string(), reason.trim().to_string());
+            log_info(None, &format!("License waiver recorded for {package}"));
+        }
+        None => {
+            if waivers.remove(package).is_none() {
+                return Err(anyhow!("Исключение для {} не найдено", package));
+            }
+            log_info(None, &format!("License waiver removed for {package}"));
+        }
+    }
+
     match mode {
-        ProjectMode::Conan => "conan",
-        ProjectMode::Clear => "clear",
+        ProjectMode::Conan => {
+            let overrides = files::read_overrides(project_root)?;
+            let policy = resolution_policy(project_root, mode, &overrides)?;
+            files::write_conanfile(
+                project_root,
+                &files::read_requires(project_root)?,
+                &overrides,
+                &policy_overrides(&policy),
+                &files::read_targets(project_root)?,
+                &waivers,
+            )
+        }
+        ProjectMode::Clear => {
+            let mut manifest = clear_store::load_manifest(project_root)?;
+            manifest.license_waivers = waivers;
+            clear_store::save_manifest(project_root, &manifest)
+        }
     }
 }
 
-fn version_suffix(version: Option<&str>) -> String {
-    version.map_or_else(String::new, |value| format!(" {}", value))
+/// `cpp_info` зависимости из её рецепта. Бинарник зависимости здесь ещё не выбран,
+/// поэтому `shared` берётся из предпочтения линковки пакета.
+fn dependency_cpp_info(
+    name: &str,
+    recipes: &BTreeMap<String, String>,
+    arch: &str,
+    linkage: &BTreeMap<String, Linkage>,
+) -> Option<PackageCppInfo> {
+    let conanfile = recipes.get(name)?;
+    let shared = linkage
+        .get(name)
+        .copied()
+        .unwrap_or_default()
+        .shared_option();
+    let profile = RecipeProfile::aurora(Some(arch)).with_shared(shared);
+    Some(recipe::analyze_recipe(name, conanfile, &profile).cpp_info)
 }
 
-#[derive(Clone, Copy)]
-enum LogLevel {
-    Info,
-    Success,
+/// `cpp_info` других пакетов, на компоненты которых ссылаются требования `pkg::comp`.
+fn required_cpp_infos(
+    cpp_info: &PackageCppInfo,
+    recipes: &BTreeMap<String, String>,
+    arch: &str,
+    linkage: &BTreeMap<String, Linkage>,
+) -> BTreeMap<String, PackageCppInfo> {
+    let mut dependencies = BTreeMap::new();
+    for required in cpp_info
+        .components
+        .iter()
+        .flat_map(|component| &component.requires)
+    {
+        let Some((name, _)) = required.split_once("::") else {
+            continue;
+        };
+        if name == cpp_info.package_name || dependencies.contains_key(name) {
+            continue;
+        }
+        let dependency =
+            dependency_cpp_info(name, recipes, arch, linkage).unwrap_or_else(|| PackageCppInfo {
+                package_name: name.to_string(),
+                ..Default::default()
+            });
+        dependencies.insert(name.to_string(), dependency);
+    }
+    dependencies
 }
 
-fn log_info(progress: Option<&ProgressBar>, message: &str) {
-    log_with_level(progress, LogLevel::Info, message);
+/// `.pc` пакетов из `requires` (`<name>/<version>`) для `Requires` пакета без компонентов:
+/// у пакета с компонентами — `.pc` каждого компонента, иначе `.pc` самого пакета.
+fn required_pc_names(
+    requires: &[String],
+    recipes: &BTreeMap<String, String>,
+    arch: &str,
+    linkage: &BTreeMap<String, Linkage>,
+) -> Vec<String> {
+    let mut out = Vec::new();
+    for required in requires {
+        let name = required.split('/').next().unwrap_or(required);
+        let cpp_info = dependency_cpp_info(name, recipes, arch, linkage).unwrap_or_default();
+        let names = if cpp_info.components.is_empty() {
+            vec![
+                cpp_info
+                    .pkg_config_name
+                    .clone()
+                    .unwrap_or_else(|| name.to_string()),
+            ]
+        } else {
+            cpp_info
+                .components
+                .iter()
+                .map(clear_store::component_pc_name)
+                .collect()
+        };
+        for pc_name in names {
+            if !out.contains(&pc_name) {
+                out.push(pc_name);
+            }
+        }
+    }
+    out
 }
 
-fn log_success(progress: Option<&ProgressBar>, message: &str) {
-    log_with_level(progress, LogLevel::Success, message);
+/// Тексты `conanfile.py` пакетов графа; недоступный рецепт пропускается с предупреждением.
+fn fetch_recipes(
+    provider: &dyn ConanProvider,
+    all_refs: &[ConanRef],
+    main_progress: Option<&ProgressBar>,
+) -> BTreeMap<String, String> {
+    let mut recipes = BTreeMap::new();
+    for reference in all_refs {
+        match provider.fetch_conanfile(&reference.name, &reference.version) {
+            Ok(conanfile) => {
+                recipes.insert(reference.name.clone(), conanfile);
+            }
+            Err(error) => log_warning(
+                main_progress,
+                &format!(
+                    "Recipe of {} is unavailable ({:#}): license is NOASSERTION, metadata comes from packaged .pc/CMake files or discovered libraries",
+                    reference.to_ref_string(),
+                    error
+                ),
+            ),
+        }
+    }
+    recipes
 }
 
-fn log_with_level(progress: Option<&ProgressBar>, level: LogLevel, message: &str) {
-    let prefix = style("[aurora-conan-cli]").dim().bold().to_string();
-    let level_tag = match level {
-        LogLevel::Info => style("INFO").cyan().bold().to_string(),
-        LogLevel::Success => style("SUCCESS").green().bold().to_string(),
+/// Прямые зависимости пакетов графа (`name/version`): из `requires` рецепта, без рецепта —
+/// пакеты его транзитивного графа, которые не тянутся через другие пакеты этого графа.
+fn package_requires(
+    provider: &dyn ConanProvider,
+    all_refs: &[ConanRef],
+    recipes: &BTreeMap<String, String>,
+    policy: &ResolutionPolicy,
+) -> BTreeMap<String, Vec<String>> {
+    let label = |reference: &ConanRef| format!("{}/{}", reference.name, reference.version);
+    let mut closures: HashMap<String, Vec<ConanRef>> = HashMap::new();
+    let mut closure = |reference: &ConanRef| -> Vec<ConanRef> {
+        closures
+            .entry(reference.to_ref_string())
+            .or_insert_with(|| {
+                provider
+                    .resolve_dependencies_without_conan(&reference.name, &reference.version, policy)
+                    .map(|resolved| resolved.packages)
+                    .unwrap_or_default()
+            })
+            .clone()
     };
-    let line = format!("{prefix} {level_tag} {message}");
 
-    match progress {
-        Some(pb) => pb.suspend(|| eprintln!("{line}")),
-        None => eprintln!("{line}"),
+    let mut out = BTreeMap::new();
+    for reference in all_refs {
+        let requires: Vec<String> = match recipes.get(&reference.name) {
+            Some(conanfile) => {
+                let recipe = recipe::analyze_recipe(
+                    &reference.name,
+                    conanfile,
+                    &RecipeProfile::aurora(None),
+                );
+                all_refs
+                    .iter()
+                    .filter(|item| {
+                        recipe
+                            .requires
+                            .iter()
+                            .any(|raw| raw.split('/').next() == Some(item.name.as_str()))
+                    })
+                    .map(label)
+                    .collect()
+            }
+            None => {
+                let transitive = closure(reference);
+                let mut direct = Vec::new();
+                for item in &transitive {
+                    let mut via_other = false;
+                    for other in transitive.iter().filter(|other| other.name != item.name) {
+                        if closure(other).iter().any(|dep| dep.name == item.name) {
+                            via_other = true;
+                            break;
+                        }
+                    }
+                    if !via_other {
+                        direct.push(label(item));
+                    }
+                }
+                direct
+            }
+        };
+        out.insert(reference.name.clone(), requires);
     }
+    out
 }
 
-#[cfg(test)]
-mod tests {
-    use std::collections::HashMap;
-    use std::fs;
-    use std::fs::File;
-    use std::io::Cursor;
-    use std::path::Path;
+/// SPDX-выражения пакетов по атрибуту `license` рецептов.
+fn collect_package_licenses(
+    all_refs: &[ConanRef],
+    recipes: &BTreeMap<String, String>,
+) -> Vec<PackageLicense> {
+    all_refs
+        .iter()
+        .map(|reference| {
+            let license = recipes
+                .get(&reference.name)
+                .map(|conanfile| {
+                    recipe::analyze_recipe(&reference.name, conanfile, &RecipeProfile::aurora(None))
+                        .license
+                })
+                .unwrap_or_default();
+            PackageLicense {
+                name: reference.name.clone(),
+                version: reference.version.clone(),
+                spdx: licenses::spdx_expression(&license),
+            }
+        })
+        .collect()
+}
 
-    use anyhow::{Context, Result, anyhow};
-    use flate2::Compression;
-    use flate2::write::GzEncoder;
-    use tar::{Builder, Header};
-    use tempfile::TempDir;
+/// Лицензии графа `direct_refs` по рецептам и их проверка по политике проекта в conan-режиме,
+/// где рецепты не нужны для синхронизации; без файла политики ничего не скачивается.
+fn enforce_license_policy(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    direct_refs: &[ConanRef],
+    policy: &ResolutionPolicy,
+    waivers: &BTreeMap<String, String>,
+    main_progress: Option<&ProgressBar>,
+) -> Result<()> {
+    if direct_refs.is_empty() || !licenses::policy_path(project_root).exists() {
+        return Ok(());
+    }
+    let all_refs = build_full_dependency_set(provider, direct_refs, policy)?.packages;
+    let recipes = fetch_recipes(provider, &all_refs, main_progress);
+    let package_licenses = collect_package_licenses(&all_refs, &recipes);
+    check_license_policy(
+        provider,
+        project_root,
+        waivers,
+        direct_refs,
+        policy,
+        &package_licenses,
+        main_progress,
+    )
+}
 
-    use super::{CliCommand, run};
-    use crate::conan::ConanProvider;
-    use crate::files;
-    use crate::model::{ConanRef, DownloadArtifact, ProjectMetadata};
+/// Проверяет лицензии пакетов по `thirdparty/aurora/license-policy.txt`; нарушение без исключения
+/// из `waivers` прерывает операцию с цепочкой зависимостей до пакета.
+fn check_license_policy(
+    provider: &dyn ConanProvider,
+    project_root: &Path,
+    waivers: &BTreeMap<String, String>,
+    direct_refs: &[ConanRef],
+    policy: &ResolutionPolicy,
+    package_licenses: &[PackageLicense],
+    main_progress: Option<&ProgressBar>,
+) -> Result<()> {
+    let Some(license_policy) = licenses::load_policy(project_root)? else {
+        return Ok(());
+    };
 
-    struct FakeProvider {
-        latest_versions: HashMap<String, String>,
-        metadata_by_names: HashMap<String, ProjectMetadata>,
-        available_versions_by_name: HashMap<String, Vec<String>>,
-        dependencies_by_ref: HashMap<String, Vec<ConanRef>>,
+    let mut dependents = HashMap::new();
+    let mut violations = Vec::new();
+    for package in package_licenses {
+        let rejected = license_policy.rejected(&package.spdx);
+        if rejected.is_empty() {
+            continue;
+        }
+        if let Some(reason) = waivers.get(&package.name) {
+            log_warning(
+                main_progress,
+                &format!(
+                    "{}/{} ({}) violates the license policy, waived: {}",
+                    package.name, package.version, package.spdx, reason
+                ),
+            );
+            continue;
+        }
+        let path = dependency_path(
+            provider,
+            direct_refs,
+            policy,
+            &package.name,
+            &mut dependents,
+        );
+        violations.push(format!(
+            "  {}/{}: {} не разрешены политикой ({})",
+            package.name,
+            package.version,
+            rejected.join(", "),
+            path.join(" -> ")
+        ));
     }
 
-    impl FakeProvider {
-        fn key(refs: &[ConanRef]) -> String {
-            let mut names: Vec<String> = refs.iter().map(|r| r.name.clone()).collect();
-            names.sort();
-            names.join(",")
-        }
+    if violations.is_empty() {
+        return Ok(());
     }
+    Err(anyhow!(
+        "Зависимости нарушают лицензионную политику {}:\n{}\nИсключение для пакета: aurora-conan-cli waive <package> --reason <text>",
+        display_relative(project_root, &licenses::policy_path(project_root)),
+        violations.join("\n")
+    ))
+}
 
-    impl ConanProvider for FakeProvider {
-        fn search_dependencies(&self, query: &str) -> Result<Vec<ConanRef>> {
-            let query_norm = query.to_lowercase();
-            let mut names: Vec<String> = self
-                .available_versions_by_name
-                .keys()
-                .filter(|name| name.to_lowercase().contains(&query_norm))
-                .cloned()
-                .collect();
-            names.sort();
+/// Цепочка `name/version` от прямой зависимости проекта до пакета `target`.
+/// `closures` кэширует транзитивные зависимости пакетов между вызовами.
+fn dependency_path(
+    provider: &dyn ConanProvider,
+    direct_refs: &[ConanRef],
+    policy: &ResolutionPolicy,
+    target: &str,
+    closures: &mut HashMap<String, Vec<ConanRef>>,
+) -> Vec<String> {
+    let mut closure = |reference: &ConanRef| -> Vec<ConanRef> {
+        closures
+            .entry(reference.to_ref_string())
+            .or_insert_with(|| {
+                provider
+                    .resolve_dependencies_without_conan(&reference.name, &reference.version, policy)
+                    .map(|resolved| resolved.packages)
+                    .unwrap_or_default()
+            })
+            .clone()
+    };
+    let label = |reference: &ConanRef| format!("{}/{}", reference.name, reference.version);
 
-            if names.is_empty() {
-                return Err(anyhow!("Пакеты по запросу '{query}' не найдены"));
-            }
-
-            let mut refs = Vec::new();
-            for name in names {
-                let versions = self
-                    .available_versions_by_name
-                    .get(&name)
-                    .cloned()
-                    .ok_or_else(|| anyhow!("Пакет {name} не найден"))?;
+    for direct in direct_refs {
+        if direct.name == target {
+            return vec![label(direct)];
+        }
+        let transitive = closure(direct);
+        let Some(found) = transitive.iter().find(|item| item.name == target).cloned() else {
+            continue;
+        };
 
-                for version in versions {
-                    refs.push(ConanRef {
-                        name: name.clone(),
-                        version,
-                        user: "aurora".to_string(),
-                    });
+        // Спускаемся через пакет с самым широким графом, который ещё тянет target:
+        // это ближайший к текущему узлу шаг цепочки.
+        let mut path = vec![label(direct)];
+        let mut current = direct.clone();
+        let mut visited = BTreeSet::from([direct.name.clone()]);
+        loop {
+            let mut next: Option<(usize, ConanRef)> = None;
+            for child in closure(&current) {
+                if child.name == target || visited.contains(&child.name) {
+                    continue;
+                }
+                let child_closure = closure(&child);
+                if child_closure.iter().any(|item| item.name == target)
+                    && next
+                        .as_ref()
+                        .is_none_or(|(size, _)| child_closure.len() > *size)
+                {
+                    next = Some((child_closure.len(), child));
                 }
             }
+            let Some((_, child)) = next else {
+                break;
+            };
+            visited.insert(child.name.clone());
+            path.push(label(&child));
+            current = child;
+        }
+        path.push(label(&found));
+        return path;
+    }
+    vec![target.to_string()]
+}
 
-            Ok(refs)
+/// Печатает SPDX-выражения всех пакетов проекта; в clear-режиме переписывает
+/// THIRD-PARTY-NOTICES по текстам из clear-store.
+fn list_licenses(provider: &dyn ConanProvider, project_root: &Path) -> Result<()> {
+    let mode = mode::detect_mode(project_root, files::CONANFILE)?;
+    let direct_refs = match mode {
+        ProjectMode::Conan => files::read_requires(project_root)?,
+        ProjectMode::Clear => clear_store::load_manifest(project_root)?.direct_requires,
+    };
+    let all_refs = if direct_refs.is_empty() {
+        Vec::new()
+    } else {
+        let policy =
+            resolution_policy(project_root, mode, &project_overrides(project_root, mode)?)?;
+        build_full_dependency_set(provider, &direct_refs, &policy)?.packages
+    };
+
+    let recipes = fetch_recipes(provider, &all_refs, None);
+    let package_licenses = collect_package_licenses(&all_refs, &recipes);
+    for package in &package_licenses {
+        let texts = licenses::package_license_files(project_root, &package.name, &package.version)?;
+        println!(
+            "{}/{}\t{}\t{} license files",
+            package.name,
+            package.version,
+            package.spdx,
+            texts.len()
+        );
+    }
+
+    match mode {
+        ProjectMode::Clear => {
+            let path = licenses::write_notices(project_root, &package_licenses)?;
+            log_info(
+                None,
+                &format!(
+                    "Notices written to {}",
+                    display_relative(project_root, &path)
+                ),
+            );
         }
+        ProjectMode::Conan => log_info(
+            None,
+            "License texts are collected into THIRD-PARTY-NOTICES only in clear mode",
+        ),
+    }
+    Ok(())
+}
 
-        fn list_dependency_versions(&self, name: &str) -> Result<Vec<String>> {
-            self.available_versions_by_name
-                .get(name)
-                .cloned()
-                .ok_or_else(|| anyhow!("Пакет {name} не найден"))
+/// Прямые и транзитивные пакеты проекта; версии, подменённые политикой, сведены по пакетам.
+fn build_full_dependency_set(
+    provider: &dyn ConanProvider,
+    direct_refs: &[ConanRef],
+    policy: &ResolutionPolicy,
+) -> Result<ResolvedGraph> {
+    let mut all = direct_refs.to_vec();
+    let mut overrides: Vec<AppliedOverride> = Vec::new();
+
+    for direct in direct_refs {
+        // Прямую зависимость force не подменяет: её версию выбирает проект
+        if let Some(forced) = policy.forced.get(&direct.name)
+            && forced.version != direct.version
+        {
+            return Err(anyhow!(
+                "Прямая зависимость {}/{} противоречит версии {}, принудительно заданной {}\nУкажите её версию: aurora-conan-cli add {} {}",
+                direct.name,
+                direct.version,
+                forced.version,
+                forced.source,
+                direct.name,
+                forced.version
+            ));
+        }
+        let resolved =
+            provider.resolve_dependencies_without_conan(&direct.name, &direct.version, policy)?;
+        for item in resolved.overrides {
+            match overrides
+                .iter_mut()
+                .find(|existing| existing.name == item.name)
+            {
+                Some(existing) => {
+                    for raw in item.requested {
+                        if !existing.requested.contains(&raw) {
+                            existing.requested.push(raw);
+                        }
+                    }
+                    for raw in item.violated {
+                        if !existing.violated.contains(&raw) {
+                            existing.violated.push(raw);
+                        }
+                    }
+                }
+                None => overrides.push(item),
+            }
         }
+        for item in resolved.packages {
+            if item.version == "error" {
+                return Err(anyhow!(
+                    "Не удалось определить версию транзитивной зависимости {}",
+                    item.name
+                ));
+            }
 
-        fn download_dependency_archives(
-            &self,
-            package_name: &str,
-            version: &str,
-            destination_root: &Path,
-        ) -> Result<Vec<DownloadArtifact>> {
-            let versions = self
-                .available_versions_by_name
-                .get(package_name)
-                .ok_or_else(|| anyhow!("Пакет {package_name} не найден"))?;
-            if !versions.iter().any(|candidate| candidate == version) {
-                return Err(anyhow!("Версия {version} для {package_name} не найдена"));
+            if let Some(existing) = all.iter().find(|ref_item| ref_item.name == item.name) {
+                if existing.version != item.version {
+                    return Err(anyhow!(
+                        "Конфликт версий зависимости '{}': {} и {}\nЗакрепите версию: aurora-conan-cli override {} <version>",
+                        item.name,
+                        existing.version,
+                        item.version,
+                        item.name
+                    ));
+                }
+                continue;
             }
+            all.push(item);
+        }
+    }
 
-            let download_dir = destination_root
-                .join("downloads")
-                .join(package_name)
-                .join(version);
-            fs::create_dir_all(&download_dir)?;
+    all.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+    overrides.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(ResolvedGraph {
+        packages: all,
+        overrides,
+    })
+}
 
-            let armv8_file = download_dir.join(format!("{package_name}-{version}-armv8.tgz"));
-            create_test_tgz(&armv8_file, package_name, true)?;
-            let x86_64_file = download_dir.join(format!("{package_name}-{version}-x86_64.tgz"));
-            create_test_tgz(&x86_64_file, package_name, true)?;
+/// `override from <source>, requested: <ограничения рецептов>` для вывода `deps` и логов.
+fn describe_override(item: &AppliedOverride) -> String {
+    let mut text = format!("override from {}", item.source);
+    if !item.requested.is_empty() {
+        text.push_str(&format!(", requested: {}", item.requested.join(", ")));
+    }
+    text
+}
 
-            let package_file = download_dir.join(format!("{package_name}-{version}-package.tgz"));
-            create_test_tgz(&package_file, package_name, false)?;
+/// Пишет в лог подменённые версии; нарушение объявленных рецептами ограничений — предупреждение.
+fn log_overrides(overrides: &[AppliedOverride], main_progress: Option<&ProgressBar>) {
+    for item in overrides {
+        if item.violated.is_empty() {
+            log_info(
+                main_progress,
+                &format!(
+                    "{}/{}: {}",
+                    item.name,
+                    item.version,
+                    describe_override(item)
+                ),
+            );
+        } else {
+            log_warning(
+                main_progress,
+                &format!(
+                    "{}/{} from {} violates declared constraints: {}",
+                    item.name,
+                    item.version,
+                    item.source,
+                    item.violated.join(", ")
+                ),
+            );
+        }
+    }
+}
 
-            Ok(vec![
-                DownloadArtifact {
-                    arch: "armv8".to_string(),
-                    path: armv8_file,
-                },
-                DownloadArtifact {
-                    arch: "x86_64".to_string(),
-                    path: x86_64_file,
-                },
-                DownloadArtifact {
-                    arch: "package".to_string(),
-                    path: package_file,
-                },
-            ])
+/// Политика организации с переопределениями версий проекта; переопределение проекта
+/// главнее принудительной версии из политики.
+fn resolution_policy(
+    project_root: &Path,
+    mode: ProjectMode,
+    overrides: &[ConanRef],
+) -> Result<ResolutionPolicy> {
+    let mut policy = policy::load_org_policy(project_root)?;
+    let source = match mode {
+        ProjectMode::Conan => files::CONANFILE.to_string(),
+        ProjectMode::Clear => {
+            display_relative(project_root, &clear_store::manifest_path(project_root))
         }
+    };
+    for reference in overrides {
+        policy.forced.insert(
+            reference.name.clone(),
+            ForcedVersion {
+                version: reference.version.clone(),
+                user: reference.user.clone(),
+                source: source.clone(),
+            },
+        );
+    }
+    Ok(policy)
+}
 
-        fn resolve_direct_dependency(
-            &self,
-            _project_root: &Path,
-            name: &str,
-            version: Option<&str>,
-        ) -> Result<ConanRef> {
-            let version = match version {
-                Some(v) => v.to_string(),
-                None => self
-                    .latest_versions
-                    .get(name)
-                    .cloned()
-                    .ok_or_else(|| anyhow!("Не настроена версия для {name}"))?,
-            };
+/// Принудительные версии политики для `override=True` в conanfile.py: Conan применяет только
+/// записанные туда; переопределения проекта `write_conanfile` оставляет главнее них.
+fn policy_overrides(policy: &ResolutionPolicy) -> Vec<ConanRef> {
+    policy
+        .forced
+        .iter()
+        .map(|(name, forced)| ConanRef {
+            name: name.clone(),
+            version: forced.version.clone(),
+            user: forced.user.clone(),
+        })
+        .collect()
+}
 
-            Ok(ConanRef {
-                name: name.to_string(),
-                version,
-                user: "aurora".to_string(),
+/// Упорядочивает пакеты так, чтобы зависимые шли раньше своих зависимостей,
+/// как того требует порядок библиотек при линковке. Равноправные пакеты — по имени.
+fn order_dependents_first(
+    packages: Vec<VendoredPackage>,
+    dependencies: &BTreeMap<String, BTreeSet<String>>,
+) -> Vec<VendoredPackage> {
+    let mut remaining: BTreeMap<String, VendoredPackage> = packages
+        .into_iter()
+        .map(|package| (package.name.clone(), package))
+        .collect();
+    let mut ordered = Vec::with_capacity(remaining.len());
+
+    while !remaining.is_empty() {
+        // Пакет готов, если от него не зависит ни один из ещё не выведенных пакетов
+        let ready: Vec<String> = remaining
+            .keys()
+            .filter(|name| {
+                !remaining.keys().any(|other| {
+                    dependencies
+                        .get(other)
+                        .is_some_and(|deps| deps.contains(*name))
+                })
             })
+            .cloned()
+            .collect();
+        // Цикл зависимостей: выводим оставшиеся по имени
+        let ready = if ready.is_empty() {
+            remaining.keys().cloned().collect()
+        } else {
+            ready
+        };
+        for name in ready {
+            if let Some(package) = remaining.remove(&name) {
+                ordered.push(package);
+            }
         }
+    }
+    ordered
+}
 
-        fn resolve_dependencies_without_conan(
-            &self,
-            package_name: &str,
-            version: &str,
-        ) -> Result<Vec<ConanRef>> {
-            let key = format!("{package_name}/{version}");
-            self.dependencies_by_ref
-                .get(&key)
-                .cloned()
-                .ok_or_else(|| anyhow!("Зависимости для {key} не настроены"))
-        }
-
-        fn resolve_project_metadata(
-            &self,
-            _project_root: &Path,
-            direct_refs: &[ConanRef],
-        ) -> Result<ProjectMetadata> {
-            let key = Self::key(direct_refs);
-            self.metadata_by_names
-                .get(&key)
-                .cloned()
-                .ok_or_else(|| anyhow!("metadata не настроены для ключа {key}"))
-        }
+fn project_overrides(project_root: &Path, mode: ProjectMode) -> Result<Vec<ConanRef>> {
+    match mode {
+        ProjectMode::Conan => files::read_overrides(project_root),
+        ProjectMode::Clear => Ok(clear_store::load_manifest(project_root)?.overrides),
     }
+}
 
-    fn setup_project() -> Result<(TempDir, FakeProvider)> {
-        let temp = tempfile::tempdir()?;
-        fs::create_dir_all(temp.path().join("rpm"))?;
+/// Проверяет сборочную систему и возвращает .spec, которые нужно обновлять.
+fn ensure_project_files_exist(
+    project_root: &Path,
+    explicit_specs: &[PathBuf],
+) -> Result<Vec<SpecConfig>> {
+    files::detect_build_system(project_root)?;
+    let configured = mode::load_specs(project_root)?;
+    files::resolve_specs(project_root, explicit_specs, &configured)
+}
 
-        fs::write(
-            temp.path().join("CMakeLists.txt"),
-            r#"cmake_minimum_required(VERSION 3.5)
-project(ru.auroraos.TestApp CXX)
+/// Проверяет `%files` всех .spec до загрузки пакетов и правки файлов проекта.
+fn check_spec_layouts(project_root: &Path, specs: &[SpecConfig], mode: ProjectMode) -> Result<()> {
+    for spec in specs {
+        files::check_spec_layout(project_root, spec, mode == ProjectMode::Clear)?;
+    }
+    Ok(())
+}
 
-add_executable(${PROJECT_NAME} src/main.cpp)
-"#,
-        )?;
+fn upsert_reference(references: &mut Vec<ConanRef>, new_ref: ConanRef) {
+    if let Some(existing) = references.iter_mut().find(|item| item.name == new_ref.name) {
+        *existing = new_ref;
+    } else {
+        references.push(new_ref);
+    }
 
-        fs::write(
-            temp.path().join("rpm").join("ru.auroraos.TestApp.spec"),
-            r#"Name:       ru.auroraos.TestApp
-Summary:    Test app
-Version:    0.1
-Release:    1
-License:    BSD-3-Clause
-URL:        https://auroraos.ru
-Source0:    %{name}-%{version}.tar.bz2
+    references.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+}
 
-BuildRequires:  pkgconfig(Qt5Core)
+fn apply_conan_changes(
+    project_root: &Path,
+    specs: &[SpecConfig],
+    metadata: &ProjectMetadata,
+) -> Result<()> {
+    let changed = match files::detect_build_system(project_root)? {
+        BuildSystem::CMake => files::update_cmake(project_root, metadata)?,
+        BuildSystem::Qmake(pro_path) => files::update_qmake(project_root, &pro_path, metadata)?,
+    };
+    report_changed_files(project_root, &changed);
+    report_system_lib_requires(project_root, &metadata.system_libs)?;
+    report_recipe_warnings(&metadata.recipe_warnings);
+    for spec in specs {
+        files::update_spec(project_root, spec, metadata)?;
+    }
+    Ok(())
+}
 
-%description
-Test app
+fn apply_clear_changes(
+    project_root: &Path,
+    specs: &[SpecConfig],
+    metadata: &ProjectMetadata,
+) -> Result<()> {
+    let changed = match files::detect_build_system(project_root)? {
+        BuildSystem::CMake => files::update_cmake_clear(project_root, metadata)?,
+        BuildSystem::Qmake(pro_path) => {
+            files::update_qmake_clear(project_root, &pro_path, metadata)?
+        }
+    };
+    report_changed_files(project_root, &changed);
+    report_system_lib_requires(project_root, &metadata.system_libs)?;
+    report_recipe_warnings(&metadata.recipe_warnings);
+    licenses::write_notices(project_root, &metadata.package_licenses)?;
+    for spec in specs {
+        files::update_spec_clear(project_root, spec, metadata)?;
+    }
+    Ok(())
+}
 
-%prep
-%autosetup
+fn display_relative(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .display()
+        .to_string()
+}
 
-%build
-%cmake
-%make_build
+fn report_changed_files(project_root: &Path, changed: &[PathBuf]) {
+    for path in changed {
+        let relative = path.strip_prefix(project_root).unwrap_or(path);
+        log_info(None, &format!("Updated {}", relative.display()));
+    }
+}
 
-%install
-%make_install
+/// Сообщает, какие `system_libs` покрыты glibc и для каких нет `Requires:`.
+fn report_system_lib_requires(project_root: &Path, system_libs: &[String]) -> Result<()> {
+    let resolved = files::resolve_system_lib_requires(project_root, system_libs)?;
+    if !resolved.glibc.is_empty() {
+        log_info(
+            None,
+            &format!(
+                "System libs covered by glibc: {}",
+                resolved.glibc.join(", ")
+            ),
+        );
+    }
+    for lib in &resolved.unavailable {
+        log_warning(
+            None,
+            &format!("System lib {} is not available on Aurora OS", lib),
+        );
+    }
+    for lib in &resolved.unmapped {
+        log_warning(
+            None,
+            &format!(
+                "No Requires mapping for system lib {}: add it to thirdparty/aurora/system-lib-requires.txt",
+                lib
+            ),
+        );
+    }
+    Ok(())
+}
 
-%files
-%{_bindir}/%{name}
-"#,
-        )?;
+/// Перечисляет конструкции рецептов, которые разбор не понял: их метаданные могут быть неполными.
+fn report_recipe_warnings(warnings: &[String]) {
+    for warning in warnings {
+        log_warning(
+            None,
+            &format!("Recipe construct not understood: {}", warning),
+        );
+    }
+}
 
-        let provider = FakeProvider {
-            latest_versions: HashMap::from([
-                ("ffmpeg".to_string(), "6.1.1".to_string()),
-                ("a".to_string(), "1.0.0".to_string()),
-                ("c".to_string(), "1.0.0".to_string()),
-            ]),
-            metadata_by_names: HashMap::from([
-                (
-                    "".to_string(),
-                    ProjectMetadata {
-                        direct_pkg_modules: Vec::new(),
-                        shared_lib_patterns: Vec::new(),
-                        system_libs: Vec::new(),
-                    },
-                ),
+fn create_progress_bar(total: u64, message: impl Into<String>) -> ProgressBar {
+    let progress = ProgressBar::new(total);
+    if !io::stderr().is_terminal() {
+        progress.set_draw_target(ProgressDrawTarget::hidden());
+    }
+    let style = ProgressStyle::with_template(
+        "[{elapsed_precise}] [{bar:40.cyan/blue}] {pos:>3}/{len:3} {msg}",
+    )
+    .unwrap_or_else(|_| ProgressStyle::default_bar())
+    .progress_chars("=>-");
+    progress.set_style(style);
+    progress.set_message(message.into());
+    progress
+}
+
+fn progress_step(progress: &ProgressBar, message: &str) {
+    progress.inc(1);
+    progress.set_message(message.to_string());
+    log_info(Some(progress), message);
+}
+
+fn mode_label(mode: ProjectMode) -> &'static str {
+    match mode {
+        ProjectMode::Conan => "conan",
+        ProjectMode::Clear => "clear",
+    }
+}
+
+fn version_suffix(version: Option<&str>) -> String {
+    version.map_or_else(String::new, |value| format!(" {}", value))
+}
+
+#[derive(Clone, Copy)]
+enum LogLevel {
+    Info,
+    Warning,
+    Success,
+}
+
+fn log_info(progress: Option<&ProgressBar>, message: &str) {
+    log_with_level(progress, LogLevel::Info, message);
+}
+
+fn log_warning(progress: Option<&ProgressBar>, message: &str) {
+    log_with_level(progress, LogLevel::Warning, message);
+}
+
+fn log_success(progress: Option<&ProgressBar>, message: &str) {
+    log_with_level(progress, LogLevel::Success, message);
+}
+
+fn log_with_level(progress: Option<&ProgressBar>, level: LogLevel, message: &str) {
+    let prefix = style("[aurora-conan-cli]").dim().bold().to_string();
+    let level_tag = match level {
+        LogLevel::Info => style("INFO").cyan().bold().to_string(),
+        LogLevel::Warning => style("WARN").yellow().bold().to_string(),
+        LogLevel::Success => style("SUCCESS").green().bold().to_string(),
+    };
+    let line = format!("{prefix} {level_tag} {message}");
+
+    match progress {
+        Some(pb) => pb.suspend(|| eprintln!("{line}")),
+        None => eprintln!("{line}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+    use std::fs;
+    use std::fs::File;
+    use std::io::Cursor;
+    use std::path::Path;
+
+    use anyhow::{Result, anyhow};
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use tar::{Builder, Header};
+    use tempfile::TempDir;
+
+    use super::{CliCommand, build_full_dependency_set, locked_dependency_path, run};
+    use crate::audit;
+    use crate::clear_store;
+    use crate::conan::ConanProvider;
+    use crate::elf::tests::{EM_AARCH64, EM_X86_64, build_elf64};
+    use crate::files;
+    use crate::licenses;
+    use crate::mode;
+    use crate::model::{
+        AppliedOverride, BinaryOrigin, ConanRef, DownloadArtifact, LinkVisibility, Linkage,
+        ProjectMetadata, ResolvedGraph, SpecConfig, TargetAssignment,
+    };
+    use crate::policy::{ForcedVersion, ResolutionPolicy};
+    use crate::sbom::SbomFormat;
+
+    type FakeBinary = (String, Option<String>, Vec<String>);
+
+    struct FakeProvider {
+        latest_versions: HashMap<String, String>,
+        metadata_by_names: HashMap<String, ProjectMetadata>,
+        available_versions_by_name: HashMap<String, Vec<String>>,
+        dependencies_by_ref: HashMap<String, Vec<ConanRef>>,
+        /// Файлы `lib/` по пакетам: имя файла, SONAME и DT_NEEDED.
+        binaries_by_name: HashMap<String, Vec<FakeBinary>>,
+        /// Пакеты, у которых в armv8-архиве лежат x86_64-бинарники.
+        mislabelled_packages: Vec<String>,
+        /// Тексты `conanfile.py` по пакетам; без записи рецепт недоступен.
+        conanfiles: HashMap<String, String>,
+        /// Пакеты, запрещённые политикой: граф с ними не строится.
+        denied_packages: Vec<String>,
+        /// Дополнительные файлы бинарных архивов по пакетам: путь и содержимое.
+        packaged_files: HashMap<String, Vec<(String, String)>>,
+    }
+
+    impl FakeProvider {
+        fn key(refs: &[ConanRef]) -> String {
+            let mut names: Vec<String> = refs.iter().map(|r| r.name.clone()).collect();
+            names.sort();
+            names.join(",")
+        }
+    }
+
+    impl ConanProvider for FakeProvider {
+        fn search_dependencies(&self, query: &str) -> Result<Vec<ConanRef>> {
+            let query_norm = query.to_lowercase();
+            let mut names: Vec<String> = self
+                .available_versions_by_name
+                .keys()
+                .filter(|name| name.to_lowercase().contains(&query_norm))
+                .cloned()
+                .collect();
+            names.sort();
+
+            if names.is_empty() {
+                return Err(anyhow!("Пакеты по запросу '{query}' не найдены"));
+            }
+
+            let mut refs = Vec::new();
+            for name in names {
+                let versions = self
+                    .available_versions_by_name
+                    .get(&name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Пакет {name} не найден"))?;
+
+                for version in versions {
+                    refs.push(ConanRef {
+                        name: name.clone(),
+                        version,
+                        user: "aurora".to_string(),
+                    });
+                }
+            }
+
+            Ok(refs)
+        }
+
+        fn list_dependency_versions(&self, name: &str) -> Result<Vec<String>> {
+            self.available_versions_by_name
+                .get(name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Пакет {name} не найден"))
+        }
+
+        fn download_dependency_archives(
+            &self,
+            package_name: &str,
+            version: &str,
+            destination_root: &Path,
+        ) -> Result<Vec<DownloadArtifact>> {
+            let versions = self
+                .available_versions_by_name
+                .get(package_name)
+                .ok_or_else(|| anyhow!("Пакет {package_name} не найден"))?;
+            if !versions.iter().any(|candidate| candidate == version) {
+                return Err(anyhow!("Версия {version} для {package_name} не найдена"));
+            }
+
+            let download_dir = destination_root
+                .join("downloads")
+                .join(package_name)
+                .join(version);
+            fs::create_dir_all(&download_dir)?;
+
+            let binaries = self.binaries_by_name.get(package_name);
+            let packaged = self
+                .packaged_files
+                .get(package_name)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            let armv8_machine = if self.mislabelled_packages.iter().any(|p| p == package_name) {
+                EM_X86_64
+            } else {
+                EM_AARCH64
+            };
+            let armv8_file = download_dir.join(format!("{package_name}-{version}-armv8.tgz"));
+            create_test_tgz(
+                &armv8_file,
+                package_name,
+                true,
+                binaries.map(|items| (armv8_machine, items)),
+                packaged,
+            )?;
+            let x86_64_file = download_dir.join(format!("{package_name}-{version}-x86_64.tgz"));
+            create_test_tgz(
+                &x86_64_file,
+                package_name,
+                true,
+                binaries.map(|items| (EM_X86_64, items)),
+                packaged,
+            )?;
+
+            let package_file = download_dir.join(format!("{package_name}-{version}-package.tgz"));
+            create_test_tgz(&package_file, package_name, false, None, &[])?;
+
+            let origin = |package_id: &str| {
+                BinaryOrigin::from_url(&format!(
+                    "https://conan.example/{package_name}/{version}/_/rrev1/package/{package_id}/prev1/conan_package.tgz"
+                ))
+            };
+            Ok(vec![
+                DownloadArtifact {
+                    arch: "armv8".to_string(),
+                    shared: None,
+                    path: armv8_file,
+                    origin: origin("pid-armv8"),
+                },
+                DownloadArtifact {
+                    arch: "x86_64".to_string(),
+                    shared: None,
+                    path: x86_64_file,
+                    origin: origin("pid-x86_64"),
+                },
+                DownloadArtifact {
+                    arch: "package".to_string(),
+                    shared: None,
+                    path: package_file,
+                    origin: origin("pid-header"),
+                },
+            ])
+        }
+
+        fn resolve_direct_dependency(
+            &self,
+            _project_root: &Path,
+            name: &str,
+            version: Option<&str>,
+        ) -> Result<ConanRef> {
+            let version = match version {
+                Some(v) => v.to_string(),
+                None => self
+                    .latest_versions
+                    .get(name)
+                    .cloned()
+                    .ok_or_else(|| anyhow!("Не настроена версия для {name}"))?,
+            };
+
+            Ok(ConanRef {
+                name: name.to_string(),
+                version,
+                user: "aurora".to_string(),
+            })
+        }
+
+        fn fetch_conanfile(&self, package_name: &str, _version: &str) -> Result<String> {
+            self.conanfiles
+                .get(package_name)
+                .cloned()
+                .ok_or_else(|| anyhow!("Рецепт {package_name} не настроен"))
+        }
+
+        fn resolve_dependencies_without_conan(
+            &self,
+            package_name: &str,
+            version: &str,
+            policy: &ResolutionPolicy,
+        ) -> Result<ResolvedGraph> {
+            let key = format!("{package_name}/{version}");
+            if self.denied_packages.iter().any(|name| name == package_name) {
+                return Err(anyhow!("Пакет {key} запрещён политикой"));
+            }
+            let mut packages = self
+                .dependencies_by_ref
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| anyhow!("Зависимости для {key} не настроены"))?;
+            if let Some(denied) = packages
+                .iter()
+                .find(|reference| self.denied_packages.contains(&reference.name))
+            {
+                return Err(anyhow!(
+                    "Пакет {} запрещён политикой",
+                    denied.to_ref_string()
+                ));
+            }
+            let mut overrides = Vec::new();
+            for reference in &mut packages {
+                if let Some(forced) = policy.forced.get(&reference.name)
+                    && forced.version != reference.version
+                {
+                    overrides.push(AppliedOverride {
+                        name: reference.name.clone(),
+                        version: forced.version.clone(),
+                        source: forced.source.clone(),
+                        requested: vec![reference.to_ref_string()],
+                        violated: vec![reference.to_ref_string()],
+                    });
+                    reference.version = forced.version.clone();
+                }
+            }
+            Ok(ResolvedGraph {
+                packages,
+                overrides,
+            })
+        }
+
+        fn resolve_project_metadata(
+            &self,
+            _project_root: &Path,
+            direct_refs: &[ConanRef],
+            _policy: &ResolutionPolicy,
+        ) -> Result<ProjectMetadata> {
+            let key = Self::key(direct_refs);
+            self.metadata_by_names
+                .get(&key)
+                .cloned()
+                .ok_or_else(|| anyhow!("metadata не настроены для ключа {key}"))
+        }
+    }
+
+    fn setup_project() -> Result<(TempDir, FakeProvider)> {
+        let temp = tempfile::tempdir()?;
+        fs::create_dir_all(temp.path().join("rpm"))?;
+
+        fs::write(
+            temp.path().join("CMakeLists.txt"),
+            r#"cmake_minimum_required(VERSION 3.5)
+project(ru.auroraos.TestApp CXX)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+"#,
+        )?;
+
+        fs::write(
+            temp.path().join("rpm").join("ru.auroraos.TestApp.spec"),
+            r#"Name:       ru.auroraos.TestApp
+Summary:    Test app
+Version:    0.1
+Release:    1
+License:    BSD-3-Clause
+URL:        https://auroraos.ru
+Source0:    %{name}-%{version}.tar.bz2
+
+BuildRequires:  pkgconfig(Qt5Core)
+
+%description
+Test app
+
+%prep
+%autosetup
+
+%build
+%cmake
+%make_build
+
+%install
+%make_install
+
+%files
+%{_bindir}/%{name}
+"#,
+        )?;
+
+        let provider = FakeProvider {
+            latest_versions: HashMap::from([
+                ("ffmpeg".to_string(), "6.1.1".to_string()),
+                ("a".to_string(), "1.0.0".to_string()),
+                ("c".to_string(), "1.0.0".to_string()),
+            ]),
+            metadata_by_names: HashMap::from([
+                ("".to_string(), ProjectMetadata::default()),
                 (
                     "ffmpeg".to_string(),
                     ProjectMetadata {
@@ -849,7 +2848,7 @@ Test app
                             "libavcodec.*".to_string(),
                             "libavutil.*".to_string(),
                         ],
-                        system_libs: Vec::new(),
+                        ..Default::default()
                     },
                 ),
                 (
@@ -857,7 +2856,7 @@ Test app
                     ProjectMetadata {
                         direct_pkg_modules: vec!["a".to_string()],
                         shared_lib_patterns: vec!["liba.*".to_string(), "libb.*".to_string()],
-                        system_libs: Vec::new(),
+                        ..Default::default()
                     },
                 ),
                 (
@@ -869,334 +2868,1605 @@ Test app
                             "libb.*".to_string(),
                             "libc.*".to_string(),
                         ],
-                        system_libs: Vec::new(),
+                        package_lib_patterns: BTreeMap::from([
+                            (
+                                "a".to_string(),
+                                vec!["liba.*".to_string(), "libb.*".to_string()],
+                            ),
+                            ("c".to_string(), vec!["libc.*".to_string()]),
+                        ]),
+                        ..Default::default()
                     },
                 ),
-            ]),
-            available_versions_by_name: HashMap::from([
+            ]),
+            available_versions_by_name: HashMap::from([
+                (
+                    "ffmpeg".to_string(),
+                    vec!["6.1.1".to_string(), "6.1.0".to_string()],
+                ),
+                (
+                    "onnx".to_string(),
+                    vec!["1.16.0".to_string(), "1.15.0".to_string()],
+                ),
+                ("onnxruntime".to_string(), vec!["1.18.1".to_string()]),
+                ("ms-gsl".to_string(), vec!["4.0.0".to_string()]),
+                ("openssl".to_string(), vec!["3.2.0".to_string()]),
+            ]),
+            dependencies_by_ref: HashMap::from([
+                ("openssl/3.2.0".to_string(), Vec::new()),
+                ("ffmpeg/6.1.1".to_string(), Vec::new()),
+                ("a/1.0.0".to_string(), Vec::new()),
+                ("c/1.0.0".to_string(), Vec::new()),
+                (
+                    "onnxruntime/1.18.1".to_string(),
+                    vec![
+                        ConanRef {
+                            name: "onnx".to_string(),
+                            version: "1.16.0".to_string(),
+                            user: "aurora".to_string(),
+                        },
+                        ConanRef {
+                            name: "ms-gsl".to_string(),
+                            version: "4.0.0".to_string(),
+                            user: "aurora".to_string(),
+                        },
+                    ],
+                ),
+            ]),
+            binaries_by_name: HashMap::from([(
+                "openssl".to_string(),
+                vec![
+                    (
+                        "libssl.so.3".to_string(),
+                        Some("libssl.so.3".to_string()),
+                        vec!["libcrypto.so.3".to_string()],
+                    ),
+                    (
+                        "libcrypto.so.3".to_string(),
+                        Some("libcrypto.so.3".to_string()),
+                        vec!["libc.so.6".to_string()],
+                    ),
+                ],
+            )]),
+            mislabelled_packages: Vec::new(),
+            denied_packages: Vec::new(),
+            packaged_files: HashMap::new(),
+            conanfiles: HashMap::new(),
+        };
+
+        Ok((temp, provider))
+    }
+
+    fn setup_qmake_project() -> Result<(TempDir, FakeProvider)> {
+        let (temp, provider) = setup_project()?;
+        fs::remove_file(temp.path().join("CMakeLists.txt"))?;
+        fs::write(
+            temp.path().join("ru.auroraos.TestApp.pro"),
+            r#"TARGET = ru.auroraos.TestApp
+CONFIG += auroraapp
+
+SOURCES += src/main.cpp
+"#,
+        )?;
+        Ok((temp, provider))
+    }
+
+    fn create_test_tgz(
+        path: &Path,
+        package_name: &str,
+        with_lib: bool,
+        binaries: Option<(u16, &Vec<FakeBinary>)>,
+        packaged: &[(String, String)],
+    ) -> Result<()> {
+        let file = File::create(path)?;
+        let encoder = GzEncoder::new(file, Compression::default());
+        let mut tar = Builder::new(encoder);
+
+        append_bytes(
+            &mut tar,
+            format!("include/{package_name}.h").as_str(),
+            b"// test header\n",
+        )?;
+        if with_lib {
+            append_bytes(
+                &mut tar,
+                "licenses/LICENSE.txt",
+                format!("{package_name} license text\n").as_bytes(),
+            )?;
+            match binaries {
+                Some((machine, items)) => {
+                    for (name, soname, needed) in items {
+                        let needed: Vec<&str> = needed.iter().map(String::as_str).collect();
+                        let bytes = build_elf64(machine, soname.as_deref(), &needed);
+                        append_bytes(&mut tar, format!("lib/{name}").as_str(), &bytes)?;
+                    }
+                }
+                None => append_bytes(
+                    &mut tar,
+                    format!("lib/lib{package_name}.so").as_str(),
+                    b"binary-placeholder",
+                )?,
+            }
+            for (path, content) in packaged {
+                append_bytes(&mut tar, path, content.as_bytes())?;
+            }
+
+            let mut header = Header::new_gnu();
+            let script = b"#!/bin/sh\n";
+            header.set_size(script.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            tar.append_data(
+                &mut header,
+                format!("bin/{package_name}"),
+                Cursor::new(script),
+            )?;
+        }
+
+        tar.finish()?;
+        Ok(())
+    }
+
+    fn append_bytes<W: std::io::Write>(
+        tar: &mut Builder<W>,
+        path: &str,
+        bytes: &[u8],
+    ) -> Result<()> {
+        let mut header = Header::new_gnu();
+        header.set_size(bytes.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, path, Cursor::new(bytes))?;
+        Ok(())
+    }
+
+    #[test]
+    fn init_creates_conanfile_and_patches_templates() -> Result<()> {
+        let (project, provider) = setup_project()?;
+
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+
+        let conanfile = fs::read_to_string(project.path().join("conanfile.py"))?;
+        assert!(conanfile.contains("requires = ("));
+
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains("find_package(PkgConfig REQUIRED)"));
+        assert!(cmake.contains("set(CMAKE_INSTALL_RPATH"));
+
+        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
+        assert!(spec.contains("%define _cmake_skip_rpath %{nil}"));
+        assert!(spec.contains("BuildRequires:  conan"));
+        assert!(spec.contains("conan-install-if-modified"));
+        assert!(spec.contains("conan-deploy-libraries"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn init_clear_creates_manifest_and_clear_spec_snippets() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+
+        assert!(
+            project
+                .path()
+                .join("thirdparty/aurora/manifest.lock.json")
+                .exists()
+        );
+        assert!(!project.path().join("conanfile.py").exists());
+
+        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
+        assert!(!spec.contains("BuildRequires:  conan"));
+        assert!(spec.contains("THIRDPARTY_ROOT"));
+
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains("CMAKE_SYSTEM_PROCESSOR"));
+        assert!(cmake.contains("AURORA_TP_ARCH"));
+        assert!(cmake.contains("AURORA_TP_PKGCONFIG_DIR"));
+        Ok(())
+    }
+
+    #[test]
+    fn conan_mode_denied_add_leaves_conanfile_unchanged() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider
+            .metadata_by_names
+            .insert("openssl".to_string(), ProjectMetadata::default());
+        provider.denied_packages.push("ms-gsl".to_string());
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+        let add = |dependency: &str, version: &str| CliCommand::Add {
+            dependency: dependency.to_string(),
+            version: Some(version.to_string()),
+            targets: Vec::new(),
+            linkage: None,
+            tool: false,
+        };
+        run(&provider, project.path(), &[], add("openssl", "3.2.0"))?;
+        let conanfile = project.path().join("conanfile.py");
+        let before = fs::read_to_string(&conanfile)?;
+
+        let err = run(&provider, project.path(), &[], add("onnxruntime", "1.18.1"))
+            .expect_err("ms-gsl is denied");
+        assert!(format!("{err:#}").contains("ms-gsl/4.0.0@aurora запрещён"));
+        assert_eq!(fs::read_to_string(&conanfile)?, before);
+        Ok(())
+    }
+
+    #[test]
+    fn add_dependency_updates_conanfile_cmake_and_spec() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "ffmpeg".to_string(),
+                version: None,
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let refs = files::read_requires(project.path())?;
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].to_ref_string(), "ffmpeg/6.1.1@aurora");
+
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains("pkg_check_modules(FFMPEG REQUIRED IMPORTED_TARGET ffmpeg)"));
+        assert!(cmake.contains("PkgConfig::FFMPEG"));
+
+        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
+        assert!(spec.contains("libavcodec.*"));
+        assert!(spec.contains("libavutil.*"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn remove_dependency_keeps_shared_transitive_libs_from_remaining_direct_dep() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "a".to_string(),
+                version: None,
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "c".to_string(),
+                version: None,
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Remove {
+                dependency: "c".to_string(),
+            },
+        )?;
+
+        let refs = files::read_requires(project.path())?;
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].name, "a");
+
+        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
+        assert!(spec.contains("liba.*"));
+        assert!(spec.contains("libb.*"));
+        assert!(!spec.contains("libc.*"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn add_same_dependency_is_idempotent() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "ffmpeg".to_string(),
+                version: Some("6.1.1".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "ffmpeg".to_string(),
+                version: Some("6.1.1".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let refs = files::read_requires(project.path())?;
+        assert_eq!(refs.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn search_returns_versions_for_known_package() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Search {
+                dependency: "onnx".to_string(),
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn search_fails_for_unknown_package() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        let err = run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Search {
+                dependency: "unknown".to_string(),
+            },
+        )
+        .expect_err("expected search to fail for unknown package");
+
+        assert!(err.to_string().contains("не найден"));
+        Ok(())
+    }
+
+    #[test]
+    fn download_creates_artifact_for_selected_version() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Download {
+                dependency: "onnxruntime".to_string(),
+                version: "1.18.1".to_string(),
+            },
+        )?;
+
+        let artifact = project
+            .path()
+            .join("downloads/onnxruntime/1.18.1/onnxruntime-1.18.1-armv8.tgz");
+        assert!(artifact.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn deps_returns_dependencies_for_selected_package_version() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Deps {
+                dependency: "onnxruntime".to_string(),
+                version: "1.18.1".to_string(),
+            },
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn deps_fails_for_unknown_package_version() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        let err = run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Deps {
+                dependency: "unknown".to_string(),
+                version: "0.0.1".to_string(),
+            },
+        )
+        .expect_err("expected deps to fail for unknown package version");
+
+        assert!(err.to_string().contains("не настроены"));
+        Ok(())
+    }
+
+    #[test]
+    fn clear_sync_prefers_packaged_pc_and_cmake_files() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.conanfiles.insert(
+            "openssl".to_string(),
+            "class OpenSSLConan(ConanFile):\n    name = \"openssl\"\n\n    def package_info(self):\n        self.cpp_info.libs = [\"ssl\", \"crypto\"]\n"
+                .to_string(),
+        );
+        provider.packaged_files.insert(
+            "openssl".to_string(),
+            vec![
+                (
+                    "lib/pkgconfig/openssl.pc".to_string(),
+                    "prefix=/build/openssl\n\nName: OpenSSL-packaged\nRequires: libssl\n".to_string(),
+                ),
                 (
-                    "ffmpeg".to_string(),
-                    vec!["6.1.1".to_string(), "6.1.0".to_string()],
+                    "lib/pkgconfig/libssl.pc".to_string(),
+                    "prefix=/build/openssl\nlibdir=${prefix}/lib\n\nName: libssl\nLibs: -L${libdir} -lssl\n"
+                        .to_string(),
                 ),
                 (
-                    "onnx".to_string(),
-                    vec!["1.16.0".to_string(), "1.15.0".to_string()],
+                    "lib/cmake/openssl/opensslConfig.cmake".to_string(),
+                    "# packaged\n".to_string(),
                 ),
-                ("onnxruntime".to_string(), vec!["1.18.1".to_string()]),
-                ("ms-gsl".to_string(), vec!["4.0.0".to_string()]),
-            ]),
-            dependencies_by_ref: HashMap::from([(
-                "onnxruntime/1.18.1".to_string(),
-                vec![
-                    ConanRef {
-                        name: "onnx".to_string(),
-                        version: "1.16.0".to_string(),
-                        user: "aurora".to_string(),
-                    },
-                    ConanRef {
-                        name: "ms-gsl".to_string(),
-                        version: "4.0.0".to_string(),
-                        user: "aurora".to_string(),
-                    },
-                ],
-            )]),
+            ],
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let arch_root = project.path().join("thirdparty/aurora/armv8");
+        let pc = fs::read_to_string(arch_root.join("pkgconfig/openssl.pc"))?;
+        assert!(pc.contains("Name: OpenSSL-packaged\n"), "{pc}");
+        assert!(pc.contains("prefix=${pcfiledir}/../packages/openssl/3.2.0\n"));
+        let config = fs::read_to_string(arch_root.join("cmake/openssl/opensslConfig.cmake"))?;
+        assert!(config.contains("packaged config"), "{config}");
+        assert!(
+            !arch_root
+                .join("cmake/openssl/opensslConfigVersion.cmake")
+                .exists()
+        );
+
+        // Линкуются поставляемые модули, а не сгенерированные по рецепту
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains("pkg_check_modules(LIBSSL REQUIRED IMPORTED_TARGET libssl)"));
+        assert!(cmake.contains("pkg_check_modules(OPENSSL REQUIRED IMPORTED_TARGET openssl)"));
+        Ok(())
+    }
+
+    #[test]
+    fn clear_sync_evaluates_recipe_shared_option_for_linkage() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.conanfiles.insert(
+            "openssl".to_string(),
+            "class OpenSSLConan(ConanFile):\n    name = \"openssl\"\n    options = {\"shared\": [True, False]}\n    default_options = {\"shared\": False}\n\n    def package_info(self):\n        self.cpp_info.libs = [\"ssl\", \"crypto\"]\n        if self.options.shared:\n            self.cpp_info.system_libs = [\"dl\"]\n        else:\n            self.cpp_info.system_libs = [\"pthread\"]\n"
+                .to_string(),
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        let add = |linkage| CliCommand::Add {
+            dependency: "openssl".to_string(),
+            version: Some("3.2.0".to_string()),
+            targets: Vec::new(),
+            linkage,
+            tool: false,
         };
+        let pc_path = project
+            .path()
+            .join("thirdparty/aurora/armv8/pkgconfig/openssl.pc");
+
+        // По умолчанию линкуется shared-бинарник: ветка default_options не берётся
+        run(&provider, project.path(), &[], add(None))?;
+        let pc = fs::read_to_string(&pc_path)?;
+        assert!(pc.contains(" -ldl"), "{pc}");
+        assert!(!pc.contains("-lpthread"), "{pc}");
+
+        run(&provider, project.path(), &[], add(Some(Linkage::Static)))?;
+        let pc = fs::read_to_string(&pc_path)?;
+        assert!(pc.contains(" -lpthread"), "{pc}");
+        assert!(!pc.contains("-ldl"), "{pc}");
+        Ok(())
+    }
 
-        Ok((temp, provider))
+    #[test]
+    fn add_and_remove_dependency_in_clear_mode_manage_thirdparty_store() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "onnxruntime".to_string(),
+                version: Some("1.18.1".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let arch_root = project.path().join("thirdparty/aurora/armv8");
+        let pc = fs::read_to_string(arch_root.join("pkgconfig/onnxruntime.pc"))?;
+        assert!(pc.contains("Requires: onnx, ms-gsl\n"), "{pc}");
+        assert!(
+            arch_root
+                .join("packages/onnxruntime/1.18.1/lib/libonnxruntime.so")
+                .exists()
+        );
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Remove {
+                dependency: "onnxruntime".to_string(),
+            },
+        )?;
+
+        let manifest =
+            fs::read_to_string(project.path().join("thirdparty/aurora/manifest.lock.json"))?;
+        assert!(manifest.contains("\"direct_requires\": []"));
+        Ok(())
+    }
+
+    #[test]
+    fn tool_packages_are_vendored_for_host_and_kept_out_of_rpm() -> Result<()> {
+        let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: true,
+            },
+        )?;
+
+        let tool = "thirdparty/aurora/host/packages/openssl/3.2.0/bin/openssl";
+        assert!(project.path().join(tool).exists());
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains(&format!(
+            "set(AURORA_TOOL_OPENSSL \"${{CMAKE_CURRENT_SOURCE_DIR}}/{tool}\")"
+        )));
+        let manifest = clear_store::load_manifest(project.path())?;
+        assert!(manifest.direct_requires.is_empty());
+        assert_eq!(manifest.tools.len(), 1);
+        let install =
+            fs::read_to_string(clear_store::install_manifest_path(project.path(), "armv8"));
+        assert!(install.unwrap_or_default().is_empty());
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Remove {
+                dependency: "openssl".to_string(),
+            },
+        )?;
+        assert!(!project.path().join("thirdparty/aurora/host").exists());
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(!cmake.contains("AURORA_TOOL_OPENSSL"));
+        Ok(())
+    }
+
+    #[test]
+    fn clear_sync_writes_third_party_notices() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.conanfiles.insert(
+            "openssl".to_string(),
+            "class OpenSSLConan(ConanFile):\n    name = \"openssl\"\n    license = \"Apache 2.0\"\n"
+                .to_string(),
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        let notices = fs::read_to_string(licenses::notices_path(project.path()))?;
+        assert!(notices.contains("No third-party packages are bundled."));
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let notices = fs::read_to_string(licenses::notices_path(project.path()))?;
+        assert!(notices.contains("openssl 3.2.0\nSPDX-License-Identifier: Apache-2.0\n"));
+        assert!(notices.contains("----- licenses/LICENSE.txt -----\nopenssl license text\n"));
+        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
+        assert!(spec.contains(
+            "if [ -f \"$NOTICES\" ]; then install -m 0644 \"$NOTICES\" \"$LICENSES/\"; fi"
+        ));
+
+        run(&provider, project.path(), &[], CliCommand::Licenses)?;
+        let notices = fs::read_to_string(licenses::notices_path(project.path()))?;
+        assert!(notices.contains("SPDX-License-Identifier: Apache-2.0"));
+        Ok(())
+    }
+
+    #[test]
+    fn forced_version_conflicting_with_direct_requirement_names_policy() -> Result<()> {
+        let (_project, mut provider) = setup_project()?;
+        let direct = |name: &str, version: &str| ConanRef {
+            name: name.to_string(),
+            version: version.to_string(),
+            user: "aurora".to_string(),
+        };
+        let mut policy = ResolutionPolicy::default();
+        policy.forced.insert(
+            "ms-gsl".to_string(),
+            ForcedVersion {
+                version: "4.1.0".to_string(),
+                user: "aurora".to_string(),
+                source: "/etc/aurora/policy.txt".to_string(),
+            },
+        );
+
+        let direct_refs = [direct("ms-gsl", "4.0.0"), direct("onnxruntime", "1.18.1")];
+        let err = build_full_dependency_set(&provider, &direct_refs, &policy)
+            .expect_err("forced version contradicts direct requirement");
+        let message = err.to_string();
+        assert!(
+            message.contains(
+                "Прямая зависимость ms-gsl/4.0.0 противоречит версии 4.1.0, принудительно заданной /etc/aurora/policy.txt"
+            ),
+            "{message}"
+        );
+        assert!(message.contains("aurora-conan-cli add ms-gsl 4.1.0"));
+        assert!(!message.contains("override"));
+
+        // Прямая версия, совпадающая с force, согласуется с подменой у транзитивных
+        provider
+            .dependencies_by_ref
+            .insert("ms-gsl/4.1.0".to_string(), Vec::new());
+        let direct_refs = [direct("ms-gsl", "4.1.0"), direct("onnxruntime", "1.18.1")];
+        let graph = build_full_dependency_set(&provider, &direct_refs, &policy)?;
+        assert!(
+            graph
+                .packages
+                .iter()
+                .any(|item| item.name == "ms-gsl" && item.version == "4.1.0")
+        );
+        assert_eq!(graph.overrides.len(), 1);
+        assert_eq!(graph.overrides[0].source, "/etc/aurora/policy.txt");
+        Ok(())
+    }
+
+    #[test]
+    fn license_policy_blocks_add_until_package_is_waived() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.dependencies_by_ref.insert(
+            "onnx/1.16.0".to_string(),
+            vec![ConanRef {
+                name: "ms-gsl".to_string(),
+                version: "4.0.0".to_string(),
+                user: "aurora".to_string(),
+            }],
+        );
+        provider.conanfiles.insert(
+            "ms-gsl".to_string(),
+            "class GslConan(ConanFile):\n    license = \"GPL-3.0\"\n".to_string(),
+        );
+        for name in ["onnxruntime", "onnx"] {
+            provider.conanfiles.insert(
+                name.to_string(),
+                "class OnnxConan(ConanFile):\n    license = \"MIT\"\n".to_string(),
+            );
+        }
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        fs::write(licenses::policy_path(project.path()), "deny GPL-* AGPL-*\n")?;
+        let add = || CliCommand::Add {
+            dependency: "onnxruntime".to_string(),
+            version: Some("1.18.1".to_string()),
+            targets: Vec::new(),
+            linkage: None,
+            tool: false,
+        };
+
+        let err = run(&provider, project.path(), &[], add()).expect_err("GPL dependency is denied");
+        let message = format!("{err:#}");
+        assert!(message.contains("ms-gsl/4.0.0: GPL-3.0-only"));
+        assert!(message.contains("(onnxruntime/1.18.1 -> onnx/1.16.0 -> ms-gsl/4.0.0)"));
+        assert!(
+            clear_store::load_manifest(project.path())?
+                .direct_requires
+                .is_empty()
+        );
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Waive {
+                package: "ms-gsl".to_string(),
+                reason: "header-only, approved by legal".to_string(),
+            },
+        )?;
+        run(&provider, project.path(), &[], add())?;
+        let manifest = clear_store::load_manifest(project.path())?;
+        assert_eq!(manifest.direct_requires.len(), 1);
+        assert_eq!(
+            manifest.license_waivers.get("ms-gsl").map(String::as_str),
+            Some("header-only, approved by legal")
+        );
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Unwaive {
+                package: "ms-gsl".to_string(),
+            },
+        )?;
+        assert!(
+            clear_store::load_manifest(project.path())?
+                .license_waivers
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn conan_mode_add_checks_license_policy_and_waivers() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.dependencies_by_ref.insert(
+            "onnx/1.16.0".to_string(),
+            vec![ConanRef {
+                name: "ms-gsl".to_string(),
+                version: "4.0.0".to_string(),
+                user: "aurora".to_string(),
+            }],
+        );
+        provider.conanfiles.insert(
+            "ms-gsl".to_string(),
+            "class GslConan(ConanFile):\n    license = \"GPL-3.0\"\n".to_string(),
+        );
+        // У onnx нет рецепта: NOASSERTION не проходит политику из одних запретов
+        provider.conanfiles.insert(
+            "onnxruntime".to_string(),
+            "class OnnxRuntimeConan(ConanFile):\n    license = \"MIT\"\n".to_string(),
+        );
+        provider.metadata_by_names.insert(
+            "onnxruntime".to_string(),
+            ProjectMetadata {
+                direct_pkg_modules: vec!["onnxruntime".to_string()],
+                ..Default::default()
+            },
+        );
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+        fs::create_dir_all(clear_store::thirdparty_root(project.path()))?;
+        fs::write(licenses::policy_path(project.path()), "deny GPL-*\n")?;
+        let conanfile_before = fs::read_to_string(project.path().join(files::CONANFILE))?;
+        let add = || CliCommand::Add {
+            dependency: "onnxruntime".to_string(),
+            version: Some("1.18.1".to_string()),
+            targets: Vec::new(),
+            linkage: None,
+            tool: false,
+        };
+
+        let err = run(&provider, project.path(), &[], add()).expect_err("policy violation");
+        let message = format!("{err:#}");
+        assert!(message.contains("ms-gsl/4.0.0: GPL-3.0-only"));
+        assert!(message.contains("onnx/1.16.0: NOASSERTION"));
+        assert_eq!(
+            fs::read_to_string(project.path().join(files::CONANFILE))?,
+            conanfile_before
+        );
+
+        for package in ["ms-gsl", "onnx"] {
+            run(
+                &provider,
+                project.path(),
+                &[],
+                CliCommand::Waive {
+                    package: package.to_string(),
+                    reason: "approved by legal".to_string(),
+                },
+            )?;
+        }
+        run(&provider, project.path(), &[], add())?;
+        assert_eq!(files::read_requires(project.path())?.len(), 1);
+        assert_eq!(files::read_license_waivers(project.path())?.len(), 2);
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Unwaive {
+                package: "onnx".to_string(),
+            },
+        )?;
+        let waivers = files::read_license_waivers(project.path())?;
+        assert_eq!(waivers.keys().collect::<Vec<_>>(), vec!["ms-gsl"]);
+        assert_eq!(files::read_requires(project.path())?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn override_resolves_transitive_version_conflict() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.available_versions_by_name.insert(
+            "ms-gsl".to_string(),
+            vec!["4.1.0".to_string(), "4.0.0".to_string()],
+        );
+        provider.dependencies_by_ref.insert(
+            "openssl/3.2.0".to_string(),
+            vec![ConanRef {
+                name: "ms-gsl".to_string(),
+                version: "4.1.0".to_string(),
+                user: "aurora".to_string(),
+            }],
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        let add = |dependency: &str, version: &str| CliCommand::Add {
+            dependency: dependency.to_string(),
+            version: Some(version.to_string()),
+            targets: Vec::new(),
+            linkage: None,
+            tool: false,
+        };
+        let set_override = |package: &str, version: Option<&str>| CliCommand::Override {
+            package: package.to_string(),
+            version: version.map(ToString::to_string),
+        };
+        run(&provider, project.path(), &[], add("onnxruntime", "1.18.1"))?;
+
+        let err = run(&provider, project.path(), &[], add("openssl", "3.2.0"))
+            .expect_err("ms-gsl versions conflict");
+        let message = format!("{err:#}");
+        assert!(message.contains("Конфликт версий зависимости 'ms-gsl'"));
+        assert!(message.contains("aurora-conan-cli override ms-gsl <version>"));
+
+        run(
+            &provider,
+            project.path(),
+            &[],
+            set_override("ms-gsl", Some("4.1.0")),
+        )?;
+        run(&provider, project.path(), &[], add("openssl", "3.2.0"))?;
+        let manifest = clear_store::load_manifest(project.path())?;
+        assert_eq!(
+            manifest
+                .overrides
+                .iter()
+                .map(ConanRef::to_ref_string)
+                .collect::<Vec<_>>(),
+            vec!["ms-gsl/4.1.0@aurora"]
+        );
+        assert!(
+            manifest
+                .packages
+                .iter()
+                .any(|package| package.name == "ms-gsl" && package.version == "4.1.0")
+        );
+
+        let err = run(
+            &provider,
+            project.path(),
+            &[],
+            set_override("openssl", Some("3.2.0")),
+        )
+        .expect_err("direct dependency cannot be overridden");
+        assert!(err.to_string().contains("прямая зависимость проекта"));
+
+        // Без переопределения граф снова не собирается, и манифест остаётся прежним
+        run(&provider, project.path(), &[], set_override("ms-gsl", None))
+            .expect_err("conflict returns without override");
+        assert_eq!(clear_store::load_manifest(project.path())?, manifest);
+        Ok(())
+    }
+
+    #[test]
+    fn sbom_exports_synced_binaries_with_checksums() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.conanfiles.insert(
+            "openssl".to_string(),
+            "class OpenSSLConan(ConanFile):\n    license = \"Apache-2.0\"\n".to_string(),
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let manifest = clear_store::load_manifest(project.path())?;
+        let locked = &manifest.packages[0];
+        assert_eq!(locked.license, "Apache-2.0");
+        let armv8 = locked
+            .binaries
+            .iter()
+            .find(|binary| binary.arch == "armv8")
+            .expect("armv8 binary");
+        assert_eq!(armv8.origin.package_id.as_deref(), Some("pid-armv8"));
+        let archive = project
+            .path()
+            .join("downloads/openssl/3.2.0/openssl-3.2.0-armv8.tgz");
+        assert_eq!(armv8.sha256, clear_store::sha256_file(&archive)?);
+
+        let output = project.path().join("sbom.cdx.json");
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Sbom {
+                format: SbomFormat::CycloneDx,
+                output: Some(output.clone()),
+            },
+        )?;
+        let bom: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output)?)?;
+        assert_eq!(bom["metadata"]["component"]["name"], "ru.auroraos.TestApp");
+        assert_eq!(bom["dependencies"][0]["dependsOn"][0], "openssl/3.2.0");
+        let component = &bom["components"][0];
+        assert_eq!(
+            component["purl"],
+            "pkg:conan/openssl@3.2.0?user=aurora&rrev=rrev1"
+        );
+        assert!(
+            component["externalReferences"]
+                .as_array()
+                .is_some_and(|references| references
+                    .iter()
+                    .any(|item| item["hashes"][0]["content"] == armv8.sha256.as_str()))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sbom_in_conan_mode_resolves_binaries_through_provider() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.conanfiles.insert(
+            "openssl".to_string(),
+            "class OpenSSLConan(ConanFile):\n    license = \"Apache-2.0\"\n".to_string(),
+        );
+        provider
+            .metadata_by_names
+            .insert("openssl".to_string(), ProjectMetadata::default());
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let output = project.path().join("sbom.cdx.json");
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Sbom {
+                format: SbomFormat::CycloneDx,
+                output: Some(output.clone()),
+            },
+        )?;
+        let bom: serde_json::Value = serde_json::from_str(&fs::read_to_string(&output)?)?;
+        let component = &bom["components"][0];
+        assert_eq!(
+            component["purl"],
+            "pkg:conan/openssl@3.2.0?user=aurora&rrev=rrev1"
+        );
+        let references = component["externalReferences"]
+            .as_array()
+            .expect("binary references");
+        assert!(references.iter().any(|item| {
+            item["url"]
+                .as_str()
+                .is_some_and(|url| url.contains("pid-armv8"))
+                && item["hashes"][0]["content"]
+                    .as_str()
+                    .is_some_and(|hash| hash.len() == 64)
+        }));
+        assert!(!project.path().join("downloads").exists());
+
+        provider.available_versions_by_name.remove("openssl");
+        let err = run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Sbom {
+                format: SbomFormat::CycloneDx,
+                output: Some(output),
+            },
+        )
+        .expect_err("binaries are unavailable");
+        assert!(format!("{err:#}").contains("Не удалось получить бинарники пакета openssl"));
+        Ok(())
     }
 
-    fn create_test_tgz(path: &Path, package_name: &str, with_lib: bool) -> Result<()> {
-        let file = File::create(path)?;
-        let encoder = GzEncoder::new(file, Compression::default());
-        let mut tar = Builder::new(encoder);
+    #[test]
+    fn audit_fails_on_vulnerable_transitive_package() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.dependencies_by_ref.insert(
+            "onnx/1.16.0".to_string(),
+            vec![ConanRef {
+                name: "ms-gsl".to_string(),
+                version: "4.0.0".to_string(),
+                user: "aurora".to_string(),
+            }],
+        );
+        provider.available_versions_by_name.insert(
+            "ms-gsl".to_string(),
+            vec!["4.1.0".to_string(), "4.0.0".to_string()],
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "onnxruntime".to_string(),
+                version: Some("1.18.1".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+
+        let database = project.path().join("osv");
+        fs::create_dir_all(&database)?;
+        let audit = || CliCommand::Audit {
+            database: Some(database.clone()),
+        };
+        run(&provider, project.path(), &[], audit())?;
 
-        append_bytes(
-            &mut tar,
-            format!("include/{package_name}.h").as_str(),
-            b"// test header\n",
+        fs::write(
+            database.join("GSL-1.json"),
+            r#"{"id": "GSL-1", "summary": "Span bounds check",
+                "affected": [{"package": {"ecosystem": "ConanCenter", "name": "ms-gsl"},
+                  "ranges": [{"type": "ECOSYSTEM", "events": [{"introduced": "0"}, {"fixed": "4.1.0"}]}]}]}"#,
         )?;
-        if with_lib {
-            append_bytes(
-                &mut tar,
-                format!("lib/lib{package_name}.so").as_str(),
-                b"binary-placeholder",
-            )?;
-        }
-
-        tar.finish()?;
-        Ok(())
-    }
+        let err = run(&provider, project.path(), &[], audit()).expect_err("vulnerable ms-gsl");
+        assert!(err.to_string().contains("Найдено уязвимостей: 1"));
+        assert_eq!(
+            err.downcast_ref::<audit::FindingsError>(),
+            Some(&audit::FindingsError { count: 1 })
+        );
 
-    fn append_bytes<W: std::io::Write>(
-        tar: &mut Builder<W>,
-        path: &str,
-        bytes: &[u8],
-    ) -> Result<()> {
-        let mut header = Header::new_gnu();
-        header.set_size(bytes.len() as u64);
-        header.set_mode(0o644);
-        header.set_cksum();
-        tar.append_data(&mut header, path, Cursor::new(bytes))?;
+        let manifest = clear_store::load_manifest(project.path())?;
+        assert_eq!(
+            locked_dependency_path(&manifest.direct_requires, &manifest.packages, "ms-gsl"),
+            vec!["onnxruntime/1.18.1", "onnx/1.16.0", "ms-gsl/4.0.0"]
+        );
         Ok(())
     }
 
     #[test]
-    fn init_creates_conanfile_and_patches_templates() -> Result<()> {
+    fn clear_mode_excludes_exact_bundled_sonames() -> Result<()> {
         let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
 
-        run(&provider, project.path(), CliCommand::Init)?;
-
-        let conanfile = fs::read_to_string(project.path().join("conanfile.py"))?;
-        assert!(conanfile.contains("requires = ("));
-
-        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
-        assert!(cmake.contains("find_package(PkgConfig REQUIRED)"));
-        assert!(cmake.contains("set(CMAKE_INSTALL_RPATH"));
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
 
         let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
-        assert!(spec.contains("%define _cmake_skip_rpath %{nil}"));
-        assert!(spec.contains("BuildRequires:  conan"));
-        assert!(spec.contains("conan-install-if-modified"));
-        assert!(spec.contains("conan-deploy-libraries"));
+        assert!(spec.contains(
+            "%define __requires_exclude ^(libcrypto[.]so[.]3([(].*)?|libssl[.]so[.]3([(].*)?)$"
+        ));
+        assert!(!spec.contains("libopenssl"));
+
+        let config = fs::read_to_string(
+            project
+                .path()
+                .join("thirdparty/aurora/armv8/cmake/openssl/opensslConfig.cmake"),
+        )?;
+        assert!(config.contains("add_library(openssl::openssl INTERFACE IMPORTED)"));
+        assert!(config.contains("${_AURORA_TP_PREFIX}/lib/libssl.so.3"));
 
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains(
+            "set(CMAKE_PREFIX_PATH \"${AURORA_TP_ROOT}/${AURORA_TP_ARCH}/cmake\" ${CMAKE_PREFIX_PATH})"
+        ));
         Ok(())
     }
 
     #[test]
-    fn init_clear_creates_manifest_and_clear_spec_snippets() -> Result<()> {
-        let (project, provider) = setup_project()?;
-        run(&provider, project.path(), CliCommand::InitClear)?;
+    fn clear_mode_rejects_binaries_built_for_other_arch() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        provider.mislabelled_packages.push("openssl".to_string());
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
 
+        let err = run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )
+        .expect_err("expected arch mismatch");
+
+        let message = format!("{err:#}");
+        assert!(message.contains("openssl/3.2.0@aurora"));
+        assert!(message.contains("libssl.so.3") || message.contains("libcrypto.so.3"));
+        assert!(message.contains("armv8"));
         assert!(
-            project
+            !project
                 .path()
-                .join("thirdparty/aurora/manifest.lock.json")
+                .join("thirdparty/aurora/armv8/packages/openssl/3.2.0")
                 .exists()
         );
-        assert!(!project.path().join("conanfile.py").exists());
-
-        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
-        assert!(!spec.contains("BuildRequires:  conan"));
-        assert!(spec.contains("THIRDPARTY_ROOT"));
-
-        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
-        assert!(cmake.contains("CMAKE_SYSTEM_PROCESSOR"));
-        assert!(cmake.contains("AURORA_TP_ARCH"));
-        assert!(cmake.contains("AURORA_TP_PKGCONFIG_DIR"));
         Ok(())
     }
 
     #[test]
-    fn add_dependency_updates_conanfile_cmake_and_spec() -> Result<()> {
-        let (project, provider) = setup_project()?;
-        run(&provider, project.path(), CliCommand::Init)?;
+    fn failed_clear_sync_keeps_previous_manifest() -> Result<()> {
+        let (project, mut provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
+        for (dependency, version) in [("openssl", "3.2.0"), ("onnxruntime", "1.18.1")] {
+            run(
+                &provider,
+                project.path(),
+                &[],
+                CliCommand::Add {
+                    dependency: dependency.to_string(),
+                    version: Some(version.to_string()),
+                    targets: Vec::new(),
+                    linkage: None,
+                    tool: false,
+                },
+            )?;
+        }
+        let manifest_path = project.path().join("thirdparty/aurora/manifest.lock.json");
+        let before = fs::read_to_string(&manifest_path)?;
 
+        provider.mislabelled_packages.push("openssl".to_string());
         run(
             &provider,
             project.path(),
-            CliCommand::Add {
-                dependency: "ffmpeg".to_string(),
-                version: None,
+            &[],
+            CliCommand::Remove {
+                dependency: "onnxruntime".to_string(),
             },
-        )?;
-
-        let refs = files::read_requires(project.path())?;
-        assert_eq!(refs.len(), 1);
-        assert_eq!(refs[0].to_ref_string(), "ffmpeg/6.1.1@aurora");
-
-        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
-        assert!(cmake.contains("pkg_check_modules(FFMPEG REQUIRED IMPORTED_TARGET ffmpeg)"));
-        assert!(cmake.contains("PkgConfig::FFMPEG"));
-
-        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
-        assert!(spec.contains("libavcodec.*"));
-        assert!(spec.contains("libavutil.*"));
+        )
+        .expect_err("expected arch mismatch");
 
+        assert_eq!(fs::read_to_string(&manifest_path)?, before);
         Ok(())
     }
 
     #[test]
-    fn remove_dependency_keeps_shared_transitive_libs_from_remaining_direct_dep() -> Result<()> {
+    fn validate_accepts_clear_project_with_self_contained_bundle() -> Result<()> {
         let (project, provider) = setup_project()?;
-        run(&provider, project.path(), CliCommand::Init)?;
-
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
         run(
             &provider,
             project.path(),
+            &[],
             CliCommand::Add {
-                dependency: "a".to_string(),
-                version: None,
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
             },
         )?;
+
         run(
             &provider,
             project.path(),
-            CliCommand::Add {
-                dependency: "c".to_string(),
-                version: None,
-            },
+            &[],
+            CliCommand::Validate { allowed_libs: None },
         )?;
 
-        run(
+        let allowed = project.path().join("allowed.txt");
+        fs::write(&allowed, "libm.so.6\n")?;
+        let err = run(
             &provider,
             project.path(),
-            CliCommand::Remove {
-                dependency: "c".to_string(),
+            &[],
+            CliCommand::Validate {
+                allowed_libs: Some(allowed),
             },
-        )?;
+        )
+        .expect_err("libc.so.6 is not in the custom allowed list");
+        assert!(err.to_string().contains("нарушений"));
+        Ok(())
+    }
 
-        let refs = files::read_requires(project.path())?;
-        assert_eq!(refs.len(), 1);
-        assert_eq!(refs[0].name, "a");
+    #[test]
+    fn qmake_project_includes_generated_pri_in_conan_mode() -> Result<()> {
+        let (project, provider) = setup_qmake_project()?;
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+        for _ in 0..2 {
+            run(
+                &provider,
+                project.path(),
+                &[],
+                CliCommand::Add {
+                    dependency: "ffmpeg".to_string(),
+                    version: None,
+                    targets: Vec::new(),
+                    linkage: None,
+                    tool: false,
+                },
+            )?;
+        }
 
-        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
-        assert!(spec.contains("liba.*"));
-        assert!(spec.contains("libb.*"));
-        assert!(!spec.contains("libc.*"));
+        let pro = fs::read_to_string(project.path().join("ru.auroraos.TestApp.pro"))?;
+        assert_eq!(
+            pro.matches("include($$PWD/thirdparty/aurora/aurora-deps.pri)")
+                .count(),
+            1
+        );
+        assert!(!project.path().join("CMakeLists.txt").exists());
 
+        let pri = fs::read_to_string(project.path().join(files::QMAKE_DEPS_FILE))?;
+        assert!(pri.contains("CONFIG += link_pkgconfig"));
+        assert!(pri.contains("PKGCONFIG += ffmpeg"));
+        assert!(pri.contains("AURORA_CONAN_OUTPUT = $$OUT_PWD/conan-libs"));
+        assert!(pri.contains(
+            "PKG_CONFIG = PKG_CONFIG_PATH=$$AURORA_CONAN_OUTPUT:$$(PKG_CONFIG_PATH) $$PKG_CONFIG"
+        ));
+        assert!(pri.contains("QMAKE_RPATHDIR += /usr/share/$${TARGET}/lib"));
+
+        let spec = fs::read_to_string(project.path().join("rpm/ru.auroraos.TestApp.spec"))?;
+        assert!(spec.contains("libavcodec.*"));
         Ok(())
     }
 
     #[test]
-    fn add_same_dependency_is_idempotent() -> Result<()> {
-        let (project, provider) = setup_project()?;
-        run(&provider, project.path(), CliCommand::Init)?;
-
+    fn qmake_project_links_vendored_packages_in_clear_mode() -> Result<()> {
+        let (project, mut provider) = setup_qmake_project()?;
+        provider.conanfiles.insert(
+            "openssl".to_string(),
+            r#"class OpenSSLConan(ConanFile):
+    name = "openssl"
+
+    def package_info(self):
+        self.cpp_info.includedirs = ["include", "include/openssl"]
+        self.cpp_info.libs = ["ssl", "crypto"]
+"#
+            .to_string(),
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
         run(
             &provider,
             project.path(),
+            &[],
             CliCommand::Add {
-                dependency: "ffmpeg".to_string(),
-                version: Some("6.1.1".to_string()),
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
             },
         )?;
+
+        let pri = fs::read_to_string(project.path().join(files::QMAKE_DEPS_FILE))?;
+        assert!(pri.contains("AURORA_TP_ARCH = armv8"));
+        assert!(pri.contains(
+            "PKG_CONFIG = PKG_CONFIG_PATH=$$AURORA_TP_ROOT/$$AURORA_TP_ARCH/pkgconfig:$$(PKG_CONFIG_PATH) $$PKG_CONFIG"
+        ));
+        assert!(pri.contains("CONFIG += link_pkgconfig"));
+        assert!(pri.contains("PKGCONFIG += openssl"));
+        assert!(pri.contains(
+            "INCLUDEPATH += $$AURORA_TP_PACKAGES/openssl/3.2.0/include \
+             $$AURORA_TP_PACKAGES/openssl/3.2.0/include/openssl\n"
+        ));
+        assert!(
+            pri.contains("LIBS += -L$$AURORA_TP_PACKAGES/openssl/3.2.0/lib -lssl -lcrypto\n"),
+            "{pri}"
+        );
+
         run(
             &provider,
             project.path(),
-            CliCommand::Add {
-                dependency: "ffmpeg".to_string(),
-                version: Some("6.1.1".to_string()),
+            &[],
+            CliCommand::Remove {
+                dependency: "openssl".to_string(),
             },
         )?;
-
-        let refs = files::read_requires(project.path())?;
-        assert_eq!(refs.len(), 1);
-
+        let pri = fs::read_to_string(project.path().join(files::QMAKE_DEPS_FILE))?;
+        assert!(!pri.contains("openssl"));
+        let pro = fs::read_to_string(project.path().join("ru.auroraos.TestApp.pro"))?;
+        assert_eq!(pro.matches("aurora-deps.pri").count(), 1);
         Ok(())
     }
 
     #[test]
-    fn search_returns_versions_for_known_package() -> Result<()> {
-        let (project, provider) = setup_project()?;
+    fn qmake_clear_mode_lists_dependents_before_dependencies() -> Result<()> {
+        let (project, mut provider) = setup_qmake_project()?;
+        provider.binaries_by_name.insert(
+            "onnxruntime".to_string(),
+            vec![(
+                "libonnxruntime.so.1".to_string(),
+                Some("libonnxruntime.so.1".to_string()),
+                vec!["libonnx.so.1".to_string(), "libc.so.6".to_string()],
+            )],
+        );
+        provider.binaries_by_name.insert(
+            "onnx".to_string(),
+            vec![(
+                "libonnx.so.1".to_string(),
+                Some("libonnx.so.1".to_string()),
+                vec!["libc.so.6".to_string()],
+            )],
+        );
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
         run(
             &provider,
             project.path(),
-            CliCommand::Search {
-                dependency: "onnx".to_string(),
+            &[],
+            CliCommand::Add {
+                dependency: "onnxruntime".to_string(),
+                version: Some("1.18.1".to_string()),
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
             },
         )?;
+
+        let pri = fs::read_to_string(project.path().join(files::QMAKE_DEPS_FILE))?;
+        assert!(
+            pri.contains("PKGCONFIG += ms-gsl onnxruntime onnx\n"),
+            "{pri}"
+        );
         Ok(())
     }
 
     #[test]
-    fn search_fails_for_unknown_package() -> Result<()> {
+    fn add_with_target_links_dependency_to_that_target_only() -> Result<()> {
         let (project, provider) = setup_project()?;
-        let err = run(
+        run(&provider, project.path(), &[], CliCommand::Init)?;
+        run(
             &provider,
             project.path(),
-            CliCommand::Search {
-                dependency: "unknown".to_string(),
+            &[],
+            CliCommand::Add {
+                dependency: "ffmpeg".to_string(),
+                version: None,
+                targets: vec![TargetAssignment {
+                    target: "core".to_string(),
+                    visibility: LinkVisibility::Public,
+                }],
+                linkage: None,
+                tool: false,
             },
-        )
-        .expect_err("expected search to fail for unknown package");
+        )?;
 
-        assert!(err.to_string().contains("не найден"));
-        Ok(())
-    }
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains("target_link_libraries(core PUBLIC\n    PkgConfig::FFMPEG\n  )"));
+        assert!(!cmake.contains("target_link_libraries(${PROJECT_NAME} PRIVATE"));
+        assert_eq!(files::read_targets(project.path())?.len(), 1);
 
-    #[test]
-    fn download_creates_artifact_for_selected_version() -> Result<()> {
-        let (project, provider) = setup_project()?;
+        // Повторный add без --target сохраняет привязку
         run(
             &provider,
             project.path(),
-            CliCommand::Download {
-                dependency: "onnxruntime".to_string(),
-                version: "1.18.1".to_string(),
+            &[],
+            CliCommand::Add {
+                dependency: "ffmpeg".to_string(),
+                version: None,
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
             },
         )?;
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains("aurora-conan-cli:targets-core:begin"));
 
-        let artifact = project
-            .path()
-            .join("downloads/onnxruntime/1.18.1/onnxruntime-1.18.1-armv8.tgz");
-        assert!(artifact.exists());
+        run(
+            &provider,
+            project.path(),
+            &[],
+            CliCommand::Remove {
+                dependency: "ffmpeg".to_string(),
+            },
+        )?;
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(!cmake.contains("targets-core"));
+        assert!(files::read_targets(project.path())?.is_empty());
         Ok(())
     }
 
     #[test]
-    fn deps_returns_dependencies_for_selected_package_version() -> Result<()> {
+    fn clear_mode_stores_target_assignments_in_manifest() -> Result<()> {
         let (project, provider) = setup_project()?;
+        run(&provider, project.path(), &[], CliCommand::InitClear)?;
         run(
             &provider,
             project.path(),
-            CliCommand::Deps {
-                dependency: "onnxruntime".to_string(),
-                version: "1.18.1".to_string(),
+            &[],
+            CliCommand::Add {
+                dependency: "openssl".to_string(),
+                version: Some("3.2.0".to_string()),
+                targets: vec![TargetAssignment {
+                    target: "tests".to_string(),
+                    visibility: LinkVisibility::Private,
+                }],
+                linkage: None,
+                tool: false,
             },
         )?;
+
+        let manifest =
+            fs::read_to_string(project.path().join("thirdparty/aurora/manifest.lock.json"))?;
+        assert!(manifest.contains("\"target\": \"tests\""));
+        assert!(manifest.contains("\"visibility\": \"PRIVATE\""));
+
+        let cmake = fs::read_to_string(project.path().join("CMakeLists.txt"))?;
+        assert!(cmake.contains(
+            "if(TARGET tests)\n  target_link_libraries(tests PRIVATE\n    PkgConfig::OPENSSL"
+        ));
         Ok(())
     }
 
     #[test]
-    fn deps_fails_for_unknown_package_version() -> Result<()> {
+    fn configured_specs_get_their_own_dependency_subsets() -> Result<()> {
         let (project, provider) = setup_project()?;
-        let err = run(
+        let main_spec = project.path().join("rpm/ru.auroraos.TestApp.spec");
+        let tests_spec = project.path().join("rpm/ru.auroraos.TestApp-tests.spec");
+        fs::copy(&main_spec, &tests_spec)?;
+
+        let error = run(&provider, project.path(), &[], CliCommand::Init).unwrap_err();
+        assert!(error.to_string().contains("--spec"));
+
+        run(
             &provider,
             project.path(),
-            CliCommand::Deps {
-                dependency: "unknown".to_string(),
-                version: "0.0.1".to_string(),
-            },
-        )
-        .expect_err("expected deps to fail for unknown package version");
+            &[main_spec.clone(), tests_spec.clone()],
+            CliCommand::Init,
+        )?;
+        let mut specs = mode::load_specs(project.path())?;
+        assert_eq!(specs.len(), 2);
+        specs[1].packages = vec!["c".to_string()];
+        mode::save_specs(project.path(), &specs)?;
+
+        for dependency in ["a", "c"] {
+            run(
+                &provider,
+                project.path(),
+                &[],
+                CliCommand::Add {
+                    dependency: dependency.to_string(),
+                    version: None,
+                    targets: Vec::new(),
+                    linkage: None,
+                    tool: false,
+                },
+            )?;
+        }
 
-        assert!(err.to_string().contains("не настроены"));
+        assert_eq!(
+            files::read_spec_define(&main_spec, "__requires_exclude")?.as_deref(),
+            Some("^(liba.*|libb.*|libc.*)$")
+        );
+        assert_eq!(
+            files::read_spec_define(&tests_spec, "__requires_exclude")?.as_deref(),
+            Some("^(libc.*)$")
+        );
         Ok(())
     }
 
     #[test]
-    fn add_and_remove_dependency_in_clear_mode_manage_thirdparty_store() -> Result<()> {
+    fn add_with_spec_records_package_in_that_spec() -> Result<()> {
         let (project, provider) = setup_project()?;
-        run(&provider, project.path(), CliCommand::InitClear)?;
-
+        let main_spec = project.path().join("rpm/ru.auroraos.TestApp.spec");
+        let tests_spec = project.path().join("rpm/ru.auroraos.TestApp-tests.spec");
+        run(&provider, project.path(), &[], CliCommand::Init)?;
         run(
             &provider,
             project.path(),
+            &[],
             CliCommand::Add {
-                dependency: "onnxruntime".to_string(),
-                version: Some("1.18.1".to_string()),
+                dependency: "a".to_string(),
+                version: None,
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
+            },
+        )?;
+        fs::copy(&main_spec, &tests_spec)?;
+        run(
+            &provider,
+            project.path(),
+            std::slice::from_ref(&tests_spec),
+            CliCommand::Add {
+                dependency: "c".to_string(),
+                version: None,
+                targets: Vec::new(),
+                linkage: None,
+                tool: false,
             },
         )?;
+        let specs = mode::load_specs(project.path())?;
+        assert_eq!(
+            specs,
+            vec![
+                SpecConfig {
+                    path: "rpm/ru.auroraos.TestApp.spec".into(),
+                    packages: Vec::new(),
+                },
+                SpecConfig {
+                    path: "rpm/ru.auroraos.TestApp-tests.spec".into(),
+                    packages: vec!["c".to_string()],
+                },
+            ]
+        );
 
-        let arch_root = project.path().join("thirdparty/aurora/armv8");
-        assert!(arch_root.join("pkgconfig/onnxruntime.pc").exists());
-        assert!(
-            arch_root
-                .join("packages/onnxruntime/1.18.1/lib/libonnxruntime.so")
-                .exists()
+        assert_eq!(
+            files::read_spec_define(&main_spec, "__requires_exclude")?.as_deref(),
+            Some("^(liba.*|libb.*|libc.*)$")
+        );
+        assert_eq!(
+            files::read_spec_define(&tests_spec, "__requires_exclude")?.as_deref(),
+            Some("^(libc.*)$")
         );
 
         run(
             &provider,
             project.path(),
+            &[],
             CliCommand::Remove {
-                dependency: "onnxruntime".to_string(),
+                dependency: "c".to_string(),
             },
         )?;
-
-        let manifest =
-            fs::read_to_string(project.path().join("thirdparty/aurora/manifest.lock.json"))?;
-        assert!(manifest.contains("\"direct_requires\": []"));
+        let specs = mode::load_specs(project.path())?;
+        assert_eq!(specs.len(), 1);
+        assert!(specs[0].packages.is_empty());
+        assert_eq!(
+            files::read_spec_define(&tests_spec, "__requires_exclude")?.as_deref(),
+            Some("^$")
+        );
         Ok(())
     }
 }
@@ -0,0 +1,413 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use serde_json::Value;
+
+/// Экосистема OSV, в которой публикуются уязвимости пакетов Conan.
+const CONAN_ECOSYSTEM: &str = "ConanCenter";
+
+/// Код завершения `audit`, если найдены уязвимости: отличает находки от прочих ошибок (код 1).
+pub const FINDINGS_EXIT_CODE: i32 = 3;
+
+/// Ошибка `audit` о найденных уязвимостях; `main` завершается с [`FINDINGS_EXIT_CODE`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FindingsError {
+    pub count: usize,
+}
+
+impl fmt::Display for FindingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Найдено уязвимостей: {}", self.count)
+    }
+}
+
+impl std::error::Error for FindingsError {}
+
+/// Запись OSV: идентификатор, описание, серьёзность и затронутые пакеты.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Advisory {
+    pub id: String,
+    pub aliases: Vec<String>,
+    pub summary: String,
+    /// `CRITICAL`/`HIGH`/... из `database_specific`, иначе CVSS-вектор, иначе `UNKNOWN`.
+    pub severity: String,
+    affected: Vec<AffectedPackage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct AffectedPackage {
+    name: String,
+    /// Перечисленные явно затронутые версии.
+    versions: Vec<String>,
+    /// События диапазонов `ECOSYSTEM`/`SEMVER` в порядке записи.
+    ranges: Vec<Vec<RangeEvent>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RangeEvent {
+    Introduced(String),
+    Fixed(String),
+    LastAffected(String),
+}
+
+impl Advisory {
+    /// Затрагивает ли запись версию пакета.
+    pub fn affects(&self, name: &str, version: &str) -> bool {
+        self.packages(name)
+            .any(|affected| affected.contains(version))
+    }
+
+    /// Версии из событий `fixed` для пакета.
+    pub fn fixed_versions(&self, name: &str) -> Vec<String> {
+        let mut fixed: Vec<String> = self
+            .packages(name)
+            .flat_map(|affected| affected.ranges.iter().flatten())
+            .filter_map(|event| match event {
+                RangeEvent::Fixed(version) => Some(version.clone()),
+                _ => None,
+            })
+            .collect();
+        fixed.sort_by(|a, b| compare_versions(a, b));
+        fixed.dedup();
+        fixed
+    }
+
+    fn packages<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a AffectedPackage> {
+        self.affected
+            .iter()
+            .filter(move |affected| affected.name.eq_ignore_ascii_case(name))
+    }
+}
+
+impl AffectedPackage {
+    fn contains(&self, version: &str) -> bool {
+        if self.versions.iter().any(|item| item == version) {
+            return true;
+        }
+        self.ranges.iter().any(|events| {
+            let mut introduced: Option<&str> = None;
+            for event in events {
+                match event {
+                    RangeEvent::Introduced(start) => introduced = Some(start),
+                    RangeEvent::Fixed(end) => {
+                        if introduced.take().is_some_and(|start| {
+                            at_least(version, start) && compare_versions(version, end).is_lt()
+                        }) {
+                            return true;
+                        }
+                    }
+                    RangeEvent::LastAffected(end) => {
+                        if introduced.take().is_some_and(|start| {
+                            at_least(version, start) && compare_versions(version, end).is_le()
+                        }) {
+                            return true;
+                        }
+                    }
+                }
+            }
+            // Диапазон без верхней границы
+            introduced.is_some_and(|start| at_least(version, start))
+        })
+    }
+}
+
+fn at_least(version: &str, start: &str) -> bool {
+    start == "0" || compare_versions(version, start).is_ge()
+}
+
+/// Сравнивает версии по сегментам через `.`: числовые префиксы — как числа, остаток — как строки.
+/// Часть после `-` — пререлиз, он идёт раньше релиза (`1.0.0-rc1` < `1.0.0`); метаданные сборки
+/// после `+` не учитываются.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let split = |version: &str| {
+        let version = version.split_once('+').map_or(version, |(core, _)| core);
+        match version.split_once('-') {
+            Some((release, prerelease)) => (release.to_string(), Some(prerelease.to_string())),
+            None => (version.to_string(), None),
+        }
+    };
+    let (a_release, a_prerelease) = split(a);
+    let (b_release, b_prerelease) = split(b);
+    compare_dotted(&a_release, &b_release).then_with(|| match (a_prerelease, b_prerelease) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(x), Some(y)) => compare_dotted(&x, &y),
+    })
+}
+
+fn compare_dotted(a: &str, b: &str) -> Ordering {
+    let mut left = a.split('.');
+    let mut right = b.split('.');
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            // Недостающие хвостовые сегменты считаются нулями: 3.0 == 3.0.0
+            (x, y) => {
+                let ordering = compare_segments(x.unwrap_or("0"), y.unwrap_or("0"));
+                if ordering.is_ne() {
+                    return ordering;
+                }
+            }
+        }
+    }
+}
+
+fn compare_segments(a: &str, b: &str) -> Ordering {
+    let split = |segment: &str| {
+        let digits = segment
+            .find(|ch: char| !ch.is_ascii_digit())
+            .unwrap_or(segment.len());
+        (
+            segment[..digits].parse::<u64>().ok(),
+            segment[digits..].to_string(),
+        )
+    };
+    let (a_number, a_rest) = split(a);
+    let (b_number, b_rest) = split(b);
+    a_number.cmp(&b_number).then_with(|| a_rest.cmp(&b_rest))
+}
+
+/// Читает все `*.json` каталога OSV (рекурсивно); записи без пакетов Conan отбрасываются.
+pub fn load_advisories(database: &Path) -> Result<Vec<Advisory>> {
+    if !database.is_dir() {
+        return Err(anyhow!(
+            "Каталог базы уязвимостей {} не найден",
+            database.display()
+        ));
+    }
+
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut pending = vec![database.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Не удалось прочитать {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "json") {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+
+    let mut advisories = Vec::new();
+    for path in files {
+        let text = fs::read_to_string(&path)
+            .with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+        let payload: Value = serde_json::from_str(&text)
+            .with_context(|| format!("Некорректный JSON в {}", path.display()))?;
+        let advisory = parse_advisory(&payload);
+        if !advisory.id.is_empty() && !advisory.affected.is_empty() {
+            advisories.push(advisory);
+        }
+    }
+    Ok(advisories)
+}
+
+fn parse_advisory(payload: &Value) -> Advisory {
+    let text = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let specific_severity = |value: &Value| {
+        value
+            .get("database_specific")
+            .or_else(|| value.get("ecosystem_specific"))
+            .and_then(|item| item.get("severity"))
+            .and_then(Value::as_str)
+            .map(str::to_ascii_uppercase)
+    };
+
+    let affected: Vec<(AffectedPackage, Option<String>)> = payload
+        .get("affected")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|item| {
+            let package = item.get("package")?;
+            let ecosystem = text(package.get("ecosystem"));
+            let purl = text(package.get("purl"));
+            let name = if let Some(rest) = purl.strip_prefix("pkg:conan/") {
+                rest.split(['@', '?', '#'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            } else if ecosystem.eq_ignore_ascii_case(CONAN_ECOSYSTEM) {
+                text(package.get("name"))
+            } else {
+                return None;
+            };
+            let versions = item
+                .get("versions")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .map(ToString::to_string)
+                .collect();
+            let ranges = item
+                .get("ranges")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                // В GIT-диапазонах коммиты, а не версии
+                .filter(|range| {
+                    matches!(
+                        range.get("type").and_then(Value::as_str),
+                        Some("ECOSYSTEM" | "SEMVER")
+                    )
+                })
+                .map(|range| {
+                    range
+                        .get("events")
+                        .and_then(Value::as_array)
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|event| {
+                            if let Some(version) = event.get("introduced").and_then(Value::as_str) {
+                                Some(RangeEvent::Introduced(version.to_string()))
+                            } else if let Some(version) = event.get("fixed").and_then(Value::as_str)
+                            {
+                                Some(RangeEvent::Fixed(version.to_string()))
+                            } else {
+                                event
+                                    .get("last_affected")
+                                    .and_then(Value::as_str)
+                                    .map(|version| RangeEvent::LastAffected(version.to_string()))
+                            }
+                        })
+                        .collect()
+                })
+                .collect();
+            Some((
+                AffectedPackage {
+                    name,
+                    versions,
+                    ranges,
+                },
+                specific_severity(item),
+            ))
+        })
+        .collect();
+
+    let cvss = payload
+        .get("severity")
+        .and_then(Value::as_array)
+        .and_then(|items| items.first())
+        .map(|item| text(item.get("score")))
+        .filter(|score| !score.is_empty());
+    let severity = specific_severity(payload)
+        .or_else(|| affected.iter().find_map(|(_, severity)| severity.clone()))
+        .or(cvss)
+        .unwrap_or_else(|| "UNKNOWN".to_string());
+
+    Advisory {
+        id: text(payload.get("id")),
+        aliases: payload
+            .get("aliases")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+            .map(ToString::to_string)
+            .collect(),
+        summary: text(payload.get("summary")),
+        severity,
+        affected: affected.into_iter().map(|(package, _)| package).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use super::{compare_versions, load_advisories};
+
+    #[test]
+    fn compares_numeric_and_suffixed_versions() {
+        assert_eq!(compare_versions("1.10.0", "1.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.1.1w", "1.1.1t"), Ordering::Greater);
+        assert_eq!(compare_versions("1.1.1", "1.1.1a"), Ordering::Less);
+        assert_eq!(compare_versions("3.0", "3.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("3.0", "3.0.1"), Ordering::Less);
+        assert_eq!(compare_versions("1.3.1", "1.3.1"), Ordering::Equal);
+    }
+
+    #[test]
+    fn orders_prereleases_before_release() {
+        assert_eq!(compare_versions("1.0.0-rc1", "1.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("1.0.0", "1.0.0-rc1"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-rc1", "1.0.0-rc2"), Ordering::Less);
+        assert_eq!(
+            compare_versions("1.0.0-rc.10", "1.0.0-rc.9"),
+            Ordering::Greater
+        );
+        assert_eq!(compare_versions("1.0.0-rc1", "0.9.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0+build.5", "1.0.0"), Ordering::Equal);
+    }
+
+    #[test]
+    fn matches_conan_ranges_from_osv_directory() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("openssl"))?;
+        fs::write(
+            dir.path().join("openssl/OSV-2024-1.json"),
+            r#"{
+  "id": "OSV-2024-1",
+  "aliases": ["CVE-2024-0001"],
+  "summary": "Overflow in X.509 parsing",
+  "database_specific": {"severity": "high"},
+  "affected": [
+    {"package": {"ecosystem": "ConanCenter", "name": "openssl"},
+     "ranges": [{"type": "ECOSYSTEM", "events": [
+        {"introduced": "3.0.0"}, {"fixed": "3.0.13"},
+        {"introduced": "3.2.0"}, {"fixed": "3.2.1"}]}]},
+    {"package": {"ecosystem": "npm", "name": "zlib"},
+     "ranges": [{"type": "SEMVER", "events": [{"introduced": "0"}]}]}
+  ]
+}"#,
+        )?;
+        fs::write(
+            dir.path().join("zlib.json"),
+            r#"{
+  "id": "OSV-2024-2",
+  "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"}],
+  "affected": [
+    {"package": {"purl": "pkg:conan/zlib"}, "versions": ["1.2.11"],
+     "ranges": [{"type": "SEMVER", "events": [{"introduced": "1.2.12"}, {"last_affected": "1.2.13"}]}]}
+  ]
+}"#,
+        )?;
+
+        let advisories = load_advisories(dir.path())?;
+        assert_eq!(advisories.len(), 2);
+        let openssl = &advisories[0];
+        assert_eq!(openssl.severity, "HIGH");
+        assert_eq!(openssl.aliases, vec!["CVE-2024-0001"]);
+        assert!(openssl.affects("openssl", "3.2.0"));
+        assert!(openssl.affects("openssl", "3.0.12"));
+        assert!(!openssl.affects("openssl", "3.0.13"));
+        assert!(!openssl.affects("openssl", "3.1.4"));
+        assert!(!openssl.affects("zlib", "1.3.1"));
+        assert_eq!(openssl.fixed_versions("openssl"), vec!["3.0.13", "3.2.1"]);
+
+        let zlib = &advisories[1];
+        assert!(zlib.severity.starts_with("CVSS:3.1/"));
+        assert!(zlib.affects("zlib", "1.2.11"));
+        assert!(zlib.affects("zlib", "1.2.13"));
+        assert!(!zlib.affects("zlib", "1.3"));
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Cursor;
 use std::path::{Path, PathBuf};
@@ -5,17 +6,41 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result, anyhow};
 use flate2::read::GzDecoder;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tar::Archive;
 
-use crate::model::{ConanRef, DownloadArtifact};
+use crate::elf;
+use crate::model::{
+    ComponentInfo, ConanRef, CppBuildInfo, DownloadArtifact, Linkage, LockedPackage,
+    PackageCppInfo, TargetAssignment,
+};
 
 const ROOT_DIR: &str = "thirdparty/aurora";
 const MANIFEST_FILE: &str = "manifest.lock.json";
+const INSTALL_MANIFEST_FILE: &str = "install-manifest.txt";
+const HOST_DIR: &str = "host";
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ClearManifest {
     pub version: u32,
     pub direct_requires: Vec<ConanRef>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub targets: BTreeMap<String, Vec<TargetAssignment>>,
+    /// Предпочтительная линковка пакета; без записи — shared.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub linkage: BTreeMap<String, Linkage>,
+    /// Пакеты, чьи исполняемые файлы нужны на машине сборки (`add --tool`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<ConanRef>,
+    /// Версии транзитивных пакетов, выбираемые вместо запрошенных рецептами (`override`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<ConanRef>,
+    /// Исключения из лицензионной политики: пакет -> обоснование.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub license_waivers: BTreeMap<String, String>,
+    /// Пакеты и бинарники, поставленные последней синхронизацией.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<LockedPackage>,
 }
 
 impl Default for ClearManifest {
@@ -23,6 +48,12 @@ impl Default for ClearManifest {
         Self {
             version: 1,
             direct_requires: Vec::new(),
+            targets: BTreeMap::new(),
+            linkage: BTreeMap::new(),
+            tools: Vec::new(),
+            overrides: Vec::new(),
+            license_waivers: BTreeMap::new(),
+            packages: Vec::new(),
         }
     }
 }
@@ -80,15 +111,15 @@ pub fn supported_arches() -> &'static [&'static str] {
 }
 
 pub fn resolve_target_arches() -> Result<(Vec<String>, bool)> {
-    if let Ok(value) = std::env::var("AURORA_CONAN_ARCH") {
-        if !value.trim().is_empty() {
-            return Ok((vec![normalize_arch(&value)?], true));
-        }
+    if let Ok(value) = std::env::var("AURORA_CONAN_ARCH")
+        && !value.trim().is_empty()
+    {
+        return Ok((vec![normalize_arch(&value)?], true));
     }
-    if let Ok(value) = std::env::var("RPM_ARCH") {
-        if !value.trim().is_empty() {
-            return Ok((vec![normalize_arch(&value)?], true));
-        }
+    if let Ok(value) = std::env::var("RPM_ARCH")
+        && !value.trim().is_empty()
+    {
+        return Ok((vec![normalize_arch(&value)?], true));
     }
 
     Ok((
@@ -100,6 +131,70 @@ pub fn resolve_target_arches() -> Result<(Vec<String>, bool)> {
     ))
 }
 
+/// Архитектура машины сборки для tool-пакетов: `AURORA_CONAN_HOST_ARCH`, иначе архитектура CLI.
+pub fn host_arch() -> Result<String> {
+    if let Ok(value) = std::env::var("AURORA_CONAN_HOST_ARCH")
+        && !value.trim().is_empty()
+    {
+        return normalize_arch(&value);
+    }
+    normalize_arch(std::env::consts::ARCH)
+}
+
+/// Каталог tool-пакетов; отделён от `<arch>/packages`, поэтому в `%install` не попадает.
+pub fn host_root(project_root: &Path) -> PathBuf {
+    thirdparty_root(project_root).join(HOST_DIR)
+}
+
+pub fn host_package_root(project_root: &Path, package: &str, version: &str) -> PathBuf {
+    host_root(project_root)
+        .join("packages")
+        .join(package)
+        .join(version)
+}
+
+pub fn reset_host_layout(project_root: &Path) -> Result<()> {
+    let root = host_root(project_root);
+    if root.exists() {
+        fs::remove_dir_all(&root)
+            .with_context(|| format!("Не удалось удалить {}", root.display()))?;
+    }
+    Ok(())
+}
+
+/// Исполняемые файлы из `<package>/bin`, отсортированные по имени.
+pub fn discover_executables(package_prefix: &Path) -> Result<Vec<PathBuf>> {
+    let bin_dir = package_prefix.join("bin");
+    if !bin_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut out = Vec::new();
+    for entry in fs::read_dir(&bin_dir)
+        .with_context(|| format!("Не удалось прочитать {}", bin_dir.display()))?
+    {
+        let path = entry?.path();
+        let metadata = fs::metadata(&path)
+            .with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+        if metadata.is_file() && is_executable(&metadata) {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &fs::Metadata) -> bool {
+    true
+}
+
 pub fn arch_root(project_root: &Path, arch: &str) -> PathBuf {
     thirdparty_root(project_root).join(arch)
 }
@@ -111,10 +206,55 @@ pub fn package_root(project_root: &Path, arch: &str, package: &str, version: &st
         .join(version)
 }
 
+/// Установленные пакеты архитектуры: `(name, version, prefix)`, отсортированные по имени.
+pub fn installed_packages(
+    project_root: &Path,
+    arch: &str,
+) -> Result<Vec<(String, String, PathBuf)>> {
+    let packages_dir = arch_root(project_root, arch).join("packages");
+    if !packages_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut out = Vec::new();
+    for name_entry in fs::read_dir(&packages_dir)
+        .with_context(|| format!("Не удалось прочитать {}", packages_dir.display()))?
+    {
+        let name_path = name_entry?.path();
+        if !name_path.is_dir() {
+            continue;
+        }
+        let name = name_path
+            .file_name()
+            .map(|value| value.to_string_lossy().to_string())
+            .unwrap_or_default();
+        for version_entry in fs::read_dir(&name_path)
+            .with_context(|| format!("Не удалось прочитать {}", name_path.display()))?
+        {
+            let version_path = version_entry?.path();
+            if !version_path.is_dir() {
+                continue;
+            }
+            let version = version_path
+                .file_name()
+                .map(|value| value.to_string_lossy().to_string())
+                .unwrap_or_default();
+            out.push((name.clone(), version, version_path));
+        }
+    }
+
+    out.sort();
+    Ok(out)
+}
+
 pub fn pkgconfig_dir(project_root: &Path, arch: &str) -> PathBuf {
     arch_root(project_root, arch).join("pkgconfig")
 }
 
+pub fn cmake_config_dir(project_root: &Path, arch: &str) -> PathBuf {
+    arch_root(project_root, arch).join("cmake")
+}
+
 pub fn reset_arch_layout(project_root: &Path, arch: &str) -> Result<()> {
     let root = arch_root(project_root, arch);
     if root.exists() {
@@ -129,25 +269,44 @@ pub fn reset_arch_layout(project_root: &Path, arch: &str) -> Result<()> {
     Ok(())
 }
 
+/// Выбирает бинарник под архитектуру; из shared- и static-сборок берёт ту, что задана `linkage`,
+/// а при её отсутствии — оставшуюся.
 pub fn choose_artifact<'a>(
     artifacts: &'a [DownloadArtifact],
     target_arch: &str,
+    linkage: Linkage,
 ) -> Result<&'a DownloadArtifact> {
     let target_norm = normalize_arch(target_arch)?;
+    let preferred = |candidates: Vec<&'a DownloadArtifact>| {
+        candidates
+            .iter()
+            .find(|item| item.shared == Some(linkage.shared_option()))
+            .or_else(|| candidates.iter().find(|item| item.shared.is_none()))
+            .or_else(|| candidates.first())
+            .copied()
+    };
 
-    if let Some(exact) = artifacts.iter().find(|item| {
-        normalize_arch(&item.arch)
-            .map(|value| value == target_norm)
-            .unwrap_or(false)
-    }) {
+    let exact = artifacts
+        .iter()
+        .filter(|item| {
+            normalize_arch(&item.arch)
+                .map(|value| value == target_norm)
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    if let Some(exact) = preferred(exact) {
         return Ok(exact);
     }
 
-    if let Some(header_only) = artifacts.iter().find(|item| {
-        normalize_arch(&item.arch)
-            .map(|value| value == "package")
-            .unwrap_or(false)
-    }) {
+    let header_only = artifacts
+        .iter()
+        .filter(|item| {
+            normalize_arch(&item.arch)
+                .map(|value| value == "package")
+                .unwrap_or(false)
+        })
+        .collect::<Vec<_>>();
+    if let Some(header_only) = preferred(header_only) {
         return Ok(header_only);
     }
 
@@ -163,6 +322,16 @@ pub fn choose_artifact<'a>(
     ))
 }
 
+/// SHA-256 файла в шестнадцатеричном виде.
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+    Ok(Sha256::digest(&bytes)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect())
+}
+
 pub fn extract_tgz(archive_path: &Path, destination: &Path) -> Result<()> {
     if destination.exists() {
         fs::remove_dir_all(destination)
@@ -180,6 +349,7 @@ pub fn extract_tgz(archive_path: &Path, destination: &Path) -> Result<()> {
         .with_context(|| format!("Не удалось распаковать {}", archive_path.display()))
 }
 
+/// Имена библиотек из `<package>/lib`: `lib<name>.so*` и статические `lib<name>.a`.
 pub fn discover_lib_names(package_prefix: &Path) -> Result<Vec<String>> {
     let lib_dir = package_prefix.join("lib");
     if !lib_dir.exists() {
@@ -197,11 +367,13 @@ pub fn discover_lib_names(package_prefix: &Path) -> Result<Vec<String>> {
         if !name.starts_with("lib") {
             continue;
         }
-        if !name.contains(".so") {
+        let stem = if path.extension().is_some_and(|ext| ext == "a") {
+            name.trim_end_matches(".a")
+        } else if name.contains(".so") {
+            name.split(".so").next().unwrap_or(name)
+        } else {
             continue;
-        }
-
-        let stem = name.split(".so").next().unwrap_or(name);
+        };
         if stem.len() <= 3 {
             continue;
         }
@@ -215,60 +387,915 @@ pub fn discover_lib_names(package_prefix: &Path) -> Result<Vec<String>> {
     Ok(out)
 }
 
+/// Запись манифеста установки: что `%install` кладёт в `%{_datadir}/%{name}/lib`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstallEntry {
+    /// Реальный файл; `source` — путь относительно `<arch>/packages`.
+    File {
+        package: String,
+        source: String,
+        name: String,
+    },
+    /// Symlink на файл из того же каталога.
+    Link {
+        package: String,
+        target: String,
+        name: String,
+    },
+}
+
+impl InstallEntry {
+    /// Строка манифеста: поля через табуляцию, чтобы пути с пробелами читались `read` целиком.
+    fn render(&self) -> Result<String> {
+        let fields = match self {
+            InstallEntry::File {
+                package,
+                source,
+                name,
+            } => ["file", package, source, name],
+            InstallEntry::Link {
+                package,
+                target,
+                name,
+            } => ["link", package, target, name],
+        };
+        if let Some(field) = fields.iter().find(|field| field.contains(['\t', '\n'])) {
+            return Err(anyhow!(
+                "Путь '{}' содержит табуляцию или перевод строки и не может быть записан в {}",
+                field.escape_debug(),
+                INSTALL_MANIFEST_FILE
+            ));
+        }
+        Ok(format!("{}\n", fields.join("\t")))
+    }
+}
+
+pub fn install_manifest_path(project_root: &Path, arch: &str) -> PathBuf {
+    arch_root(project_root, arch).join(INSTALL_MANIFEST_FILE)
+}
+
+/// Собирает `lib*.so*` установленных пакетов архитектуры с цепочками symlink и пишет
+/// `<arch>/install-manifest.txt`, который построчно воспроизводит `%install`.
+///
+/// Одноимённые файлы разных пакетов допустимы только с одинаковым содержимым.
+pub fn write_install_manifest(project_root: &Path, arch: &str) -> Result<Vec<InstallEntry>> {
+    let packages_dir = arch_root(project_root, arch).join("packages");
+    let mut entries: Vec<InstallEntry> = Vec::new();
+    // Имя файла или SONAME -> (путь, пакет), откуда он взят первым
+    let mut origins: BTreeMap<String, (PathBuf, String)> = BTreeMap::new();
+    let mut sonames: BTreeMap<String, (PathBuf, String)> = BTreeMap::new();
+
+    for (name, version, prefix) in installed_packages(project_root, arch)? {
+        let label = format!("{name}/{version}");
+        let lib_dir = prefix.join("lib");
+        if !lib_dir.is_dir() {
+            continue;
+        }
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&lib_dir)
+            .with_context(|| format!("Не удалось прочитать {}", lib_dir.display()))?
+        {
+            let path = entry?.path();
+            let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            // Битые ссылки и ссылки на каталоги не устанавливаются
+            if file_name.starts_with("lib") && file_name.contains(".so") && path.is_file() {
+                files.push((file_name.to_string(), path));
+            }
+        }
+        files.sort();
+
+        for (file_name, path) in &files {
+            let is_symlink = fs::symlink_metadata(path)
+                .with_context(|| format!("Не удалось прочитать {}", path.display()))?
+                .file_type()
+                .is_symlink();
+            let link_target = if is_symlink {
+                local_link_target(&lib_dir, path)?
+                    .filter(|target| files.iter().any(|(other, _)| other == target))
+            } else {
+                None
+            };
+            let entry = match link_target {
+                Some(target) => InstallEntry::Link {
+                    package: name.clone(),
+                    target,
+                    name: file_name.clone(),
+                },
+                None => {
+                    if let Some(soname) = elf::read_elf_info(path)?.and_then(|info| info.soname) {
+                        check_same_content(&sonames, &soname, path, &label, arch)?;
+                        sonames
+                            .entry(soname)
+                            .or_insert_with(|| (path.clone(), label.clone()));
+                    }
+                    let source = path
+                        .strip_prefix(&packages_dir)
+                        .unwrap_or(path)
+                        .to_string_lossy()
+                        .replace('\\', "/");
+                    InstallEntry::File {
+                        package: name.clone(),
+                        source,
+                        name: file_name.clone(),
+                    }
+                }
+            };
+
+            // Одинаковые копии остаются у каждого пакета, чтобы .spec с частью пакетов
+            // не получил symlink без файла
+            check_same_content(&origins, file_name, path, &label, arch)?;
+            origins
+                .entry(file_name.clone())
+                .or_insert_with(|| (path.clone(), label.clone()));
+            entries.push(entry);
+        }
+    }
+
+    let body = entries
+        .iter()
+        .map(InstallEntry::render)
+        .collect::<Result<String>>()?;
+    let path = install_manifest_path(project_root, arch);
+    fs::write(&path, body).with_context(|| format!("Не удалось записать {}", path.display()))?;
+    Ok(entries)
+}
+
+/// Одноимённые библиотеки разных пакетов должны совпадать побайтно, иначе `%install` молча
+/// перезапишет одну другой.
+fn check_same_content(
+    origins: &BTreeMap<String, (PathBuf, String)>,
+    key: &str,
+    path: &Path,
+    package: &str,
+    arch: &str,
+) -> Result<()> {
+    let Some((existing, owner)) = origins.get(key) else {
+        return Ok(());
+    };
+    let read = |path: &Path| {
+        fs::read(path).with_context(|| format!("Не удалось прочитать {}", path.display()))
+    };
+    if owner == package || read(existing)? == read(path)? {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Библиотеку {} ({}) поставляют пакеты {} и {} с разным содержимым",
+        key,
+        arch,
+        owner,
+        package
+    ))
+}
+
+/// Имя файла, на который указывает symlink, если он лежит в том же каталоге.
+fn local_link_target(lib_dir: &Path, link: &Path) -> Result<Option<String>> {
+    let target =
+        fs::read_link(link).with_context(|| format!("Не удалось прочитать {}", link.display()))?;
+    let resolved = lib_dir.join(&target);
+    let same_dir = match (
+        resolved.parent().map(fs::canonicalize),
+        fs::canonicalize(lib_dir),
+    ) {
+        (Some(Ok(parent)), Ok(dir)) => parent == dir,
+        _ => false,
+    };
+    if !same_dir {
+        return Ok(None);
+    }
+    Ok(resolved
+        .file_name()
+        .and_then(|name| name.to_str())
+        .map(str::to_string))
+}
+
+/// Пишет `.pc` пакета без компонентов; `requires` — `.pc` других пакетов для `Requires`.
 pub fn write_pkg_config(
     project_root: &Path,
     arch: &str,
     package: &ConanRef,
-    libs: &[String],
+    cpp_info: &PackageCppInfo,
     requires: &[String],
+) -> Result<()> {
+    let pc_name = cpp_info
+        .pkg_config_name
+        .clone()
+        .unwrap_or_else(|| package.name.clone());
+    write_pc_file(
+        project_root,
+        arch,
+        package,
+        &PcFile {
+            name: &pc_name,
+            libs: &cpp_info.libs,
+            system_libs: &cpp_info.system_libs,
+            build_info: &cpp_info.build_info,
+            requires,
+        },
+    )
+}
+
+/// Пишет `.pc` компонента. Требования к соседним компонентам и к компонентам других пакетов
+/// (`zlib::zlib`, по `dependencies`) идут в `Requires`, как в PkgConfigDeps.
+pub fn write_component_pkg_config(
+    project_root: &Path,
+    arch: &str,
+    package: &ConanRef,
+    cpp_info: &PackageCppInfo,
+    component: &ComponentInfo,
+    dependencies: &BTreeMap<String, PackageCppInfo>,
+) -> Result<()> {
+    let mut requires = Vec::new();
+    for required in &component.requires {
+        let (package_name, component_name) = match required.split_once("::") {
+            Some((name, component_name)) => (name, component_name),
+            None => (package.name.as_str(), required.as_str()),
+        };
+        let fallback = PackageCppInfo::default();
+        let owner = if package_name == package.name {
+            cpp_info
+        } else {
+            dependencies.get(package_name).unwrap_or(&fallback)
+        };
+        let pc_name = match owner.components.iter().find(|c| c.name == component_name) {
+            Some(required_component) => component_pc_name(required_component),
+            None if package_name == package.name => continue,
+            None => owner
+                .pkg_config_name
+                .clone()
+                .unwrap_or_else(|| package_name.to_string()),
+        };
+        if !requires.contains(&pc_name) {
+            requires.push(pc_name);
+        }
+    }
+
+    write_pc_file(
+        project_root,
+        arch,
+        package,
+        &PcFile {
+            name: &component_pc_name(component),
+            libs: &component.libs,
+            system_libs: &component.system_libs,
+            build_info: &component.build_info,
+            requires: &requires,
+        },
+    )
+}
+
+/// Имя `.pc` компонента: `pkg_config_name` рецепта или имя компонента.
+pub fn component_pc_name(component: &ComponentInfo) -> String {
+    component
+        .pkg_config_name
+        .clone()
+        .unwrap_or_else(|| component.name.clone())
+}
+
+/// Содержимое одного `.pc`.
+struct PcFile<'a> {
+    name: &'a str,
+    libs: &'a [String],
+    /// Системные библиотеки идут в `Libs` вслед за библиотеками пакета, как в PkgConfigDeps.
+    system_libs: &'a [String],
+    build_info: &'a CppBuildInfo,
+    requires: &'a [String],
+}
+
+fn write_pc_file(
+    project_root: &Path,
+    arch: &str,
+    package: &ConanRef,
+    pc: &PcFile<'_>,
 ) -> Result<()> {
     let pkg_dir = pkgconfig_dir(project_root, arch);
     fs::create_dir_all(&pkg_dir)
         .with_context(|| format!("Не удалось создать {}", pkg_dir.display()))?;
 
-    let path = pkg_dir.join(format!("{}.pc", package.name));
+    let path = pkg_dir.join(format!("{}.pc", pc.name));
     let prefix_rel = format!(
         "${{pcfiledir}}/../packages/{}/{}",
         package.name, package.version
     );
 
+    let includedirs = pc_dir_variables("includedir", &pc.build_info.includedirs());
+    let libdirs = pc_dir_variables("libdir", &pc.build_info.libdirs());
+    let bindirs = pc_dir_variables("bindir", &pc.build_info.bindirs());
+
     let mut body = String::new();
     body.push_str(&format!("prefix={}\n", prefix_rel));
-    body.push_str("includedir=${prefix}/include\n");
-    body.push_str("libdir=${prefix}/lib\n\n");
-    body.push_str(&format!("Name: {}\n", package.name));
+    for (variable, value) in includedirs.iter().chain(&libdirs).chain(&bindirs) {
+        body.push_str(&format!("{variable}={value}\n"));
+    }
+    body.push('\n');
+    body.push_str(&format!("Name: {}\n", pc.name));
     body.push_str(&format!(
         "Description: {} {} (vendored by aurora-conan-cli)\n",
         package.name, package.version
     ));
     body.push_str(&format!("Version: {}\n", package.version));
 
-    if !requires.is_empty() {
-        body.push_str(&format!("Requires: {}\n", requires.join(", ")));
+    if !pc.requires.is_empty() {
+        body.push_str(&format!("Requires: {}\n", pc.requires.join(", ")));
+    }
+
+    let build = pc.build_info;
+    let mut cflags: Vec<String> = includedirs
+        .iter()
+        .map(|(variable, _)| format!("-I${{{variable}}}"))
+        .collect();
+    cflags.extend(build.cflags.iter().cloned());
+    cflags.extend(build.cxxflags.iter().cloned());
+    cflags.extend(build.defines.iter().map(|define| format!("-D{define}")));
+    body.push_str(&pc_field("Cflags", &cflags));
+
+    let mut flags: Vec<String> = Vec::new();
+    if !pc.libs.is_empty() {
+        flags.extend(
+            libdirs
+                .iter()
+                .map(|(variable, _)| format!("-L${{{variable}}}")),
+        );
+        flags.extend(pc.libs.iter().map(|lib| format!("-l{}", lib)));
+    }
+    for lib in pc.system_libs {
+        let flag = format!("-l{}", lib);
+        if !flags.contains(&flag) {
+            flags.push(flag);
+        }
+    }
+    flags.extend(build.sharedlinkflags.iter().cloned());
+    flags.extend(build.exelinkflags.iter().cloned());
+    body.push_str(&pc_field("Libs", &flags));
+
+    fs::write(&path, body).with_context(|| format!("Не удалось записать {}", path.display()))
+}
+
+/// Переменные `.pc` для каталогов пакета: `includedir`, `includedir2`, ... относительно `${prefix}`.
+fn pc_dir_variables(base: &str, dirs: &[String]) -> Vec<(String, String)> {
+    dirs.iter()
+        .enumerate()
+        .map(|(index, dir)| {
+            let variable = if index == 0 {
+                base.to_string()
+            } else {
+                format!("{base}{}", index + 1)
+            };
+            let dir = dir.trim_matches('/');
+            let value = if dir.is_empty() || dir == "." {
+                "${prefix}".to_string()
+            } else {
+                format!("${{prefix}}/{dir}")
+            };
+            (variable, value)
+        })
+        .collect()
+}
+
+fn pc_field(name: &str, values: &[String]) -> String {
+    if values.is_empty() {
+        format!("{name}:\n")
+    } else {
+        format!("{name}: {}\n", values.join(" "))
+    }
+}
+
+/// `.pc`, которые пакет поставляет сам: `lib/pkgconfig` и `share/pkgconfig`.
+pub fn find_packaged_pkg_configs(package_prefix: &Path) -> Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    for dir in ["lib/pkgconfig", "share/pkgconfig"] {
+        let dir = package_prefix.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Не удалось прочитать {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_file() && path.extension().is_some_and(|ext| ext == "pc") {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// Копирует поставляемый `.pc` в `<arch>/pkgconfig`, переписывая `prefix` на каталог пакета.
+/// Возвращает имя модуля.
+pub fn install_packaged_pkg_config(
+    project_root: &Path,
+    arch: &str,
+    package: &ConanRef,
+    source: &Path,
+) -> Result<String> {
+    let content = fs::read_to_string(source)
+        .with_context(|| format!("Не удалось прочитать {}", source.display()))?;
+    let name = source
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| anyhow!("Некорректное имя файла {}", source.display()))?
+        .to_string();
+
+    let pkg_dir = pkgconfig_dir(project_root, arch);
+    fs::create_dir_all(&pkg_dir)
+        .with_context(|| format!("Не удалось создать {}", pkg_dir.display()))?;
+    let path = pkg_dir.join(format!("{name}.pc"));
+    let prefix = format!(
+        "${{pcfiledir}}/../packages/{}/{}",
+        package.name, package.version
+    );
+    fs::write(&path, relocate_pkg_config(&content, &prefix))
+        .with_context(|| format!("Не удалось записать {}", path.display()))?;
+    Ok(name)
+}
+
+/// Заменяет `prefix=` и абсолютные пути под старым префиксом на `${prefix}`.
+fn relocate_pkg_config(content: &str, prefix: &str) -> String {
+    let old_prefix = content
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("prefix="))
+        .map(|value| value.trim().trim_end_matches('/').to_string())
+        .filter(|value| value.starts_with('/'));
+
+    let mut out = String::new();
+    if !content
+        .lines()
+        .any(|line| line.trim().starts_with("prefix="))
+    {
+        out.push_str(&format!("prefix={prefix}\n"));
+    }
+    for line in content.lines() {
+        if line.trim().starts_with("prefix=") {
+            out.push_str(&format!("prefix={prefix}\n"));
+            continue;
+        }
+        match &old_prefix {
+            Some(old) if !old.is_empty() => out.push_str(&line.replace(old.as_str(), "${prefix}")),
+            _ => out.push_str(line),
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// CMake-конфиги, которые пакет поставляет сам: `lib/cmake/*/<Name>Config.cmake`
+/// или `<name>-config.cmake`.
+pub fn find_packaged_cmake_configs(package_prefix: &Path) -> Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    for dir in ["lib/cmake", "share/cmake"] {
+        let dir = package_prefix.join(dir);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Не удалось прочитать {}", dir.display()))?
+        {
+            let config_dir = entry?.path();
+            if !config_dir.is_dir() {
+                continue;
+            }
+            for file in fs::read_dir(&config_dir)
+                .with_context(|| format!("Не удалось прочитать {}", config_dir.display()))?
+            {
+                let path = file?.path();
+                if path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(cmake_config_package_name)
+                    .is_some()
+                {
+                    out.push(path);
+                }
+            }
+        }
     }
+    out.sort();
+    Ok(out)
+}
+
+/// Имя для `find_package`, под которым подключается поставляемый конфиг.
+pub fn packaged_cmake_config_name(config: &Path) -> Option<String> {
+    config
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(cmake_config_package_name)
+        .map(str::to_string)
+}
 
-    body.push_str("Cflags: -I${includedir}\n");
-    if libs.is_empty() {
-        body.push_str("Libs:\n");
+/// Имя пакета для `find_package` по имени файла конфига.
+fn cmake_config_package_name(file_name: &str) -> Option<&str> {
+    file_name
+        .strip_suffix("Config.cmake")
+        .or_else(|| file_name.strip_suffix("-config.cmake"))
+        .filter(|name| !name.is_empty())
+}
+
+/// Подключает поставляемый CMake-конфиг: в `<arch>/cmake/<Name>/` пишутся файлы, которые
+/// включают конфиг и его версию из каталога пакета. Возвращает имя для `find_package`.
+pub fn install_packaged_cmake_config(
+    project_root: &Path,
+    arch: &str,
+    package: &ConanRef,
+    config: &Path,
+) -> Result<String> {
+    let file_name = config
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| anyhow!("Некорректное имя файла {}", config.display()))?;
+    let name = cmake_config_package_name(file_name)
+        .ok_or_else(|| anyhow!("{} не является CMake-конфигом", config.display()))?
+        .to_string();
+    let package_dir = package_root(project_root, arch, &package.name, &package.version);
+    let relative = config
+        .parent()
+        .and_then(|dir| dir.strip_prefix(&package_dir).ok())
+        .ok_or_else(|| anyhow!("{} находится вне каталога пакета", config.display()))?;
+    let source_dir = format!(
+        "${{CMAKE_CURRENT_LIST_DIR}}/../../packages/{}/{}/{}",
+        package.name,
+        package.version,
+        relative.to_string_lossy().replace('\\', "/")
+    );
+
+    let config_dir = cmake_config_dir(project_root, arch).join(&name);
+    if config_dir.exists() {
+        fs::remove_dir_all(&config_dir)
+            .with_context(|| format!("Не удалось удалить {}", config_dir.display()))?;
+    }
+    fs::create_dir_all(&config_dir)
+        .with_context(|| format!("Не удалось создать {}", config_dir.display()))?;
+
+    let version_file = match file_name.strip_suffix("-config.cmake") {
+        Some(stem) => format!("{stem}-config-version.cmake"),
+        None => format!("{name}ConfigVersion.cmake"),
+    };
+    let mut forwards = vec![file_name.to_string()];
+    if config.with_file_name(&version_file).exists() {
+        forwards.push(version_file);
+    }
+    for forward in forwards {
+        let path = config_dir.join(&forward);
+        let body = format!(
+            "# Generated by aurora-conan-cli for {}: packaged config\ninclude(\"{}/{}\")\n",
+            package.to_ref_string(),
+            source_dir,
+            forward
+        );
+        fs::write(&path, body)
+            .with_context(|| format!("Не удалось записать {}", path.display()))?;
+    }
+    Ok(name)
+}
+
+/// Пишет `<Name>Config.cmake` и `<Name>ConfigVersion.cmake` для `find_package(<Name> CONFIG)`.
+///
+/// Имена файла и целей берутся из свойств рецепта `cmake_file_name`/`cmake_target_name`,
+/// по умолчанию как в CMakeDeps: `<package>` и `<package>::<package>`.
+///
+/// `dependencies` — `cpp_info` пакетов графа по именам: по ним требования компонентов вида
+/// `zlib::zlib` превращаются в `find_dependency` и ссылку на цель другого пакета.
+pub fn write_cmake_config(
+    project_root: &Path,
+    arch: &str,
+    package: &ConanRef,
+    cpp_info: &PackageCppInfo,
+    dependencies: &BTreeMap<String, PackageCppInfo>,
+) -> Result<()> {
+    let file_name = cmake_file_name(&package.name, cpp_info);
+    let root_target = cmake_root_target(&package.name, cpp_info);
+    let prefix = package_root(project_root, arch, &package.name, &package.version);
+
+    let mut body = format!(
+        "# Generated by aurora-conan-cli for {}\n\nget_filename_component(_AURORA_TP_PREFIX \"${{CMAKE_CURRENT_LIST_DIR}}/../../packages/{}/{}\" ABSOLUTE)\n",
+        package.to_ref_string(),
+        package.name,
+        package.version
+    );
+
+    if cpp_info.components.is_empty() {
+        let items = cmake_link_items(
+            &prefix,
+            &cpp_info.libs,
+            &cpp_info.system_libs,
+            &cpp_info.build_info,
+        );
+        body.push_str(&cmake_imported_target(
+            &root_target,
+            &prefix,
+            &items,
+            Some(&cpp_info.build_info),
+        ));
     } else {
-        let flags = libs
+        let mut found_dependencies = Vec::new();
+        let mut component_targets = Vec::new();
+        let mut blocks = String::new();
+        for component in &cpp_info.components {
+            let target = cmake_component_target(&package.name, cpp_info, component);
+            let mut items = cmake_link_items(
+                &prefix,
+                &component.libs,
+                &component.system_libs,
+                &component.build_info,
+            );
+            for required in &component.requires {
+                match required.split_once("::") {
+                    Some((dependency, _)) if dependency != package.name => {
+                        let (dependency_file, dependency_target) =
+                            cmake_foreign_target(required, dependencies);
+                        if !found_dependencies.contains(&dependency_file) {
+                            found_dependencies.push(dependency_file);
+                        }
+                        items.push(dependency_target);
+                    }
+                    _ => {
+                        let name = required.rsplit("::").next().unwrap_or(required);
+                        if let Some(sibling) = cpp_info.components.iter().find(|c| c.name == name) {
+                            items.push(cmake_component_target(&package.name, cpp_info, sibling));
+                        }
+                    }
+                }
+            }
+            blocks.push_str(&cmake_imported_target(
+                &target,
+                &prefix,
+                &items,
+                Some(&component.build_info),
+            ));
+            component_targets.push(target);
+        }
+
+        // Цели других пакетов появляются только после их find_package
+        if !found_dependencies.is_empty() {
+            body.push_str(
+                "
+include(CMakeFindDependencyMacro)
+",
+            );
+            for dependency in &found_dependencies {
+                body.push_str(&format!(
+                    "find_dependency({dependency} CONFIG)
+"
+                ));
+            }
+        }
+        body.push_str(&blocks);
+
+        if !component_targets.contains(&root_target) {
+            body.push_str(&cmake_imported_target(
+                &root_target,
+                &prefix,
+                &component_targets,
+                None,
+            ));
+        }
+    }
+    body.push_str("\nunset(_AURORA_TP_PREFIX)\n");
+
+    // Совместимость SameMajorVersion, как у write_basic_package_version_file
+    let version_body = [
+        format!("set(PACKAGE_VERSION \"{}\")", package.version),
+        String::new(),
+        "if(PACKAGE_FIND_VERSION VERSION_GREATER PACKAGE_VERSION)".to_string(),
+        "  set(PACKAGE_VERSION_COMPATIBLE FALSE)".to_string(),
+        "else()".to_string(),
+        "  string(REGEX MATCH \"^[0-9]+\" _AURORA_TP_MAJOR \"${PACKAGE_VERSION}\")".to_string(),
+        "  if(PACKAGE_FIND_VERSION_MAJOR STREQUAL _AURORA_TP_MAJOR)".to_string(),
+        "    set(PACKAGE_VERSION_COMPATIBLE TRUE)".to_string(),
+        "  else()".to_string(),
+        "    set(PACKAGE_VERSION_COMPATIBLE FALSE)".to_string(),
+        "  endif()".to_string(),
+        "  if(PACKAGE_FIND_VERSION STREQUAL PACKAGE_VERSION)".to_string(),
+        "    set(PACKAGE_VERSION_EXACT TRUE)".to_string(),
+        "  endif()".to_string(),
+        "  unset(_AURORA_TP_MAJOR)".to_string(),
+        "endif()".to_string(),
+        String::new(),
+    ]
+    .join("\n");
+
+    let config_dir = cmake_config_dir(project_root, arch).join(&file_name);
+    fs::create_dir_all(&config_dir)
+        .with_context(|| format!("Не удалось создать {}", config_dir.display()))?;
+    for (path, content) in [
+        (config_dir.join(format!("{file_name}Config.cmake")), body),
+        (
+            config_dir.join(format!("{file_name}ConfigVersion.cmake")),
+            version_body,
+        ),
+    ] {
+        fs::write(&path, content)
+            .with_context(|| format!("Не удалось записать {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Имя CMake-пакета для `find_package`.
+pub fn cmake_file_name(package_name: &str, cpp_info: &PackageCppInfo) -> String {
+    cpp_info
+        .cmake_file_name
+        .clone()
+        .unwrap_or_else(|| package_name.to_string())
+}
+
+fn cmake_root_target(package_name: &str, cpp_info: &PackageCppInfo) -> String {
+    cpp_info.cmake_target_name.clone().unwrap_or_else(|| {
+        let file_name = cmake_file_name(package_name, cpp_info);
+        format!("{file_name}::{file_name}")
+    })
+}
+
+fn cmake_component_target(
+    package_name: &str,
+    cpp_info: &PackageCppInfo,
+    component: &ComponentInfo,
+) -> String {
+    component.cmake_target_name.clone().unwrap_or_else(|| {
+        format!(
+            "{}::{}",
+            cmake_file_name(package_name, cpp_info),
+            component.name
+        )
+    })
+}
+
+/// CMake-пакет и цель для требования `pkg::comp` другого пакета; без компонента
+/// с таким именем — корневая цель пакета, как в CMakeDeps.
+fn cmake_foreign_target(
+    required: &str,
+    dependencies: &BTreeMap<String, PackageCppInfo>,
+) -> (String, String) {
+    let (package_name, component_name) = required.split_once("::").unwrap_or((required, required));
+    let fallback = PackageCppInfo::default();
+    let cpp_info = dependencies.get(package_name).unwrap_or(&fallback);
+    let target = match cpp_info
+        .components
+        .iter()
+        .find(|component| component.name == component_name)
+    {
+        Some(component) => cmake_component_target(package_name, cpp_info, component),
+        None => cmake_root_target(package_name, cpp_info),
+    };
+    (cmake_file_name(package_name, cpp_info), target)
+}
+
+/// Элементы `INTERFACE_LINK_LIBRARIES`: файлы библиотек пакета, найденные в его `libdirs`,
+/// и системные библиотеки.
+fn cmake_link_items(
+    prefix: &Path,
+    libs: &[String],
+    system_libs: &[String],
+    build_info: &CppBuildInfo,
+) -> Vec<String> {
+    let libdirs = build_info.libdirs();
+    let mut items: Vec<String> = libs
+        .iter()
+        .map(|lib| {
+            libdirs
+                .iter()
+                .find_map(|dir| {
+                    find_library_file(&prefix.join(dir), lib)
+                        .map(|file| format!("{}/{file}", cmake_prefix_dir(dir)))
+                })
+                .unwrap_or_else(|| lib.clone())
+        })
+        .collect();
+    items.extend(system_libs.iter().cloned());
+    items
+}
+
+/// Каталог пакета относительно `${_AURORA_TP_PREFIX}`.
+fn cmake_prefix_dir(dir: &str) -> String {
+    match dir.trim_matches('/') {
+        "" | "." => "${_AURORA_TP_PREFIX}".to_string(),
+        dir => format!("${{_AURORA_TP_PREFIX}}/{dir}"),
+    }
+}
+
+/// Библиотека есть в пакете только как `lib<name>.a`: в RPM её не кладут.
+pub fn is_static_only_library(package_prefix: &Path, name: &str) -> bool {
+    find_library_file(&package_prefix.join("lib"), name).is_some_and(|file| file.ends_with(".a"))
+}
+
+/// Ищет `lib<name>.so`, затем `lib<name>.so.*`, затем `lib<name>.a`.
+fn find_library_file(lib_dir: &Path, name: &str) -> Option<String> {
+    let shared = format!("lib{name}.so");
+    if lib_dir.join(&shared).exists() {
+        return Some(shared);
+    }
+
+    let versioned_prefix = format!("{shared}.");
+    let mut versioned: Vec<String> = fs::read_dir(lib_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .filter(|file| file.starts_with(&versioned_prefix))
+        .collect();
+    versioned.sort_by_key(|file| (file.len(), file.clone()));
+    if let Some(first) = versioned.into_iter().next() {
+        return Some(first);
+    }
+
+    let static_lib = format!("lib{name}.a");
+    lib_dir.join(&static_lib).exists().then_some(static_lib)
+}
+
+/// Imported-цель с usage requirements из `cpp_info`, как у CMakeDeps: флаги компиляции
+/// по языку, флаги линковки по типу цели-потребителя. Несуществующие каталоги пропускаются:
+/// CMake отвергает их в `INTERFACE_INCLUDE_DIRECTORIES`.
+fn cmake_imported_target(
+    target: &str,
+    prefix: &Path,
+    link_items: &[String],
+    build_info: Option<&CppBuildInfo>,
+) -> String {
+    let existing_dirs = |dirs: Vec<String>| -> Vec<String> {
+        dirs.iter()
+            .filter(|dir| prefix.join(dir).is_dir())
+            .map(|dir| cmake_prefix_dir(dir))
+            .collect()
+    };
+    let mut properties = Vec::new();
+    let mut property = |name: &str, values: Vec<String>| {
+        if !values.is_empty() {
+            properties.push(format!("    {name} \"{}\"", values.join(";")));
+        }
+    };
+
+    if let Some(build) = build_info {
+        property(
+            "INTERFACE_INCLUDE_DIRECTORIES",
+            existing_dirs(build.includedirs()),
+        );
+        if link_items
             .iter()
-            .map(|lib| format!("-l{}", lib))
-            .collect::<Vec<_>>()
-            .join(" ");
-        body.push_str(&format!("Libs: -L${{libdir}} {}\n", flags));
+            .any(|item| !item.contains('/') && !item.contains("::"))
+        {
+            property("INTERFACE_LINK_DIRECTORIES", existing_dirs(build.libdirs()));
+        }
+        property("INTERFACE_COMPILE_DEFINITIONS", build.defines.clone());
+        property(
+            "INTERFACE_COMPILE_OPTIONS",
+            build
+                .cflags
+                .iter()
+                .map(|flag| format!("$<$<COMPILE_LANGUAGE:C>:{flag}>"))
+                .chain(
+                    build
+                        .cxxflags
+                        .iter()
+                        .map(|flag| format!("$<$<COMPILE_LANGUAGE:CXX>:{flag}>")),
+                )
+                .collect(),
+        );
+        let by_type = |kind: &str, flags: &[String]| -> Vec<String> {
+            flags
+                .iter()
+                .map(|flag| format!("$<$<STREQUAL:$<TARGET_PROPERTY:TYPE>,{kind}>:{flag}>"))
+                .collect()
+        };
+        property(
+            "INTERFACE_LINK_OPTIONS",
+            [
+                by_type("SHARED_LIBRARY", &build.sharedlinkflags),
+                by_type("MODULE_LIBRARY", &build.sharedlinkflags),
+                by_type("EXECUTABLE", &build.exelinkflags),
+            ]
+            .concat(),
+        );
     }
+    property("INTERFACE_LINK_LIBRARIES", link_items.to_vec());
 
-    fs::write(&path, body).with_context(|| format!("Не удалось записать {}", path.display()))
+    let mut block =
+        format!("\nif(NOT TARGET {target})\n  add_library({target} INTERFACE IMPORTED)\n");
+    if !properties.is_empty() {
+        block.push_str(&format!(
+            "  set_target_properties({target} PROPERTIES\n{}\n  )\n",
+            properties.join("\n")
+        ));
+    }
+    block.push_str("endif()\n");
+    block
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
+
     use anyhow::Result;
     use tempfile::tempdir;
 
-    use super::{ClearManifest, choose_artifact, load_manifest, normalize_arch, save_manifest};
-    use crate::model::DownloadArtifact;
+    use super::{
+        ClearManifest, choose_artifact, cmake_config_dir, discover_lib_names,
+        find_packaged_cmake_configs, find_packaged_pkg_configs, install_manifest_path,
+        install_packaged_cmake_config, install_packaged_pkg_config, is_static_only_library,
+        load_manifest, normalize_arch, package_root, pkgconfig_dir, save_manifest,
+        write_cmake_config, write_component_pkg_config, write_install_manifest, write_pkg_config,
+    };
+    use crate::model::{
+        ComponentInfo, ConanRef, CppBuildInfo, DownloadArtifact, Linkage, PackageCppInfo,
+    };
 
     #[test]
     fn normalizes_arch_values() -> Result<()> {
@@ -283,19 +1310,127 @@ mod tests {
         let items = vec![
             DownloadArtifact {
                 arch: "package".to_string(),
+                shared: None,
                 path: "/tmp/header.tgz".into(),
+                ..Default::default()
             },
             DownloadArtifact {
                 arch: "armv8".to_string(),
+                shared: None,
                 path: "/tmp/armv8.tgz".into(),
+                ..Default::default()
             },
         ];
 
-        let chosen = choose_artifact(&items, "aarch64")?;
+        let chosen = choose_artifact(&items, "aarch64", Linkage::Shared)?;
         assert!(chosen.path.to_string_lossy().contains("armv8"));
         Ok(())
     }
 
+    #[test]
+    fn chooses_preferred_linkage_when_both_binaries_are_published() -> Result<()> {
+        let artifact = |shared: Option<bool>, path: &str| DownloadArtifact {
+            arch: "armv8".to_string(),
+            shared,
+            path: path.into(),
+            ..Default::default()
+        };
+        let both = vec![
+            artifact(Some(true), "/tmp/shared.tgz"),
+            artifact(Some(false), "/tmp/static.tgz"),
+        ];
+        let chosen = choose_artifact(&both, "armv8", Linkage::Static)?;
+        assert!(chosen.path.ends_with("static.tgz"));
+        let chosen = choose_artifact(&both, "armv8", Linkage::Shared)?;
+        assert!(chosen.path.ends_with("shared.tgz"));
+
+        let shared_only = vec![artifact(Some(true), "/tmp/shared.tgz")];
+        let chosen = choose_artifact(&shared_only, "armv8", Linkage::Static)?;
+        assert!(chosen.path.ends_with("shared.tgz"));
+        Ok(())
+    }
+
+    #[test]
+    fn discovers_and_references_static_archives() -> Result<()> {
+        let dir = tempdir()?;
+        let package = ConanRef {
+            name: "fmt".to_string(),
+            version: "10.2.1".to_string(),
+            user: "aurora".to_string(),
+        };
+        let prefix = package_root(dir.path(), "armv8", &package.name, &package.version);
+        std::fs::create_dir_all(prefix.join("lib"))?;
+        std::fs::write(prefix.join("lib/libfmt.a"), b"!<arch>\n")?;
+        std::fs::write(prefix.join("lib/libfmt.la"), b"")?;
+
+        assert_eq!(discover_lib_names(&prefix)?, vec!["fmt".to_string()]);
+        assert!(is_static_only_library(&prefix, "fmt"));
+
+        let cpp_info = PackageCppInfo {
+            package_name: "fmt".to_string(),
+            libs: vec!["fmt".to_string()],
+            ..Default::default()
+        };
+        write_pkg_config(dir.path(), "armv8", &package, &cpp_info, &[])?;
+        let pc = std::fs::read_to_string(pkgconfig_dir(dir.path(), "armv8").join("fmt.pc"))?;
+        assert!(pc.contains("Libs: -L${libdir} -lfmt\n"));
+
+        write_cmake_config(dir.path(), "armv8", &package, &cpp_info, &BTreeMap::new())?;
+        let config = std::fs::read_to_string(
+            cmake_config_dir(dir.path(), "armv8").join("fmt/fmtConfig.cmake"),
+        )?;
+        assert!(config.contains("INTERFACE_LINK_LIBRARIES \"${_AURORA_TP_PREFIX}/lib/libfmt.a\""));
+        Ok(())
+    }
+
+    #[test]
+    fn install_manifest_keeps_symlink_chains_and_detects_collisions() -> Result<()> {
+        let dir = tempdir()?;
+        let lib_dir = |name: &str, version: &str| -> Result<std::path::PathBuf> {
+            let lib = package_root(dir.path(), "armv8", name, version).join("lib");
+            std::fs::create_dir_all(&lib)?;
+            Ok(lib)
+        };
+
+        let zlib = lib_dir("zlib", "1.3.1")?;
+        std::fs::write(zlib.join("libz.so.1.3.1"), b"zlib")?;
+        std::os::unix::fs::symlink("libz.so.1.3.1", zlib.join("libz.so.1"))?;
+        std::os::unix::fs::symlink("libz.so.1", zlib.join("libz.so"))?;
+        std::fs::write(zlib.join("libz.a"), b"!<arch>")?;
+        // Побайтно одинаковая копия в другом пакете не считается конфликтом
+        let bundled = lib_dir("minizip", "4.0.3")?;
+        std::fs::write(bundled.join("libz.so.1.3.1"), b"zlib")?;
+
+        write_install_manifest(dir.path(), "armv8")?;
+        let manifest = std::fs::read_to_string(install_manifest_path(dir.path(), "armv8"))?;
+        assert_eq!(
+            manifest,
+            "file\tminizip\tminizip/4.0.3/lib/libz.so.1.3.1\tlibz.so.1.3.1\n\
+             link\tzlib\tlibz.so.1\tlibz.so\n\
+             link\tzlib\tlibz.so.1.3.1\tlibz.so.1\n\
+             file\tzlib\tzlib/1.3.1/lib/libz.so.1.3.1\tlibz.so.1.3.1\n"
+        );
+
+        // Пробелы в путях сохраняются: поля разделены табуляцией
+        let spaced = lib_dir("qt plugins", "1.0 beta")?;
+        std::fs::write(spaced.join("libplugin core.so"), b"plugin")?;
+        write_install_manifest(dir.path(), "armv8")?;
+        let manifest = std::fs::read_to_string(install_manifest_path(dir.path(), "armv8"))?;
+        assert!(manifest.contains(
+            "file\tqt plugins\tqt plugins/1.0 beta/lib/libplugin core.so\tlibplugin core.so\n"
+        ));
+        std::fs::write(spaced.join("libtab\tname.so"), b"plugin")?;
+        let error = write_install_manifest(dir.path(), "armv8").expect_err("tab in file name");
+        assert!(error.to_string().contains("табуляцию"));
+        std::fs::remove_dir_all(package_root(dir.path(), "armv8", "qt plugins", "1.0 beta"))?;
+
+        std::fs::write(bundled.join("libz.so.1.3.1"), b"patched zlib")?;
+        let error = write_install_manifest(dir.path(), "armv8").expect_err("expected collision");
+        assert!(error.to_string().contains("libz.so.1.3.1"));
+        assert!(error.to_string().contains("minizip/4.0.3"));
+        Ok(())
+    }
+
     #[test]
     fn persists_manifest() -> Result<()> {
         let dir = tempdir()?;
@@ -305,4 +1440,289 @@ mod tests {
         assert_eq!(loaded.version, 1);
         Ok(())
     }
+
+    #[test]
+    fn writes_cmake_config_with_recipe_target_names() -> Result<()> {
+        let dir = tempdir()?;
+        let prefix = package_root(dir.path(), "armv8", "openssl", "3.2.0");
+        std::fs::create_dir_all(prefix.join("include"))?;
+        std::fs::create_dir_all(prefix.join("lib"))?;
+        std::fs::write(prefix.join("lib/libssl.so.3"), b"")?;
+        std::fs::write(prefix.join("lib/libcrypto.so.3"), b"")?;
+
+        let package = ConanRef {
+            name: "openssl".to_string(),
+            version: "3.2.0".to_string(),
+            user: "aurora".to_string(),
+        };
+        let cpp_info = PackageCppInfo {
+            package_name: "openssl".to_string(),
+            cmake_file_name: Some("OpenSSL".to_string()),
+            cmake_target_name: Some("OpenSSL::OpenSSL".to_string()),
+            components: vec![
+                ComponentInfo {
+                    name: "ssl".to_string(),
+                    libs: vec!["ssl".to_string()],
+                    requires: vec!["crypto".to_string()],
+                    cmake_target_name: Some("OpenSSL::SSL".to_string()),
+                    ..Default::default()
+                },
+                ComponentInfo {
+                    name: "crypto".to_string(),
+                    libs: vec!["crypto".to_string()],
+                    system_libs: vec!["pthread".to_string()],
+                    requires: vec!["zlib::zlib".to_string()],
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let dependencies = BTreeMap::from([(
+            "zlib".to_string(),
+            PackageCppInfo {
+                package_name: "zlib".to_string(),
+                cmake_file_name: Some("ZLIB".to_string()),
+                cmake_target_name: Some("ZLIB::ZLIB".to_string()),
+                ..Default::default()
+            },
+        )]);
+        write_cmake_config(dir.path(), "armv8", &package, &cpp_info, &dependencies)?;
+
+        let config_dir = cmake_config_dir(dir.path(), "armv8").join("OpenSSL");
+        let config = std::fs::read_to_string(config_dir.join("OpenSSLConfig.cmake"))?;
+        assert!(
+            config.contains("\"${CMAKE_CURRENT_LIST_DIR}/../../packages/openssl/3.2.0\" ABSOLUTE")
+        );
+        assert!(config.contains(
+            "INTERFACE_LINK_LIBRARIES \"${_AURORA_TP_PREFIX}/lib/libssl.so.3;OpenSSL::crypto\""
+        ));
+        assert!(config.contains(
+            "INTERFACE_LINK_LIBRARIES \"${_AURORA_TP_PREFIX}/lib/libcrypto.so.3;pthread;ZLIB::ZLIB\""
+        ));
+        assert!(
+            config.contains("include(CMakeFindDependencyMacro)\nfind_dependency(ZLIB CONFIG)\n")
+        );
+        assert!(
+            config.find("find_dependency").expect("find_dependency")
+                < config.find("add_library").expect("add_library")
+        );
+        assert!(config.contains("add_library(OpenSSL::OpenSSL INTERFACE IMPORTED)"));
+        assert!(config.contains("INTERFACE_LINK_LIBRARIES \"OpenSSL::SSL;OpenSSL::crypto\""));
+
+        let version = std::fs::read_to_string(config_dir.join("OpenSSLConfigVersion.cmake"))?;
+        assert!(version.contains("set(PACKAGE_VERSION \"3.2.0\")"));
+        assert!(version.contains("if(PACKAGE_FIND_VERSION_MAJOR STREQUAL _AURORA_TP_MAJOR)"));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_system_libs_and_component_requires_to_pc() -> Result<()> {
+        let dir = tempdir()?;
+        let package = ConanRef {
+            name: "openssl".to_string(),
+            version: "3.2.0".to_string(),
+            user: "aurora".to_string(),
+        };
+        let ssl = ComponentInfo {
+            name: "ssl".to_string(),
+            libs: vec!["ssl".to_string()],
+            system_libs: vec!["dl".to_string(), "pthread".to_string()],
+            requires: vec!["crypto".to_string(), "zlib::zlib".to_string()],
+            pkg_config_name: Some("libssl".to_string()),
+            ..Default::default()
+        };
+        let cpp_info = PackageCppInfo {
+            package_name: "openssl".to_string(),
+            components: vec![
+                ssl.clone(),
+                ComponentInfo {
+                    name: "crypto".to_string(),
+                    pkg_config_name: Some("libcrypto".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        let dependencies = BTreeMap::from([(
+            "zlib".to_string(),
+            PackageCppInfo {
+                package_name: "zlib".to_string(),
+                pkg_config_name: Some("zlib-ng".to_string()),
+                ..Default::default()
+            },
+        )]);
+        write_component_pkg_config(
+            dir.path(),
+            "armv8",
+            &package,
+            &cpp_info,
+            &ssl,
+            &dependencies,
+        )?;
+        let pc = std::fs::read_to_string(pkgconfig_dir(dir.path(), "armv8").join("libssl.pc"))?;
+        assert!(pc.contains("Requires: libcrypto, zlib-ng\n"));
+        assert!(!pc.contains("Requires.private"));
+        assert!(pc.contains("Libs: -L${libdir} -lssl -ldl -lpthread\n"));
+
+        let header_only = PackageCppInfo {
+            package_name: "threads".to_string(),
+            system_libs: vec!["pthread".to_string()],
+            ..Default::default()
+        };
+        write_pkg_config(dir.path(), "armv8", &package, &header_only, &[])?;
+        let pc = std::fs::read_to_string(pkgconfig_dir(dir.path(), "armv8").join("openssl.pc"))?;
+        assert!(pc.contains("Libs: -lpthread\n"));
+        assert!(!pc.contains("Requires"));
+
+        // Пакет без компонентов требует .pc своих зависимостей по графу
+        let static_openssl = PackageCppInfo {
+            package_name: "openssl".to_string(),
+            libs: vec!["ssl".to_string(), "crypto".to_string()],
+            ..Default::default()
+        };
+        write_pkg_config(
+            dir.path(),
+            "armv8",
+            &package,
+            &static_openssl,
+            &["zlib".to_string()],
+        )?;
+        let pc = std::fs::read_to_string(pkgconfig_dir(dir.path(), "armv8").join("openssl.pc"))?;
+        assert!(pc.contains("Requires: zlib\n"));
+        assert!(pc.contains("Libs: -L${libdir} -lssl -lcrypto\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_recipe_dirs_and_flags_to_pc() -> Result<()> {
+        let dir = tempdir()?;
+        let package = ConanRef {
+            name: "foo".to_string(),
+            version: "1.0.0".to_string(),
+            user: "aurora".to_string(),
+        };
+        let cpp_info = PackageCppInfo {
+            package_name: "foo".to_string(),
+            libs: vec!["foo".to_string()],
+            build_info: CppBuildInfo {
+                includedirs: Some(vec!["include/foo".to_string(), "include".to_string()]),
+                bindirs: Some(Vec::new()),
+                defines: vec!["FOO_SHARED".to_string()],
+                cxxflags: vec!["-pthread".to_string()],
+                sharedlinkflags: vec!["-Wl,--as-needed".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        write_pkg_config(dir.path(), "armv8", &package, &cpp_info, &[])?;
+
+        let pc = std::fs::read_to_string(pkgconfig_dir(dir.path(), "armv8").join("foo.pc"))?;
+        assert!(pc.contains("includedir=${prefix}/include/foo\nincludedir2=${prefix}/include\n"));
+        assert!(pc.contains("libdir=${prefix}/lib\n"));
+        assert!(!pc.contains("bindir"));
+        assert!(pc.contains("Cflags: -I${includedir} -I${includedir2} -pthread -DFOO_SHARED\n"));
+        assert!(pc.contains("Libs: -L${libdir} -lfoo -Wl,--as-needed\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_recipe_dirs_and_flags_to_cmake_config() -> Result<()> {
+        let dir = tempdir()?;
+        let package = ConanRef {
+            name: "foo".to_string(),
+            version: "1.0.0".to_string(),
+            user: "aurora".to_string(),
+        };
+        let prefix = package_root(dir.path(), "armv8", "foo", "1.0.0");
+        std::fs::create_dir_all(prefix.join("include/foo"))?;
+        std::fs::create_dir_all(prefix.join("lib64"))?;
+        std::fs::write(prefix.join("lib64/libfoo.so.1"), "")?;
+        let cpp_info = PackageCppInfo {
+            package_name: "foo".to_string(),
+            libs: vec!["foo".to_string(), "bar".to_string()],
+            build_info: CppBuildInfo {
+                includedirs: Some(vec!["include/foo".to_string(), "missing".to_string()]),
+                libdirs: Some(vec!["lib64".to_string()]),
+                defines: vec!["FOO_SHARED".to_string()],
+                cflags: vec!["-std=c99".to_string()],
+                cxxflags: vec!["-pthread".to_string()],
+                sharedlinkflags: vec!["-Wl,--as-needed".to_string()],
+                exelinkflags: vec!["-rdynamic".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        write_cmake_config(dir.path(), "armv8", &package, &cpp_info, &BTreeMap::new())?;
+
+        let config = std::fs::read_to_string(
+            cmake_config_dir(dir.path(), "armv8").join("foo/fooConfig.cmake"),
+        )?;
+        assert!(
+            config.contains("INTERFACE_INCLUDE_DIRECTORIES \"${_AURORA_TP_PREFIX}/include/foo\"\n")
+        );
+        assert!(config.contains("INTERFACE_LINK_DIRECTORIES \"${_AURORA_TP_PREFIX}/lib64\"\n"));
+        assert!(config.contains("INTERFACE_COMPILE_DEFINITIONS \"FOO_SHARED\"\n"));
+        assert!(config.contains(
+            "INTERFACE_COMPILE_OPTIONS \"$<$<COMPILE_LANGUAGE:C>:-std=c99>;$<$<COMPILE_LANGUAGE:CXX>:-pthread>\"\n"
+        ));
+        assert!(config.contains(
+            "INTERFACE_LINK_OPTIONS \"$<$<STREQUAL:$<TARGET_PROPERTY:TYPE>,SHARED_LIBRARY>:-Wl,--as-needed>;\
+             $<$<STREQUAL:$<TARGET_PROPERTY:TYPE>,MODULE_LIBRARY>:-Wl,--as-needed>;\
+             $<$<STREQUAL:$<TARGET_PROPERTY:TYPE>,EXECUTABLE>:-rdynamic>\"\n"
+        ));
+        assert!(
+            config.contains(
+                "INTERFACE_LINK_LIBRARIES \"${_AURORA_TP_PREFIX}/lib64/libfoo.so.1;bar\"\n"
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn relocates_packaged_pkg_config_and_forwards_cmake_config() -> Result<()> {
+        let dir = tempdir()?;
+        let package = ConanRef {
+            name: "foo".to_string(),
+            version: "1.0.0".to_string(),
+            user: "aurora".to_string(),
+        };
+        let prefix = package_root(dir.path(), "armv8", "foo", "1.0.0");
+        std::fs::create_dir_all(prefix.join("lib/pkgconfig"))?;
+        std::fs::create_dir_all(prefix.join("lib/cmake/Foo"))?;
+        std::fs::write(
+            prefix.join("lib/pkgconfig/libfoo.pc"),
+            "prefix=/build/foo/package\nlibdir=/build/foo/package/lib\nincludedir=${prefix}/include\n\nName: libfoo\nLibs: -L${libdir} -lfoo\n",
+        )?;
+        std::fs::write(prefix.join("lib/cmake/Foo/FooConfig.cmake"), "")?;
+        std::fs::write(prefix.join("lib/cmake/Foo/FooConfigVersion.cmake"), "")?;
+        std::fs::write(prefix.join("lib/cmake/Foo/FooTargets.cmake"), "")?;
+
+        let pcs = find_packaged_pkg_configs(&prefix)?;
+        assert_eq!(pcs, vec![prefix.join("lib/pkgconfig/libfoo.pc")]);
+        assert_eq!(
+            install_packaged_pkg_config(dir.path(), "armv8", &package, &pcs[0])?,
+            "libfoo"
+        );
+        let pc = std::fs::read_to_string(pkgconfig_dir(dir.path(), "armv8").join("libfoo.pc"))?;
+        assert!(pc.starts_with(
+            "prefix=${pcfiledir}/../packages/foo/1.0.0\nlibdir=${prefix}/lib\nincludedir=${prefix}/include\n"
+        ));
+
+        let configs = find_packaged_cmake_configs(&prefix)?;
+        assert_eq!(configs, vec![prefix.join("lib/cmake/Foo/FooConfig.cmake")]);
+        let generated = cmake_config_dir(dir.path(), "armv8").join("Foo");
+        std::fs::create_dir_all(&generated)?;
+        std::fs::write(generated.join("foo-config.cmake"), "")?;
+        assert_eq!(
+            install_packaged_cmake_config(dir.path(), "armv8", &package, &configs[0])?,
+            "Foo"
+        );
+        assert!(!generated.join("foo-config.cmake").exists());
+        let forward = std::fs::read_to_string(generated.join("FooConfig.cmake"))?;
+        assert!(forward.contains(
+            "include(\"${CMAKE_CURRENT_LIST_DIR}/../../packages/foo/1.0.0/lib/cmake/Foo/FooConfig.cmake\")"
+        ));
+        assert!(generated.join("FooConfigVersion.cmake").exists());
+        Ok(())
+    }
 }
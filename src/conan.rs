@@ -1,4 +1,4 @@
-use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
 use std::fs;
 use std::path::Path;
 use std::sync::OnceLock;
@@ -6,17 +6,22 @@ use std::thread;
 use std::time::Duration;
 
 use anyhow::{Context, Result, anyhow};
+#[cfg(test)]
 use regex::Regex;
 use reqwest::StatusCode;
 use reqwest::Url;
 use reqwest::blocking::Client;
 use serde_json::Value;
 
-use crate::model::{ComponentInfo, ConanRef, DownloadArtifact, PackageCppInfo, ProjectMetadata};
+use crate::model::{
+    AppliedOverride, BinaryOrigin, ConanRef, DownloadArtifact, PackageCppInfo, ProjectMetadata,
+    ResolvedGraph,
+};
+use crate::policy::{DeniedPackage, ResolutionPolicy};
+use crate::recipe::{self, RecipeInfo, RecipeProfile};
 
-const DEFAULT_USER: &str = "aurora";
+pub const DEFAULT_USER: &str = "aurora";
 const ERROR_VERSION: &str = "error";
-const AURORA_DEVELOPER_BASE_URL: &str = "https://developer.auroraos.ru/";
 const AURORA_DEVELOPER_USER_AGENT: &str = "aurora-conan-cli/0.1 (+https://developer.auroraos.ru)";
 const AURORA_ARTIFACTORY_CONAN_STORAGE_URL: &str =
     "https://conan.omp.ru/artifactory/api/storage/public/aurora/";
@@ -25,12 +30,14 @@ const AURORA_ARTIFACTORY_PUBLIC_URL: &str = "https://conan.omp.ru/artifactory/pu
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct PackageDownloadSource {
     arch: String,
+    shared: Option<bool>,
     download_url: String,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct PackageBinaryRecord {
     arch: String,
+    shared: Option<bool>,
     download_url: String,
     requires: Vec<String>,
 }
@@ -74,11 +81,15 @@ pub trait ConanProvider {
         version: &str,
         destination_root: &Path,
     ) -> Result<Vec<DownloadArtifact>>;
+    /// Текст `conanfile.py` рецепта пакета.
+    fn fetch_conanfile(&self, package_name: &str, version: &str) -> Result<String>;
+    /// Транзитивный граф пакета по правилам `policy`.
     fn resolve_dependencies_without_conan(
         &self,
         package_name: &str,
         version: &str,
-    ) -> Result<Vec<ConanRef>>;
+        policy: &ResolutionPolicy,
+    ) -> Result<ResolvedGraph>;
 
     fn resolve_direct_dependency(
         &self,
@@ -91,6 +102,7 @@ pub trait ConanProvider {
         &self,
         project_root: &Path,
         direct_refs: &[ConanRef],
+        policy: &ResolutionPolicy,
     ) -> Result<ProjectMetadata>;
 }
 
@@ -166,11 +178,18 @@ impl ConanProvider for CliConanProvider {
 
         let mut artifacts = Vec::new();
         for source in sources {
+            // shared- и static-бинарники одной архитектуры не должны перезаписывать друг друга
+            let linkage_suffix = match source.shared {
+                Some(true) => "-shared",
+                Some(false) => "-static",
+                None => "",
+            };
             let file_name = format!(
-                "{}-{}-{}.tgz",
+                "{}-{}-{}{}.tgz",
                 package_name,
                 version,
-                sanitize_arch_for_filename(&source.arch)
+                sanitize_arch_for_filename(&source.arch),
+                linkage_suffix
             );
             let file_path = download_dir.join(file_name);
 
@@ -196,61 +215,134 @@ impl ConanProvider for CliConanProvider {
 
             artifacts.push(DownloadArtifact {
                 arch: source.arch,
+                shared: source.shared,
                 path: file_path,
+                origin: BinaryOrigin::from_url(&source.download_url),
             });
         }
 
         Ok(artifacts)
     }
 
+    fn fetch_conanfile(&self, package_name: &str, version: &str) -> Result<String> {
+        fetch_conanfile_from_artifactory(package_name, version)
+    }
+
     fn resolve_dependencies_without_conan(
         &self,
         package_name: &str,
         version: &str,
-    ) -> Result<Vec<ConanRef>> {
+        policy: &ResolutionPolicy,
+    ) -> Result<ResolvedGraph> {
         let mut source = ArtifactoryDependencyDataSource::default();
-        resolve_dependency_graph(package_name, version, &mut source)
+        resolve_dependency_graph(package_name, version, policy, &mut source)
     }
 
     fn resolve_project_metadata(
         &self,
         _project_root: &Path,
         direct_refs: &[ConanRef],
+        policy: &ResolutionPolicy,
     ) -> Result<ProjectMetadata> {
         if direct_refs.is_empty() {
-            return Ok(ProjectMetadata {
-                direct_pkg_modules: Vec::new(),
-                shared_lib_patterns: Vec::new(),
-                system_libs: Vec::new(),
-            });
+            return Ok(ProjectMetadata::default());
         }
 
         let mut direct_modules: Vec<String> = direct_refs.iter().map(|r| r.name.clone()).collect();
         direct_modules.sort();
         direct_modules.dedup();
         let mut all_packages = BTreeSet::new();
+        let mut versions = HashMap::new();
+        let mut package_dependencies = BTreeMap::new();
         for reference in direct_refs {
             all_packages.insert(reference.name.clone());
-            let transitives =
-                self.resolve_dependencies_without_conan(&reference.name, &reference.version)?;
+            versions.insert(reference.name.clone(), reference.version.clone());
+            let transitives = self
+                .resolve_dependencies_without_conan(&reference.name, &reference.version, policy)?
+                .packages;
+            let mut dependencies = Vec::new();
             for dep in transitives {
                 if dep.version != ERROR_VERSION {
-                    all_packages.insert(dep.name);
+                    all_packages.insert(dep.name.clone());
+                    dependencies.push(dep.name.clone());
+                    versions.entry(dep.name).or_insert(dep.version);
                 }
             }
+            package_dependencies.insert(reference.name.clone(), dependencies);
         }
 
-        let mut patterns = all_packages
-            .into_iter()
-            .map(|name| format!("lib{}.*", name))
-            .collect::<Vec<_>>();
+        // Бинарники в conan-режиме ставит сам Conan, поэтому SONAME прочитать негде:
+        // берём имена библиотек из cpp_info рецепта, а имя пакета — лишь как запасной вариант.
+        let mut patterns = Vec::new();
+        let mut package_lib_patterns = BTreeMap::new();
+        let mut system_libs = Vec::new();
+        let mut package_system_libs = BTreeMap::new();
+        let mut recipe_warnings = Vec::new();
+        let profile = RecipeProfile::aurora(None);
+        for package in &all_packages {
+            let version = versions
+                .get(package)
+                .map(String::as_str)
+                .unwrap_or_default();
+            let recipe = match fetch_recipe_from_artifactory(package, version, &profile) {
+                Ok(recipe) => Some(recipe),
+                Err(e) => {
+                    eprintln!("Warning: failed to fetch recipe for {package}/{version}: {e:#}");
+                    None
+                }
+            };
+            if let Some(recipe) = &recipe {
+                recipe_warnings.extend(
+                    recipe
+                        .unsupported
+                        .iter()
+                        .map(|warning| format!("{package}/{version}: {warning}")),
+                );
+            }
+            let cpp_info = recipe.map(|recipe| recipe.cpp_info);
+            let lib_names = cpp_info
+                .as_ref()
+                .map(cpp_info_lib_names)
+                .unwrap_or_default();
+            if let Some(info) = &cpp_info {
+                let own_system_libs = info.all_system_libs();
+                system_libs.extend(own_system_libs.iter().cloned());
+                package_system_libs.insert(package.clone(), own_system_libs);
+            }
+            let own_patterns: Vec<String> = if lib_names.is_empty() {
+                eprintln!(
+                    "Warning: no library names known for {package}, falling back to lib{package}.*"
+                );
+                vec![format!("lib{}.*", package)]
+            } else {
+                lib_names
+                    .iter()
+                    .map(|lib| format!("lib{}.*", lib))
+                    .collect()
+            };
+            patterns.extend(own_patterns.iter().cloned());
+            package_lib_patterns.insert(package.clone(), own_patterns);
+        }
         patterns.sort();
         patterns.dedup();
+        system_libs.sort();
+        system_libs.dedup();
+
+        let package_modules = direct_modules
+            .iter()
+            .map(|name| (name.clone(), vec![name.clone()]))
+            .collect();
 
         Ok(ProjectMetadata {
             direct_pkg_modules: direct_modules,
             shared_lib_patterns: patterns,
-            system_libs: Vec::new(),
+            system_libs,
+            package_modules,
+            package_lib_patterns,
+            package_system_libs,
+            package_dependencies,
+            recipe_warnings,
+            ..Default::default()
         })
     }
 }
@@ -289,57 +381,6 @@ impl DependencyDataSource for ArtifactoryDependencyDataSource {
     }
 }
 
-fn fetch_package_page_html(package_name: &str) -> Result<String> {
-    let client = Client::builder()
-        .user_agent(AURORA_DEVELOPER_USER_AGENT)
-        .connect_timeout(Duration::from_secs(20))
-        .timeout(Duration::from_secs(60))
-        .build()
-        .context("Не удалось инициализировать HTTP-клиент")?;
-
-    let mut url = Url::parse(AURORA_DEVELOPER_BASE_URL)
-        .context("Не удалось подготовить URL developer.auroraos.ru")?;
-    url.path_segments_mut()
-        .map_err(|_| anyhow!("Некорректный базовый URL developer.auroraos.ru"))?
-        .push("conan")
-        .push(package_name);
-
-    let response = send_get_with_retries(&client, &url)
-        .with_context(|| format!("Не удалось запросить {}", url.as_str()))?;
-
-    let status = response.status();
-    if status == StatusCode::NOT_FOUND {
-        return Err(anyhow!(
-            "Пакет '{}' не найден: {} вернул 404",
-            package_name,
-            url.as_str()
-        ));
-    }
-
-    if !status.is_success() {
-        return Err(anyhow!(
-            "Не удалось получить пакет '{}' из {}: HTTP {}",
-            package_name,
-            url.as_str(),
-            status.as_u16()
-        ));
-    }
-
-    response
-        .text()
-        .context("Не удалось прочитать HTML-ответ страницы пакета")
-}
-
-fn fetch_package_versions_from_portal(package_name: &str) -> Result<Vec<String>> {
-    let html = fetch_package_page_html(package_name)?;
-    parse_package_versions_html(&html).with_context(|| {
-        format!(
-            "Не удалось извлечь список версий пакета '{}' со страницы /conan/{}",
-            package_name, package_name
-        )
-    })
-}
-
 fn fetch_package_versions_from_artifactory(package_name: &str) -> Result<Vec<String>> {
     let payload = fetch_artifactory_storage_payload(&[package_name])?;
     parse_artifactory_storage_versions(&payload).with_context(|| {
@@ -479,6 +520,7 @@ fn fetch_package_download_sources_from_artifactory(
     for item in binaries {
         sources.push(PackageDownloadSource {
             arch: item.arch,
+            shared: item.shared,
             download_url: item.download_url,
         });
     }
@@ -613,7 +655,7 @@ fn fetch_package_binaries_from_artifactory(
             .text()
             .with_context(|| format!("Не удалось прочитать {}", info_url.as_str()))?;
 
-        let (arch, requires) = parse_conaninfo_text(&info_text);
+        let (arch, shared, requires) = parse_conaninfo_text(&info_text);
         let download_url = build_artifactory_public_url(&[
             package_name,
             version,
@@ -628,6 +670,7 @@ fn fetch_package_binaries_from_artifactory(
 
         result.push(PackageBinaryRecord {
             arch,
+            shared,
             download_url,
             requires,
         });
@@ -686,6 +729,7 @@ fn fetch_latest_package_revision(
     Ok(latest)
 }
 
+#[cfg(test)]
 fn parse_latest_revision_from_index(payload: &Value) -> Result<String> {
     let revisions = payload
         .get("revisions")
@@ -749,19 +793,7 @@ fn build_artifactory_public_url(segments: &[&str]) -> Result<Url> {
     Ok(url)
 }
 
-fn fetch_json_by_url(url: &Url) -> Result<Value> {
-    let client = artifactory_http_client()?;
-
-    let body = send_get_with_retries(client, url)?
-        .error_for_status()
-        .with_context(|| format!("HTTP ошибка при чтении {}", url.as_str()))?
-        .text()
-        .with_context(|| format!("Не удалось прочитать {}", url.as_str()))?;
-
-    serde_json::from_str(&body).with_context(|| format!("Некорректный JSON в {}", url.as_str()))
-}
-
-fn fetch_conanfile_from_artifactory(package_name: &str, version: &str) -> Result<String> {
+pub fn fetch_conanfile_from_artifactory(package_name: &str, version: &str) -> Result<String> {
     let rrev = fetch_latest_recipe_revision(package_name, version)?;
     let url = build_artifactory_public_url(&[
         package_name,
@@ -781,212 +813,53 @@ fn fetch_conanfile_from_artifactory(package_name: &str, version: &str) -> Result
         .with_context(|| format!("Не удалось прочитать {}", url.as_str()))
 }
 
-/// Извлекает cpp_info из conanfile.py пакета
-pub fn fetch_cpp_info_from_artifactory(package_name: &str, version: &str) -> Result<PackageCppInfo> {
-    let conanfile = fetch_conanfile_from_artifactory(package_name, version)?;
-    Ok(parse_cpp_info_from_text(package_name, &conanfile))
-}
-
-/// Парсит cpp_info метаданные из текста conanfile.py
-pub fn parse_cpp_info_from_text(package_name: &str, conanfile: &str) -> PackageCppInfo {
-    let mut info = PackageCppInfo {
-        package_name: package_name.to_string(),
-        ..Default::default()
-    };
-
-    // Проверяем header-library
-    let header_only_re = Regex::new(r#"package_type\s*=\s*["']header-library["']"#)
-        .unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile header_only regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-    info.is_header_only = header_only_re.is_match(conanfile);
-
-    // Парсим корневые libs
-    if let Some(libs) = parse_string_list(conanfile, r#"cpp_info\.libs\s*=\s*\[([^\]]*)\]"#) {
-        info.libs = libs;
-    }
-
-    // Парсим корневые system_libs (= [...])
-    if let Some(system_libs) = parse_string_list(conanfile, r#"cpp_info\.system_libs\s*=\s*\[([^\]]*)\]"#) {
-        info.system_libs = system_libs;
-    }
-
-    // Парсим корневые system_libs.append("...")
-    let append_re = Regex::new(r#"cpp_info\.system_libs\.append\(\s*["']([^"']+)["']\s*\)"#)
-        .unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile system_libs.append regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-    for caps in append_re.captures_iter(conanfile) {
-        let lib = caps[1].to_string();
-        if !info.system_libs.contains(&lib) {
-            info.system_libs.push(lib);
-        }
-    }
-
-    // Парсим корневые system_libs.extend([...])
-    if let Some(system_libs) = parse_string_list(conanfile, r#"cpp_info\.system_libs\.extend\(\s*\[([^\]]*)\]\s*\)"#) {
-        for lib in system_libs {
-            if !info.system_libs.contains(&lib) {
-                info.system_libs.push(lib);
-            }
-        }
-    }
-
-    // Парсим pkg_config_name для корня
-    let pkg_name_re = Regex::new(r#"cpp_info\.set_property\(\s*["']pkg_config_name["']\s*,\s*["']([^"']+)["']\s*\)"#)
-        .unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile pkg_config_name regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-    if let Some(caps) = pkg_name_re.captures(conanfile) {
-        info.pkg_config_name = Some(caps[1].to_string());
-    }
-
-    // Парсим компоненты
-    info.components = parse_components(conanfile);
+/// Текст документа по http(s)-URL.
+pub fn http_get_text(raw_url: &str) -> Result<String> {
+    let url = Url::parse(raw_url).with_context(|| format!("Некорректный URL {raw_url}"))?;
+    let client = artifactory_http_client()?;
 
-    info
+    send_get_with_retries(client, &url)
+        .with_context(|| format!("Не удалось запросить {}", url.as_str()))?
+        .error_for_status()
+        .with_context(|| format!("HTTP ошибка при чтении {}", url.as_str()))?
+        .text()
+        .with_context(|| format!("Не удалось прочитать {}", url.as_str()))
 }
 
-/// Парсит список строк из Python-массива в conanfile
-fn parse_string_list(content: &str, pattern: &str) -> Option<Vec<String>> {
-    let re = Regex::new(pattern).ok()?;
-    let caps = re.captures(content)?;
-    let array_content = caps.get(1)?.as_str();
-
-    let mut result = Vec::new();
-    // Парсим строки в одинарных или двойных кавычках
-    let string_re = Regex::new(r#"["']([^"']*)["']"#).ok()?;
-    for str_caps in string_re.captures_iter(array_content) {
-        let s = str_caps[1].trim().to_string();
-        if !s.is_empty() && !result.contains(&s) {
-            result.push(s);
-        }
-    }
-    Some(result)
+/// Скачивает conanfile.py пакета и вычисляет его cpp_info для профиля.
+pub fn fetch_recipe_from_artifactory(
+    package_name: &str,
+    version: &str,
+    profile: &RecipeProfile,
+) -> Result<RecipeInfo> {
+    let conanfile = fetch_conanfile_from_artifactory(package_name, version)?;
+    Ok(recipe::analyze_recipe(package_name, &conanfile, profile))
 }
 
-/// Парсит компоненты из conanfile.py
-fn parse_components(conanfile: &str) -> Vec<ComponentInfo> {
-    let mut components: Vec<ComponentInfo> = Vec::new();
-
-    // Находим все объявления компонентов: cpp_info.components["name"]
-    let component_decl_re = Regex::new(r#"cpp_info\.components\["([^"]+)"\]"#)
-        .unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile component_decl regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-
-    let mut component_names: Vec<String> = Vec::new();
-    for caps in component_decl_re.captures_iter(conanfile) {
-        let name = caps[1].to_string();
-        if !component_names.contains(&name) {
-            component_names.push(name);
-        }
-    }
-
-    for name in component_names {
-        let mut component = ComponentInfo {
-            name: name.clone(),
-            ..Default::default()
-        };
-
-        // Парсим libs компонента
-        let libs_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.libs\s*=\s*\[([^\]]*)\]"#,
-            regex::escape(&name)
-        );
-        if let Some(libs) = parse_string_list(conanfile, &libs_pattern) {
-            component.libs = libs;
-        }
-
-        // Парсим system_libs компонента (= [...])
-        let sys_libs_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.system_libs\s*=\s*\[([^\]]*)\]"#,
-            regex::escape(&name)
-        );
-        if let Some(system_libs) = parse_string_list(conanfile, &sys_libs_pattern) {
-            component.system_libs = system_libs;
-        }
-
-        // Парсим system_libs.append компонента
-        let append_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.system_libs\.append\(\s*["']([^"']+)["']\s*\)"#,
-            regex::escape(&name)
-        );
-        let append_re = Regex::new(&append_pattern).unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile component system_libs.append regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-        for caps in append_re.captures_iter(conanfile) {
-            let lib = caps[1].to_string();
-            if !component.system_libs.contains(&lib) {
-                component.system_libs.push(lib);
-            }
-        }
-
-        // Парсим system_libs.extend компонента
-        let extend_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.system_libs\.extend\(\s*\[([^\]]*)\]\s*\)"#,
-            regex::escape(&name)
-        );
-        if let Some(system_libs) = parse_string_list(conanfile, &extend_pattern) {
-            for lib in system_libs {
-                if !component.system_libs.contains(&lib) {
-                    component.system_libs.push(lib);
-                }
-            }
-        }
-
-        // Парсим pkg_config_name компонента
-        let pkg_name_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.set_property\(\s*["']pkg_config_name["']\s*,\s*["']([^"']+)["']\s*\)"#,
-            regex::escape(&name)
-        );
-        let pkg_name_re = Regex::new(&pkg_name_pattern).unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile component pkg_config_name regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-        if let Some(caps) = pkg_name_re.captures(conanfile) {
-            component.pkg_config_name = Some(caps[1].to_string());
-        }
-
-        // Парсим requires компонента (= [...])
-        let requires_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.requires\s*=\s*\[([^\]]*)\]"#,
-            regex::escape(&name)
-        );
-        if let Some(requires) = parse_string_list(conanfile, &requires_pattern) {
-            component.requires = requires;
-        }
+/// Парсит cpp_info метаданные из текста conanfile.py для профиля ОС Аврора без `arch`.
+#[cfg(test)]
+fn parse_cpp_info_from_text(package_name: &str, conanfile: &str) -> PackageCppInfo {
+    recipe::analyze_recipe(package_name, conanfile, &RecipeProfile::aurora(None)).cpp_info
+}
 
-        // Парсим requires.append компонента
-        let req_append_pattern = format!(
-            r#"cpp_info\.components\["{}\"]\.requires\.append\(\s*["']([^"']+)["']\s*\)"#,
-            regex::escape(&name)
-        );
-        let req_append_re = Regex::new(&req_append_pattern).unwrap_or_else(|e| {
-            eprintln!("Warning: failed to compile component requires.append regex: {e}");
-            Regex::new(r"^$").unwrap()
-        });
-        for caps in req_append_re.captures_iter(conanfile) {
-            let req = caps[1].to_string();
-            if !component.requires.contains(&req) {
-                component.requires.push(req);
+/// Имена библиотек пакета: корневые `libs` и `libs` всех компонентов.
+fn cpp_info_lib_names(info: &PackageCppInfo) -> Vec<String> {
+    let mut names = info.libs.clone();
+    for component in &info.components {
+        for lib in &component.libs {
+            if !names.contains(lib) {
+                names.push(lib.clone());
             }
         }
-
-        components.push(component);
     }
-
-    components
+    names
 }
 
-fn parse_conaninfo_text(content: &str) -> (String, Vec<String>) {
+/// Архитектура, опция `shared` и зависимости бинарника из его conaninfo.txt.
+fn parse_conaninfo_text(content: &str) -> (String, Option<bool>, Vec<String>) {
     let mut current_section = "";
     let mut arch = String::new();
+    let mut shared = None;
     let mut requires = BTreeSet::new();
 
     for raw in content.lines() {
@@ -1008,6 +881,13 @@ fn parse_conaninfo_text(content: &str) -> (String, Vec<String>) {
             continue;
         }
 
+        if current_section == "options"
+            && let Some(value) = line.strip_prefix("shared=")
+        {
+            shared = parse_python_bool(value);
+            continue;
+        }
+
         if current_section == "requires" {
             let normalized = normalize_dependency_ref(line);
             if !normalized.is_empty() {
@@ -1021,7 +901,15 @@ fn parse_conaninfo_text(content: &str) -> (String, Vec<String>) {
     } else {
         arch
     };
-    (arch, requires.into_iter().collect())
+    (arch, shared, requires.into_iter().collect())
+}
+
+fn parse_python_bool(value: &str) -> Option<bool> {
+    match value.trim() {
+        "True" | "true" => Some(true),
+        "False" | "false" => Some(false),
+        _ => None,
+    }
 }
 
 fn artifactory_http_client() -> Result<&'static Client> {
@@ -1081,46 +969,7 @@ fn normalize_dependency_ref(raw: &str) -> String {
         .to_string()
 }
 
-fn fetch_all_package_names_from_portal() -> Result<Vec<String>> {
-    let client = Client::builder()
-        .user_agent(AURORA_DEVELOPER_USER_AGENT)
-        .connect_timeout(Duration::from_secs(20))
-        .timeout(Duration::from_secs(60))
-        .build()
-        .context("Не удалось инициализировать HTTP-клиент")?;
-
-    let mut url = Url::parse(AURORA_DEVELOPER_BASE_URL)
-        .context("Не удалось подготовить URL developer.auroraos.ru")?;
-    url.path_segments_mut()
-        .map_err(|_| anyhow!("Некорректный базовый URL developer.auroraos.ru"))?
-        .push("conan");
-
-    let response = client
-        .get(url.clone())
-        .send()
-        .with_context(|| format!("Не удалось запросить {}", url.as_str()))?;
-
-    let status = response.status();
-    if !status.is_success() {
-        return Err(anyhow!(
-            "Не удалось получить список пакетов из {}: HTTP {}",
-            url.as_str(),
-            status.as_u16()
-        ));
-    }
-
-    let html = response
-        .text()
-        .context("Не удалось прочитать HTML-ответ страницы со списком пакетов")?;
-
-    parse_package_names_html(&html).with_context(|| {
-        format!(
-            "Не удалось извлечь список пакетов со страницы {}",
-            url.as_str()
-        )
-    })
-}
-
+#[cfg(test)]
 fn parse_package_versions_html(html: &str) -> Result<Vec<String>> {
     let section_re = Regex::new(r#"(?s)<h5>\s*Версия\s*</h5>\s*<select[^>]*>(.*?)</select>"#)
         .context("Не удалось подготовить regex для блока версий")?;
@@ -1167,6 +1016,7 @@ fn parse_package_versions_html(html: &str) -> Result<Vec<String>> {
     Ok(versions)
 }
 
+#[cfg(test)]
 fn parse_package_download_sources(
     html: &str,
     package_name: &str,
@@ -1229,9 +1079,19 @@ fn parse_package_download_sources(
             .filter(|value| !value.is_empty())
             .unwrap_or("package")
             .to_string();
+        let shared = package
+            .get("conanInfo")
+            .and_then(|value| value.get("options"))
+            .and_then(|value| value.get("shared"))
+            .and_then(|value| match value {
+                Value::Bool(flag) => Some(*flag),
+                Value::String(text) => parse_python_bool(text),
+                _ => None,
+            });
 
         result.push(PackageDownloadSource {
             arch,
+            shared,
             download_url: normalize_download_url(download_url),
         });
     }
@@ -1247,6 +1107,7 @@ fn parse_package_download_sources(
     Ok(result)
 }
 
+#[cfg(test)]
 fn parse_next_data_json(html: &str) -> Result<Value> {
     let next_data_re =
         Regex::new(r#"(?s)<script[^>]*id=["']__NEXT_DATA__["'][^>]*>(.*?)</script>"#)
@@ -1260,6 +1121,7 @@ fn parse_next_data_json(html: &str) -> Result<Value> {
     serde_json::from_str(payload).context("Не удалось разобрать JSON из __NEXT_DATA__")
 }
 
+#[cfg(test)]
 fn parse_versions_from_next_data(next_data: &Value) -> Result<Vec<Value>> {
     let page_props = next_data
         .get("props")
@@ -1306,6 +1168,7 @@ fn sanitize_arch_for_filename(arch: &str) -> String {
     }
 }
 
+#[cfg(test)]
 fn normalize_download_url(raw_url: &str) -> String {
     let Ok(mut url) = Url::parse(raw_url) else {
         return raw_url.to_string();
@@ -1322,6 +1185,7 @@ fn normalize_download_url(raw_url: &str) -> String {
     raw_url.to_string()
 }
 
+#[cfg(test)]
 fn parse_package_names_html(html: &str) -> Result<Vec<String>> {
     let package_re = Regex::new(r#"href="/conan/([^"/?#]+)""#)
         .context("Не удалось подготовить regex для ссылок пакетов")?;
@@ -1362,12 +1226,26 @@ fn filter_package_names_by_query(package_names: &[String], query: &str) -> Vec<S
     filtered
 }
 
+/// Граф зависимостей пакета. Запрещённые `policy` пакеты прерывают резолв, принудительные
+/// версии выбираются вместо запрошенных рецептами и раскрываются дальше уже они.
 fn resolve_dependency_graph(
     root_package: &str,
     root_version: &str,
+    policy: &ResolutionPolicy,
     source: &mut dyn DependencyDataSource,
-) -> Result<Vec<ConanRef>> {
+) -> Result<ResolvedGraph> {
     let debug_deps = std::env::var_os("AURORA_CONAN_DEBUG_DEPS").is_some();
+    if let Some(rule) = policy.denial(root_package, root_version) {
+        return Err(denied_package_error(rule, root_package, root_version, None));
+    }
+    let root_error = || ResolvedGraph {
+        packages: vec![ConanRef {
+            name: root_package.to_string(),
+            version: ERROR_VERSION.to_string(),
+            user: DEFAULT_USER.to_string(),
+        }],
+        overrides: Vec::new(),
+    };
     let root_versions = match source.list_versions(root_package) {
         Ok(versions) => versions,
         Err(error) => {
@@ -1377,11 +1255,7 @@ fn resolve_dependency_graph(
                     root_package, root_version
                 );
             }
-            return Ok(vec![ConanRef {
-                name: root_package.to_string(),
-                version: ERROR_VERSION.to_string(),
-                user: DEFAULT_USER.to_string(),
-            }]);
+            return Ok(root_error());
         }
     };
     if !root_versions.iter().any(|item| item == root_version) {
@@ -1395,6 +1269,7 @@ fn resolve_dependency_graph(
 
     let mut constraints: HashMap<String, Vec<DependencyConstraint>> = HashMap::new();
     let mut selected: HashMap<String, ConanRef> = HashMap::new();
+    let mut overrides: BTreeMap<String, AppliedOverride> = BTreeMap::new();
     let mut queue = VecDeque::new();
     let mut visited: HashSet<(String, String)> = HashSet::new();
 
@@ -1428,22 +1303,58 @@ fn resolve_dependency_graph(
                     // сохраняем найденную версию и продолжаем резолв без углубления.
                     continue;
                 }
-                return Ok(vec![ConanRef {
-                    name: root_package.to_string(),
-                    version: ERROR_VERSION.to_string(),
-                    user: DEFAULT_USER.to_string(),
-                }]);
+                return Ok(root_error());
             }
         };
         for constraint in dependency_constraints {
             let package_name = constraint.name.clone();
+            let requested = constraint.raw.clone();
             let package_constraints = constraints.entry(constraint.name.clone()).or_default();
             if !package_constraints.contains(&constraint) {
                 package_constraints.push(constraint);
             }
 
-            let resolved_user = resolve_user_for_constraints(&package_name, package_constraints)?;
-            let resolved_version = if let Some(exact) =
+            let mut resolved_user =
+                resolve_user_for_constraints(&package_name, package_constraints)?;
+            let resolved_version = if let Some(forced) = policy.forced.get(&package_name) {
+                if !overrides.contains_key(&package_name) {
+                    let available_versions =
+                        source.list_versions(&package_name).with_context(|| {
+                            format!(
+                                "Не удалось проверить версию '{}' пакета '{}', заданную {}",
+                                forced.version, package_name, forced.source
+                            )
+                        })?;
+                    if !available_versions.contains(&forced.version) {
+                        return Err(anyhow!(
+                            "Для пакета '{}' не найдена версия '{}', заданная {}. Доступные версии: {}",
+                            package_name,
+                            forced.version,
+                            forced.source,
+                            available_versions.join(", ")
+                        ));
+                    }
+                }
+                overrides.insert(
+                    package_name.clone(),
+                    AppliedOverride {
+                        name: package_name.clone(),
+                        version: forced.version.clone(),
+                        source: forced.source.clone(),
+                        requested: package_constraints
+                            .iter()
+                            .map(|item| item.raw.clone())
+                            .collect(),
+                        violated: package_constraints
+                            .iter()
+                            .filter(|item| !matcher_satisfies(&item.matcher, &forced.version))
+                            .map(|item| item.raw.clone())
+                            .collect(),
+                    },
+                );
+                resolved_user = forced.user.clone();
+                forced.version.clone()
+            } else if let Some(exact) =
                 resolve_exact_without_remote_lookup(&package_name, package_constraints)?
             {
                 exact
@@ -1458,6 +1369,15 @@ fn resolve_dependency_graph(
                 }
             };
 
+            if let Some(rule) = policy.denial(&package_name, &resolved_version) {
+                return Err(denied_package_error(
+                    rule,
+                    &package_name,
+                    &resolved_version,
+                    Some((&current, &requested)),
+                ));
+            }
+
             let resolved_ref = ConanRef {
                 name: package_name,
                 version: resolved_version,
@@ -1478,7 +1398,35 @@ fn resolve_dependency_graph(
 
     let mut refs: Vec<ConanRef> = selected.into_values().collect();
     refs.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
-    Ok(refs)
+    Ok(ResolvedGraph {
+        packages: refs,
+        overrides: overrides.into_values().collect(),
+    })
+}
+
+fn denied_package_error(
+    rule: &DeniedPackage,
+    package_name: &str,
+    version: &str,
+    required_by: Option<(&ConanRef, &str)>,
+) -> anyhow::Error {
+    let mut message = format!(
+        "Пакет {}/{} запрещён правилом 'deny {}' политики {}",
+        package_name,
+        version,
+        rule.pattern(),
+        rule.source
+    );
+    if !rule.reason.is_empty() {
+        message.push_str(&format!(": {}", rule.reason));
+    }
+    if let Some((parent, raw)) = required_by {
+        message.push_str(&format!(
+            " (требуется {}/{} как {})",
+            parent.name, parent.version, raw
+        ));
+    }
+    anyhow!(message)
 }
 
 fn resolve_user_for_constraints(
@@ -1599,53 +1547,7 @@ fn matcher_satisfies(matcher: &VersionMatcher, candidate: &str) -> bool {
     }
 }
 
-fn fetch_package_version_nodes_from_portal(package_name: &str) -> Result<Vec<Value>> {
-    let html = fetch_package_page_html(package_name)?;
-    let next_data = parse_next_data_json(&html)?;
-    parse_versions_from_next_data(&next_data).with_context(|| {
-        format!(
-            "Не удалось извлечь версии для пакета '{}' из __NEXT_DATA__",
-            package_name
-        )
-    })
-}
-
-fn extract_versions_from_nodes(version_nodes: &[Value]) -> Vec<String> {
-    let mut versions = Vec::new();
-    for item in version_nodes {
-        if let Some(version) = item.get("version").and_then(Value::as_str) {
-            let normalized = version.trim();
-            if !normalized.is_empty() && !versions.iter().any(|v| v == normalized) {
-                versions.push(normalized.to_string());
-            }
-        }
-    }
-    versions
-}
-
-fn find_version_node<'a>(
-    version_nodes: &'a [Value],
-    package_name: &str,
-    requested_version: &str,
-) -> Result<&'a Value> {
-    version_nodes
-        .iter()
-        .find(|item| {
-            item.get("version")
-                .and_then(Value::as_str)
-                .is_some_and(|version| version == requested_version)
-        })
-        .ok_or_else(|| {
-            let available = extract_versions_from_nodes(version_nodes);
-            anyhow!(
-                "Для пакета '{}' не найдена версия '{}'. Доступные версии: {}",
-                package_name,
-                requested_version,
-                available.join(", ")
-            )
-        })
-}
-
+#[cfg(test)]
 fn parse_dependency_constraints_from_version_node(
     version_node: &Value,
     package_name: &str,
@@ -1669,6 +1571,7 @@ fn parse_dependency_constraints_from_version_node(
     Ok(parsed)
 }
 
+#[cfg(test)]
 fn collect_requires_from_conan_info(version_node: &Value) -> Vec<String> {
     let packages = version_node
         .get("data")
@@ -1697,6 +1600,7 @@ fn collect_requires_from_conan_info(version_node: &Value) -> Vec<String> {
     refs.into_iter().collect()
 }
 
+#[cfg(test)]
 fn collect_requires_from_conanfile(version_node: &Value) -> Vec<String> {
     let conanfile = version_node
         .get("data")
@@ -1711,20 +1615,13 @@ fn collect_requires_from_conanfile_text(conanfile: &str) -> Vec<String> {
         return Vec::new();
     }
 
-    let requires_re = Regex::new(r#"self\.requires\(\s*f?["']([^"']+)["']"#)
-        .expect("regex for self.requires must be valid");
-
-    let mut refs = BTreeSet::new();
-    for captures in requires_re.captures_iter(conanfile) {
-        let candidate = captures
-            .get(1)
-            .map(|m| normalize_dependency_ref(m.as_str()))
-            .unwrap_or_default();
-        if !candidate.is_empty() {
-            refs.insert(candidate);
-        }
-    }
-
+    let recipe = recipe::analyze_recipe("", conanfile, &RecipeProfile::aurora(None));
+    let refs: BTreeSet<String> = recipe
+        .requires
+        .iter()
+        .map(|raw| normalize_dependency_ref(raw))
+        .filter(|candidate| !candidate.is_empty())
+        .collect();
     refs.into_iter().collect()
 }
 
@@ -1826,13 +1723,14 @@ mod tests {
 
     use super::{
         DependencyConstraint, DependencyDataSource, VersionMatcher, filter_package_names_by_query,
-        normalize_download_url, parse_artifactory_storage_versions, parse_dependency_constraint,
-        parse_dependency_constraints_from_version_node, parse_latest_revision_from_index,
-        parse_package_download_sources, parse_package_names_html, parse_package_versions_html,
-        parse_version_matcher, parse_versions_from_next_data, resolve_dependency_graph,
-        resolve_exact_without_remote_lookup, sanitize_arch_for_filename, select_dependency_version,
-        select_version_for_constraints,
+        normalize_download_url, parse_artifactory_storage_versions, parse_conaninfo_text,
+        parse_dependency_constraint, parse_dependency_constraints_from_version_node,
+        parse_latest_revision_from_index, parse_package_download_sources, parse_package_names_html,
+        parse_package_versions_html, parse_version_matcher, parse_versions_from_next_data,
+        resolve_dependency_graph, resolve_exact_without_remote_lookup, sanitize_arch_for_filename,
+        select_dependency_version, select_version_for_constraints,
     };
+    use crate::policy::{DeniedPackage, ForcedVersion, ResolutionPolicy};
 
     #[test]
     fn parses_versions_from_version_select_block() -> Result<()> {
@@ -1952,6 +1850,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn parses_shared_option_from_conaninfo() {
+        let (arch, shared, requires) = parse_conaninfo_text(
+            "[settings]\narch=armv8\nos=Linux\n[options]\nfPIC=True\nshared=False\n[requires]\nzlib/1.3.1#abc\n",
+        );
+        assert_eq!(arch, "armv8");
+        assert_eq!(shared, Some(false));
+        assert_eq!(requires, vec!["zlib/1.3.1".to_string()]);
+
+        let (_, shared, _) = parse_conaninfo_text("[settings]\narch=armv8\n");
+        assert_eq!(shared, None);
+    }
+
     #[test]
     fn parses_download_sources_uses_package_for_header_only_without_arch() -> Result<()> {
         let html = r#"
@@ -2006,7 +1917,7 @@ mod tests {
                 "packages": [
                     { "conanInfo": {} }
                 ],
-                "conanfile": "def requirements(self):\\n    self.requires(\"x/1.0.0@aurora\")\\n    self.requires('y/2.1.Z@aurora')\\n"
+                "conanfile": "def requirements(self):\n    self.requires(\"x/1.0.0@aurora\")\n    self.requires('y/2.1.Z@aurora')\n"
             }
         });
 
@@ -2163,8 +2074,13 @@ mod tests {
             ]),
         };
 
-        let resolved = resolve_dependency_graph("root", "1.0.0", &mut source)?;
-        let got: Vec<String> = resolved.into_iter().map(|r| r.to_ref_string()).collect();
+        let resolved =
+            resolve_dependency_graph("root", "1.0.0", &ResolutionPolicy::default(), &mut source)?;
+        let got: Vec<String> = resolved
+            .packages
+            .into_iter()
+            .map(|r| r.to_ref_string())
+            .collect();
         assert_eq!(
             got,
             vec!["a/1.3.2@aurora".to_string(), "b/2.5.0@aurora".to_string(),]
@@ -2200,8 +2116,9 @@ mod tests {
             ]),
         };
 
-        let err = resolve_dependency_graph("root", "1.0.0", &mut source)
-            .expect_err("expected conflict for b");
+        let err =
+            resolve_dependency_graph("root", "1.0.0", &ResolutionPolicy::default(), &mut source)
+                .expect_err("expected conflict for b");
         assert!(err.to_string().contains("конфликтующие точные версии"));
         Ok(())
     }
@@ -2216,8 +2133,13 @@ mod tests {
             )]),
         };
 
-        let resolved = resolve_dependency_graph("root", "1.0.0", &mut source)?;
-        let got: Vec<String> = resolved.into_iter().map(|r| r.to_ref_string()).collect();
+        let resolved =
+            resolve_dependency_graph("root", "1.0.0", &ResolutionPolicy::default(), &mut source)?;
+        let got: Vec<String> = resolved
+            .packages
+            .into_iter()
+            .map(|r| r.to_ref_string())
+            .collect();
         assert_eq!(got, vec!["blocked/error@aurora".to_string()]);
         Ok(())
     }
@@ -2235,8 +2157,13 @@ mod tests {
             )]),
         };
 
-        let resolved = resolve_dependency_graph("root", "1.0.0", &mut source)?;
-        let got: Vec<String> = resolved.into_iter().map(|r| r.to_ref_string()).collect();
+        let resolved =
+            resolve_dependency_graph("root", "1.0.0", &ResolutionPolicy::default(), &mut source)?;
+        let got: Vec<String> = resolved
+            .packages
+            .into_iter()
+            .map(|r| r.to_ref_string())
+            .collect();
         assert_eq!(got, vec!["blocked/1.2.3@aurora".to_string()]);
         Ok(())
     }
@@ -2248,12 +2175,112 @@ mod tests {
             constraints_by_ref: HashMap::new(),
         };
 
-        let resolved = resolve_dependency_graph("root", "1.0.0", &mut source)?;
-        let got: Vec<String> = resolved.into_iter().map(|r| r.to_ref_string()).collect();
+        let resolved =
+            resolve_dependency_graph("root", "1.0.0", &ResolutionPolicy::default(), &mut source)?;
+        let got: Vec<String> = resolved
+            .packages
+            .into_iter()
+            .map(|r| r.to_ref_string())
+            .collect();
         assert_eq!(got, vec!["root/error@aurora".to_string()]);
         Ok(())
     }
 
+    #[test]
+    fn resolve_dependency_graph_applies_forced_versions_and_denied_packages() -> Result<()> {
+        let versions = |items: &[&str]| items.iter().map(|item| item.to_string()).collect();
+        let key = |name: &str, version: &str| (name.to_string(), version.to_string());
+        let mut source = FakeDependencyDataSource {
+            versions_by_package: HashMap::from([
+                ("root".to_string(), versions(&["1.0.0"])),
+                ("a".to_string(), versions(&["1.0.0"])),
+                ("b".to_string(), versions(&["2.0.0"])),
+                ("zlib".to_string(), versions(&["1.3.1", "1.2.13", "1.2.11"])),
+                ("legacy".to_string(), versions(&["1.0.0"])),
+            ]),
+            constraints_by_ref: HashMap::from([
+                (
+                    key("root", "1.0.0"),
+                    vec![
+                        parse_dependency_constraint("a/1.0.0@aurora")?,
+                        parse_dependency_constraint("b/2.0.0@aurora")?,
+                    ],
+                ),
+                (
+                    key("a", "1.0.0"),
+                    vec![parse_dependency_constraint("zlib/1.2.11@aurora")?],
+                ),
+                (
+                    key("b", "2.0.0"),
+                    vec![parse_dependency_constraint("zlib/1.2.Z@aurora")?],
+                ),
+                (
+                    key("zlib", "1.2.11"),
+                    vec![parse_dependency_constraint("legacy/1.0.0@aurora")?],
+                ),
+                (key("zlib", "1.3.1"), Vec::new()),
+                (key("legacy", "1.0.0"), Vec::new()),
+            ]),
+        };
+
+        let mut policy = ResolutionPolicy::default();
+        policy.forced.insert(
+            "zlib".to_string(),
+            ForcedVersion {
+                version: "1.3.1".to_string(),
+                user: "aurora".to_string(),
+                source: "policy.txt".to_string(),
+            },
+        );
+        let resolved = resolve_dependency_graph("root", "1.0.0", &policy, &mut source)?;
+        let got: Vec<String> = resolved
+            .packages
+            .iter()
+            .map(|r| r.to_ref_string())
+            .collect();
+        assert_eq!(
+            got,
+            vec!["a/1.0.0@aurora", "b/2.0.0@aurora", "zlib/1.3.1@aurora"]
+        );
+        assert_eq!(resolved.overrides.len(), 1);
+        assert_eq!(resolved.overrides[0].version, "1.3.1");
+        assert_eq!(
+            resolved.overrides[0].requested,
+            vec!["zlib/1.2.11@aurora", "zlib/1.2.Z@aurora"]
+        );
+        assert_eq!(
+            resolved.overrides[0].violated,
+            resolved.overrides[0].requested
+        );
+
+        let zlib_versions = source.versions_by_package.remove("zlib").expect("zlib");
+        let err = resolve_dependency_graph("root", "1.0.0", &policy, &mut source)
+            .expect_err("forced version cannot be checked");
+        let message = format!("{err:#}");
+        assert!(message.contains("Не удалось проверить версию '1.3.1' пакета 'zlib'"));
+        assert!(message.contains("unknown package zlib"));
+        source
+            .versions_by_package
+            .insert("zlib".to_string(), zlib_versions);
+
+        policy.denied.push(DeniedPackage {
+            name: "b".to_string(),
+            version: Some("2.*".to_string()),
+            reason: "deprecated".to_string(),
+            source: "policy.txt".to_string(),
+        });
+        let err = resolve_dependency_graph("root", "1.0.0", &policy, &mut source)
+            .expect_err("b is denied");
+        let message = err.to_string();
+        assert!(message.contains("b/2.0.0 запрещён правилом 'deny b/2.*'"));
+        assert!(message.contains("deprecated (требуется root/1.0.0 как b/2.0.0@aurora)"));
+
+        let err =
+            resolve_dependency_graph("b", "2.0.0", &policy, &mut source).expect_err("denied root");
+        assert!(err.to_string().contains("Пакет b/2.0.0 запрещён"));
+        Ok(())
+    }
+
     #[test]
     fn sanitize_arch_for_filename_replaces_invalid_chars() {
         assert_eq!(sanitize_arch_for_filename("armv8"), "armv8");
@@ -2362,17 +2389,37 @@ class OpensslConan(ConanFile):
         let info = super::parse_cpp_info_from_text("openssl", conanfile);
         assert_eq!(info.components.len(), 2);
 
-        let crypto = info.components.iter().find(|c| c.name == "crypto").expect("crypto component");
+        let crypto = info
+            .components
+            .iter()
+            .find(|c| c.name == "crypto")
+            .expect("crypto component");
         assert_eq!(crypto.libs, vec!["crypto"]);
         assert_eq!(crypto.system_libs, vec!["pthread", "dl", "rt"]);
         assert_eq!(crypto.pkg_config_name, Some("libcrypto".to_string()));
 
-        let ssl = info.components.iter().find(|c| c.name == "ssl").expect("ssl component");
+        let ssl = info
+            .components
+            .iter()
+            .find(|c| c.name == "ssl")
+            .expect("ssl component");
         assert_eq!(ssl.libs, vec!["ssl"]);
         assert_eq!(ssl.requires, vec!["crypto"]);
         assert_eq!(ssl.pkg_config_name, Some("libssl".to_string()));
     }
 
+    #[test]
+    fn cpp_info_lib_names_include_component_libs() {
+        let conanfile = r#"
+class OpensslConan(ConanFile):
+    def package_info(self):
+        self.cpp_info.components["ssl"].libs = ["ssl"]
+        self.cpp_info.components["crypto"].libs = ["crypto"]
+"#;
+        let info = super::parse_cpp_info_from_text("openssl", conanfile);
+        assert_eq!(super::cpp_info_lib_names(&info), vec!["ssl", "crypto"]);
+    }
+
     #[test]
     fn test_parse_pkg_config_name() {
         let conanfile = r#"
@@ -2387,6 +2434,26 @@ class CurlConan(ConanFile):
         assert_eq!(info.pkg_config_name, Some("libcurl".to_string()));
     }
 
+    #[test]
+    fn parses_cmake_names_for_root_and_components() {
+        let conanfile = r#"
+class OpensslConan(ConanFile):
+    def package_info(self):
+        self.cpp_info.set_property("cmake_file_name", "OpenSSL")
+        self.cpp_info.set_property("cmake_target_name", "OpenSSL::OpenSSL")
+        self.cpp_info.components["ssl"].set_property("cmake_target_name", "OpenSSL::SSL")
+        self.cpp_info.components["crypto"].libs = ["crypto"]
+"#;
+        let info = super::parse_cpp_info_from_text("openssl", conanfile);
+        assert_eq!(info.cmake_file_name.as_deref(), Some("OpenSSL"));
+        assert_eq!(info.cmake_target_name.as_deref(), Some("OpenSSL::OpenSSL"));
+
+        let ssl = info.components.iter().find(|c| c.name == "ssl").unwrap();
+        assert_eq!(ssl.cmake_target_name.as_deref(), Some("OpenSSL::SSL"));
+        let crypto = info.components.iter().find(|c| c.name == "crypto").unwrap();
+        assert_eq!(crypto.cmake_target_name, None);
+    }
+
     #[test]
     fn test_parse_header_only() {
         let conanfile = r#"
@@ -2421,32 +2488,34 @@ class SomeConan(ConanFile):
         assert!(comp.system_libs.contains(&"pthread".to_string()));
         assert!(comp.system_libs.contains(&"dl".to_string()));
     }
-}
 
-fn collect_libs(value: &Value, libs: &mut BTreeSet<String>) {
-    match value {
-        Value::Object(map) => {
-            for (key, nested) in map {
-                if key == "libs" {
-                    if let Value::Array(arr) = nested {
-                        for item in arr {
-                            if let Value::String(lib_name) = item {
-                                let normalized = lib_name.trim();
-                                if !normalized.is_empty() {
-                                    libs.insert(normalized.to_string());
-                                }
-                            }
-                        }
-                    }
-                }
-                collect_libs(nested, libs);
-            }
-        }
-        Value::Array(arr) => {
-            for nested in arr {
-                collect_libs(nested, libs);
-            }
-        }
-        _ => {}
+    #[test]
+    fn parses_dirs_and_flags_for_root_and_components() {
+        let conanfile = r#"
+class FooConan(ConanFile):
+    def package_info(self):
+        self.cpp_info.includedirs = ["include/foo"]
+        self.cpp_info.defines = ["FOO_SHARED"]
+        self.cpp_info.cxxflags.append("-pthread")
+        self.cpp_info.sharedlinkflags.extend(["-Wl,--as-needed"])
+        self.cpp_info.components["core"].libs = ["foo_core"]
+        self.cpp_info.components["core"].libdirs.append("lib/extra")
+        self.cpp_info.components["core"].bindirs = []
+"#;
+        let info = super::parse_cpp_info_from_text("foo", conanfile);
+        assert_eq!(
+            info.build_info.includedirs,
+            Some(vec!["include/foo".to_string()])
+        );
+        assert_eq!(info.build_info.libdirs, None);
+        assert_eq!(info.build_info.libdirs(), vec!["lib"]);
+        assert_eq!(info.build_info.defines, vec!["FOO_SHARED"]);
+        assert_eq!(info.build_info.cxxflags, vec!["-pthread"]);
+        assert_eq!(info.build_info.sharedlinkflags, vec!["-Wl,--as-needed"]);
+
+        let core = &info.components[0].build_info;
+        assert_eq!(core.libdirs(), vec!["lib", "lib/extra"]);
+        assert_eq!(core.bindirs, Some(Vec::new()));
+        assert!(core.defines.is_empty());
     }
 }
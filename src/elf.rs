@@ -0,0 +1,747 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+
+const ELF_MAGIC: &[u8; 4] = b"\x7fELF";
+const SHT_DYNAMIC: u32 = 6;
+const DT_NULL: u64 = 0;
+const DT_NEEDED: u64 = 1;
+const DT_SONAME: u64 = 14;
+const AR_MAGIC: &[u8] = b"!<arch>\n";
+const AR_HEADER_SIZE: usize = 60;
+const EM_386: u16 = 3;
+const EM_ARM: u16 = 40;
+pub const EM_X86_64: u16 = 62;
+pub const EM_AARCH64: u16 = 183;
+const SYSTEM_LIBRARIES: &str = include_str!("../data/system-libraries.txt");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElfClass {
+    Elf32,
+    Elf64,
+}
+
+/// Сведения из заголовка и секции `.dynamic` ELF-файла.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ElfInfo {
+    pub class: ElfClass,
+    pub machine: u16,
+    pub soname: Option<String>,
+    pub needed: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SharedLibrary {
+    pub path: PathBuf,
+    pub info: ElfInfo,
+}
+
+impl SharedLibrary {
+    /// SONAME библиотеки, а при его отсутствии — имя файла.
+    pub fn provided_name(&self) -> String {
+        self.info.soname.clone().unwrap_or_else(|| {
+            self.path
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    class: ElfClass,
+    little_endian: bool,
+}
+
+impl Reader<'_> {
+    fn slice(&self, offset: u64, len: u64) -> Result<&[u8]> {
+        let start = usize::try_from(offset).map_err(|_| anyhow!("Смещение вне файла"))?;
+        let len = usize::try_from(len).map_err(|_| anyhow!("Размер вне файла"))?;
+        let end = start
+            .checked_add(len)
+            .ok_or_else(|| anyhow!("Переполнение смещения ELF"))?;
+        self.bytes
+            .get(start..end)
+            .ok_or_else(|| anyhow!("ELF обрезан: ожидалось {} байт по смещению {}", len, start))
+    }
+
+    fn u16(&self, offset: u64) -> Result<u16> {
+        let raw: [u8; 2] = self.slice(offset, 2)?.try_into()?;
+        Ok(if self.little_endian {
+            u16::from_le_bytes(raw)
+        } else {
+            u16::from_be_bytes(raw)
+        })
+    }
+
+    fn u32(&self, offset: u64) -> Result<u32> {
+        let raw: [u8; 4] = self.slice(offset, 4)?.try_into()?;
+        Ok(if self.little_endian {
+            u32::from_le_bytes(raw)
+        } else {
+            u32::from_be_bytes(raw)
+        })
+    }
+
+    fn u64(&self, offset: u64) -> Result<u64> {
+        let raw: [u8; 8] = self.slice(offset, 8)?.try_into()?;
+        Ok(if self.little_endian {
+            u64::from_le_bytes(raw)
+        } else {
+            u64::from_be_bytes(raw)
+        })
+    }
+
+    /// Читает машинное слово: 4 байта для ELF32, 8 байт для ELF64.
+    fn word(&self, offset: u64) -> Result<u64> {
+        match self.class {
+            ElfClass::Elf32 => self.u32(offset).map(u64::from),
+            ElfClass::Elf64 => self.u64(offset),
+        }
+    }
+
+    fn c_string(&self, offset: u64) -> Result<String> {
+        let start = usize::try_from(offset).map_err(|_| anyhow!("Смещение вне файла"))?;
+        let tail = self
+            .bytes
+            .get(start..)
+            .ok_or_else(|| anyhow!("Строка ELF вне файла (смещение {})", start))?;
+        let end = tail
+            .iter()
+            .position(|byte| *byte == 0)
+            .ok_or_else(|| anyhow!("Незавершённая строка ELF (смещение {})", start))?;
+        Ok(String::from_utf8_lossy(&tail[..end]).to_string())
+    }
+}
+
+/// Сумма смещений из заголовков ELF; переполнение означает повреждённый файл.
+fn offset_add(base: u64, delta: u64) -> Result<u64> {
+    base.checked_add(delta)
+        .ok_or_else(|| anyhow!("Переполнение смещения ELF"))
+}
+
+struct SectionHeader {
+    kind: u32,
+    offset: u64,
+    size: u64,
+    link: u32,
+    entry_size: u64,
+}
+
+fn open_reader(bytes: &[u8]) -> Result<Option<Reader<'_>>> {
+    if bytes.len() < 16 || &bytes[..4] != ELF_MAGIC {
+        return Ok(None);
+    }
+
+    let class = match bytes[4] {
+        1 => ElfClass::Elf32,
+        2 => ElfClass::Elf64,
+        other => return Err(anyhow!("Неизвестный класс ELF: {}", other)),
+    };
+    let little_endian = match bytes[5] {
+        1 => true,
+        2 => false,
+        other => return Err(anyhow!("Неизвестный порядок байт ELF: {}", other)),
+    };
+    Ok(Some(Reader {
+        bytes,
+        class,
+        little_endian,
+    }))
+}
+
+/// Читает только класс и `e_machine` из заголовка ELF.
+pub fn parse_elf_header(bytes: &[u8]) -> Result<Option<(ElfClass, u16)>> {
+    let Some(reader) = open_reader(bytes)? else {
+        return Ok(None);
+    };
+    Ok(Some((reader.class, reader.u16(18)?)))
+}
+
+/// Разбирает ELF-файл. Возвращает `None`, если данные не являются ELF.
+pub fn parse_elf(bytes: &[u8]) -> Result<Option<ElfInfo>> {
+    let Some(reader) = open_reader(bytes)? else {
+        return Ok(None);
+    };
+    let class = reader.class;
+
+    let machine = reader.u16(18)?;
+    let (shoff, shentsize, shnum) = match class {
+        ElfClass::Elf32 => (u64::from(reader.u32(32)?), reader.u16(46)?, reader.u16(48)?),
+        ElfClass::Elf64 => (reader.u64(40)?, reader.u16(58)?, reader.u16(60)?),
+    };
+
+    let mut sections = Vec::new();
+    for index in 0..u64::from(shnum) {
+        let base = index
+            .checked_mul(u64::from(shentsize))
+            .ok_or_else(|| anyhow!("Переполнение смещения ELF"))
+            .and_then(|delta| offset_add(shoff, delta))?;
+        let field = |delta: u64| offset_add(base, delta);
+        let header = match class {
+            ElfClass::Elf32 => SectionHeader {
+                kind: reader.u32(field(4)?)?,
+                offset: u64::from(reader.u32(field(16)?)?),
+                size: u64::from(reader.u32(field(20)?)?),
+                link: reader.u32(field(24)?)?,
+                entry_size: u64::from(reader.u32(field(36)?)?),
+            },
+            ElfClass::Elf64 => SectionHeader {
+                kind: reader.u32(field(4)?)?,
+                offset: reader.u64(field(24)?)?,
+                size: reader.u64(field(32)?)?,
+                link: reader.u32(field(40)?)?,
+                entry_size: reader.u64(field(56)?)?,
+            },
+        };
+        sections.push(header);
+    }
+
+    let mut info = ElfInfo {
+        class,
+        machine,
+        soname: None,
+        needed: Vec::new(),
+    };
+
+    let Some(dynamic) = sections.iter().find(|section| section.kind == SHT_DYNAMIC) else {
+        return Ok(Some(info));
+    };
+    let strtab = sections
+        .get(dynamic.link as usize)
+        .ok_or_else(|| anyhow!("Секция .dynamic ссылается на несуществующую таблицу строк"))?;
+
+    let word_size = match class {
+        ElfClass::Elf32 => 4,
+        ElfClass::Elf64 => 8,
+    };
+    let entry_size = if dynamic.entry_size == 0 {
+        word_size * 2
+    } else {
+        dynamic.entry_size
+    };
+
+    let mut offset = dynamic.offset;
+    let dynamic_end = offset_add(dynamic.offset, dynamic.size)?;
+    while offset_add(offset, entry_size)? <= dynamic_end {
+        let tag = reader.word(offset)?;
+        let value = reader.word(offset_add(offset, word_size)?)?;
+        offset = offset_add(offset, entry_size)?;
+
+        match tag {
+            DT_NULL => break,
+            DT_NEEDED => {
+                let name = reader.c_string(offset_add(strtab.offset, value)?)?;
+                if !info.needed.contains(&name) {
+                    info.needed.push(name);
+                }
+            }
+            DT_SONAME => info.soname = Some(reader.c_string(offset_add(strtab.offset, value)?)?),
+            _ => {}
+        }
+    }
+
+    Ok(Some(info))
+}
+
+pub fn read_elf_info(path: &Path) -> Result<Option<ElfInfo>> {
+    let bytes =
+        fs::read(path).with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+    parse_elf(&bytes).with_context(|| format!("Не удалось разобрать ELF {}", path.display()))
+}
+
+/// Находит реальные (не symlink) ELF-библиотеки `lib*.so*` в `<package>/lib`.
+pub fn scan_shared_libraries(package_prefix: &Path) -> Result<Vec<SharedLibrary>> {
+    let lib_dir = package_prefix.join("lib");
+    if !lib_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut out = Vec::new();
+    for entry in fs::read_dir(&lib_dir)
+        .with_context(|| format!("Не удалось прочитать {}", lib_dir.display()))?
+    {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !name.starts_with("lib") || !name.contains(".so") {
+            continue;
+        }
+
+        if let Some(info) = read_elf_info(&path)? {
+            out.push(SharedLibrary { path, info });
+        }
+    }
+
+    out.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(out)
+}
+
+/// Ожидаемые класс ELF и `e_machine` для архитектуры thirdparty-каталога.
+pub fn expected_machine(arch: &str) -> Option<(ElfClass, u16)> {
+    match arch {
+        "armv7" => Some((ElfClass::Elf32, EM_ARM)),
+        "armv8" => Some((ElfClass::Elf64, EM_AARCH64)),
+        "x86_64" => Some((ElfClass::Elf64, EM_X86_64)),
+        _ => None,
+    }
+}
+
+fn describe_machine(class: ElfClass, machine: u16) -> String {
+    let name = match machine {
+        EM_386 => "i386".to_string(),
+        EM_ARM => "ARM".to_string(),
+        EM_X86_64 => "x86_64".to_string(),
+        EM_AARCH64 => "AArch64".to_string(),
+        other => format!("e_machine={other}"),
+    };
+    let bits = match class {
+        ElfClass::Elf32 => "ELF32",
+        ElfClass::Elf64 => "ELF64",
+    };
+    format!("{name} {bits}")
+}
+
+/// Проверяет, что исполняемый файл собран под архитектуру `arch`; скрипты и другие не-ELF пропускаются.
+pub fn verify_executable_arch(path: &Path, arch: &str) -> Result<()> {
+    let Some((expected_class, expected_machine)) = expected_machine(arch) else {
+        return Ok(());
+    };
+    let bytes =
+        fs::read(path).with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+    let Some((class, machine)) = parse_elf_header(&bytes)
+        .with_context(|| format!("Не удалось разобрать ELF {}", path.display()))?
+    else {
+        return Ok(());
+    };
+    if class == expected_class && machine == expected_machine {
+        return Ok(());
+    }
+    Err(anyhow!(
+        "Файл {} собран для {}, а машина сборки — {}",
+        path.display(),
+        describe_machine(class, machine),
+        describe_machine(expected_class, expected_machine)
+    ))
+}
+
+/// Проверяет, что все `.so*` и `.a` в `<package>/lib` собраны под архитектуру `arch`.
+///
+/// Для статических архивов проверяется каждый объектный файл внутри.
+pub fn verify_package_arch(package_prefix: &Path, arch: &str) -> Result<()> {
+    let Some((expected_class, expected_machine)) = expected_machine(arch) else {
+        return Ok(());
+    };
+
+    let lib_dir = package_prefix.join("lib");
+    if !lib_dir.exists() {
+        return Ok(());
+    }
+
+    let mut pending = vec![lib_dir];
+    while let Some(dir) = pending.pop() {
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Не удалось прочитать {}", dir.display()))?
+        {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            if !file_type.is_file() {
+                continue;
+            }
+            let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let is_shared = name.contains(".so");
+            let is_static = name.ends_with(".a");
+            if !is_shared && !is_static {
+                continue;
+            }
+
+            let bytes = fs::read(&path)
+                .with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+            let headers = if is_static && bytes.starts_with(AR_MAGIC) {
+                ar_member_headers(&bytes)
+                    .with_context(|| format!("Не удалось разобрать архив {}", path.display()))?
+            } else {
+                parse_elf_header(&bytes)
+                    .with_context(|| format!("Не удалось разобрать ELF {}", path.display()))?
+                    .map(|header| vec![(String::new(), header)])
+                    .unwrap_or_default()
+            };
+
+            for (member, (class, machine)) in headers {
+                if class == expected_class && machine == expected_machine {
+                    continue;
+                }
+                let location = if member.is_empty() {
+                    path.display().to_string()
+                } else {
+                    format!("{}({})", path.display(), member)
+                };
+                return Err(anyhow!(
+                    "Файл {} собран для {}, а каталог {} ожидает {}",
+                    location,
+                    describe_machine(class, machine),
+                    arch,
+                    describe_machine(expected_class, expected_machine)
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Возвращает заголовки ELF всех объектных файлов статического архива `ar`.
+///
+/// Длинные имена участников разрешаются в обоих форматах: GNU (`/<смещение>` в таблице `//`)
+/// и BSD (`#1/<длина>`, имя в начале данных участника).
+fn ar_member_headers(bytes: &[u8]) -> Result<Vec<(String, (ElfClass, u16))>> {
+    let mut out = Vec::new();
+    let mut long_names: &[u8] = &[];
+    let mut offset = AR_MAGIC.len();
+    while offset + AR_HEADER_SIZE <= bytes.len() {
+        let header = &bytes[offset..offset + AR_HEADER_SIZE];
+        if &header[58..60] != b"`\n" {
+            return Err(anyhow!(
+                "Повреждён заголовок участника по смещению {}",
+                offset
+            ));
+        }
+        let name = String::from_utf8_lossy(&header[..16])
+            .trim_end()
+            .to_string();
+        let size: usize = String::from_utf8_lossy(&header[48..58])
+            .trim()
+            .parse()
+            .with_context(|| format!("Некорректный размер участника {}", name))?;
+
+        let data_start = offset + AR_HEADER_SIZE;
+        let truncated = || anyhow!("Архив обрезан на участнике {}", name);
+        let data_end = data_start.checked_add(size).ok_or_else(truncated)?;
+        let data = bytes.get(data_start..data_end).ok_or_else(truncated)?;
+        offset = data_end + size % 2;
+
+        let (member, data) = if name == "//" {
+            long_names = data;
+            continue;
+        } else if let Some(index) = name
+            .strip_prefix('/')
+            .filter(|rest| !rest.is_empty() && rest.bytes().all(|byte| byte.is_ascii_digit()))
+        {
+            (gnu_long_name(long_names, index)?, data)
+        } else if let Some(length) = name.strip_prefix("#1/") {
+            let length: usize = length
+                .parse()
+                .with_context(|| format!("Некорректная длина имени участника {}", name))?;
+            let raw = data.get(..length).ok_or_else(truncated)?;
+            let member = String::from_utf8_lossy(raw)
+                .trim_end_matches('\0')
+                .to_string();
+            (member, &data[length..])
+        } else {
+            (name.trim_end_matches('/').to_string(), data)
+        };
+        if let Some(header) = parse_elf_header(data)? {
+            out.push((member, header));
+        }
+    }
+    Ok(out)
+}
+
+/// Имя участника из GNU-таблицы длинных имён `//`: записи завершаются `/\n`.
+fn gnu_long_name(table: &[u8], index: &str) -> Result<String> {
+    let start: usize = index
+        .parse()
+        .with_context(|| format!("Некорректная ссылка на длинное имя /{}", index))?;
+    let tail = table
+        .get(start..)
+        .filter(|tail| !tail.is_empty())
+        .ok_or_else(|| anyhow!("Длинное имя /{} вне таблицы имён архива", index))?;
+    let end = tail
+        .iter()
+        .position(|byte| *byte == b'\n')
+        .unwrap_or(tail.len());
+    Ok(String::from_utf8_lossy(&tail[..end])
+        .trim_end_matches('/')
+        .to_string())
+}
+
+/// Проверяет, предоставляется ли библиотека базовой системой ОС Аврора.
+pub fn is_system_library(soname: &str) -> bool {
+    library_list_matches(SYSTEM_LIBRARIES, soname)
+}
+
+/// Ищет SONAME в списке библиотек: одна запись на строку, `#` — комментарий, `*` — любые символы.
+pub fn library_list_matches(list: &str, soname: &str) -> bool {
+    list.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .any(|pattern| glob_matches(pattern, soname))
+}
+
+/// Сравнение с шаблоном, где `*` соответствует любой подстроке.
+pub(crate) fn glob_matches(pattern: &str, candidate: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == candidate,
+        Some((head, tail)) => {
+            let Some(rest) = candidate.strip_prefix(head) else {
+                return false;
+            };
+            (0..=rest.len())
+                .filter(|index| rest.is_char_boundary(*index))
+                .any(|index| glob_matches(tail, &rest[index..]))
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use anyhow::Result;
+
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use super::{ElfClass, ar_member_headers, is_system_library, parse_elf, verify_package_arch};
+
+    pub use super::{EM_AARCH64, EM_X86_64};
+
+    /// Собирает минимальный ELF64 LE с секциями `.dynamic` и `.dynstr`.
+    pub fn build_elf64(machine: u16, soname: Option<&str>, needed: &[&str]) -> Vec<u8> {
+        let mut strtab = vec![0u8];
+        let mut add_str = |value: &str| {
+            let offset = strtab.len() as u64;
+            strtab.extend_from_slice(value.as_bytes());
+            strtab.push(0);
+            offset
+        };
+
+        let mut dynamic: Vec<(u64, u64)> = Vec::new();
+        for item in needed {
+            dynamic.push((1, add_str(item)));
+        }
+        if let Some(name) = soname {
+            dynamic.push((14, add_str(name)));
+        }
+        dynamic.push((0, 0));
+
+        let header_size = 64u64;
+        let strtab_offset = header_size;
+        let dynamic_offset = strtab_offset + strtab.len() as u64;
+        let dynamic_size = dynamic.len() as u64 * 16;
+        let shoff = dynamic_offset + dynamic_size;
+
+        let mut out = vec![0u8; header_size as usize];
+        out[..4].copy_from_slice(b"\x7fELF");
+        out[4] = 2;
+        out[5] = 1;
+        out[6] = 1;
+        out[16..18].copy_from_slice(&3u16.to_le_bytes());
+        out[18..20].copy_from_slice(&machine.to_le_bytes());
+        out[40..48].copy_from_slice(&shoff.to_le_bytes());
+        out[58..60].copy_from_slice(&64u16.to_le_bytes());
+        out[60..62].copy_from_slice(&3u16.to_le_bytes());
+
+        out.extend_from_slice(&strtab);
+        for (tag, value) in &dynamic {
+            out.extend_from_slice(&tag.to_le_bytes());
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let section = |kind: u32, offset: u64, size: u64, link: u32, entsize: u64| {
+            let mut header = vec![0u8; 64];
+            header[4..8].copy_from_slice(&kind.to_le_bytes());
+            header[24..32].copy_from_slice(&offset.to_le_bytes());
+            header[32..40].copy_from_slice(&size.to_le_bytes());
+            header[40..44].copy_from_slice(&link.to_le_bytes());
+            header[56..64].copy_from_slice(&entsize.to_le_bytes());
+            header
+        };
+        out.extend(section(0, 0, 0, 0, 0));
+        out.extend(section(3, strtab_offset, strtab.len() as u64, 0, 0));
+        out.extend(section(6, dynamic_offset, dynamic_size, 1, 16));
+        out
+    }
+
+    #[test]
+    fn reads_soname_and_needed_entries() -> Result<()> {
+        let bytes = build_elf64(
+            EM_X86_64,
+            Some("libssl.so.3"),
+            &["libcrypto.so.3", "libc.so.6"],
+        );
+        let info = parse_elf(&bytes)?.expect("ELF expected");
+        assert_eq!(info.class, ElfClass::Elf64);
+        assert_eq!(info.machine, EM_X86_64);
+        assert_eq!(info.soname.as_deref(), Some("libssl.so.3"));
+        assert_eq!(info.needed, vec!["libcrypto.so.3", "libc.so.6"]);
+        Ok(())
+    }
+
+    #[test]
+    fn returns_none_for_non_elf_data() -> Result<()> {
+        assert!(parse_elf(b"binary-placeholder")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn fails_on_truncated_elf() {
+        let bytes = build_elf64(EM_X86_64, Some("libz.so.1"), &[]);
+        assert!(parse_elf(&bytes[..80]).is_err());
+    }
+
+    #[test]
+    fn fails_on_overflowing_offsets() {
+        let mut bytes = build_elf64(EM_X86_64, Some("libz.so.1"), &[]);
+        bytes[40..48].copy_from_slice(&(u64::MAX - 2).to_le_bytes());
+        let err = parse_elf(&bytes).expect_err("section headers beyond u64");
+        assert!(err.to_string().contains("Переполнение смещения ELF"));
+
+        let mut archive = build_ar(&[("libz.o", build_elf64(EM_X86_64, None, &[]))]);
+        archive[8 + 48..8 + 58].copy_from_slice(b"9999999999");
+        let err = ar_member_headers(&archive).expect_err("member beyond archive");
+        assert!(
+            err.to_string()
+                .contains("Архив обрезан на участнике libz.o/")
+        );
+    }
+
+    #[test]
+    fn matches_system_libraries_from_data_table() {
+        assert!(is_system_library("libc.so.6"));
+        assert!(is_system_library("ld-linux-armhf.so.3"));
+        assert!(is_system_library("libQt5Core.so.5"));
+        assert!(!is_system_library("libssl.so.3"));
+    }
+
+    fn build_ar(members: &[(&str, Vec<u8>)]) -> Vec<u8> {
+        let members: Vec<(String, Vec<u8>)> = members
+            .iter()
+            .map(|(name, data)| (format!("{name}/"), data.clone()))
+            .collect();
+        build_raw_ar(&members)
+    }
+
+    /// Архив с участниками как есть: имя в заголовке не дополняется `/`.
+    fn build_raw_ar(members: &[(String, Vec<u8>)]) -> Vec<u8> {
+        let mut out = b"!<arch>\n".to_vec();
+        for (name, data) in members {
+            out.extend(
+                format!(
+                    "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+                    name,
+                    0,
+                    0,
+                    0,
+                    644,
+                    data.len()
+                )
+                .into_bytes(),
+            );
+            out.extend_from_slice(data);
+            if data.len() % 2 == 1 {
+                out.push(b'\n');
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn accepts_libraries_matching_arch() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("lib"))?;
+        fs::write(
+            dir.path().join("lib/libz.so.1"),
+            build_elf64(EM_AARCH64, Some("libz.so.1"), &[]),
+        )?;
+        fs::write(
+            dir.path().join("lib/libz.a"),
+            build_ar(&[("deflate.o", build_elf64(EM_AARCH64, None, &[]))]),
+        )?;
+
+        verify_package_arch(dir.path(), "armv8")?;
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_shared_library_for_other_arch() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("lib"))?;
+        fs::write(
+            dir.path().join("lib/libz.so.1"),
+            build_elf64(EM_X86_64, Some("libz.so.1"), &[]),
+        )?;
+
+        let err = verify_package_arch(dir.path(), "armv7").expect_err("arch mismatch expected");
+        let message = err.to_string();
+        assert!(message.contains("libz.so.1"));
+        assert!(message.contains("x86_64 ELF64"));
+        assert!(message.contains("ARM ELF32"));
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_static_archive_member_for_other_arch() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("lib"))?;
+        fs::write(
+            dir.path().join("lib/libz.a"),
+            build_ar(&[
+                ("inflate.o", build_elf64(EM_AARCH64, None, &[])),
+                ("deflate.o", build_elf64(EM_X86_64, None, &[])),
+            ]),
+        )?;
+
+        let err = verify_package_arch(dir.path(), "armv8").expect_err("arch mismatch expected");
+        assert!(err.to_string().contains("libz.a(deflate.o)"));
+        Ok(())
+    }
+
+    #[test]
+    fn names_gnu_long_member_for_other_arch() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("lib"))?;
+        let table = b"zlib_inflate_window.o/\nzlib_deflate_huffman.o/\n".to_vec();
+        fs::write(
+            dir.path().join("lib/libz.a"),
+            build_raw_ar(&[
+                ("//".to_string(), table),
+                ("/0".to_string(), build_elf64(EM_AARCH64, None, &[])),
+                ("/23".to_string(), build_elf64(EM_X86_64, None, &[])),
+            ]),
+        )?;
+
+        let err = verify_package_arch(dir.path(), "armv8").expect_err("arch mismatch expected");
+        assert!(err.to_string().contains("libz.a(zlib_deflate_huffman.o)"));
+        Ok(())
+    }
+
+    #[test]
+    fn checks_bsd_long_member_for_other_arch() -> Result<()> {
+        let dir = tempdir()?;
+        fs::create_dir_all(dir.path().join("lib"))?;
+        let mut data = b"zlib_deflate_huffman.o\0\0".to_vec();
+        data.extend(build_elf64(EM_X86_64, None, &[]));
+        fs::write(
+            dir.path().join("lib/libz.a"),
+            build_raw_ar(&[("#1/24".to_string(), data)]),
+        )?;
+
+        let err = verify_package_arch(dir.path(), "armv8").expect_err("arch mismatch expected");
+        assert!(err.to_string().contains("libz.a(zlib_deflate_huffman.o)"));
+        Ok(())
+    }
+}
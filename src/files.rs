@@ -1,37 +1,204 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result, anyhow};
 use regex::Regex;
 
-use crate::model::{ConanRef, ProjectMetadata};
+use crate::clear_store;
+use crate::elf;
+use crate::mode;
+use crate::model::{ConanRef, LinkVisibility, ProjectMetadata, SpecConfig, TargetAssignment};
+
+const BUILTIN_SYSTEM_LIB_REQUIRES: &str = include_str!("../data/system-lib-requires.txt");
+const PROJECT_SYSTEM_LIB_REQUIRES: &str = "system-lib-requires.txt";
 
 pub const CMAKE_FILE: &str = "CMakeLists.txt";
 pub const CONANFILE: &str = "conanfile.py";
+pub const QMAKE_DEPS_FILE: &str = "thirdparty/aurora/aurora-deps.pri";
+/// Метка строк `override=True`, записанных из политики организации, а не из `override`.
+const POLICY_OVERRIDE_MARKER: &str = "# aurora-policy";
+
+/// Сборочная система проекта.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BuildSystem {
+    CMake,
+    Qmake(PathBuf),
+}
+
+/// Определяет сборочную систему: `CMakeLists.txt` в приоритете, иначе единственный `.pro` в корне.
+pub fn detect_build_system(project_root: &Path) -> Result<BuildSystem> {
+    if project_root.join(CMAKE_FILE).exists() {
+        return Ok(BuildSystem::CMake);
+    }
+
+    let mut pro_paths: Vec<PathBuf> = fs::read_dir(project_root)
+        .with_context(|| format!("Не удалось прочитать {}", project_root.display()))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "pro"))
+        .collect();
+
+    pro_paths.sort();
+
+    match pro_paths.as_slice() {
+        [single] => Ok(BuildSystem::Qmake(single.clone())),
+        [] => Err(anyhow!(
+            "Не найден {} или .pro файл в {}",
+            CMAKE_FILE,
+            project_root.display()
+        )),
+        _ => Err(anyhow!(
+            "Найдено несколько .pro файлов в корне проекта. Ожидается ровно один"
+        )),
+    }
+}
 
 pub fn read_requires(project_root: &Path) -> Result<Vec<ConanRef>> {
+    Ok(read_conanfile_refs(project_root)?
+        .into_iter()
+        .filter_map(|(reference, is_override)| (!is_override).then_some(reference))
+        .collect())
+}
+
+/// Переопределения версий: `self.requires("<ref>", override=True)` в conanfile.py.
+pub fn read_overrides(project_root: &Path) -> Result<Vec<ConanRef>> {
+    Ok(read_conanfile_refs(project_root)?
+        .into_iter()
+        .filter_map(|(reference, is_override)| is_override.then_some(reference))
+        .collect())
+}
+
+fn read_conanfile_refs(project_root: &Path) -> Result<Vec<(ConanRef, bool)>> {
     let conanfile_path = project_root.join(CONANFILE);
     if !conanfile_path.exists() {
         return Ok(Vec::new());
     }
 
     let content = read_text(&conanfile_path)?;
-    let re = Regex::new(r#"\"([^/\"\s]+)\/([^@\"\s]+)@([^\"\s]+)\""#)
+    // Ссылки читаем только из `requires` и тела `requirements()`: строки `aurora_link_targets`
+    // и `aurora_license_waivers` тоже могут быть похожи на ссылки
+    let requires_re = Regex::new(r"(?s)\n    requires = \((.*?)\n    \)")
+        .context("Не удалось подготовить regex для requires")?;
+    let requirements_re =
+        Regex::new(r"(?m)^    def requirements\(self\):\n((?:        .*(?:\n|$))*)")
+            .context("Не удалось подготовить regex для requirements()")?;
+    let re = Regex::new(r#"\"([^/\"\s]+)\/([^@\"\s]+)@([^\"\s]+)\"(\s*,\s*override\s*=\s*True)?"#)
         .context("Не удалось подготовить regex для чтения requires")?;
 
-    let refs = re
-        .captures_iter(&content)
-        .map(|caps| ConanRef {
-            name: caps[1].to_string(),
-            version: caps[2].to_string(),
-            user: caps[3].to_string(),
+    let mut sections = Vec::new();
+    if let Some(caps) = requires_re.captures(&content) {
+        sections.push(caps[1].to_string());
+    }
+    if let Some(caps) = requirements_re.captures(&content) {
+        // Принудительные версии политики пересчитываются при каждой записи и в проект не входят
+        let body = caps[1]
+            .lines()
+            .filter(|line| !line.trim_end().ends_with(POLICY_OVERRIDE_MARKER))
+            .collect::<Vec<_>>()
+            .join("\n");
+        sections.push(body);
+    }
+
+    let refs = sections
+        .iter()
+        .flat_map(|section| re.captures_iter(section))
+        .map(|caps| {
+            (
+                ConanRef {
+                    name: caps[1].to_string(),
+                    version: caps[2].to_string(),
+                    user: caps[3].to_string(),
+                },
+                caps.get(4).is_some(),
+            )
         })
         .collect();
 
     Ok(refs)
 }
 
-pub fn write_conanfile(project_root: &Path, refs: &[ConanRef]) -> Result<()> {
+/// Читает исключения из лицензионной политики из `aurora_license_waivers` в conanfile.py.
+pub fn read_license_waivers(project_root: &Path) -> Result<BTreeMap<String, String>> {
+    let conanfile_path = project_root.join(CONANFILE);
+    if !conanfile_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = read_text(&conanfile_path)?;
+    let block_re = Regex::new(r"(?s)\n    aurora_license_waivers = \{\n(.*?)\n    \}")
+        .context("Не удалось подготовить regex для aurora_license_waivers")?;
+    let entry_re = Regex::new(r#"(?m)^\s*"([^"]+)":\s*"((?:[^"\\]|\\.)*)""#)
+        .context("Не удалось подготовить regex для записи aurora_license_waivers")?;
+
+    let Some(block) = block_re.captures(&content) else {
+        return Ok(BTreeMap::new());
+    };
+    Ok(entry_re
+        .captures_iter(&block[1])
+        .map(|entry| {
+            let mut reason = String::new();
+            let mut chars = entry[2].chars();
+            while let Some(ch) = chars.next() {
+                match ch {
+                    '\\' => reason.extend(chars.next()),
+                    ch => reason.push(ch),
+                }
+            }
+            (entry[1].to_string(), reason)
+        })
+        .collect())
+}
+
+/// Читает привязки зависимостей к CMake-целям из `aurora_link_targets` в conanfile.py.
+pub fn read_targets(project_root: &Path) -> Result<BTreeMap<String, Vec<TargetAssignment>>> {
+    let conanfile_path = project_root.join(CONANFILE);
+    if !conanfile_path.exists() {
+        return Ok(BTreeMap::new());
+    }
+
+    let content = read_text(&conanfile_path)?;
+    let block_re = Regex::new(r"(?s)\n    aurora_link_targets = \{\n(.*?)\n    \}")
+        .context("Не удалось подготовить regex для aurora_link_targets")?;
+    let entry_re = Regex::new(r#"(?m)^\s*"([^"]+)":\s*\[([^\]]*)\]"#)
+        .context("Не удалось подготовить regex для записи aurora_link_targets")?;
+    let item_re = Regex::new(r#""([^":]+):([A-Za-z]+)""#)
+        .context("Не удалось подготовить regex для цели aurora_link_targets")?;
+
+    let mut targets = BTreeMap::new();
+    let Some(block) = block_re.captures(&content) else {
+        return Ok(targets);
+    };
+    for entry in entry_re.captures_iter(&block[1]) {
+        let mut assignments = Vec::new();
+        for item in item_re.captures_iter(&entry[2]) {
+            let visibility = LinkVisibility::parse(&item[2]).ok_or_else(|| {
+                anyhow!(
+                    "Неизвестная видимость '{}' для цели {} в {}",
+                    &item[2],
+                    &item[1],
+                    CONANFILE
+                )
+            })?;
+            assignments.push(TargetAssignment {
+                target: item[1].to_string(),
+                visibility,
+            });
+        }
+        targets.insert(entry[1].to_string(), assignments);
+    }
+    Ok(targets)
+}
+
+/// Пишет conanfile.py; `policy_overrides` — принудительные версии политики, их перекрывают
+/// одноимённые `overrides` проекта.
+pub fn write_conanfile(
+    project_root: &Path,
+    refs: &[ConanRef],
+    overrides: &[ConanRef],
+    policy_overrides: &[ConanRef],
+    targets: &BTreeMap<String, Vec<TargetAssignment>>,
+    license_waivers: &BTreeMap<String, String>,
+) -> Result<()> {
     let mut sorted = refs.to_vec();
     sorted.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
 
@@ -45,15 +212,63 @@ pub fn write_conanfile(project_root: &Path, refs: &[ConanRef]) -> Result<()> {
 
     content.push_str("    )\n");
 
+    if !targets.is_empty() {
+        content.push_str("\n    aurora_link_targets = {\n");
+        for (package, assignments) in targets {
+            let items = assignments
+                .iter()
+                .map(|item| format!("\"{}:{}\"", item.target, item.visibility.as_cmake()))
+                .collect::<Vec<_>>()
+                .join(", ");
+            content.push_str(&format!("        \"{package}\": [{items}],\n"));
+        }
+        content.push_str("    }\n");
+    }
+
+    if !license_waivers.is_empty() {
+        content.push_str("\n    aurora_license_waivers = {\n");
+        for (package, reason) in license_waivers {
+            content.push_str(&format!(
+                "        \"{package}\": \"{}\",\n",
+                reason.replace('\\', "\\\\").replace('"', "\\\"")
+            ));
+        }
+        content.push_str("    }\n");
+    }
+
+    if !overrides.is_empty() || !policy_overrides.is_empty() {
+        let mut sorted = overrides.to_vec();
+        sorted.sort_by(|a, b| a.name.cmp(&b.name));
+        content.push_str("\n    def requirements(self):\n");
+        for reference in &sorted {
+            content.push_str(&format!(
+                "        self.requires(\"{}\", override=True)\n",
+                reference.to_ref_string()
+            ));
+        }
+        let mut forced = policy_overrides
+            .iter()
+            .filter(|reference| !overrides.iter().any(|item| item.name == reference.name))
+            .collect::<Vec<_>>();
+        forced.sort_by(|a, b| a.name.cmp(&b.name));
+        for reference in forced {
+            content.push_str(&format!(
+                "        self.requires(\"{}\", override=True)  {POLICY_OVERRIDE_MARKER}\n",
+                reference.to_ref_string()
+            ));
+        }
+    }
+
     let path = project_root.join(CONANFILE);
     write_text(&path, &content)
 }
 
-pub fn update_cmake(project_root: &Path, metadata: &ProjectMetadata) -> Result<()> {
+/// Обновляет CMake-файлы проекта и возвращает пути изменённых файлов.
+pub fn update_cmake(project_root: &Path, metadata: &ProjectMetadata) -> Result<Vec<PathBuf>> {
     update_cmake_impl(project_root, metadata, false)
 }
 
-pub fn update_cmake_clear(project_root: &Path, metadata: &ProjectMetadata) -> Result<()> {
+pub fn update_cmake_clear(project_root: &Path, metadata: &ProjectMetadata) -> Result<Vec<PathBuf>> {
     update_cmake_impl(project_root, metadata, true)
 }
 
@@ -61,16 +276,70 @@ fn update_cmake_impl(
     project_root: &Path,
     metadata: &ProjectMetadata,
     clear_mode: bool,
-) -> Result<()> {
+) -> Result<Vec<PathBuf>> {
     let path = project_root.join(CMAKE_FILE);
-    let mut content = read_text(&path)?;
+    let mut sources: BTreeMap<PathBuf, (String, String)> = BTreeMap::new();
+    for source in cmake_source_files(project_root)? {
+        let text = read_text(&source)?;
+        sources.insert(source, (text.clone(), text));
+    }
 
-    content = ensure_find_package_pkgconfig(&content)?;
+    let mut links = target_links(metadata);
+    let default_links = links.remove(DEFAULT_TARGET).unwrap_or_default();
+    let mut targets = vec![(
+        "targets".to_string(),
+        DEFAULT_TARGET.to_string(),
+        default_links,
+    )];
+    for (target, target_links) in links {
+        targets.push((
+            format!("{TARGET_BLOCK_PREFIX}{target}"),
+            target,
+            target_links,
+        ));
+    }
+
+    // Блок линковки кладём в файл, где объявлена цель: в корневом файле после
+    // add_subdirectory() проверка if(TARGET ...) срабатывает раньше объявления.
+    let project_name = project_name(&sources[&path].1);
+    let mut placements: BTreeMap<String, PathBuf> = BTreeMap::new();
+    let mut blocks = Vec::new();
+    for (key, target, target_links) in &targets {
+        let defined_in = sources
+            .iter()
+            .find(|(_, (_, text))| defines_target(text, target, project_name.as_deref()))
+            .map(|(source, _)| source.clone());
+        if defined_in.is_none() && !target_links.is_empty() {
+            eprintln!(
+                "Warning: no CMakeLists.txt defines target {target}, link block is placed in {}",
+                path.display()
+            );
+        }
+        let defined_in = defined_in.unwrap_or_else(|| path.clone());
+        let body = if target_links.is_empty() {
+            "# No Conan dependencies configured.".to_string()
+        } else {
+            let plain =
+                uses_plain_link_signature(&sources[&defined_in].1, target, project_name.as_deref());
+            target_link_block(target, target_links, plain)
+        };
+        placements.insert(key.clone(), defined_in);
+        blocks.push((key.clone(), body));
+    }
+    let global_imports = placements.values().any(|source| source != &path);
+
+    let root = &mut sources.get_mut(&path).expect("root CMakeLists.txt").1;
+    let mut content = ensure_find_package_pkgconfig(root)?;
     if clear_mode {
         content = upsert_block_after_project(&content, "clear-arch", &clear_arch_block())?;
     } else {
         content = remove_managed_block(&content, "clear-arch")?;
     }
+    if clear_mode && !metadata.host_tools.is_empty() {
+        content = upsert_block_after_project(&content, "tools", &tools_block(metadata))?;
+    } else {
+        content = remove_managed_block(&content, "tools")?;
+    }
 
     let rpath_body = [
         "set(CMAKE_SKIP_RPATH FALSE)",
@@ -87,12 +356,14 @@ fn update_cmake_impl(
         modules.sort();
         modules.dedup();
 
+        // GLOBAL нужен, чтобы imported-цели были видны из подкаталогов
+        let scope = if global_imports { " GLOBAL" } else { "" };
         modules
             .into_iter()
             .map(|module| {
                 let alias = module_to_alias(&module);
                 format!(
-                    "pkg_check_modules({alias} REQUIRED IMPORTED_TARGET {module})",
+                    "pkg_check_modules({alias} REQUIRED IMPORTED_TARGET{scope} {module})",
                     alias = alias,
                     module = module
                 )
@@ -101,27 +372,246 @@ fn update_cmake_impl(
             .join("\n")
     };
     content = upsert_managed_block(&content, "pkgconfig", &pkg_body)?;
+    *root = content;
 
-    let targets_body = if metadata.direct_pkg_modules.is_empty() {
-        "# No Conan dependencies configured.".to_string()
-    } else {
-        let mut modules = metadata.direct_pkg_modules.clone();
-        modules.sort();
-        modules.dedup();
+    for (source, (_, content)) in sources.iter_mut() {
+        for key in managed_block_keys(content)? {
+            let is_targets_block = key == "targets" || key.starts_with(TARGET_BLOCK_PREFIX);
+            if is_targets_block && placements.get(&key) != Some(source) {
+                *content = remove_managed_block(content, &key)?;
+            }
+        }
+        for (key, body) in &blocks {
+            if placements.get(key) == Some(source) {
+                *content = upsert_managed_block(content, key, body)?;
+            }
+        }
+    }
+
+    let mut changed = Vec::new();
+    for (source, (original, content)) in &sources {
+        if original != content {
+            write_text(source, content)?;
+            changed.push(source.clone());
+        }
+    }
+    Ok(changed)
+}
+
+/// `CMakeLists.txt` проекта: корневой и подключённые через `add_subdirectory()`.
+///
+/// В пути раскрываются `${CMAKE_CURRENT_SOURCE_DIR}`, `${CMAKE_SOURCE_DIR}` и
+/// `${PROJECT_SOURCE_DIR}`; подкаталоги с другими переменными пропускаются с предупреждением.
+fn cmake_source_files(project_root: &Path) -> Result<Vec<PathBuf>> {
+    let subdirectory_re = Regex::new(r#"(?m)^\s*add_subdirectory\(\s*"?([^\s")]+)"?"#)
+        .context("Не удалось подготовить regex для add_subdirectory()")?;
+
+    let mut queue = vec![project_root.join(CMAKE_FILE)];
+    let mut out = Vec::new();
+    while let Some(path) = queue.pop() {
+        if out.contains(&path) || !path.exists() {
+            continue;
+        }
+        let content = read_text(&path)?;
+        let dir = path.parent().unwrap_or(project_root).to_path_buf();
+        for caps in subdirectory_re.captures_iter(&content) {
+            let root = project_root.to_string_lossy();
+            let subdirectory = caps[1]
+                .replace("${CMAKE_CURRENT_SOURCE_DIR}", &dir.to_string_lossy())
+                .replace("${CMAKE_SOURCE_DIR}", &root)
+                .replace("${PROJECT_SOURCE_DIR}", &root);
+            if subdirectory.contains("${") {
+                eprintln!(
+                    "Warning: {}: add_subdirectory({}) is not followed, path uses unknown variables",
+                    path.display(),
+                    &caps[1]
+                );
+                continue;
+            }
+            queue.push(dir.join(subdirectory).join(CMAKE_FILE));
+        }
+        out.push(path);
+    }
+
+    if out.is_empty() {
+        return Err(anyhow!(
+            "Не найден {}",
+            project_root.join(CMAKE_FILE).display()
+        ));
+    }
+    Ok(out)
+}
+
+fn project_name(content: &str) -> Option<String> {
+    Regex::new(r"(?m)^project\s*\(\s*([^\s)]+)")
+        .ok()?
+        .captures(content)
+        .map(|caps| caps[1].to_string())
+}
+
+/// Объявлена ли цель в файле через `add_executable()`/`add_library()`.
+fn defines_target(content: &str, target: &str, project_name: Option<&str>) -> bool {
+    Regex::new(&format!(
+        r"(?m)^\s*add_(?:executable|library)\(\s*(?:{})[\s)]",
+        target_names_pattern(target, project_name)
+    ))
+    .is_ok_and(|re| re.is_match(content))
+}
+
+/// Линкуется ли цель вне управляемых блоков через `target_link_libraries()` без ключевых слов.
+/// CMake не позволяет смешивать такие вызовы с PRIVATE/PUBLIC/INTERFACE для одной цели (CMP0023).
+fn uses_plain_link_signature(content: &str, target: &str, project_name: Option<&str>) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "PRIVATE",
+        "PUBLIC",
+        "INTERFACE",
+        "LINK_PRIVATE",
+        "LINK_PUBLIC",
+        "LINK_INTERFACE_LIBRARIES",
+    ];
+    let Ok(managed) =
+        Regex::new(r"(?s)# >>> aurora-conan-cli:(\S+):begin\n.*?# <<< aurora-conan-cli:\S+:end")
+    else {
+        return false;
+    };
+    let Ok(call) = Regex::new(&format!(
+        r"(?m)^\s*target_link_libraries\(\s*(?:{})\s+([^\s)]+)",
+        target_names_pattern(target, project_name)
+    )) else {
+        return false;
+    };
+    let user_content = managed.replace_all(content, "");
+    call.captures_iter(&user_content)
+        .any(|caps| !KEYWORDS.contains(&&caps[1]))
+}
+
+/// Имена цели для regex: `${PROJECT_NAME}` совпадает и с именем из `project()`.
+fn target_names_pattern(target: &str, project_name: Option<&str>) -> String {
+    let mut names = vec![regex::escape(target)];
+    if target == DEFAULT_TARGET
+        && let Some(name) = project_name
+    {
+        names.push(regex::escape(name));
+    }
+    names.join("|")
+}
+
+const DEFAULT_TARGET: &str = "${PROJECT_NAME}";
+const TARGET_BLOCK_PREFIX: &str = "targets-";
+
+/// Раскладывает pkg-config модули прямых зависимостей по CMake-целям и видимости.
+/// Зависимости без явной привязки линкуются к `${PROJECT_NAME}` как PRIVATE.
+fn target_links(
+    metadata: &ProjectMetadata,
+) -> BTreeMap<String, BTreeMap<LinkVisibility, BTreeSet<String>>> {
+    let mut links: BTreeMap<String, BTreeMap<LinkVisibility, BTreeSet<String>>> = BTreeMap::new();
+    let mut assigned_modules = BTreeSet::new();
+
+    for (package, assignments) in &metadata.target_assignments {
+        let modules = metadata
+            .package_modules
+            .get(package)
+            .cloned()
+            .unwrap_or_else(|| vec![package.clone()]);
+        let modules: Vec<String> = modules
+            .into_iter()
+            .filter(|module| metadata.direct_pkg_modules.contains(module))
+            .collect();
+        for assignment in assignments {
+            links
+                .entry(assignment.target.clone())
+                .or_default()
+                .entry(assignment.visibility)
+                .or_default()
+                .extend(modules.iter().cloned());
+        }
+        assigned_modules.extend(modules);
+    }
+
+    for module in &metadata.direct_pkg_modules {
+        if !assigned_modules.contains(module) {
+            links
+                .entry(DEFAULT_TARGET.to_string())
+                .or_default()
+                .entry(LinkVisibility::Private)
+                .or_default()
+                .insert(module.clone());
+        }
+    }
+
+    links.retain(|_, by_visibility| by_visibility.values().any(|modules| !modules.is_empty()));
+    links
+}
 
-        let target_entries = modules
+/// Imported-цели pkg_check_modules несут include dirs и флаги компиляции как
+/// usage requirements, поэтому достаточно target_link_libraries.
+///
+/// Для цели, которая уже линкуется без ключевых слов (`plain`), видимость не выразить:
+/// все модули перечисляются в одном вызове той же сигнатуры.
+fn target_link_block(
+    target: &str,
+    links: &BTreeMap<LinkVisibility, BTreeSet<String>>,
+    plain: bool,
+) -> String {
+    let entries = |modules: &BTreeSet<String>| {
+        modules
             .iter()
-            .map(|module| format!("      PkgConfig::{}", module_to_alias(module)))
+            .map(|module| format!("    PkgConfig::{}", module_to_alias(module)))
             .collect::<Vec<_>>()
-            .join("\n");
-
-        format!(
-            "if(TARGET ${{PROJECT_NAME}})\n  target_include_directories(${{PROJECT_NAME}} PRIVATE\n    $<BUILD_INTERFACE:\n{target_entries}\n    >\n  )\n\n  target_link_libraries(${{PROJECT_NAME}} PRIVATE\n{target_entries}\n  )\nendif()"
-        )
+            .join("\n")
     };
-    content = upsert_managed_block(&content, "targets", &targets_body)?;
+    let mut body = format!("if(TARGET {target})");
+    if plain {
+        let modules: BTreeSet<String> = links.values().flatten().cloned().collect();
+        body.push_str(&format!(
+            "\n  target_link_libraries({target}\n{}\n  )",
+            entries(&modules)
+        ));
+    } else {
+        for (visibility, modules) in links {
+            if modules.is_empty() {
+                continue;
+            }
+            body.push_str(&format!(
+                "\n  target_link_libraries({target} {}\n{}\n  )",
+                visibility.as_cmake(),
+                entries(modules)
+            ));
+        }
+    }
+    body.push_str("\nendif()");
+    body
+}
 
-    write_text(&path, &content)
+/// Пути инструментов сборки из `thirdparty/aurora/host`: `AURORA_TOOL_<NAME>`.
+fn tools_block(metadata: &ProjectMetadata) -> String {
+    metadata
+        .host_tools
+        .iter()
+        .map(|tool| {
+            format!(
+                "set({} \"${{CMAKE_CURRENT_SOURCE_DIR}}/{}\")",
+                tool_variable(&tool.name),
+                tool.path
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `protoc` -> `AURORA_TOOL_PROTOC`, `qt-moc` -> `AURORA_TOOL_QT_MOC`.
+fn tool_variable(name: &str) -> String {
+    let suffix: String = name
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() {
+                ch.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("AURORA_TOOL_{suffix}")
 }
 
 fn clear_arch_block() -> String {
@@ -143,33 +633,190 @@ fn clear_arch_block() -> String {
         "else()",
         "  set(ENV{PKG_CONFIG_PATH} \"${AURORA_TP_PKGCONFIG_DIR}\")",
         "endif()",
+        "set(CMAKE_PREFIX_PATH \"${AURORA_TP_ROOT}/${AURORA_TP_ARCH}/cmake\" ${CMAKE_PREFIX_PATH})",
     ]
     .join("\n")
 }
 
-pub fn update_spec(project_root: &Path, metadata: &ProjectMetadata) -> Result<()> {
-    let spec_path = find_spec_file(project_root)?;
-    let mut content = read_text(&spec_path)?;
+pub fn update_qmake(
+    project_root: &Path,
+    pro_path: &Path,
+    metadata: &ProjectMetadata,
+) -> Result<Vec<PathBuf>> {
+    update_qmake_impl(project_root, pro_path, metadata, false)
+}
 
-    content = upsert_define(&content, "_cmake_skip_rpath", "%{nil}")?;
-    content = upsert_define(
-        &content,
-        "__provides_exclude_from",
-        "^%{_datadir}/%{name}/lib/.*$",
-    )?;
+pub fn update_qmake_clear(
+    project_root: &Path,
+    pro_path: &Path,
+    metadata: &ProjectMetadata,
+) -> Result<Vec<PathBuf>> {
+    update_qmake_impl(project_root, pro_path, metadata, true)
+}
 
-    let mut patterns = metadata.shared_lib_patterns.clone();
-    patterns.sort();
-    patterns.dedup();
+fn update_qmake_impl(
+    project_root: &Path,
+    pro_path: &Path,
+    metadata: &ProjectMetadata,
+    clear_mode: bool,
+) -> Result<Vec<PathBuf>> {
+    let mut changed = Vec::new();
+    let pri_path = project_root.join(QMAKE_DEPS_FILE);
+    if let Some(parent) = pri_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Не удалось создать {}", parent.display()))?;
+    }
+    let pri = qmake_deps_pri(metadata, clear_mode);
+    if fs::read_to_string(&pri_path).ok().as_deref() != Some(pri.as_str()) {
+        write_text(&pri_path, &pri)?;
+        changed.push(pri_path);
+    }
 
-    let requires_pattern = if patterns.is_empty() {
-        "^$".to_string()
+    // .pri использует $$TARGET, поэтому include() держим в конце .pro
+    let original = read_text(pro_path)?;
+    let include = format!("include($$PWD/{QMAKE_DEPS_FILE})");
+    let content = upsert_managed_block(&original, "qmake-deps", &include)?;
+    if content != original {
+        write_text(pro_path, &content)?;
+        changed.push(pro_path.to_path_buf());
+    }
+    Ok(changed)
+}
+
+fn qmake_deps_pri(metadata: &ProjectMetadata, clear_mode: bool) -> String {
+    let mut lines = vec![
+        "# Generated by aurora-conan-cli. Changes are overwritten by add/remove.".to_string(),
+        String::new(),
+        "QMAKE_RPATHDIR += /usr/share/$${TARGET}/lib".to_string(),
+        String::new(),
+    ];
+
+    if clear_mode {
+        lines.extend(
+            [
+                "contains(QT_ARCH, arm64) {",
+                "    AURORA_TP_ARCH = armv8",
+                "} else:contains(QT_ARCH, arm) {",
+                "    AURORA_TP_ARCH = armv7",
+                "} else:contains(QT_ARCH, x86_64) {",
+                "    AURORA_TP_ARCH = x86_64",
+                "} else {",
+                "    error(\"Unsupported architecture: $$QT_ARCH\")",
+                "}",
+                "",
+                "AURORA_TP_ROOT = $$PWD",
+                "AURORA_TP_PACKAGES = $$AURORA_TP_ROOT/$$AURORA_TP_ARCH/packages",
+            ]
+            .map(String::from),
+        );
+
+        // Порядок пакетов уже задан при синхронизации: зависимые раньше зависимостей.
+        // INCLUDEPATH и LIBS из cpp_info видны qmake и модели кода IDE без pkg-config,
+        // PKGCONFIG добавляет флаги и Requires из .pc.
+        for package in &metadata.vendored_packages {
+            let prefix = format!("$$AURORA_TP_PACKAGES/{}/{}", package.name, package.version);
+            let package_path = |dir: &String| match dir.trim_matches('/') {
+                "" | "." => prefix.clone(),
+                dir => format!("{prefix}/{dir}"),
+            };
+            lines.push(String::new());
+            if !package.include_dirs.is_empty() {
+                let dirs: Vec<String> = package.include_dirs.iter().map(package_path).collect();
+                lines.push(format!("INCLUDEPATH += {}", dirs.join(" ")));
+            }
+            if !package.libs.is_empty() {
+                let items: Vec<String> = package
+                    .lib_dirs
+                    .iter()
+                    .map(|dir| format!("-L{}", package_path(dir)))
+                    .chain(package.libs.iter().map(|lib| format!("-l{lib}")))
+                    .collect();
+                lines.push(format!("LIBS += {}", items.join(" ")));
+            }
+        }
+
+        let mut modules = Vec::new();
+        for package in &metadata.vendored_packages {
+            for module in &package.pkg_modules {
+                if !modules.contains(module) {
+                    modules.push(module.clone());
+                }
+            }
+        }
+        if !modules.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(qmake_pkgconfig_lines(
+            "$$AURORA_TP_ROOT/$$AURORA_TP_ARCH/pkgconfig",
+            &modules,
+        ));
+
+        if !metadata.system_libs.is_empty() {
+            let mut system_libs = metadata.system_libs.clone();
+            system_libs.sort();
+            system_libs.dedup();
+            let libs = system_libs
+                .iter()
+                .map(|lib| format!("-l{lib}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+            lines.push(String::new());
+            lines.push(format!("LIBS += {libs}"));
+        }
+    } else if metadata.direct_pkg_modules.is_empty() {
+        lines.push("# No Conan dependencies configured.".to_string());
     } else {
-        format!("^({})$", patterns.join("|"))
-    };
+        let mut modules = metadata.direct_pkg_modules.clone();
+        modules.sort();
+        modules.dedup();
+        // В rpmbuild PKG_CONFIG_PATH уже указывает на вывод Conan; вне его берём
+        // CONAN_LIB_DIR или conan-libs в каталоге сборки, как в %build
+        lines.extend(
+            [
+                "isEmpty(AURORA_CONAN_OUTPUT): AURORA_CONAN_OUTPUT = $$(CONAN_LIB_DIR)",
+                "isEmpty(AURORA_CONAN_OUTPUT): AURORA_CONAN_OUTPUT = $$OUT_PWD/conan-libs",
+            ]
+            .map(String::from),
+        );
+        lines.extend(qmake_pkgconfig_lines("$$AURORA_CONAN_OUTPUT", &modules));
+    }
 
-    content = upsert_define(&content, "__requires_exclude", &requires_pattern)?;
-    content = ensure_buildrequires_conan(&content)?;
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+/// `link_pkgconfig` вызывает `$$PKG_CONFIG`, поэтому каталог .pc добавляем в его окружение.
+fn qmake_pkgconfig_lines(pkgconfig_dir: &str, modules: &[String]) -> Vec<String> {
+    if modules.is_empty() {
+        return Vec::new();
+    }
+    vec![
+        "isEmpty(PKG_CONFIG): PKG_CONFIG = pkg-config".to_string(),
+        format!("PKG_CONFIG = PKG_CONFIG_PATH={pkgconfig_dir}:$$(PKG_CONFIG_PATH) $$PKG_CONFIG"),
+        "CONFIG += link_pkgconfig".to_string(),
+        format!("PKGCONFIG += {}", modules.join(" ")),
+    ]
+}
+
+pub fn update_spec(
+    project_root: &Path,
+    spec_config: &SpecConfig,
+    metadata: &ProjectMetadata,
+) -> Result<()> {
+    let spec_path = project_root.join(&spec_config.path);
+    let mut spec = SpecDocument::parse(&read_text(&spec_path)?);
+    let managed_dirs = spec_managed_dirs(false);
+    spec.check_files_layout(managed_dirs)
+        .with_context(|| format!("Проверка {}", spec_path.display()))?;
+
+    let metadata = metadata.for_packages(&spec_config.packages);
+    set_common_spec_defines(&mut spec, &metadata);
+    upsert_system_requires(
+        &mut spec,
+        &resolve_system_lib_requires(project_root, &metadata.system_libs)?,
+    )?;
+    spec.ensure_build_requires("conan");
 
     let build_body = [
         "CONAN_LIB_DIR=\"%{_builddir}/conan-libs/\"",
@@ -179,7 +826,7 @@ pub fn update_spec(project_root: &Path, metadata: &ProjectMetadata) -> Result<()
         "export PKG_CONFIG_PATH",
     ]
     .join("\n");
-    content = upsert_block_in_section(&content, "build", "build-snippet", &build_body)?;
+    spec.upsert_block("build", "build-snippet", &build_body, BlockPosition::Start)?;
 
     let install_body = [
         "EXECUTABLE=\"%{buildroot}/%{_bindir}/%{name}\"",
@@ -189,32 +836,36 @@ pub fn update_spec(project_root: &Path, metadata: &ProjectMetadata) -> Result<()
         "conan-deploy-libraries \"$EXECUTABLE\" \"$CONAN_LIB_DIR\" \"$SHARED_LIBRARIES\"",
     ]
     .join("\n");
-    content = upsert_block_in_section(&content, "install", "install-snippet", &install_body)?;
+    spec.upsert_block(
+        "install",
+        "install-snippet",
+        &install_body,
+        BlockPosition::End,
+    )?;
+    spec.upsert_files_block(managed_dirs)?;
 
-    write_text(&spec_path, &content)
+    write_text(&spec_path, &spec.render())
 }
 
-pub fn update_spec_clear(project_root: &Path, metadata: &ProjectMetadata) -> Result<()> {
-    let spec_path = find_spec_file(project_root)?;
-    let mut content = read_text(&spec_path)?;
-
-    content = upsert_define(&content, "_cmake_skip_rpath", "%{nil}")?;
-    content = upsert_define(
-        &content,
-        "__provides_exclude_from",
-        "^%{_datadir}/%{name}/lib/.*$",
+pub fn update_spec_clear(
+    project_root: &Path,
+    spec_config: &SpecConfig,
+    metadata: &ProjectMetadata,
+) -> Result<()> {
+    let spec_path = project_root.join(&spec_config.path);
+    let mut spec = SpecDocument::parse(&read_text(&spec_path)?);
+    let managed_dirs = spec_managed_dirs(true);
+    spec.check_files_layout(managed_dirs)
+        .with_context(|| format!("Проверка {}", spec_path.display()))?;
+
+    let packages = metadata.package_closure(&spec_config.packages);
+    let metadata = metadata.for_packages(&packages);
+    set_common_spec_defines(&mut spec, &metadata);
+    upsert_system_requires(
+        &mut spec,
+        &resolve_system_lib_requires(project_root, &metadata.system_libs)?,
     )?;
-
-    let mut patterns = metadata.shared_lib_patterns.clone();
-    patterns.sort();
-    patterns.dedup();
-    let requires_pattern = if patterns.is_empty() {
-        "^$".to_string()
-    } else {
-        format!("^({})$", patterns.join("|"))
-    };
-    content = upsert_define(&content, "__requires_exclude", &requires_pattern)?;
-    content = remove_buildrequires_conan(&content)?;
+    spec.remove_build_requires("conan");
 
     let build_body = [
         "THIRDPARTY_ROOT=\"%{_sourcedir}/../thirdparty/aurora\"",
@@ -229,9 +880,20 @@ pub fn update_spec_clear(project_root: &Path, metadata: &ProjectMetadata) -> Res
         "export PKG_CONFIG_PATH",
     ]
     .join("\n");
-    content = upsert_block_in_section(&content, "build", "build-snippet", &build_body)?;
+    spec.upsert_block("build", "build-snippet", &build_body, BlockPosition::Start)?;
 
-    let install_body = [
+    // Без списка пакетов .spec ставит библиотеки всех пакетов из манифеста
+    let package_filter = (!packages.is_empty()).then(|| {
+        format!(
+            "  case \"$package\" in {}) ;; *) continue ;; esac",
+            packages.join("|")
+        )
+    });
+    let license_loop = format!(
+        "for license_dir in {}; do",
+        package_license_globs(&packages)
+    );
+    let mut install_body = vec![
         "THIRDPARTY_ROOT=\"%{_sourcedir}/../thirdparty/aurora\"",
         "case \"%{_arch}\" in",
         "  aarch64) AURORA_TP_ARCH=\"armv8\" ;;",
@@ -242,15 +904,207 @@ pub fn update_spec_clear(project_root: &Path, metadata: &ProjectMetadata) -> Res
         "THIRDPARTY_ARCH_DIR=\"$THIRDPARTY_ROOT/$AURORA_TP_ARCH\"",
         "SHARED_LIBRARIES=\"%{buildroot}/%{_datadir}/%{name}/lib\"",
         "mkdir -p \"$SHARED_LIBRARIES\"",
-        "find \"$THIRDPARTY_ARCH_DIR/packages\" -type f -name 'lib*.so*' -exec cp -P {} \"$SHARED_LIBRARIES\" \\; 2>/dev/null || true",
-    ]
-    .join("\n");
-    content = upsert_block_in_section(&content, "install", "install-snippet", &install_body)?;
+        "INSTALL_MANIFEST=\"$THIRDPARTY_ARCH_DIR/install-manifest.txt\"",
+        "[ -f \"$INSTALL_MANIFEST\" ] || INSTALL_MANIFEST=/dev/null",
+        "TAB=\"$(printf '\\t')\"",
+        "while IFS=\"$TAB\" read -r kind package source name; do",
+    ];
+    if let Some(filter) = &package_filter {
+        install_body.push(filter);
+    }
+    install_body.extend([
+        "  case \"$kind\" in",
+        "    file) cp -p \"$THIRDPARTY_ARCH_DIR/packages/$source\" \"$SHARED_LIBRARIES/$name\" ;;",
+        "    link) ln -sfn \"$source\" \"$SHARED_LIBRARIES/$name\" ;;",
+        "  esac",
+        "done < \"$INSTALL_MANIFEST\"",
+        "LICENSES=\"%{buildroot}/%{_datadir}/%{name}/licenses\"",
+        "mkdir -p \"$LICENSES\"",
+        "NOTICES=\"$THIRDPARTY_ROOT/THIRD-PARTY-NOTICES.txt\"",
+        "if [ -f \"$NOTICES\" ]; then install -m 0644 \"$NOTICES\" \"$LICENSES/\"; fi",
+        &license_loop,
+        "  [ -d \"$license_dir\" ] || continue",
+        "  package_name=\"$(basename \"$(dirname \"$(dirname \"$license_dir\")\")\")\"",
+        "  mkdir -p \"$LICENSES/$package_name\"",
+        "  cp -a \"$license_dir/.\" \"$LICENSES/$package_name/\"",
+        "done",
+    ]);
+    let install_body = install_body.join("\n");
+    spec.upsert_block(
+        "install",
+        "install-snippet",
+        &install_body,
+        BlockPosition::End,
+    )?;
+    spec.upsert_files_block(managed_dirs)?;
 
-    write_text(&spec_path, &content)
+    write_text(&spec_path, &spec.render())
+}
+
+/// `system_libs` пакетов, разобранные по таблице `data/system-lib-requires.txt`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SystemLibRequires {
+    /// Значения `Requires:` без повторов.
+    pub requires: Vec<String>,
+    /// Библиотеки, которые входят в glibc.
+    pub glibc: Vec<String>,
+    /// Библиотеки, которых нет в ОС Аврора.
+    pub unavailable: Vec<String>,
+    /// Библиотеки без записи в таблице.
+    pub unmapped: Vec<String>,
+}
+
+/// Сопоставляет `system_libs` зависимостям RPM: встроенная таблица, поверх неё — таблица проекта.
+pub fn resolve_system_lib_requires(
+    project_root: &Path,
+    system_libs: &[String],
+) -> Result<SystemLibRequires> {
+    let mut table = BTreeMap::new();
+    parse_system_lib_table(BUILTIN_SYSTEM_LIB_REQUIRES, &mut table);
+    let project_table =
+        clear_store::thirdparty_root(project_root).join(PROJECT_SYSTEM_LIB_REQUIRES);
+    if project_table.exists() {
+        parse_system_lib_table(&read_text(&project_table)?, &mut table);
+    }
+
+    let mut libs = system_libs.to_vec();
+    libs.sort();
+    libs.dedup();
+
+    let mut out = SystemLibRequires::default();
+    for lib in libs {
+        match table.get(&lib).map(String::as_str) {
+            Some("glibc") => out.glibc.push(lib),
+            Some("!") => out.unavailable.push(lib),
+            Some(requires) => {
+                if !out.requires.iter().any(|item| item == requires) {
+                    out.requires.push(requires.to_string());
+                }
+            }
+            None => out.unmapped.push(lib),
+        }
+    }
+    out.requires.sort();
+    Ok(out)
+}
+
+fn parse_system_lib_table(content: &str, table: &mut BTreeMap<String, String>) {
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (lib, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let value = value.trim();
+        if !value.is_empty() {
+            table.insert(lib.to_string(), value.to_string());
+        }
+    }
+}
+
+/// Управляемый блок `Requires:` для системных библиотек в преамбуле основного пакета.
+fn upsert_system_requires(spec: &mut SpecDocument, resolved: &SystemLibRequires) -> Result<()> {
+    if resolved.requires.is_empty() && resolved.glibc.is_empty() {
+        spec.remove_block("requires");
+        return Ok(());
+    }
+
+    let mut body = Vec::new();
+    if !resolved.glibc.is_empty() {
+        body.push(format!("# glibc: {}", resolved.glibc.join(", ")));
+    }
+    for requires in &resolved.requires {
+        body.push(format!("Requires:       {requires}"));
+    }
+    spec.upsert_block("", "requires", &body.join("\n"), BlockPosition::End)
+}
+
+fn set_common_spec_defines(spec: &mut SpecDocument, metadata: &ProjectMetadata) {
+    spec.set_define("_cmake_skip_rpath", "%{nil}");
+    spec.set_define("__provides_exclude_from", "^%{_datadir}/%{name}/lib/.*$");
+
+    let mut patterns = metadata.shared_lib_patterns.clone();
+    patterns.sort();
+    patterns.dedup();
+    let requires_pattern = if patterns.is_empty() {
+        "^$".to_string()
+    } else {
+        format!("^({})$", patterns.join("|"))
+    };
+    spec.set_define("__requires_exclude", &requires_pattern);
+}
+
+/// `licenses/` поставляемых пакетов (`packages/<name>/<version>/licenses`) для цикла в `%install`.
+fn package_license_globs(packages: &[String]) -> String {
+    if packages.is_empty() {
+        return "\"$THIRDPARTY_ARCH_DIR/packages\"/*/*/licenses".to_string();
+    }
+    packages
+        .iter()
+        .map(|name| format!("\"$THIRDPARTY_ARCH_DIR/packages/{name}\"/*/licenses"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Каталоги, которые `%install` наполняет и которые должен упаковать `%files`.
+fn spec_managed_dirs(clear_mode: bool) -> &'static [&'static str] {
+    if clear_mode {
+        &[SPEC_LIB_DIR, SPEC_LICENSES_DIR]
+    } else {
+        &[SPEC_LIB_DIR]
+    }
+}
+
+/// Проверяет, что `%files` .spec совместим с тем, что устанавливает CLI; вызывается до загрузки пакетов.
+pub fn check_spec_layout(project_root: &Path, spec: &SpecConfig, clear_mode: bool) -> Result<()> {
+    let spec_path = project_root.join(&spec.path);
+    SpecDocument::parse(&read_text(&spec_path)?)
+        .check_files_layout(spec_managed_dirs(clear_mode))
+        .with_context(|| format!("Проверка {}", spec_path.display()))
+}
+
+/// Возвращает значение `%define`/`%global` из .spec, если оно задано.
+pub fn read_spec_define(spec_path: &Path, key: &str) -> Result<Option<String>> {
+    Ok(SpecDocument::parse(&read_text(spec_path)?).define(key))
+}
+
+/// Возвращает значение тега преамбулы .spec (`Name`, `Version`), если он задан.
+pub fn read_spec_tag(spec_path: &Path, tag: &str) -> Result<Option<String>> {
+    Ok(SpecDocument::parse(&read_text(spec_path)?).tag(tag))
+}
+
+/// Строит шаблон для `__requires_exclude` по SONAME поставляемой библиотеки.
+///
+/// RPM формирует зависимости вида `libssl.so.3()(64bit)` или `libssl.so.3(OPENSSL_3.0.0)`,
+/// поэтому после точного SONAME допускается только суффикс в скобках. Спецсимволы
+/// экранируются через `[...]`, чтобы не зависеть от обработки `\` в макросах RPM.
+pub fn soname_requires_pattern(soname: &str) -> String {
+    let mut escaped = String::new();
+    for ch in soname.chars() {
+        if ch.is_ascii_alphanumeric() || ch == '_' || ch == '-' {
+            escaped.push(ch);
+        } else {
+            escaped.push('[');
+            escaped.push(ch);
+            escaped.push(']');
+        }
+    }
+    format!("{escaped}([(].*)?")
 }
 
 pub fn find_spec_file(project_root: &Path) -> Result<PathBuf> {
+    let spec_paths = list_spec_files(project_root)?;
+    match spec_paths.as_slice() {
+        [single] => Ok(single.clone()),
+        [] => Err(anyhow!("В rpm/ не найден .spec файл")),
+        _ => Err(anyhow!(
+            "Найдено несколько .spec файлов в rpm/. Укажите нужный через --spec или перечислите их в specs файла {}",
+            mode::mode_path(project_root).display()
+        )),
+    }
+}
+
+/// Все .spec из rpm/ в порядке имён.
+pub fn list_spec_files(project_root: &Path) -> Result<Vec<PathBuf>> {
     let rpm_dir = project_root.join("rpm");
     if !rpm_dir.exists() {
         return Err(anyhow!("Не найден каталог rpm/"));
@@ -263,14 +1117,54 @@ pub fn find_spec_file(project_root: &Path) -> Result<PathBuf> {
         .collect();
 
     spec_paths.sort();
+    Ok(spec_paths)
+}
 
-    match spec_paths.as_slice() {
-        [single] => Ok(single.clone()),
-        [] => Err(anyhow!("В rpm/ не найден .spec файл")),
-        _ => Err(anyhow!(
-            "Найдено несколько .spec файлов в rpm/. Ожидается ровно один"
-        )),
+/// Выбирает .spec для обновления: `--spec`, затем `specs` из настроек проекта,
+/// затем единственный .spec в rpm/. Для `--spec` подмножество пакетов берётся из настроек.
+pub fn resolve_specs(
+    project_root: &Path,
+    explicit: &[PathBuf],
+    configured: &[SpecConfig],
+) -> Result<Vec<SpecConfig>> {
+    let specs = if !explicit.is_empty() {
+        explicit
+            .iter()
+            .map(|path| {
+                let path = path
+                    .strip_prefix(project_root)
+                    .unwrap_or(path)
+                    .to_path_buf();
+                configured
+                    .iter()
+                    .find(|spec| spec.path == path)
+                    .cloned()
+                    .unwrap_or(SpecConfig {
+                        path,
+                        packages: Vec::new(),
+                    })
+            })
+            .collect()
+    } else if !configured.is_empty() {
+        configured.to_vec()
+    } else {
+        let path = find_spec_file(project_root)?;
+        vec![SpecConfig {
+            path: path
+                .strip_prefix(project_root)
+                .unwrap_or(&path)
+                .to_path_buf(),
+            packages: Vec::new(),
+        }]
+    };
+
+    for spec in &specs {
+        let path = project_root.join(&spec.path);
+        if !path.is_file() {
+            return Err(anyhow!("Не найден .spec файл {}", path.display()));
+        }
     }
+    Ok(specs)
 }
 
 fn read_text(path: &Path) -> Result<String> {
@@ -301,6 +1195,15 @@ fn managed_markers(key: &str) -> (String, String) {
     )
 }
 
+fn managed_block_keys(content: &str) -> Result<Vec<String>> {
+    let re = Regex::new(r"(?m)^# >>> aurora-conan-cli:(\S+):begin$")
+        .context("Не удалось подготовить regex для поиска управляемых блоков")?;
+    Ok(re
+        .captures_iter(content)
+        .map(|caps| caps[1].to_string())
+        .collect())
+}
+
 fn upsert_managed_block(content: &str, key: &str, body: &str) -> Result<String> {
     let (start, end) = managed_markers(key);
     let block = format!("{start}\n{body}\n{end}");
@@ -324,22 +1227,24 @@ fn upsert_managed_block(content: &str, key: &str, body: &str) -> Result<String>
 
 fn remove_managed_block(content: &str, key: &str) -> Result<String> {
     let (start, end) = managed_markers(key);
+    // Вместе с блоком убираем пустые строки перед ним: их добавляет вставка,
+    // иначе каждый повторный upsert добавлял бы новые.
     let pattern = Regex::new(&format!(
-        r"(?s)\n?{}\n.*?\n{}\n?",
+        r"(?s)\n*{}\n.*?\n{}",
         regex::escape(&start),
         regex::escape(&end)
     ))
     .context("Не удалось подготовить regex для удаления блока")?;
 
-    Ok(pattern.replace(content, "\n").to_string())
+    Ok(pattern.replace(content, "").to_string())
 }
 
 fn upsert_block_after_project(content: &str, key: &str, body: &str) -> Result<String> {
     let without_block = remove_managed_block(content, key)?;
     let (start, end) = managed_markers(key);
-    let block = format!("{start}\n{body}\n{end}\n");
+    let block = format!("{start}\n{body}\n{end}");
 
-    let re_project = Regex::new(r"(?m)^project\s*\(.*\)\s*$")
+    let re_project = Regex::new(r"(?m)^project\s*\(.*\)[ \t]*$")
         .context("Не удалось подготовить regex для project()")?;
 
     if let Some(project_match) = re_project.find(&without_block) {
@@ -351,7 +1256,7 @@ fn upsert_block_after_project(content: &str, key: &str, body: &str) -> Result<St
         out.push_str(&without_block[insert_at..]);
         Ok(out)
     } else {
-        Ok(format!("{}\n{}", without_block.trim_end(), block))
+        Ok(format!("{}\n\n{}\n", without_block.trim_end(), block))
     }
 }
 
@@ -360,14 +1265,14 @@ fn ensure_find_package_pkgconfig(content: &str) -> Result<String> {
         return Ok(content.to_string());
     }
 
-    let re_project = Regex::new(r"(?m)^project\s*\(.*\)\s*$")
+    let re_project = Regex::new(r"(?m)^project\s*\(.*\)[ \t]*$")
         .context("Не удалось подготовить regex для project()")?;
 
     if let Some(project_match) = re_project.find(content) {
         let insert_at = project_match.end();
         let mut out = String::new();
         out.push_str(&content[..insert_at]);
-        out.push_str("\nfind_package(PkgConfig REQUIRED)");
+        out.push_str("\n\nfind_package(PkgConfig REQUIRED)");
         out.push_str(&content[insert_at..]);
         Ok(out)
     } else {
@@ -375,89 +1280,1181 @@ fn ensure_find_package_pkgconfig(content: &str) -> Result<String> {
     }
 }
 
-fn upsert_define(content: &str, key: &str, value: &str) -> Result<String> {
-    let line = format!("%define {key} {value}");
-    let re = Regex::new(&format!(r"(?m)^%define\s+{}\s+.*$", regex::escape(key)))
-        .context("Не удалось подготовить regex для %define")?;
+/// Каталог поставляемых библиотек в пакете приложения.
+pub const SPEC_LIB_DIR: &str = "%{_datadir}/%{name}/lib";
+/// Каталог лицензий поставляемых пакетов (clear-режим).
+pub const SPEC_LICENSES_DIR: &str = "%{_datadir}/%{name}/licenses";
+
+/// Директивы, с которых начинаются секции .spec.
+const SPEC_SECTIONS: &[&str] = &[
+    "package",
+    "description",
+    "prep",
+    "generate_buildrequires",
+    "conf",
+    "build",
+    "install",
+    "check",
+    "clean",
+    "files",
+    "changelog",
+    "pre",
+    "post",
+    "preun",
+    "postun",
+    "pretrans",
+    "posttrans",
+    "preuntrans",
+    "postuntrans",
+    "triggerprein",
+    "triggerin",
+    "triggerun",
+    "triggerpostun",
+    "filetriggerin",
+    "filetriggerun",
+    "filetriggerpostun",
+    "transfiletriggerin",
+    "transfiletriggerun",
+    "transfiletriggerpostun",
+    "verifyscript",
+];
+
+/// Куда вставлять управляемый блок внутри секции.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockPosition {
+    /// Сразу после заголовка, до команд сборки.
+    Start,
+    /// После последней непустой строки секции.
+    End,
+}
+
+/// Секция .spec: имя, аргументы заголовка и диапазон строк `[start, end)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SpecSection {
+    /// Имя без `%`; пустая строка — преамбула основного пакета.
+    name: String,
+    args: Vec<String>,
+    start: usize,
+    end: usize,
+}
 
-    if re.is_match(content) {
-        return Ok(re.replace(content, line.as_str()).to_string());
+impl SpecSection {
+    fn body_start(&self) -> usize {
+        if self.name.is_empty() {
+            self.start
+        } else {
+            self.start + 1
+        }
     }
 
-    let re_name = Regex::new(r"(?m)^Name:\s+").context("Не удалось подготовить regex для Name")?;
-    if let Some(m) = re_name.find(content) {
-        let mut out = String::new();
-        out.push_str(&content[..m.start()]);
-        out.push_str(&line);
-        out.push('\n');
-        out.push_str(&content[m.start()..]);
-        Ok(out)
-    } else {
-        Ok(format!("{line}\n{content}"))
+    /// Подпакет, к которому относится секция: `-n <имя>` или суффикс (`%files devel`).
+    fn subpackage(&self) -> Option<&str> {
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-n" => return args.next().map(String::as_str),
+                "-f" | "-p" => {
+                    args.next();
+                }
+                option if option.starts_with('-') => {}
+                suffix => return Some(suffix),
+            }
+        }
+        None
     }
 }
 
-fn ensure_buildrequires_conan(content: &str) -> Result<String> {
-    let has_conan = Regex::new(r"(?m)^BuildRequires:.*\bconan\b")
-        .context("Не удалось подготовить regex для BuildRequires conan")?
-        .is_match(content);
+/// Структурное представление .spec: исходные строки, секции и глубина `%if` у каждой строки.
+///
+/// Правки затрагивают только изменяемые строки, остальной текст выводится как был.
+#[derive(Debug, Clone)]
+struct SpecDocument {
+    lines: Vec<String>,
+    trailing_newline: bool,
+    sections: Vec<SpecSection>,
+    depths: Vec<usize>,
+}
 
-    if has_conan {
-        return Ok(content.to_string());
+impl SpecDocument {
+    fn parse(text: &str) -> Self {
+        let trailing_newline = text.ends_with('\n');
+        let lines = if text.is_empty() {
+            Vec::new()
+        } else {
+            text.strip_suffix('\n')
+                .unwrap_or(text)
+                .split('\n')
+                .map(str::to_string)
+                .collect()
+        };
+
+        let mut spec = Self {
+            lines,
+            trailing_newline,
+            sections: Vec::new(),
+            depths: Vec::new(),
+        };
+        spec.reindex();
+        spec
     }
 
-    let build_requires_re = Regex::new(r"(?m)^BuildRequires:.*$")
-        .context("Не удалось подготовить regex для BuildRequires")?;
-    let all: Vec<_> = build_requires_re.find_iter(content).collect();
+    fn render(&self) -> String {
+        let mut out = self.lines.join("\n");
+        if self.trailing_newline {
+            out.push('\n');
+        }
+        out
+    }
 
-    if let Some(last) = all.last() {
-        let mut out = String::new();
-        out.push_str(&content[..last.end()]);
-        out.push_str("\nBuildRequires:  conan");
-        out.push_str(&content[last.end()..]);
-        return Ok(out);
+    fn reindex(&mut self) {
+        let total = self.lines.len();
+        self.sections = vec![SpecSection {
+            name: String::new(),
+            args: Vec::new(),
+            start: 0,
+            end: total,
+        }];
+        self.depths = Vec::with_capacity(total);
+
+        let mut depth = 0usize;
+        for (index, line) in self.lines.iter().enumerate() {
+            if let Some((name, args)) = parse_section_header(line) {
+                if let Some(last) = self.sections.last_mut() {
+                    last.end = index;
+                }
+                self.sections.push(SpecSection {
+                    name,
+                    args,
+                    start: index,
+                    end: total,
+                });
+            }
+
+            match line.split_whitespace().next().unwrap_or("") {
+                "%if" | "%ifarch" | "%ifnarch" | "%ifos" | "%ifnos" => {
+                    self.depths.push(depth);
+                    depth += 1;
+                }
+                "%else" | "%elif" | "%elifarch" | "%elifos" => {
+                    self.depths.push(depth.saturating_sub(1));
+                }
+                "%endif" => {
+                    depth = depth.saturating_sub(1);
+                    self.depths.push(depth);
+                }
+                _ => self.depths.push(depth),
+            }
+        }
     }
 
-    let desc_re = Regex::new(r"(?m)^%description\b")
-        .context("Не удалось подготовить regex для %description")?;
-    if let Some(desc) = desc_re.find(content) {
-        let mut out = String::new();
-        out.push_str(&content[..desc.start()]);
-        out.push_str("BuildRequires:  conan\n");
-        out.push_str(&content[desc.start()..]);
-        Ok(out)
-    } else {
-        Ok(format!("{content}\nBuildRequires:  conan\n"))
+    fn preamble(&self) -> SpecSection {
+        self.sections[0].clone()
+    }
+
+    /// Секция основного пакета (без `-n` и суффикса подпакета).
+    fn main_section(&self, name: &str) -> Option<SpecSection> {
+        self.sections
+            .iter()
+            .find(|section| section.name == name && section.subpackage().is_none())
+            .cloned()
+    }
+
+    /// Значение `%define`/`%global`; безусловное определение важнее определения внутри `%if`.
+    fn define(&self, key: &str) -> Option<String> {
+        let mut conditional = None;
+        for (index, line) in self.lines.iter().enumerate() {
+            if let Some((_, value)) = parse_define(line, key) {
+                if self.depths[index] == 0 {
+                    return Some(value);
+                }
+                conditional.get_or_insert(value);
+            }
+        }
+        conditional
+    }
+
+    /// Задаёт макрос в преамбуле, сохраняя `%define` или `%global` существующей строки.
+    /// Повторные безусловные определения удаляются, иначе RPM взял бы последнее.
+    fn set_define(&mut self, key: &str, value: &str) {
+        let preamble = self.preamble();
+        let existing: Vec<usize> = (preamble.start..preamble.end)
+            .filter(|&index| self.depths[index] == 0)
+            .filter(|&index| parse_define(&self.lines[index], key).is_some())
+            .collect();
+
+        if let Some((&first, duplicates)) = existing.split_first() {
+            let keyword = parse_define(&self.lines[first], key)
+                .map(|(keyword, _)| keyword)
+                .unwrap_or("%define");
+            self.lines[first] = format!("{keyword} {key} {value}");
+            for &index in duplicates.iter().rev() {
+                self.lines.remove(index);
+            }
+        } else {
+            let insert_at = (preamble.start..preamble.end)
+                .find(|&index| {
+                    self.depths[index] == 0
+                        && preamble_tag(&self.lines[index])
+                            .is_some_and(|(tag, _)| tag.eq_ignore_ascii_case("Name"))
+                })
+                .unwrap_or(preamble.start);
+            self.lines
+                .insert(insert_at, format!("%define {key} {value}"));
+        }
+        self.reindex();
+    }
+
+    /// Строки `BuildRequires:` основной преамбулы.
+    fn build_requires_lines(&self) -> Vec<usize> {
+        let preamble = self.preamble();
+        (preamble.start..preamble.end)
+            .filter(|&index| {
+                preamble_tag(&self.lines[index])
+                    .is_some_and(|(tag, _)| tag.eq_ignore_ascii_case("BuildRequires"))
+            })
+            .collect()
+    }
+
+    /// Добавляет `BuildRequires:` после последнего безусловного, не заходя внутрь `%if`.
+    fn ensure_build_requires(&mut self, dependency: &str) {
+        let lines = self.build_requires_lines();
+        let present = lines.iter().any(|&index| {
+            preamble_tag(&self.lines[index]).is_some_and(|(_, value)| {
+                dependency_entries(value)
+                    .iter()
+                    .any(|(name, _)| name == dependency)
+            })
+        });
+        if present {
+            return;
+        }
+
+        let insert_at = match lines.iter().rev().find(|&&index| self.depths[index] == 0) {
+            Some(&index) => index + 1,
+            None => {
+                let preamble = self.preamble();
+                self.last_content_line(&preamble)
+                    .map(|index| index + 1)
+                    .unwrap_or(preamble.start)
+            }
+        };
+        self.lines
+            .insert(insert_at, format!("BuildRequires:  {dependency}"));
+        self.reindex();
+    }
+
+    /// Убирает зависимость из `BuildRequires:` основной преамбулы; опустевшие строки удаляются.
+    fn remove_build_requires(&mut self, dependency: &str) {
+        for index in self.build_requires_lines().into_iter().rev() {
+            let Some((tag, value)) = preamble_tag(&self.lines[index]) else {
+                continue;
+            };
+            let entries = dependency_entries(value);
+            if !entries.iter().any(|(name, _)| name == dependency) {
+                continue;
+            }
+
+            let rest: Vec<String> = entries
+                .into_iter()
+                .filter(|(name, _)| name != dependency)
+                .map(|(_, entry)| entry)
+                .collect();
+            if rest.is_empty() {
+                self.lines.remove(index);
+            } else {
+                self.lines[index] = format!("{tag}:  {}", rest.join(", "));
+            }
+        }
+        self.reindex();
+    }
+
+    /// Перезаписывает управляемый блок в секции основного пакета.
+    fn upsert_block(
+        &mut self,
+        section: &str,
+        key: &str,
+        body: &str,
+        position: BlockPosition,
+    ) -> Result<()> {
+        self.remove_block(key);
+        let target = self
+            .main_section(section)
+            .ok_or_else(|| anyhow!("Не найдена секция %{section} в .spec"))?;
+
+        let (start, end) = managed_markers(key);
+        let mut block = vec![start];
+        block.extend(body.lines().map(str::to_string));
+        block.push(end);
+
+        let insert_at = match position {
+            BlockPosition::Start => target.body_start(),
+            BlockPosition::End => match self.last_content_line(&target) {
+                Some(last) => {
+                    block.insert(0, String::new());
+                    if self
+                        .lines
+                        .get(last + 1)
+                        .is_some_and(|line| !line.trim().is_empty())
+                    {
+                        block.push(String::new());
+                    }
+                    last + 1
+                }
+                None => target.body_start(),
+            },
+        };
+
+        self.lines.splice(insert_at..insert_at, block);
+        self.reindex();
+        Ok(())
+    }
+
+    /// Удаляет управляемый блок; пустая строка перед ним уходит, если после блока тоже пусто.
+    fn remove_block(&mut self, key: &str) {
+        let (start, end) = managed_markers(key);
+        while let Some(first) = self.lines.iter().position(|line| line.trim_end() == start) {
+            let Some(last) = self.lines[first..]
+                .iter()
+                .position(|line| line.trim_end() == end)
+                .map(|offset| first + offset)
+            else {
+                break;
+            };
+
+            let blank_before = first > 0 && self.lines[first - 1].trim().is_empty();
+            let blank_after = self
+                .lines
+                .get(last + 1)
+                .is_none_or(|line| line.trim().is_empty());
+            let from = if blank_before && blank_after {
+                first - 1
+            } else {
+                first
+            };
+            self.lines.drain(from..=last);
+        }
+        self.reindex();
+    }
+
+    /// Значение `Name:` основного пакета.
+    fn name(&self) -> Option<String> {
+        self.tag("Name")
+    }
+
+    /// Значение тега преамбулы основного пакета.
+    fn tag(&self, name: &str) -> Option<String> {
+        let preamble = self.preamble();
+        (preamble.start..preamble.end).find_map(|index| {
+            preamble_tag(&self.lines[index])
+                .filter(|(tag, _)| tag.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    /// Записи `%files` основного пакета вне управляемых блоков.
+    fn main_files_entries(&self) -> Vec<FilesEntry> {
+        let Some(section) = self.main_section("files") else {
+            return Vec::new();
+        };
+
+        let mut entries = Vec::new();
+        let mut in_block = false;
+        for line in &self.lines[section.body_start()..section.end] {
+            let trimmed = line.trim();
+            if trimmed.starts_with("# >>> aurora-conan-cli:") {
+                in_block = true;
+            } else if trimmed.starts_with("# <<< aurora-conan-cli:") {
+                in_block = false;
+            } else if !in_block {
+                entries.extend(parse_files_line(trimmed));
+            }
+        }
+        entries
+    }
+
+    /// Ищет конфликты `%files` с каталогами, которые наполняет `%install` CLI.
+    fn check_files_layout(&self, managed_dirs: &[&str]) -> Result<()> {
+        if self.main_section("files").is_none() {
+            return Err(anyhow!(
+                "В .spec нет секции %files основного пакета: поставляемые библиотеки некуда упаковать"
+            ));
+        }
+
+        let name = self.name().unwrap_or_default();
+        let mut conflicts = Vec::new();
+
+        let preamble = self.preamble();
+        if (preamble.start..preamble.end).any(|index| {
+            preamble_tag(&self.lines[index]).is_some_and(|(tag, value)| {
+                tag.eq_ignore_ascii_case("BuildArch") && value.eq_ignore_ascii_case("noarch")
+            })
+        }) {
+            conflicts.push(
+                "BuildArch: noarch несовместим с поставляемыми .so в %{_datadir}/%{name}/lib"
+                    .to_string(),
+            );
+        }
+
+        for entry in self.main_files_entries() {
+            if !entry.exclude {
+                continue;
+            }
+            for dir in managed_dirs {
+                if spec_path_covers(&entry.path, dir, &name) {
+                    conflicts.push(format!(
+                        "%exclude {} исключает {}, куда CLI устанавливает файлы",
+                        entry.path, dir
+                    ));
+                }
+            }
+        }
+
+        if conflicts.is_empty() {
+            return Ok(());
+        }
+        Err(anyhow!(
+            "Раскладка %files конфликтует с устанавливаемыми файлами:\n  - {}",
+            conflicts.join("\n  - ")
+        ))
+    }
+
+    /// Перечисляет в `%files` основного пакета каталоги, которые ещё не упакованы другими записями.
+    fn upsert_files_block(&mut self, managed_dirs: &[&str]) -> Result<()> {
+        let name = self.name().unwrap_or_default();
+        let entries = self.main_files_entries();
+        let missing: Vec<&str> = managed_dirs
+            .iter()
+            .copied()
+            .filter(|dir| {
+                !entries.iter().any(|entry| {
+                    !entry.exclude && !entry.dir_only && spec_path_covers(&entry.path, dir, &name)
+                })
+            })
+            .collect();
+
+        if missing.is_empty() {
+            self.remove_block("files");
+            return Ok(());
+        }
+        self.upsert_block("files", "files", &missing.join("\n"), BlockPosition::End)
+    }
+
+    fn last_content_line(&self, section: &SpecSection) -> Option<usize> {
+        (section.body_start()..section.end)
+            .rev()
+            .find(|&index| !self.lines[index].trim().is_empty())
     }
 }
 
-fn remove_buildrequires_conan(content: &str) -> Result<String> {
-    let re = Regex::new(r"(?m)^BuildRequires:.*\bconan\b.*\n?")
-        .context("Не удалось подготовить regex для удаления BuildRequires conan")?;
-    Ok(re.replace_all(content, "").to_string())
+/// Путь из строки `%files` с учётом `%exclude` и `%dir`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FilesEntry {
+    path: String,
+    exclude: bool,
+    dir_only: bool,
 }
 
-fn upsert_block_in_section(content: &str, section: &str, key: &str, body: &str) -> Result<String> {
-    let section_re = Regex::new(&format!(r"(?m)^%{}\s*$", regex::escape(section)))
-        .with_context(|| format!("Не удалось подготовить regex для секции %{section}"))?;
-    let section_start = section_re
-        .find(content)
-        .ok_or_else(|| anyhow!("Не найдена секция %{section} в .spec"))?;
+/// Разбирает строку `%files`: снимает директивы вида `%attr(...)`/`%config(...)` перед путями.
+fn parse_files_line(line: &str) -> Vec<FilesEntry> {
+    if line.is_empty() || line.starts_with('#') {
+        return Vec::new();
+    }
 
-    let next_section_re = Regex::new(r"(?m)^%[A-Za-z]")
-        .context("Не удалось подготовить regex для поиска следующей секции")?;
+    let mut rest = line;
+    let mut exclude = false;
+    let mut dir_only = false;
+    while let Some(directive) = rest.strip_prefix('%') {
+        if directive.starts_with('{') {
+            break;
+        }
+        let name_end = directive
+            .find(|c: char| c == '(' || c.is_whitespace())
+            .unwrap_or(directive.len());
+        let mut after = &directive[name_end..];
+        if after.starts_with('(') {
+            after = after.find(')').map_or("", |index| &after[index + 1..]);
+        }
+        match &directive[..name_end] {
+            "exclude" => exclude = true,
+            "dir" => dir_only = true,
+            "attr" | "config" | "doc" | "license" | "ghost" | "verify" | "lang" | "caps"
+            | "docdir" | "readme" | "artifact" | "missingok" => {}
+            // %defattr, условия и прочие макросы путей не содержат.
+            _ => return Vec::new(),
+        }
+        rest = after.trim_start();
+    }
 
-    let section_end = next_section_re
-        .find_at(content, section_start.end())
-        .map(|m| m.start())
-        .unwrap_or(content.len());
+    rest.split_whitespace()
+        .map(|path| FilesEntry {
+            path: path.to_string(),
+            exclude,
+            dir_only,
+        })
+        .collect()
+}
 
-    let section_content = &content[section_start.start()..section_end];
-    let updated_section = upsert_managed_block(section_content, key, body)?;
+/// Раскрывает в пути `%files` макросы каталогов и `%{name}` для сравнения путей.
+fn normalize_spec_path(path: &str, name: &str) -> String {
+    let mut out = path.to_string();
+    for (spec_macro, value) in [
+        ("%{_datadir}", "/usr/share"),
+        ("%_datadir", "/usr/share"),
+        ("%{_prefix}", "/usr"),
+        ("%_prefix", "/usr"),
+        ("%{name}", name),
+        ("%name", name),
+    ] {
+        out = out.replace(spec_macro, value);
+    }
+    while out.contains("//") {
+        out = out.replace("//", "/");
+    }
+    out.trim_end_matches('/').to_string()
+}
 
-    let mut out = String::new();
-    out.push_str(&content[..section_start.start()]);
-    out.push_str(&updated_section);
-    out.push_str(&content[section_end..]);
-    Ok(out)
+/// Упаковывает ли запись `%files` каталог `dir` целиком (сам каталог или его предка).
+fn spec_path_covers(entry: &str, dir: &str, name: &str) -> bool {
+    let entry = normalize_spec_path(entry, name);
+    let mut candidate = normalize_spec_path(dir, name);
+    loop {
+        if elf::glob_matches(&entry, &candidate) {
+            return true;
+        }
+        match candidate.rfind('/') {
+            Some(index) if index > 0 => candidate.truncate(index),
+            _ => return false,
+        }
+    }
+}
+
+fn parse_section_header(line: &str) -> Option<(String, Vec<String>)> {
+    let rest = line.trim_end().strip_prefix('%')?;
+    let name_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    let name = &rest[..name_end];
+    if !SPEC_SECTIONS.contains(&name) {
+        return None;
+    }
+    let args = rest[name_end..]
+        .split_whitespace()
+        .map(str::to_string)
+        .collect();
+    Some((name.to_string(), args))
+}
+
+/// Разбирает `%define <key> <value>` или `%global <key> <value>`; возвращает ключевое слово и значение.
+fn parse_define<'a>(line: &'a str, key: &str) -> Option<(&'a str, String)> {
+    let (keyword, rest) = line.trim().split_once(char::is_whitespace)?;
+    if keyword != "%define" && keyword != "%global" {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (name, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    (name == key).then(|| (keyword, value.trim().to_string()))
+}
+
+/// Тег преамбулы (`BuildRequires:`, `Requires(post):`) и его значение.
+fn preamble_tag(line: &str) -> Option<(&str, &str)> {
+    let (tag, value) = line.split_once(':')?;
+    let valid = tag.starts_with(|c: char| c.is_ascii_alphabetic())
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '(' | ')' | ',' | '_'));
+    valid.then(|| (tag, value.trim()))
+}
+
+/// Зависимости из значения тега: имя и исходная запись с ограничением версии.
+fn dependency_entries(value: &str) -> Vec<(String, String)> {
+    let mut entries: Vec<(String, String)> = Vec::new();
+    let mut tokens = value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty());
+    while let Some(token) = tokens.next() {
+        if matches!(token, "<" | "<=" | "=" | ">=" | ">")
+            && let Some((_, entry)) = entries.last_mut()
+        {
+            let version = tokens.next().unwrap_or("");
+            entry.push_str(&format!(" {token} {version}"));
+            continue;
+        }
+        entries.push((token.to_string(), token.to_string()));
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use super::{
+        CMAKE_FILE, CONANFILE, SPEC_LIB_DIR, SPEC_LICENSES_DIR, SpecDocument, check_spec_layout,
+        cmake_source_files, read_license_waivers, read_overrides, read_requires, read_spec_define,
+        read_targets, resolve_specs, resolve_system_lib_requires, update_cmake, update_cmake_clear,
+        update_spec, update_spec_clear, write_conanfile,
+    };
+    use crate::model::{ConanRef, LinkVisibility, ProjectMetadata, SpecConfig, TargetAssignment};
+
+    fn with_modules(modules: &[&str]) -> ProjectMetadata {
+        ProjectMetadata {
+            direct_pkg_modules: modules.iter().map(|m| m.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn golden_dir(kind: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("testdata")
+            .join(kind)
+    }
+
+    fn check_golden(actual: &str, expected_path: &Path) -> Result<String> {
+        if std::env::var_os("AURORA_UPDATE_GOLDEN").is_some() {
+            fs::write(expected_path, actual)?;
+        }
+        let expected = fs::read_to_string(expected_path)?;
+        assert_eq!(
+            actual,
+            expected,
+            "golden mismatch: {}",
+            expected_path.display()
+        );
+        Ok(expected)
+    }
+
+    /// Прогоняет `<case>.input.cmake` через update и сравнивает с `<case>.<mode>.expected.cmake`.
+    /// `AURORA_UPDATE_GOLDEN=1` перезаписывает ожидаемые файлы.
+    fn assert_golden(case: &str, clear_mode: bool, metadata: &ProjectMetadata) -> Result<()> {
+        let mode = if clear_mode { "clear" } else { "conan" };
+        let input = fs::read_to_string(golden_dir("cmake").join(format!("{case}.input.cmake")))?;
+        let expected_path = golden_dir("cmake").join(format!("{case}.{mode}.expected.cmake"));
+
+        let project = tempdir()?;
+        let cmake_path = project.path().join(CMAKE_FILE);
+        fs::write(&cmake_path, input)?;
+
+        let update = if clear_mode {
+            update_cmake_clear
+        } else {
+            update_cmake
+        };
+        update(project.path(), metadata)?;
+        let expected = check_golden(&fs::read_to_string(&cmake_path)?, &expected_path)?;
+
+        update(project.path(), metadata)?;
+        assert_eq!(
+            fs::read_to_string(&cmake_path)?,
+            expected,
+            "update is not idempotent"
+        );
+        Ok(())
+    }
+
+    /// То же для `.spec`: `<case>.input.spec` → `<case>.<mode>.expected.spec`.
+    fn assert_spec_golden(case: &str, clear_mode: bool, metadata: &ProjectMetadata) -> Result<()> {
+        let mode = if clear_mode { "clear" } else { "conan" };
+        let input = fs::read_to_string(golden_dir("spec").join(format!("{case}.input.spec")))?;
+        let expected_path = golden_dir("spec").join(format!("{case}.{mode}.expected.spec"));
+
+        let project = tempdir()?;
+        fs::create_dir_all(project.path().join("rpm"))?;
+        let spec = SpecConfig {
+            path: PathBuf::from(format!("rpm/{case}.spec")),
+            packages: Vec::new(),
+        };
+        let spec_path = project.path().join(&spec.path);
+        fs::write(&spec_path, input)?;
+
+        let update = if clear_mode {
+            update_spec_clear
+        } else {
+            update_spec
+        };
+        update(project.path(), &spec, metadata)?;
+        let expected = check_golden(&fs::read_to_string(&spec_path)?, &expected_path)?;
+
+        update(project.path(), &spec, metadata)?;
+        assert_eq!(
+            fs::read_to_string(&spec_path)?,
+            expected,
+            "update is not idempotent"
+        );
+        Ok(())
+    }
+
+    fn with_shared_libs(patterns: &[&str]) -> ProjectMetadata {
+        ProjectMetadata {
+            shared_lib_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn golden_aurora_template_conan() -> Result<()> {
+        assert_golden(
+            "aurora-template",
+            false,
+            &with_modules(&["ffmpeg", "libcurl"]),
+        )
+    }
+
+    #[test]
+    fn golden_aurora_template_clear() -> Result<()> {
+        assert_golden("aurora-template", true, &with_modules(&["openssl"]))
+    }
+
+    #[test]
+    fn golden_minimal_without_dependencies() -> Result<()> {
+        assert_golden("minimal", false, &with_modules(&[]))
+    }
+
+    #[test]
+    fn golden_legacy_targets_block_is_rewritten() -> Result<()> {
+        assert_golden("legacy-managed", false, &with_modules(&["ffmpeg"]))
+    }
+
+    #[test]
+    fn golden_multi_target_links_with_visibility() -> Result<()> {
+        let mut metadata = with_modules(&["ffmpeg", "gtest", "libssl", "libcrypto"]);
+        metadata.package_modules = BTreeMap::from([
+            (
+                "openssl".to_string(),
+                vec!["libssl".to_string(), "libcrypto".to_string()],
+            ),
+            ("gtest".to_string(), vec!["gtest".to_string()]),
+        ]);
+        metadata.target_assignments = BTreeMap::from([
+            (
+                "openssl".to_string(),
+                vec![
+                    TargetAssignment {
+                        target: "core".to_string(),
+                        visibility: LinkVisibility::Public,
+                    },
+                    TargetAssignment {
+                        target: "tests".to_string(),
+                        visibility: LinkVisibility::Private,
+                    },
+                ],
+            ),
+            (
+                "gtest".to_string(),
+                vec![TargetAssignment {
+                    target: "tests".to_string(),
+                    visibility: LinkVisibility::Private,
+                }],
+            ),
+        ]);
+        assert_golden("multi-target", false, &metadata)
+    }
+
+    #[test]
+    fn conanfile_round_trips_targets_overrides_and_waivers() -> Result<()> {
+        let project = tempdir()?;
+        let refs = vec![ConanRef {
+            name: "ffmpeg".to_string(),
+            version: "6.1.1".to_string(),
+            user: "aurora".to_string(),
+        }];
+        let targets = BTreeMap::from([(
+            "ffmpeg".to_string(),
+            vec![
+                TargetAssignment {
+                    target: "core".to_string(),
+                    visibility: LinkVisibility::Public,
+                },
+                TargetAssignment {
+                    target: "ru.auroraos.App".to_string(),
+                    visibility: LinkVisibility::Private,
+                },
+            ],
+        )]);
+        let overrides = vec![ConanRef {
+            name: "zlib".to_string(),
+            version: "1.3.1".to_string(),
+            user: "aurora".to_string(),
+        }];
+        let waivers = BTreeMap::from([(
+            "ms-gsl".to_string(),
+            "header-only, \"approved\" by legal".to_string(),
+        )]);
+        let policy_overrides = vec![
+            ConanRef {
+                name: "openssl".to_string(),
+                version: "3.2.1".to_string(),
+                user: "acme".to_string(),
+            },
+            ConanRef {
+                name: "zlib".to_string(),
+                version: "1.2.13".to_string(),
+                user: "aurora".to_string(),
+            },
+        ];
+        write_conanfile(
+            project.path(),
+            &refs,
+            &overrides,
+            &policy_overrides,
+            &targets,
+            &waivers,
+        )?;
+
+        let content = fs::read_to_string(project.path().join(CONANFILE))?;
+        assert!(content.contains("self.requires(\"zlib/1.3.1@aurora\", override=True)\n"));
+        assert!(
+            content.contains(
+                "self.requires(\"openssl/3.2.1@acme\", override=True)  # aurora-policy\n"
+            )
+        );
+        // Переопределение проекта главнее принудительной версии политики
+        assert!(!content.contains("zlib/1.2.13"));
+        assert!(content.contains("\"ms-gsl\": \"header-only, \\\"approved\\\" by legal\","));
+        assert_eq!(read_requires(project.path())?, refs);
+        assert_eq!(read_overrides(project.path())?, overrides);
+        assert_eq!(read_targets(project.path())?, targets);
+        assert_eq!(read_license_waivers(project.path())?, waivers);
+        Ok(())
+    }
+
+    #[test]
+    fn conanfile_refs_ignore_waiver_reasons_that_look_like_references() -> Result<()> {
+        let project = tempdir()?;
+        let refs = vec![ConanRef {
+            name: "openssl".to_string(),
+            version: "3.2.0".to_string(),
+            user: "aurora".to_string(),
+        }];
+        let waivers = BTreeMap::from([
+            (
+                "ms-gsl".to_string(),
+                "same terms as \"openssl/1.1.1w@aurora\"".to_string(),
+            ),
+            (
+                "zlib".to_string(),
+                "pinned like \"zlib/1.2.13@aurora\", override=True".to_string(),
+            ),
+        ]);
+        write_conanfile(project.path(), &refs, &[], &[], &BTreeMap::new(), &waivers)?;
+
+        assert_eq!(read_requires(project.path())?, refs);
+        assert!(read_overrides(project.path())?.is_empty());
+        assert_eq!(read_license_waivers(project.path())?, waivers);
+        Ok(())
+    }
+
+    #[test]
+    fn link_block_goes_to_subdirectory_that_defines_target() -> Result<()> {
+        let project = tempdir()?;
+        fs::write(
+            project.path().join(CMAKE_FILE),
+            "cmake_minimum_required(VERSION 3.16)\nproject(ru.auroraos.App CXX)\n\nadd_subdirectory(src)\n\n\
+             # >>> aurora-conan-cli:targets:begin\nif(TARGET ${PROJECT_NAME})\nendif()\n# <<< aurora-conan-cli:targets:end\n",
+        )?;
+        fs::create_dir_all(project.path().join("src"))?;
+        fs::write(
+            project.path().join("src").join(CMAKE_FILE),
+            "add_executable(ru.auroraos.App main.cpp)\n",
+        )?;
+
+        let changed = update_cmake(project.path(), &with_modules(&["ffmpeg"]))?;
+        assert_eq!(
+            changed,
+            vec![
+                project.path().join(CMAKE_FILE),
+                project.path().join("src").join(CMAKE_FILE),
+            ]
+        );
+
+        let root = fs::read_to_string(project.path().join(CMAKE_FILE))?;
+        assert!(root.contains("pkg_check_modules(FFMPEG REQUIRED IMPORTED_TARGET GLOBAL ffmpeg)"));
+        assert!(!root.contains("aurora-conan-cli:targets:begin"));
+
+        let src = fs::read_to_string(project.path().join("src").join(CMAKE_FILE))?;
+        assert!(
+            src.contains("target_link_libraries(${PROJECT_NAME} PRIVATE\n    PkgConfig::FFMPEG")
+        );
+
+        assert!(update_cmake(project.path(), &with_modules(&["ffmpeg"]))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn follows_add_subdirectory_with_source_dir_variables() -> Result<()> {
+        let project = tempdir()?;
+        fs::write(
+            project.path().join(CMAKE_FILE),
+            "project(ru.auroraos.App CXX)\n\
+             add_subdirectory(${CMAKE_CURRENT_SOURCE_DIR}/src)\n\
+             add_subdirectory(\"${PROJECT_SOURCE_DIR}/tests\")\n\
+             add_subdirectory(${EXTRA_DIR})\n",
+        )?;
+        for dir in ["src", "tests"] {
+            fs::create_dir_all(project.path().join(dir))?;
+            fs::write(project.path().join(dir).join(CMAKE_FILE), "")?;
+        }
+        fs::write(
+            project.path().join("src").join(CMAKE_FILE),
+            "add_subdirectory(${CMAKE_SOURCE_DIR}/plugins)\n\
+             add_executable(${PROJECT_NAME} main.cpp)\n",
+        )?;
+        fs::create_dir_all(project.path().join("plugins"))?;
+        fs::write(project.path().join("plugins").join(CMAKE_FILE), "")?;
+
+        let mut sources = cmake_source_files(project.path())?;
+        sources.sort();
+        assert_eq!(
+            sources,
+            vec![
+                project.path().join(CMAKE_FILE),
+                project.path().join("plugins").join(CMAKE_FILE),
+                project.path().join("src").join(CMAKE_FILE),
+                project.path().join("tests").join(CMAKE_FILE),
+            ]
+        );
+
+        update_cmake(project.path(), &with_modules(&["ffmpeg"]))?;
+        let src = fs::read_to_string(project.path().join("src").join(CMAKE_FILE))?;
+        assert!(src.contains("aurora-conan-cli:targets:begin"));
+        Ok(())
+    }
+
+    #[test]
+    fn spec_parser_round_trips_aurora_specs() -> Result<()> {
+        for entry in fs::read_dir(golden_dir("spec"))? {
+            let path = entry?.path();
+            let text = fs::read_to_string(&path)?;
+            assert_eq!(
+                SpecDocument::parse(&text).render(),
+                text,
+                "{}",
+                path.display()
+            );
+
+            let crlf = text.replace('\n', "\r\n");
+            assert_eq!(SpecDocument::parse(&crlf).render(), crlf);
+            let unterminated = text.trim_end();
+            assert_eq!(SpecDocument::parse(unterminated).render(), unterminated);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn spec_parser_separates_subpackage_sections() -> Result<()> {
+        let text = fs::read_to_string(golden_dir("spec").join("subpackages.input.spec"))?;
+        let spec = SpecDocument::parse(&text);
+
+        let headers: Vec<(String, Option<&str>)> = spec
+            .sections
+            .iter()
+            .skip(1)
+            .map(|section| (spec.lines[section.start].clone(), section.subpackage()))
+            .collect();
+        assert!(headers.contains(&("%files devel".to_string(), Some("devel"))));
+        assert!(headers.contains(&(
+            "%files -n ru.auroraos.Complex-tests".to_string(),
+            Some("ru.auroraos.Complex-tests")
+        )));
+        assert!(headers.contains(&("%build -v".to_string(), None)));
+
+        let files = spec.main_section("files").expect("main %files");
+        assert_eq!(spec.lines[files.start], "%files");
+        assert_eq!(spec.define("build_tests").as_deref(), Some("1"));
+        assert_eq!(spec.define("_arch_suffix").as_deref(), Some("arm64"));
+        Ok(())
+    }
+
+    #[test]
+    fn golden_spec_qmake_template_conan() -> Result<()> {
+        assert_spec_golden(
+            "aurora-qmake-template",
+            false,
+            &with_shared_libs(&["libavcodec.*"]),
+        )
+    }
+
+    #[test]
+    fn golden_spec_subpackages_conan() -> Result<()> {
+        assert_spec_golden(
+            "subpackages",
+            false,
+            &with_shared_libs(&["libssl[.]so[.]3([(].*)?"]),
+        )
+    }
+
+    #[test]
+    fn golden_spec_subpackages_clear() -> Result<()> {
+        let mut metadata = with_shared_libs(&[]);
+        metadata.system_libs = vec!["pthread".to_string(), "stdc++".to_string(), "m".to_string()];
+        assert_spec_golden("subpackages", true, &metadata)
+    }
+
+    #[test]
+    fn clear_spec_drops_conan_from_shared_build_requires() -> Result<()> {
+        let project = tempdir()?;
+        fs::create_dir_all(project.path().join("rpm"))?;
+        let spec_path = project.path().join("rpm/app.spec");
+        fs::write(
+            &spec_path,
+            "Name: app\nBuildRequires:  cmake, conan >= 2.0\nBuildRequires: conan\n\n\
+             %description\napp\n\n%build\n%cmake\n\n%install\n%make_install\n\n%files\n%{_bindir}/%{name}\n",
+        )?;
+
+        let spec = SpecConfig {
+            path: PathBuf::from("rpm/app.spec"),
+            packages: Vec::new(),
+        };
+        update_spec_clear(project.path(), &spec, &with_shared_libs(&[]))?;
+
+        let spec = fs::read_to_string(&spec_path)?;
+        assert!(spec.contains("BuildRequires:  cmake\n\n%description"));
+        assert!(
+            !spec
+                .lines()
+                .any(|line| line.starts_with("BuildRequires") && line.contains("conan"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn resolves_explicit_and_configured_specs() -> Result<()> {
+        let project = tempdir()?;
+        fs::create_dir_all(project.path().join("rpm"))?;
+        for name in ["app.spec", "app-tests.spec"] {
+            fs::write(project.path().join("rpm").join(name), "Name: app\n")?;
+        }
+
+        let error = resolve_specs(project.path(), &[], &[]).unwrap_err();
+        assert!(error.to_string().contains("--spec"));
+
+        let configured = vec![SpecConfig {
+            path: PathBuf::from("rpm/app-tests.spec"),
+            packages: vec!["gtest".to_string()],
+        }];
+        assert_eq!(resolve_specs(project.path(), &[], &configured)?, configured);
+
+        let explicit = [project.path().join("rpm/app-tests.spec")];
+        assert_eq!(
+            resolve_specs(project.path(), &explicit, &configured)?,
+            configured
+        );
+
+        let missing = [PathBuf::from("rpm/other.spec")];
+        assert!(resolve_specs(project.path(), &missing, &configured).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn spec_with_package_subset_packages_only_its_libraries() -> Result<()> {
+        let project = tempdir()?;
+        fs::create_dir_all(project.path().join("rpm"))?;
+        let text = "Name: app-tests\n\n%description\ntests\n\n%build\n%cmake\n\n\
+                    %install\n%make_install\n\n%files\n%{_bindir}/%{name}\n";
+        fs::write(project.path().join("rpm/app-tests.spec"), text)?;
+
+        let mut metadata = with_shared_libs(&["libgtest.*", "libssl.*", "libz.*"]);
+        metadata.package_lib_patterns = BTreeMap::from([
+            ("gtest".to_string(), vec!["libgtest.*".to_string()]),
+            ("openssl".to_string(), vec!["libssl.*".to_string()]),
+            ("zlib".to_string(), vec!["libz.*".to_string()]),
+        ]);
+        // Транзитивная зависимость пакета из `packages` упаковывается вместе с ним
+        metadata.package_dependencies = BTreeMap::from([
+            ("gtest".to_string(), vec!["zlib".to_string()]),
+            ("openssl".to_string(), vec!["zlib".to_string()]),
+        ]);
+        metadata.system_libs = vec!["atomic".to_string(), "stdc++".to_string()];
+        metadata.package_system_libs = BTreeMap::from([
+            ("gtest".to_string(), vec!["stdc++".to_string()]),
+            ("openssl".to_string(), vec!["atomic".to_string()]),
+        ]);
+        let spec = SpecConfig {
+            path: PathBuf::from("rpm/app-tests.spec"),
+            packages: vec!["gtest".to_string()],
+        };
+        update_spec_clear(project.path(), &spec, &metadata)?;
+
+        let spec_path = project.path().join(&spec.path);
+        assert_eq!(
+            read_spec_define(&spec_path, "__requires_exclude")?.as_deref(),
+            Some("^(libgtest.*|libz.*)$")
+        );
+        let content = fs::read_to_string(&spec_path)?;
+        assert!(content.contains("  case \"$package\" in gtest|zlib) ;; *) continue ;; esac\n"));
+        assert!(content.contains("Requires:       libstdc++"));
+        assert!(!content.contains("libatomic"));
+        Ok(())
+    }
+
+    #[test]
+    fn files_block_skips_directories_already_packaged() -> Result<()> {
+        let spec = SpecDocument::parse(
+            "Name: app\n\n%files\n%defattr(-,root,root,-)\n%attr(755,root,root) %{_datadir}/app/*\n",
+        );
+        let mut updated = spec.clone();
+        updated.upsert_files_block(&[SPEC_LIB_DIR, SPEC_LICENSES_DIR])?;
+        assert_eq!(updated.render(), spec.render());
+
+        let mut partial = SpecDocument::parse(
+            "Name: app\n\n%files\n%dir %{_datadir}/%{name}\n%{_datadir}/%{name}/licenses\n",
+        );
+        partial.upsert_files_block(&[SPEC_LIB_DIR, SPEC_LICENSES_DIR])?;
+        assert!(partial.render().ends_with(
+            "\n\n# >>> aurora-conan-cli:files:begin\n%{_datadir}/%{name}/lib\n# <<< aurora-conan-cli:files:end\n"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn files_layout_conflicts_fail_before_spec_is_written() -> Result<()> {
+        let project = tempdir()?;
+        fs::create_dir_all(project.path().join("rpm"))?;
+        let spec_path = project.path().join("rpm/app.spec");
+        let text = "Name: app\nBuildArch: noarch\n\n%description\napp\n\n%build\n\n%install\n\n\
+                    %files\n%{_datadir}/%{name}\n%exclude %{_datadir}/%{name}/lib\n";
+        fs::write(&spec_path, text)?;
+        let spec = SpecConfig {
+            path: PathBuf::from("rpm/app.spec"),
+            packages: Vec::new(),
+        };
+
+        let error = update_spec_clear(project.path(), &spec, &with_shared_libs(&[])).unwrap_err();
+        let message = format!("{error:#}");
+        assert!(message.contains("BuildArch: noarch"), "{message}");
+        assert!(
+            message.contains("%exclude %{_datadir}/%{name}/lib"),
+            "{message}"
+        );
+        assert_eq!(fs::read_to_string(&spec_path)?, text);
+
+        fs::write(&spec_path, "Name: app\n\n%build\n\n%install\n")?;
+        assert!(check_spec_layout(project.path(), &spec, false).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn project_table_extends_system_lib_requires() -> Result<()> {
+        let project = tempdir()?;
+        fs::create_dir_all(project.path().join("thirdparty/aurora"))?;
+        fs::write(
+            project
+                .path()
+                .join("thirdparty/aurora/system-lib-requires.txt"),
+            "# проектные записи\nusb-1.0 libusb1\nz !\n",
+        )?;
+
+        let libs: Vec<String> = ["dl", "usb-1.0", "z", "stdc++", "gcc_s", "stdc++", "foo"]
+            .iter()
+            .map(|lib| lib.to_string())
+            .collect();
+        let resolved = resolve_system_lib_requires(project.path(), &libs)?;
+        assert_eq!(resolved.requires, vec!["libgcc", "libstdc++", "libusb1"]);
+        assert_eq!(resolved.glibc, vec!["dl"]);
+        assert_eq!(resolved.unavailable, vec!["z"]);
+        assert_eq!(resolved.unmapped, vec!["foo"]);
+        Ok(())
+    }
 }
@@ -0,0 +1,488 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::clear_store;
+use crate::model::PackageLicense;
+
+const SPDX_LICENSES: &str = include_str!("../data/spdx-licenses.txt");
+pub const NOTICES_FILE: &str = "THIRD-PARTY-NOTICES.txt";
+pub const POLICY_FILE: &str = "license-policy.txt";
+/// Выражение для пакета, рецепт которого не задаёт `license` или недоступен.
+pub const NOASSERTION: &str = "NOASSERTION";
+
+pub fn notices_path(project_root: &Path) -> PathBuf {
+    clear_store::thirdparty_root(project_root).join(NOTICES_FILE)
+}
+
+pub fn policy_path(project_root: &Path) -> PathBuf {
+    clear_store::thirdparty_root(project_root).join(POLICY_FILE)
+}
+
+/// Лицензионная политика проекта: строки `allow <id>...` и `deny <id>...`,
+/// `*` в конце идентификатора задаёт префикс (`GPL-*`).
+///
+/// `NOASSERTION` и `LicenseRef-*` проходят только явный `allow`: неизвестную лицензию
+/// не проверить по `deny`, поэтому для неё нужен `allow` или исключение.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LicensePolicy {
+    /// Пустой список разрешает всё, что не запрещено.
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+impl LicensePolicy {
+    /// Лицензии выражения, из-за которых оно не проходит политику; пустой список — выражение разрешено.
+    /// Из альтернатив `OR` достаточно одной разрешённой, исключение после `WITH` не проверяется.
+    pub fn rejected(&self, expression: &str) -> Vec<String> {
+        let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+        let tokens: Vec<&str> = spaced.split_whitespace().collect();
+        let mut position = 0;
+        let mut rejected = self.rejected_or(&tokens, &mut position);
+        rejected.sort();
+        rejected.dedup();
+        rejected
+    }
+
+    fn rejected_or(&self, tokens: &[&str], position: &mut usize) -> Vec<String> {
+        let mut branches = vec![self.rejected_and(tokens, position)];
+        while tokens
+            .get(*position)
+            .is_some_and(|token| token.eq_ignore_ascii_case("OR"))
+        {
+            *position += 1;
+            branches.push(self.rejected_and(tokens, position));
+        }
+        if branches.iter().any(Vec::is_empty) {
+            Vec::new()
+        } else {
+            branches.concat()
+        }
+    }
+
+    fn rejected_and(&self, tokens: &[&str], position: &mut usize) -> Vec<String> {
+        let mut rejected = self.rejected_term(tokens, position);
+        while tokens
+            .get(*position)
+            .is_some_and(|token| token.eq_ignore_ascii_case("AND"))
+        {
+            *position += 1;
+            rejected.extend(self.rejected_term(tokens, position));
+        }
+        rejected
+    }
+
+    fn rejected_term(&self, tokens: &[&str], position: &mut usize) -> Vec<String> {
+        let Some(token) = tokens.get(*position) else {
+            return Vec::new();
+        };
+        *position += 1;
+        if *token == "(" {
+            let rejected = self.rejected_or(tokens, position);
+            if tokens.get(*position) == Some(&")") {
+                *position += 1;
+            }
+            return rejected;
+        }
+        if tokens
+            .get(*position)
+            .is_some_and(|next| next.eq_ignore_ascii_case("WITH"))
+        {
+            *position += 2;
+        }
+        if self.permits(token) {
+            Vec::new()
+        } else {
+            vec![token.to_string()]
+        }
+    }
+
+    fn permits(&self, id: &str) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => id
+                .to_ascii_lowercase()
+                .starts_with(&prefix.to_ascii_lowercase()),
+            None => pattern.eq_ignore_ascii_case(id),
+        };
+        if self.deny.iter().any(matches) {
+            return false;
+        }
+        if is_unknown_license(id) {
+            return self.allow.iter().any(matches);
+        }
+        self.allow.is_empty() || self.allow.iter().any(matches)
+    }
+}
+
+/// Лицензия, которой нет в списке SPDX: рецепт без `license` или нестандартное значение.
+fn is_unknown_license(id: &str) -> bool {
+    id.eq_ignore_ascii_case(NOASSERTION) || id.starts_with("LicenseRef-")
+}
+
+/// Читает `thirdparty/aurora/license-policy.txt`; без файла политика не действует.
+pub fn load_policy(project_root: &Path) -> Result<Option<LicensePolicy>> {
+    let path = policy_path(project_root);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)
+        .with_context(|| format!("Не удалось прочитать {}", path.display()))?;
+    parse_policy(&text)
+        .map(Some)
+        .with_context(|| format!("Некорректная лицензионная политика {}", path.display()))
+}
+
+fn parse_policy(text: &str) -> Result<LicensePolicy> {
+    let table = spdx_table();
+    let mut policy = LicensePolicy::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let list = match words.next() {
+            Some("allow") => &mut policy.allow,
+            Some("deny") => &mut policy.deny,
+            Some(other) => {
+                return Err(anyhow!(
+                    "строка {}: неизвестная директива '{}', ожидается allow или deny",
+                    index + 1,
+                    other
+                ));
+            }
+            None => continue,
+        };
+        // Синонимы приводим к тем же идентификаторам, что и лицензии рецептов
+        list.extend(words.map(|word| {
+            if word.ends_with('*') || word == NOASSERTION {
+                word.to_string()
+            } else {
+                canonical_id(word, &table)
+            }
+        }));
+    }
+    Ok(policy)
+}
+
+/// SPDX-выражение по атрибуту `license` рецепта: элементы кортежа объединяются через `AND`,
+/// синонимы приводятся к идентификаторам SPDX, неизвестные значения — к `LicenseRef-<значение>`.
+pub fn spdx_expression(licenses: &[String]) -> String {
+    let table = spdx_table();
+    let parts: Vec<String> = licenses
+        .iter()
+        .map(|license| canonical_expression(license, &table))
+        .filter(|expression| !expression.is_empty())
+        .collect();
+    match parts.len() {
+        0 => NOASSERTION.to_string(),
+        1 => parts[0].clone(),
+        _ => parts
+            .iter()
+            .map(|part| {
+                if part.contains(' ') {
+                    format!("({part})")
+                } else {
+                    part.clone()
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" AND "),
+    }
+}
+
+/// Синоним в нижнем регистре -> идентификатор SPDX.
+fn spdx_table() -> HashMap<String, String> {
+    let mut table = HashMap::new();
+    for line in SPDX_LICENSES.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut words = line.split_whitespace();
+        let Some(id) = words.next() else {
+            continue;
+        };
+        table.insert(id.to_ascii_lowercase(), id.to_string());
+        for alias in words {
+            table.insert(alias.to_ascii_lowercase(), id.to_string());
+        }
+    }
+    table
+}
+
+fn canonical_expression(text: &str, table: &HashMap<String, String>) -> String {
+    let spaced = text
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .replace(',', " AND ");
+    let is_operator = |word: &str| {
+        matches!(
+            word.to_ascii_uppercase().as_str(),
+            "AND" | "OR" | "WITH" | "(" | ")"
+        )
+    };
+    // Значение без операторов — одна лицензия, даже если в нём есть пробелы: `Apache 2.0`
+    if !spaced.split_whitespace().any(is_operator) {
+        let text = text.split_whitespace().collect::<Vec<_>>().join("-");
+        return if text.is_empty() {
+            text
+        } else {
+            canonical_id(&text, table)
+        };
+    }
+    let mut tokens = Vec::new();
+    let mut after_with = false;
+    for word in spaced.split_whitespace() {
+        let upper = word.to_ascii_uppercase();
+        let token = match upper.as_str() {
+            "AND" | "OR" | "WITH" => upper.clone(),
+            "(" | ")" => word.to_string(),
+            // Исключения вроде `Classpath-exception-2.0` оставляем как есть
+            _ if after_with => word.to_string(),
+            _ => canonical_id(word, table),
+        };
+        after_with = upper == "WITH";
+        tokens.push(token);
+    }
+    tokens.join(" ").replace("( ", "(").replace(" )", ")")
+}
+
+fn canonical_id(word: &str, table: &HashMap<String, String>) -> String {
+    if let Some(id) = table.get(&word.to_ascii_lowercase()) {
+        return id.clone();
+    }
+    if word.starts_with("LicenseRef-") {
+        return word.to_string();
+    }
+    let sanitized: String = word
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("LicenseRef-{sanitized}")
+}
+
+/// Файлы `licenses/` пакета из clear-store (первая архитектура, где они есть).
+pub fn package_license_files(
+    project_root: &Path,
+    name: &str,
+    version: &str,
+) -> Result<Vec<PathBuf>> {
+    let Some(licenses_dir) = clear_store::supported_arches()
+        .iter()
+        .map(|arch| clear_store::package_root(project_root, arch, name, version).join("licenses"))
+        .find(|dir| dir.is_dir())
+    else {
+        return Ok(Vec::new());
+    };
+
+    let mut out = Vec::new();
+    let mut pending = vec![licenses_dir];
+    while let Some(dir) = pending.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in
+            fs::read_dir(&dir).with_context(|| format!("Не удалось прочитать {}", dir.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.is_file() {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// Пишет `thirdparty/aurora/THIRD-PARTY-NOTICES.txt`: SPDX-выражение и тексты лицензий пакетов.
+pub fn write_notices(project_root: &Path, packages: &[PackageLicense]) -> Result<PathBuf> {
+    let mut packages = packages.to_vec();
+    packages.sort_by(|a, b| a.name.cmp(&b.name).then(a.version.cmp(&b.version)));
+
+    let rule = "=".repeat(80);
+    let mut body = String::from(
+        "THIRD-PARTY SOFTWARE NOTICES\n\
+         Generated by aurora-conan-cli from thirdparty/aurora; do not edit.\n",
+    );
+    if packages.is_empty() {
+        body.push_str("\nNo third-party packages are bundled.\n");
+    }
+    for package in &packages {
+        body.push_str(&format!(
+            "\n{rule}\n{} {}\nSPDX-License-Identifier: {}\n{rule}\n",
+            package.name, package.version, package.spdx
+        ));
+        let files = package_license_files(project_root, &package.name, &package.version)?;
+        if files.is_empty() {
+            body.push_str("\n(the package ships no license texts)\n");
+        }
+        for file in files {
+            let text = fs::read(&file)
+                .with_context(|| format!("Не удалось прочитать {}", file.display()))?;
+            let relative = file
+                .components()
+                .rev()
+                .take_while(|component| component.as_os_str() != "licenses")
+                .collect::<Vec<_>>();
+            let relative = relative
+                .iter()
+                .rev()
+                .map(|component| component.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            body.push_str(&format!("\n----- licenses/{relative} -----\n"));
+            body.push_str(String::from_utf8_lossy(&text).trim_end());
+            body.push('\n');
+        }
+    }
+
+    let path = notices_path(project_root);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Не удалось создать {}", parent.display()))?;
+    }
+    fs::write(&path, body).with_context(|| format!("Не удалось записать {}", path.display()))?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use super::{notices_path, parse_policy, spdx_expression, write_notices};
+    use crate::clear_store;
+    use crate::model::PackageLicense;
+
+    fn licenses(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn normalizes_recipe_licenses_to_spdx() {
+        assert_eq!(spdx_expression(&licenses(&["MIT"])), "MIT");
+        assert_eq!(spdx_expression(&licenses(&["zlib"])), "Zlib");
+        assert_eq!(
+            spdx_expression(&licenses(&["Apache-2.0", "BSD-3-Clause"])),
+            "Apache-2.0 AND BSD-3-Clause"
+        );
+        assert_eq!(
+            spdx_expression(&licenses(&["LGPL-2.1+ OR mpl-2.0", "ISC"])),
+            "(LGPL-2.1-or-later OR MPL-2.0) AND ISC"
+        );
+        assert_eq!(
+            spdx_expression(&licenses(&["GPL-2.0 with Classpath-exception-2.0"])),
+            "GPL-2.0-only WITH Classpath-exception-2.0"
+        );
+        assert_eq!(spdx_expression(&licenses(&["Apache 2.0"])), "Apache-2.0");
+        assert_eq!(
+            spdx_expression(&licenses(&["Proprietary EULA"])),
+            "LicenseRef-Proprietary-EULA"
+        );
+        assert_eq!(spdx_expression(&[]), "NOASSERTION");
+    }
+
+    #[test]
+    fn evaluates_expressions_against_license_policy() -> Result<()> {
+        let deny_gpl = parse_policy("# юристы\ndeny GPL-* AGPL-*\n")?;
+        assert_eq!(deny_gpl.rejected("MIT"), Vec::<String>::new());
+        assert_eq!(deny_gpl.rejected("LGPL-2.1-or-later"), Vec::<String>::new());
+        assert_eq!(
+            deny_gpl.rejected("GPL-2.0-or-later"),
+            vec!["GPL-2.0-or-later"]
+        );
+        assert_eq!(
+            deny_gpl.rejected("GPL-2.0-only OR MIT"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            deny_gpl.rejected("(GPL-3.0-only OR AGPL-3.0-only) AND MIT"),
+            vec!["AGPL-3.0-only", "GPL-3.0-only"]
+        );
+        assert_eq!(
+            deny_gpl.rejected("GPL-2.0-only WITH Classpath-exception-2.0"),
+            vec!["GPL-2.0-only"]
+        );
+        // Неизвестные лицензии не проходят политику из одних запретов
+        assert_eq!(deny_gpl.rejected("NOASSERTION"), vec!["NOASSERTION"]);
+        assert_eq!(
+            deny_gpl.rejected("LicenseRef-Proprietary"),
+            vec!["LicenseRef-Proprietary"]
+        );
+        assert_eq!(
+            deny_gpl.rejected("LicenseRef-Proprietary OR MIT"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            deny_gpl.rejected("MIT AND LicenseRef-Proprietary"),
+            vec!["LicenseRef-Proprietary"]
+        );
+        let allow_unknown = parse_policy("deny GPL-*\nallow NOASSERTION LicenseRef-Acme-*\n")?;
+        assert_eq!(allow_unknown.rejected("NOASSERTION"), Vec::<String>::new());
+        assert_eq!(
+            allow_unknown.rejected("LicenseRef-Acme-EULA"),
+            Vec::<String>::new()
+        );
+        assert_eq!(
+            allow_unknown.rejected("LicenseRef-Other"),
+            vec!["LicenseRef-Other"]
+        );
+
+        let allow_list = parse_policy("allow MIT apache2 BSD-*\n")?;
+        assert_eq!(
+            allow_list.rejected("Apache-2.0 AND BSD-3-Clause"),
+            Vec::<String>::new()
+        );
+        assert_eq!(allow_list.rejected("Zlib"), vec!["Zlib"]);
+        assert_eq!(allow_list.rejected("NOASSERTION"), vec!["NOASSERTION"]);
+
+        let err = parse_policy("forbid GPL-3.0\n").expect_err("unknown directive");
+        assert!(err.to_string().contains("строка 1"));
+        Ok(())
+    }
+
+    #[test]
+    fn writes_notices_with_license_texts() -> Result<()> {
+        let dir = tempdir()?;
+        let prefix = clear_store::package_root(dir.path(), "armv8", "zlib", "1.3.1");
+        fs::create_dir_all(prefix.join("licenses"))?;
+        fs::write(prefix.join("licenses/LICENSE"), "zlib license text\n")?;
+
+        write_notices(
+            dir.path(),
+            &[
+                PackageLicense {
+                    name: "zlib".to_string(),
+                    version: "1.3.1".to_string(),
+                    spdx: "Zlib".to_string(),
+                },
+                PackageLicense {
+                    name: "ms-gsl".to_string(),
+                    version: "4.0.0".to_string(),
+                    spdx: "MIT".to_string(),
+                },
+            ],
+        )?;
+
+        let notices = fs::read_to_string(notices_path(dir.path()))?;
+        let gsl = notices.find("ms-gsl 4.0.0").expect("ms-gsl section");
+        let zlib = notices.find("zlib 1.3.1").expect("zlib section");
+        assert!(gsl < zlib);
+        assert!(notices.contains("SPDX-License-Identifier: Zlib\n"));
+        assert!(notices.contains("----- licenses/LICENSE -----\nzlib license text\n"));
+        assert!(notices.contains("(the package ships no license texts)"));
+        Ok(())
+    }
+}
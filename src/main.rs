@@ -1,22 +1,38 @@
 mod app;
+mod audit;
 mod clear_store;
 mod conan;
+mod elf;
 mod files;
+mod licenses;
 mod mode;
 mod model;
+mod policy;
+mod recipe;
+mod sbom;
+mod validate;
 
 use std::env;
+use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use clap::{Parser, Subcommand};
 
 use crate::app::CliCommand;
 use crate::conan::CliConanProvider;
+use crate::model::{LinkVisibility, Linkage, TargetAssignment};
+use crate::sbom::SbomFormat;
 
 #[derive(Parser)]
 #[command(name = "aurora-conan-cli")]
 #[command(about = "CLI для управления Conan зависимостями в AuroraOS Qt проектах")]
 struct Cli {
+    /// .spec для обновления вместо настроенных в проекте (можно указать несколько раз).
+    /// В `init`/`init-clear` сохраняется в настройки проекта, в `add` — записывает пакет
+    /// в `packages` этих .spec.
+    #[arg(long = "spec", global = true)]
+    specs: Vec<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -33,11 +49,35 @@ enum Commands {
     Add {
         dependency: String,
         version: Option<String>,
+        /// CMake-цель для линковки (можно указать несколько раз), `<name>[:public|:private]`.
+        /// По умолчанию `${PROJECT_NAME}`.
+        #[arg(long = "target")]
+        targets: Vec<String>,
+        /// Линковать как PUBLIC вместо PRIVATE цели из `--target` без явной видимости.
+        #[arg(long, requires = "targets")]
+        public: bool,
+        /// Какие бинарники ставить в clear-режиме, если опубликованы и shared, и static.
+        #[arg(long, value_parser = ["shared", "static"])]
+        linkage: Option<String>,
+        /// Поставить как инструмент сборки (protoc, flatc): бинарник под архитектуру хоста
+        /// в thirdparty/aurora/host, путь — в CMake-переменной AURORA_TOOL_<NAME>.
+        #[arg(long, conflicts_with_all = ["targets", "linkage"])]
+        tool: bool,
     },
 
     /// Удаляет зависимость из conanfile.py и пересчитывает CMake/.spec.
     Remove { dependency: String },
 
+    /// Закрепляет версию транзитивного пакета вместо запрошенных рецептами (override=True).
+    Override {
+        package: String,
+        #[arg(required_unless_present = "remove")]
+        version: Option<String>,
+        /// Снять переопределение.
+        #[arg(long, conflicts_with = "version")]
+        remove: bool,
+    },
+
     /// Показывает список доступных версий пакета.
     Search { dependency: String },
 
@@ -46,12 +86,57 @@ enum Commands {
 
     /// Показывает итоговый список зависимостей пакета без использования conan.
     Deps { dependency: String, version: String },
+
+    /// Проверяет поставляемые библиотеки и .spec по правилам валидатора магазина.
+    Validate {
+        /// Файл со списком разрешённых системных библиотек вместо встроенного.
+        #[arg(long)]
+        allowed_libs: Option<PathBuf>,
+    },
+
+    /// Показывает лицензии зависимостей и пишет thirdparty/aurora/THIRD-PARTY-NOTICES.txt.
+    Licenses,
+
+    /// Разрешает пакет вопреки лицензионной политике; обоснование хранится в манифесте или conanfile.py.
+    Waive {
+        package: String,
+        #[arg(long)]
+        reason: String,
+    },
+
+    /// Снимает исключение пакета из лицензионной политики.
+    Unwaive { package: String },
+
+    /// Выводит SBOM проекта: пакеты графа с ревизиями, бинарниками, лицензиями и зависимостями.
+    Sbom {
+        #[arg(long, value_parser = ["cyclonedx", "spdx"])]
+        format: String,
+        /// Файл для записи вместо stdout.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Проверяет пакеты графа по локальной базе уязвимостей OSV; при находках завершается с кодом 3.
+    Audit {
+        /// Каталог с JSON-записями OSV; по умолчанию AURORA_CONAN_OSV_DIR.
+        #[arg(long = "db")]
+        database: Option<PathBuf>,
+    },
 }
 
 fn main() {
     if let Err(error) = run_main() {
         eprintln!("Ошибка: {error:#}");
-        std::process::exit(1);
+        std::process::exit(exit_code(&error));
+    }
+}
+
+/// Код завершения по ошибке: находки `audit` — отдельный код, остальное — 1.
+fn exit_code(error: &anyhow::Error) -> i32 {
+    if error.downcast_ref::<audit::FindingsError>().is_some() {
+        audit::FINDINGS_EXIT_CODE
+    } else {
+        1
     }
 }
 
@@ -66,11 +151,23 @@ fn run_main() -> Result<()> {
         Commands::Add {
             dependency,
             version,
+            targets,
+            public,
+            linkage,
+            tool,
         } => CliCommand::Add {
             dependency,
             version,
+            targets: target_assignments(targets, public)?,
+            linkage: linkage.as_deref().and_then(Linkage::parse),
+            tool,
         },
         Commands::Remove { dependency } => CliCommand::Remove { dependency },
+        Commands::Override {
+            package,
+            version,
+            remove: _,
+        } => CliCommand::Override { package, version },
         Commands::Search { dependency } => CliCommand::Search { dependency },
         Commands::Download {
             dependency,
@@ -86,7 +183,117 @@ fn run_main() -> Result<()> {
             dependency,
             version,
         },
+        Commands::Validate { allowed_libs } => CliCommand::Validate { allowed_libs },
+        Commands::Licenses => CliCommand::Licenses,
+        Commands::Waive { package, reason } => CliCommand::Waive { package, reason },
+        Commands::Unwaive { package } => CliCommand::Unwaive { package },
+        Commands::Sbom { format, output } => CliCommand::Sbom {
+            format: SbomFormat::parse(&format).unwrap_or(SbomFormat::CycloneDx),
+            output,
+        },
+        Commands::Audit { database } => CliCommand::Audit { database },
     };
 
-    app::run(&provider, &project_root, command)
+    app::run(&provider, &project_root, &cli.specs, command)
+}
+
+/// Привязки `--target <name>[:public|:private]`; цели без суффикса получают видимость `--public`.
+fn target_assignments(targets: Vec<String>, public: bool) -> Result<Vec<TargetAssignment>> {
+    let default = if public {
+        LinkVisibility::Public
+    } else {
+        LinkVisibility::Private
+    };
+    targets
+        .into_iter()
+        .map(|value| match value.rsplit_once(':') {
+            Some((target, suffix)) => {
+                let visibility = LinkVisibility::parse(suffix).ok_or_else(|| {
+                    anyhow!("Неизвестная видимость '{suffix}' цели {target}: ожидается public или private")
+                })?;
+                Ok(TargetAssignment {
+                    target: target.to_string(),
+                    visibility,
+                })
+            }
+            None => Ok(TargetAssignment {
+                target: value,
+                visibility: default,
+            }),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use anyhow::{Result, anyhow};
+    use clap::Parser;
+
+    use super::{Cli, Commands, exit_code, target_assignments};
+    use crate::audit::{FINDINGS_EXIT_CODE, FindingsError};
+    use crate::model::LinkVisibility;
+
+    #[test]
+    fn parses_visibility_per_target() -> Result<()> {
+        let cli = Cli::try_parse_from([
+            "aurora-conan-cli",
+            "add",
+            "zlib",
+            "--target",
+            "core",
+            "--target",
+            "app:private",
+            "--public",
+        ])?;
+        let Commands::Add {
+            targets, public, ..
+        } = cli.command
+        else {
+            panic!("expected add");
+        };
+
+        let assignments = target_assignments(targets, public)?;
+        let visibility: Vec<_> = assignments
+            .iter()
+            .map(|assignment| (assignment.target.as_str(), assignment.visibility))
+            .collect();
+        assert_eq!(
+            visibility,
+            vec![
+                ("core", LinkVisibility::Public),
+                ("app", LinkVisibility::Private)
+            ]
+        );
+
+        let assignments = target_assignments(vec!["core:PUBLIC".to_string()], false)?;
+        assert_eq!(assignments[0].visibility, LinkVisibility::Public);
+        let err = target_assignments(vec!["core:interface".to_string()], false)
+            .expect_err("unknown visibility");
+        assert!(err.to_string().contains("'interface'"));
+        Ok(())
+    }
+
+    #[test]
+    fn accepts_spec_after_add_subcommand() -> Result<()> {
+        let cli = Cli::try_parse_from([
+            "aurora-conan-cli",
+            "add",
+            "gtest",
+            "--spec",
+            "rpm/app-tests.spec",
+        ])?;
+        assert!(matches!(cli.command, Commands::Add { .. }));
+        assert_eq!(cli.specs, vec![PathBuf::from("rpm/app-tests.spec")]);
+        Ok(())
+    }
+
+    #[test]
+    fn audit_findings_exit_with_distinct_code() {
+        let findings = anyhow::Error::from(FindingsError { count: 2 }).context("audit");
+        assert_eq!(exit_code(&findings), FINDINGS_EXIT_CODE);
+        assert_ne!(FINDINGS_EXIT_CODE, 1);
+        assert_eq!(exit_code(&anyhow!("Файл не найден")), 1);
+    }
 }
@@ -4,6 +4,8 @@ use std::path::{Path, PathBuf};
 use anyhow::{Context, Result, anyhow};
 use serde::{Deserialize, Serialize};
 
+use crate::model::SpecConfig;
+
 const MODE_FILE: &str = ".aurora-conan-cli-mode.json";
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -17,11 +19,18 @@ pub enum ProjectMode {
 pub struct ModeState {
     pub version: u32,
     pub mode: ProjectMode,
+    /// .spec файлы проекта; пусто — единственный .spec из rpm/.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub specs: Vec<SpecConfig>,
 }
 
 impl ModeState {
     pub fn new(mode: ProjectMode) -> Self {
-        Self { version: 1, mode }
+        Self {
+            version: 1,
+            mode,
+            specs: Vec::new(),
+        }
     }
 }
 
@@ -29,20 +38,46 @@ pub fn mode_path(project_root: &Path) -> PathBuf {
     project_root.join(MODE_FILE)
 }
 
+/// Сохраняет режим; настроенные .spec из прежнего состояния сохраняются.
 pub fn save_mode(project_root: &Path, mode: ProjectMode) -> Result<()> {
-    let path = mode_path(project_root);
-    let payload = serde_json::to_string_pretty(&ModeState::new(mode))
-        .context("Не удалось сериализовать mode-state")?;
-    fs::write(&path, payload).with_context(|| format!("Не удалось записать {}", path.display()))
+    let mut state = ModeState::new(mode);
+    if mode_path(project_root).exists() {
+        state.specs = load_state(project_root)?.specs;
+    }
+    write_state(project_root, &state)
 }
 
 pub fn load_mode(project_root: &Path) -> Result<ProjectMode> {
+    Ok(load_state(project_root)?.mode)
+}
+
+/// Настроенные .spec проекта; без mode-state список пуст.
+pub fn load_specs(project_root: &Path) -> Result<Vec<SpecConfig>> {
+    if !mode_path(project_root).exists() {
+        return Ok(Vec::new());
+    }
+    Ok(load_state(project_root)?.specs)
+}
+
+pub fn save_specs(project_root: &Path, specs: &[SpecConfig]) -> Result<()> {
+    let mut state = load_state(project_root)?;
+    state.specs = specs.to_vec();
+    write_state(project_root, &state)
+}
+
+fn load_state(project_root: &Path) -> Result<ModeState> {
     let path = mode_path(project_root);
     let payload = fs::read_to_string(&path)
         .with_context(|| format!("Не найден mode-state {}", path.display()))?;
-    let state: ModeState = serde_json::from_str(&payload)
-        .with_context(|| format!("Повреждён mode-state {}", path.display()))?;
-    Ok(state.mode)
+    serde_json::from_str(&payload)
+        .with_context(|| format!("Повреждён mode-state {}", path.display()))
+}
+
+fn write_state(project_root: &Path, state: &ModeState) -> Result<()> {
+    let path = mode_path(project_root);
+    let payload =
+        serde_json::to_string_pretty(state).context("Не удалось сериализовать mode-state")?;
+    fs::write(&path, payload).with_context(|| format!("Не удалось записать {}", path.display()))
 }
 
 pub fn detect_mode(project_root: &Path, conanfile_name: &str) -> Result<ProjectMode> {
@@ -67,7 +102,8 @@ mod tests {
     use anyhow::Result;
     use tempfile::tempdir;
 
-    use super::{ProjectMode, detect_mode, load_mode, save_mode};
+    use super::{ProjectMode, detect_mode, load_mode, load_specs, save_mode, save_specs};
+    use crate::model::SpecConfig;
 
     #[test]
     fn saves_and_loads_mode_state() -> Result<()> {
@@ -87,4 +123,20 @@ mod tests {
         assert_eq!(mode, ProjectMode::Conan);
         Ok(())
     }
+
+    #[test]
+    fn switching_mode_keeps_configured_specs() -> Result<()> {
+        let dir = tempdir()?;
+        save_mode(dir.path(), ProjectMode::Conan)?;
+        let specs = vec![SpecConfig {
+            path: "rpm/app-tests.spec".into(),
+            packages: vec!["gtest".to_string()],
+        }];
+        save_specs(dir.path(), &specs)?;
+
+        save_mode(dir.path(), ProjectMode::Clear)?;
+        assert_eq!(load_mode(dir.path())?, ProjectMode::Clear);
+        assert_eq!(load_specs(dir.path())?, specs);
+        Ok(())
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::PathBuf;
 
 use serde::{Deserialize, Serialize};
@@ -15,14 +16,433 @@ impl ConanRef {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ProjectMetadata {
     pub direct_pkg_modules: Vec<String>,
     pub shared_lib_patterns: Vec<String>,
+    pub system_libs: Vec<String>,
+    /// Пакеты clear-режима в порядке линковки: зависимые раньше своих зависимостей.
+    pub vendored_packages: Vec<VendoredPackage>,
+    /// pkg-config модули каждой прямой зависимости.
+    pub package_modules: BTreeMap<String, Vec<String>>,
+    /// CMake-цели, к которым линкуется прямая зависимость; без записи — `${PROJECT_NAME}`.
+    pub target_assignments: BTreeMap<String, Vec<TargetAssignment>>,
+    /// Шаблоны `__requires_exclude` по пакетам (прямым и транзитивным).
+    pub package_lib_patterns: BTreeMap<String, Vec<String>>,
+    /// `system_libs` из `cpp_info` по пакетам (прямым и транзитивным).
+    pub package_system_libs: BTreeMap<String, Vec<String>>,
+    /// Зависимости пакетов графа по именам.
+    pub package_dependencies: BTreeMap<String, Vec<String>>,
+    /// Конструкции рецептов, которые не удалось разобрать: `<pkg>/<version>: line <N>: ...`.
+    pub recipe_warnings: Vec<String>,
+    /// Инструменты сборки из `thirdparty/aurora/host`; в RPM не попадают.
+    pub host_tools: Vec<HostTool>,
+    /// Лицензии поставляемых пакетов для THIRD-PARTY-NOTICES.
+    pub package_licenses: Vec<PackageLicense>,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+impl ProjectMetadata {
+    /// Пакеты вместе с их транзитивными зависимостями, в порядке имён.
+    pub fn package_closure(&self, packages: &[String]) -> Vec<String> {
+        dependency_closure(&self.package_dependencies, packages)
+    }
+
+    /// Срез метаданных для .spec, который упаковывает только перечисленные пакеты и их
+    /// транзитивные зависимости. Пустой список означает все пакеты проекта.
+    pub fn for_packages(&self, packages: &[String]) -> ProjectMetadata {
+        if packages.is_empty() {
+            return self.clone();
+        }
+        let packages = &self.package_closure(packages);
+
+        let mut shared_lib_patterns: Vec<String> = packages
+            .iter()
+            .filter_map(|name| self.package_lib_patterns.get(name))
+            .flatten()
+            .cloned()
+            .collect();
+        shared_lib_patterns.sort();
+        shared_lib_patterns.dedup();
+        let mut system_libs: Vec<String> = packages
+            .iter()
+            .filter_map(|name| self.package_system_libs.get(name))
+            .flatten()
+            .cloned()
+            .collect();
+        system_libs.sort();
+        system_libs.dedup();
+
+        ProjectMetadata {
+            shared_lib_patterns,
+            system_libs,
+            vendored_packages: self
+                .vendored_packages
+                .iter()
+                .filter(|package| packages.contains(&package.name))
+                .cloned()
+                .collect(),
+            package_lib_patterns: self
+                .package_lib_patterns
+                .iter()
+                .filter(|(name, _)| packages.contains(name))
+                .map(|(name, patterns)| (name.clone(), patterns.clone()))
+                .collect(),
+            package_system_libs: self
+                .package_system_libs
+                .iter()
+                .filter(|(name, _)| packages.contains(name))
+                .map(|(name, libs)| (name.clone(), libs.clone()))
+                .collect(),
+            ..self.clone()
+        }
+    }
+}
+
+/// Пакеты вместе с их транзитивными зависимостями по карте `dependencies`, в порядке имён.
+/// Пустой список означает все пакеты проекта и остаётся пустым.
+pub fn dependency_closure(
+    dependencies: &BTreeMap<String, Vec<String>>,
+    packages: &[String],
+) -> Vec<String> {
+    let mut closure = BTreeSet::new();
+    let mut queue = packages.to_vec();
+    while let Some(name) = queue.pop() {
+        if let Some(items) = dependencies.get(&name) {
+            queue.extend(
+                items
+                    .iter()
+                    .filter(|item| !closure.contains(*item))
+                    .cloned(),
+            );
+        }
+        closure.insert(name);
+    }
+    closure.into_iter().collect()
+}
+
+/// Зависимости поставленных пакетов по именам из их `requires` (`name/version`).
+pub fn package_dependencies(packages: &[LockedPackage]) -> BTreeMap<String, Vec<String>> {
+    packages
+        .iter()
+        .map(|package| {
+            let names = package
+                .requires
+                .iter()
+                .map(|raw| raw.split('/').next().unwrap_or(raw).to_string())
+                .collect();
+            (package.name.clone(), names)
+        })
+        .collect()
+}
+
+/// .spec проекта и пакеты, библиотеки которых он упаковывает.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecConfig {
+    /// Путь относительно корня проекта.
+    pub path: PathBuf,
+    /// Пустой список — все зависимости проекта.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<String>,
+}
+
+/// Видимость зависимости в `target_link_libraries`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum LinkVisibility {
+    Public,
+    #[default]
+    Private,
+}
+
+impl LinkVisibility {
+    pub fn as_cmake(self) -> &'static str {
+        match self {
+            LinkVisibility::Public => "PUBLIC",
+            LinkVisibility::Private => "PRIVATE",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_uppercase().as_str() {
+            "PUBLIC" => Some(LinkVisibility::Public),
+            "PRIVATE" => Some(LinkVisibility::Private),
+            _ => None,
+        }
+    }
+}
+
+/// Какие бинарники пакета ставить в clear-режиме, если опубликованы и shared, и static.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Linkage {
+    #[default]
+    Shared,
+    Static,
+}
+
+impl Linkage {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "shared" => Some(Linkage::Shared),
+            "static" => Some(Linkage::Static),
+            _ => None,
+        }
+    }
+
+    /// Значение опции `shared` бинарника с такой линковкой.
+    pub fn shared_option(self) -> bool {
+        self == Linkage::Shared
+    }
+}
+
+/// Привязка прямой зависимости к CMake-цели.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TargetAssignment {
+    pub target: String,
+    pub visibility: LinkVisibility,
+}
+
+/// Пакет из `thirdparty/aurora/<arch>/packages`: модули pkg-config и каталоги и библиотеки
+/// `cpp_info` для линковки без pkg-config. Каталоги — относительно корня пакета.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VendoredPackage {
+    pub name: String,
+    pub version: String,
+    pub pkg_modules: Vec<String>,
+    pub include_dirs: Vec<String>,
+    pub lib_dirs: Vec<String>,
+    pub libs: Vec<String>,
+}
+
+/// Исполняемый файл tool-пакета, запускаемый на машине сборки.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostTool {
+    /// Имя файла из `bin/`, по нему строится `AURORA_TOOL_<NAME>`.
+    pub name: String,
+    pub package: String,
+    /// Путь относительно корня проекта.
+    pub path: String,
+}
+
+/// Лицензия пакета по атрибуту `license` его рецепта.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageLicense {
+    pub name: String,
+    pub version: String,
+    /// SPDX-выражение; `NOASSERTION`, если рецепт лицензию не указывает.
+    pub spdx: String,
+}
+
+/// Компонент пакета из `cpp_info.components[...]`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComponentInfo {
+    pub name: String,
+    pub libs: Vec<String>,
+    pub system_libs: Vec<String>,
+    pub requires: Vec<String>,
+    pub pkg_config_name: Option<String>,
+    pub cmake_target_name: Option<String>,
+    pub build_info: CppBuildInfo,
+}
+
+/// Каталоги и флаги `cpp_info` корня пакета или компонента.
+///
+/// `None` у каталогов — значение не задано в рецепте, действуют умолчания Conan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CppBuildInfo {
+    pub includedirs: Option<Vec<String>>,
+    pub libdirs: Option<Vec<String>>,
+    pub bindirs: Option<Vec<String>>,
+    pub defines: Vec<String>,
+    pub cflags: Vec<String>,
+    pub cxxflags: Vec<String>,
+    pub sharedlinkflags: Vec<String>,
+    pub exelinkflags: Vec<String>,
+}
+
+impl CppBuildInfo {
+    pub const DEFAULT_INCLUDEDIRS: &[&str] = &["include"];
+    pub const DEFAULT_LIBDIRS: &[&str] = &["lib"];
+    pub const DEFAULT_BINDIRS: &[&str] = &["bin"];
+
+    pub fn includedirs(&self) -> Vec<String> {
+        dirs_or_default(&self.includedirs, Self::DEFAULT_INCLUDEDIRS)
+    }
+
+    pub fn libdirs(&self) -> Vec<String> {
+        dirs_or_default(&self.libdirs, Self::DEFAULT_LIBDIRS)
+    }
+
+    pub fn bindirs(&self) -> Vec<String> {
+        dirs_or_default(&self.bindirs, Self::DEFAULT_BINDIRS)
+    }
+}
+
+fn dirs_or_default(dirs: &Option<Vec<String>>, default: &[&str]) -> Vec<String> {
+    match dirs {
+        Some(dirs) => dirs.clone(),
+        None => default.iter().map(|dir| dir.to_string()).collect(),
+    }
+}
+
+/// Метаданные `package_info()` пакета, извлечённые из conanfile.py.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PackageCppInfo {
+    pub package_name: String,
+    pub libs: Vec<String>,
+    pub system_libs: Vec<String>,
+    pub pkg_config_name: Option<String>,
+    pub cmake_file_name: Option<String>,
+    pub cmake_target_name: Option<String>,
+    pub is_header_only: bool,
+    pub build_info: CppBuildInfo,
+    pub components: Vec<ComponentInfo>,
+}
+
+impl PackageCppInfo {
+    /// `system_libs` корня и всех компонентов без повторов.
+    pub fn all_system_libs(&self) -> Vec<String> {
+        let mut libs = self.system_libs.clone();
+        for lib in self
+            .components
+            .iter()
+            .flat_map(|component| &component.system_libs)
+        {
+            if !libs.contains(lib) {
+                libs.push(lib.clone());
+            }
+        }
+        libs
+    }
+
+    /// Библиотеки компонентов в порядке объявления, затем библиотеки корня, без повторов.
+    pub fn all_libs(&self) -> Vec<String> {
+        let mut libs: Vec<String> = Vec::new();
+        for lib in self
+            .components
+            .iter()
+            .flat_map(|component| &component.libs)
+            .chain(&self.libs)
+        {
+            if !libs.contains(lib) {
+                libs.push(lib.clone());
+            }
+        }
+        libs
+    }
+
+    /// `includedirs` корня и всех компонентов без повторов.
+    pub fn all_includedirs(&self) -> Vec<String> {
+        self.collect_dirs(CppBuildInfo::includedirs)
+    }
+
+    /// `libdirs` корня и всех компонентов без повторов.
+    pub fn all_libdirs(&self) -> Vec<String> {
+        self.collect_dirs(CppBuildInfo::libdirs)
+    }
+
+    fn collect_dirs(&self, dirs: fn(&CppBuildInfo) -> Vec<String>) -> Vec<String> {
+        let mut out = dirs(&self.build_info);
+        for dir in self
+            .components
+            .iter()
+            .flat_map(|component| dirs(&component.build_info))
+        {
+            if !out.contains(&dir) {
+                out.push(dir);
+            }
+        }
+        out
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct DownloadArtifact {
     pub arch: String,
+    /// Опция `shared` из conaninfo бинарника; `None`, если у рецепта её нет.
+    pub shared: Option<bool>,
     pub path: PathBuf,
+    pub origin: BinaryOrigin,
+}
+
+/// Откуда скачан бинарник: ссылка и ревизии Conan из пути в Artifactory.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryOrigin {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipe_revision: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package_revision: Option<String>,
+}
+
+impl BinaryOrigin {
+    /// Разбирает `.../<name>/<version>/_/<rrev>/package/<package_id>/<prev>/conan_package.tgz`.
+    pub fn from_url(url: &str) -> BinaryOrigin {
+        let segments: Vec<&str> = url
+            .split(['?', '#'])
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .collect();
+        let revisions = segments
+            .windows(5)
+            .rev()
+            .find(|window| window[0] == "_" && window[2] == "package");
+        BinaryOrigin {
+            url: Some(url.to_string()),
+            recipe_revision: revisions.map(|window| window[1].to_string()),
+            package_id: revisions.map(|window| window[3].to_string()),
+            package_revision: revisions.map(|window| window[4].to_string()),
+        }
+    }
+}
+
+/// Пакет, поставленный clear-синхронизацией: лицензия, прямые зависимости и бинарники.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub user: String,
+    /// SPDX-выражение; `NOASSERTION`, если рецепт лицензию не указывает.
+    pub license: String,
+    /// Прямые зависимости в виде `name/version`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub requires: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub binaries: Vec<LockedBinary>,
+}
+
+/// Бинарник пакета, распакованный в `thirdparty/aurora/<arch>`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedBinary {
+    pub arch: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shared: Option<bool>,
+    /// SHA-256 скачанного архива.
+    pub sha256: String,
+    #[serde(flatten)]
+    pub origin: BinaryOrigin,
+}
+
+/// Граф зависимостей пакета: найденные версии и подменённые политикой.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolvedGraph {
+    pub packages: Vec<ConanRef>,
+    pub overrides: Vec<AppliedOverride>,
+}
+
+/// Версия пакета, выбранная правилом вместо запрошенной рецептами.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AppliedOverride {
+    pub name: String,
+    pub version: String,
+    /// Файл или URL, из которого взято правило.
+    pub source: String,
+    /// Ограничения рецептов на пакет (`zlib/1.2.Z@aurora`).
+    pub requested: Vec<String>,
+    /// Ограничения, которым принудительная версия не удовлетворяет.
+    pub violated: Vec<String>,
 }
@@ -0,0 +1,220 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+
+use crate::conan;
+
+/// Путь или http(s)-URL политики организации вместо пользовательского файла.
+pub const POLICY_ENV: &str = "AURORA_CONAN_POLICY";
+const POLICY_FILE: &str = "policy.txt";
+
+/// Правила построения графа зависимостей: запрещённые пакеты и принудительные версии.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolutionPolicy {
+    pub denied: Vec<DeniedPackage>,
+    /// Версии по именам пакетов, которые выбираются вместо запрошенных рецептами.
+    pub forced: BTreeMap<String, ForcedVersion>,
+}
+
+/// Запрет всех версий пакета или версий по шаблону (`1.1.*`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeniedPackage {
+    pub name: String,
+    pub version: Option<String>,
+    pub reason: String,
+    /// Файл или URL, из которого взято правило.
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForcedVersion {
+    pub version: String,
+    /// Пользователь ссылки (`@<user>`), по умолчанию `aurora`.
+    pub user: String,
+    /// Файл или URL, из которого взято правило.
+    pub source: String,
+}
+
+impl ResolutionPolicy {
+    /// Запрет, под который попадает версия пакета.
+    pub fn denial(&self, name: &str, version: &str) -> Option<&DeniedPackage> {
+        self.denied.iter().find(|rule| {
+            rule.name == name
+                && rule
+                    .version
+                    .as_deref()
+                    .is_none_or(|pattern| match pattern.strip_suffix('*') {
+                        Some(prefix) => version.starts_with(prefix),
+                        None => version == pattern,
+                    })
+        })
+    }
+}
+
+impl DeniedPackage {
+    pub fn pattern(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{}/{}", self.name, version),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Политика организации из `AURORA_CONAN_POLICY` (путь относительно проекта или http(s)-URL),
+/// иначе из `~/.config/aurora-conan-cli/policy.txt`; без них — пустая политика.
+pub fn load_org_policy(project_root: &Path) -> Result<ResolutionPolicy> {
+    let reference = env::var(POLICY_ENV)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty());
+
+    let (text, source) = match reference {
+        Some(url) if url.starts_with("http://") || url.starts_with("https://") => {
+            let text = conan::http_get_text(&url)
+                .with_context(|| format!("Не удалось загрузить политику {url}"))?;
+            (text, url)
+        }
+        Some(path) => {
+            let path = project_root.join(path);
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("Не удалось прочитать политику {}", path.display()))?;
+            (text, path.display().to_string())
+        }
+        None => {
+            let Some(path) = user_policy_path().filter(|path| path.exists()) else {
+                return Ok(ResolutionPolicy::default());
+            };
+            let text = fs::read_to_string(&path)
+                .with_context(|| format!("Не удалось прочитать политику {}", path.display()))?;
+            (text, path.display().to_string())
+        }
+    };
+
+    parse_policy(&text, &source).with_context(|| format!("Некорректная политика {source}"))
+}
+
+fn user_policy_path() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join("aurora-conan-cli").join(POLICY_FILE))
+}
+
+/// Строки политики: `deny <pkg>[/<version>] [причина]` (`*` в конце версии задаёт префикс)
+/// и `force <pkg>/<version>[@<user>]`; `#` начинает комментарий.
+fn parse_policy(text: &str, source: &str) -> Result<ResolutionPolicy> {
+    let mut policy = ResolutionPolicy::default();
+    for (index, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (directive, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim_start();
+        let (reference, reason) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let reason = reason.trim();
+        let (name, version) = match reference.split_once('/') {
+            Some((name, version)) => (name, Some(version)),
+            None => (reference, None),
+        };
+        if name.is_empty() || version.is_some_and(str::is_empty) {
+            return Err(anyhow!(
+                "строка {}: ожидается {} <package>/<version>",
+                index + 1,
+                directive
+            ));
+        }
+
+        match directive {
+            "deny" => policy.denied.push(DeniedPackage {
+                name: name.to_string(),
+                version: version.map(ToString::to_string),
+                reason: reason.to_string(),
+                source: source.to_string(),
+            }),
+            "force" => {
+                let (version, user) = match version.and_then(|version| version.split_once('@')) {
+                    Some((version, user)) => (Some(version), user),
+                    None => (version, conan::DEFAULT_USER),
+                };
+                let Some(version) = version.filter(|version| !version.contains('*')) else {
+                    return Err(anyhow!(
+                        "строка {}: для force нужна точная версия пакета {}",
+                        index + 1,
+                        name
+                    ));
+                };
+                if version.is_empty() || user.is_empty() {
+                    return Err(anyhow!(
+                        "строка {}: ожидается force <package>/<version>[@<user>]",
+                        index + 1
+                    ));
+                }
+                let forced = ForcedVersion {
+                    version: version.to_string(),
+                    user: user.to_string(),
+                    source: source.to_string(),
+                };
+                if let Some(previous) = policy.forced.insert(name.to_string(), forced)
+                    && previous.version != version
+                {
+                    return Err(anyhow!(
+                        "строка {}: для пакета {} уже задана версия {}",
+                        index + 1,
+                        name,
+                        previous.version
+                    ));
+                }
+            }
+            other => {
+                return Err(anyhow!(
+                    "строка {}: неизвестная директива '{}', ожидается deny или force",
+                    index + 1,
+                    other
+                ));
+            }
+        }
+    }
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::parse_policy;
+
+    #[test]
+    fn parses_denied_packages_and_forced_versions() -> Result<()> {
+        let policy = parse_policy(
+            "# общая политика\n\
+             deny openssl/1.1.*  OpenSSL 1.1 снят с поддержки\n\
+             deny libressl\n\
+             force zlib/1.3.1\n\
+             force openssl/3.0.13@acme\n",
+            "policy.txt",
+        )?;
+
+        let denial = policy
+            .denial("openssl", "1.1.1w")
+            .expect("openssl 1.1 is denied");
+        assert_eq!(denial.pattern(), "openssl/1.1.*");
+        assert_eq!(denial.reason, "OpenSSL 1.1 снят с поддержки");
+        assert!(policy.denial("openssl", "3.0.13").is_none());
+        assert!(policy.denial("libressl", "3.8.2").is_some());
+        assert_eq!(policy.forced["zlib"].version, "1.3.1");
+        assert_eq!(policy.forced["zlib"].user, "aurora");
+        assert_eq!(policy.forced["zlib"].source, "policy.txt");
+        assert_eq!(policy.forced["openssl"].version, "3.0.13");
+        assert_eq!(policy.forced["openssl"].user, "acme");
+
+        let err = parse_policy("force zlib/1.*\n", "policy.txt").expect_err("pattern");
+        assert!(err.to_string().contains("строка 1"));
+        let err = parse_policy("pin zlib/1.3.1\n", "policy.txt").expect_err("directive");
+        assert!(err.to_string().contains("неизвестная директива 'pin'"));
+        Ok(())
+    }
+}
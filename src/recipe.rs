@@ -0,0 +1,2809 @@
+//! Разбор `conanfile.py` без интерпретатора Python.
+//!
+//! Токенизатор и разбор операторов покрывают подмножество Python, на котором пишут
+//! рецепты Conan. Обход исполняет `requirements()` и `package_info()`: переменные,
+//! циклы по спискам, comprehension, f-строки, вызовы вспомогательных методов рецепта
+//! и условия по `self.settings`/`self.options`, вычисленные для выбранного профиля.
+//! Всё, что вычислить не удалось, попадает в `unsupported` с номером строки.
+
+use std::collections::BTreeMap;
+use std::rc::Rc;
+
+use crate::model::{ComponentInfo, CppBuildInfo, PackageCppInfo};
+
+/// Списковые поля `cpp_info` и его компонентов.
+const CPP_INFO_LISTS: &[&str] = &[
+    "libs",
+    "system_libs",
+    "requires",
+    "includedirs",
+    "libdirs",
+    "bindirs",
+    "resdirs",
+    "builddirs",
+    "srcdirs",
+    "frameworkdirs",
+    "frameworks",
+    "defines",
+    "cflags",
+    "cxxflags",
+    "sharedlinkflags",
+    "exelinkflags",
+    "objects",
+];
+
+/// Атрибуты `self`, которые не влияют на метаданные пакета.
+const IGNORED_RECIPE_ATTRS: &[&str] = &[
+    "tool_requires",
+    "build_requires",
+    "test_requires",
+    "output",
+    "runenv_info",
+    "buildenv_info",
+    "env_info",
+    "user_info",
+    "conf_info",
+    "conf",
+];
+
+const BUILTINS: &[&str] = &[
+    "str",
+    "len",
+    "list",
+    "tuple",
+    "set",
+    "sorted",
+    "bool",
+    "int",
+    "is_msvc",
+    "is_msvc_static_runtime",
+    "is_apple_os",
+    "stdcpp_library",
+];
+
+const MAX_CALL_DEPTH: usize = 16;
+
+/// Settings и options, под которые вычисляются условия рецепта.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipeProfile {
+    pub settings: BTreeMap<String, String>,
+    /// Переопределения `default_options` рецепта.
+    pub options: BTreeMap<String, String>,
+}
+
+impl RecipeProfile {
+    /// Профиль сборки под ОС Аврора. Без `arch` условия по архитектуре не вычисляются.
+    pub fn aurora(arch: Option<&str>) -> Self {
+        let mut settings = BTreeMap::new();
+        settings.insert("os".to_string(), "Linux".to_string());
+        settings.insert("compiler".to_string(), "gcc".to_string());
+        settings.insert("compiler.libcxx".to_string(), "libstdc++11".to_string());
+        settings.insert("build_type".to_string(), "Release".to_string());
+        if let Some(arch) = arch {
+            settings.insert("arch".to_string(), arch.to_string());
+        }
+        Self {
+            settings,
+            options: BTreeMap::new(),
+        }
+    }
+
+    /// Тот же профиль для бинарника с опцией `shared`: от неё зависят `libs` и `system_libs`
+    /// во многих рецептах.
+    pub fn with_shared(mut self, shared: bool) -> Self {
+        let value = if shared { "True" } else { "False" };
+        self.options.insert("shared".to_string(), value.to_string());
+        self
+    }
+}
+
+/// Результат разбора рецепта.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecipeInfo {
+    pub cpp_info: PackageCppInfo,
+    /// Ссылки из `requires` рецепта и `self.requires(...)` в `requirements()`.
+    pub requires: Vec<String>,
+    /// Атрибут `license` рецепта: строка или кортеж строк.
+    pub license: Vec<String>,
+    /// Конструкции, которые не удалось понять: `line <N>: <причина>: <строка рецепта>`.
+    pub unsupported: Vec<String>,
+}
+
+/// Разбирает рецепт и вычисляет `requirements()`/`package_info()` для профиля.
+pub fn analyze_recipe(package_name: &str, conanfile: &str, profile: &RecipeProfile) -> RecipeInfo {
+    let mut walker = Walker::new(package_name, conanfile, profile);
+    match parse_module(conanfile) {
+        Ok(module) => {
+            walker.load_module(&module);
+            walker.collect_class_requires();
+            walker.run_method("requirements");
+            walker.run_method("package_info");
+        }
+        Err((line, reason)) => {
+            walker.line = line;
+            walker.note(reason);
+        }
+    }
+    walker.finish()
+}
+
+// ---------------------------------------------------------------------------
+// Токенизатор
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Name(String),
+    Number(String),
+    /// Текст строки после обработки escape-последовательностей и признак f-строки.
+    Str(String, bool),
+    Op(String),
+    Newline,
+    Indent,
+    Dedent,
+}
+
+const OPERATORS: &[&str] = &[
+    "**=", "//=", ">>=", "<<=", "...", "==", "!=", "<=", ">=", "+=", "-=", "*=", "/=", "%=", "&=",
+    "|=", "^=", "**", "//", "->", ":=", "<<", ">>", "+", "-", "*", "/", "%", "@", "&", "|", "^",
+    "~", "<", ">", "(", ")", "[", "]", "{", "}", ",", ":", ".", ";", "=",
+];
+
+fn tokenize(text: &str) -> Result<Vec<(Tok, usize)>, (usize, String)> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens: Vec<(Tok, usize)> = Vec::new();
+    let mut indents = vec![0usize];
+    let mut depth = 0usize;
+    let mut line = 1usize;
+    let mut at_line_start = true;
+    let mut i = 0usize;
+
+    while i < chars.len() {
+        if at_line_start && depth == 0 {
+            let mut width = 0usize;
+            while i < chars.len() && matches!(chars[i], ' ' | '\t' | '\x0c') {
+                width = if chars[i] == '\t' {
+                    (width / 8 + 1) * 8
+                } else {
+                    width + 1
+                };
+                i += 1;
+            }
+            if i >= chars.len() {
+                break;
+            }
+            if matches!(chars[i], '\n' | '\r' | '#') {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                if i < chars.len() {
+                    i += 1;
+                    line += 1;
+                }
+                continue;
+            }
+            at_line_start = false;
+            if width > *indents.last().unwrap_or(&0) {
+                indents.push(width);
+                tokens.push((Tok::Indent, line));
+            } else {
+                while width < *indents.last().unwrap_or(&0) {
+                    indents.pop();
+                    tokens.push((Tok::Dedent, line));
+                }
+                if width != *indents.last().unwrap_or(&0) {
+                    return Err((line, "inconsistent indentation".to_string()));
+                }
+            }
+        }
+
+        let c = chars[i];
+        match c {
+            ' ' | '\t' | '\r' | '\x0c' => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '\\' if chars.get(i + 1) == Some(&'\n')
+                || (chars.get(i + 1) == Some(&'\r') && chars.get(i + 2) == Some(&'\n')) =>
+            {
+                i += if chars[i + 1] == '\r' { 3 } else { 2 };
+                line += 1;
+            }
+            '\n' => {
+                if depth == 0 {
+                    tokens.push((Tok::Newline, line));
+                    at_line_start = true;
+                }
+                line += 1;
+                i += 1;
+            }
+            '"' | '\'' => {
+                let (token, next, lines) = read_string(&chars, i, "")
+                    .ok_or_else(|| (line, "unterminated string".to_string()))?;
+                tokens.push((token, line));
+                line += lines;
+                i = next;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect();
+                let is_prefix = matches!(
+                    word.to_ascii_lowercase().as_str(),
+                    "r" | "u" | "f" | "b" | "br" | "rb" | "fr" | "rf"
+                );
+                if is_prefix && matches!(chars.get(i), Some('"' | '\'')) {
+                    let (token, next, lines) = read_string(&chars, i, &word)
+                        .ok_or_else(|| (line, "unterminated string".to_string()))?;
+                    tokens.push((token, line));
+                    line += lines;
+                    i = next;
+                } else {
+                    tokens.push((Tok::Name(word), line));
+                }
+            }
+            c if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push((Tok::Number(chars[start..i].iter().collect()), line));
+            }
+            _ => {
+                let op = OPERATORS
+                    .iter()
+                    .find(|op| {
+                        op.chars()
+                            .enumerate()
+                            .all(|(offset, ch)| chars.get(i + offset) == Some(&ch))
+                    })
+                    .ok_or_else(|| (line, format!("unexpected character `{c}`")))?;
+                match *op {
+                    "(" | "[" | "{" => depth += 1,
+                    ")" | "]" | "}" => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                tokens.push((Tok::Op(op.to_string()), line));
+                i += op.chars().count();
+            }
+        }
+    }
+
+    if !matches!(tokens.last(), None | Some((Tok::Newline, _))) {
+        tokens.push((Tok::Newline, line));
+    }
+    while indents.len() > 1 {
+        indents.pop();
+        tokens.push((Tok::Dedent, line));
+    }
+    Ok(tokens)
+}
+
+/// Читает строковый литерал с позиции открывающей кавычки.
+/// Возвращает токен, позицию после литерала и число пройденных переводов строк.
+fn read_string(chars: &[char], start: usize, prefix: &str) -> Option<(Tok, usize, usize)> {
+    let prefix = prefix.to_ascii_lowercase();
+    let raw = prefix.contains('r');
+    let fstring = prefix.contains('f');
+    let quote = chars[start];
+    let triple = chars.get(start + 1) == Some(&quote) && chars.get(start + 2) == Some(&quote);
+    let mut i = start + if triple { 3 } else { 1 };
+    let mut value = String::new();
+    let mut lines = 0usize;
+
+    loop {
+        let c = *chars.get(i)?;
+        if c == quote
+            && (!triple || (chars.get(i + 1) == Some(&quote) && chars.get(i + 2) == Some(&quote)))
+        {
+            i += if triple { 3 } else { 1 };
+            return Some((Tok::Str(value, fstring), i, lines));
+        }
+        if c == '\n' {
+            if !triple {
+                return None;
+            }
+            lines += 1;
+        }
+        if c == '\\' {
+            let next = *chars.get(i + 1)?;
+            if next == '\n' {
+                lines += 1;
+                if raw {
+                    value.push('\\');
+                    value.push('\n');
+                }
+                i += 2;
+                continue;
+            }
+            if raw {
+                value.push('\\');
+                value.push(next);
+            } else {
+                match next {
+                    'n' => value.push('\n'),
+                    't' => value.push('\t'),
+                    '\\' | '\'' | '"' => value.push(next),
+                    _ => {
+                        value.push('\\');
+                        value.push(next);
+                    }
+                }
+            }
+            i += 2;
+            continue;
+        }
+        value.push(c);
+        i += 1;
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Синтаксическое дерево
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Const(Value),
+    /// Части строки, склеенные из соседних литералов; `true` — f-строка.
+    Str(Vec<(String, bool)>),
+    Name(String),
+    Attr(Box<Expr>, String),
+    Index(Box<Expr>, Box<Expr>),
+    Call(Box<Expr>, Vec<Expr>, Vec<(String, Expr)>),
+    List(Vec<Expr>),
+    Dict(Vec<(Expr, Expr)>),
+    Comprehension {
+        element: Box<Expr>,
+        targets: Vec<String>,
+        iter: Box<Expr>,
+        conditions: Vec<Expr>,
+    },
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(String, Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    IfElse(Box<Expr>, Box<Expr>, Box<Expr>),
+    Unsupported(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Stmt {
+    line: usize,
+    kind: StmtKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum StmtKind {
+    Pass,
+    Expr(Expr),
+    Assign(Vec<Expr>, Expr),
+    AugAssign(Expr, String, Expr),
+    If(Vec<(Expr, Vec<Stmt>)>, Vec<Stmt>),
+    For(Vec<String>, Expr, Vec<Stmt>),
+    Return(Option<Expr>),
+    /// Тело `with`/`try`: исполняется как есть, обработчики исключений пропускаются.
+    Block(Vec<Stmt>),
+    Def(String, Rc<FunctionDef>),
+    Class(Vec<Stmt>),
+    Unsupported(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FunctionDef {
+    params: Vec<(String, Option<Expr>)>,
+    property: bool,
+    body: Vec<Stmt>,
+}
+
+fn parse_module(text: &str) -> Result<Vec<Stmt>, (usize, String)> {
+    let tokens = tokenize(text)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let mut module = Vec::new();
+    while !parser.at_end() {
+        if parser.eat_tok(&Tok::Newline) || parser.eat_tok(&Tok::Dedent) {
+            continue;
+        }
+        module.extend(parser.statement());
+    }
+    Ok(module)
+}
+
+fn parse_expression_text(text: &str) -> Result<Expr, String> {
+    let tokens = tokenize(text).map_err(|(_, reason)| reason)?;
+    let mut parser = Parser { tokens, pos: 0 };
+    let expr = parser.expr()?;
+    if !parser.at_tok(&Tok::Newline) && !parser.at_end() {
+        return Err(format!("unexpected `{}`", parser.describe()));
+    }
+    Ok(expr)
+}
+
+struct Parser {
+    tokens: Vec<(Tok, usize)>,
+    pos: usize,
+}
+
+type ParseResult<T> = Result<T, String>;
+
+/// Позиционные и именованные аргументы вызова.
+type CallArgs = (Vec<Expr>, Vec<(String, Expr)>);
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.tokens.len()
+    }
+
+    fn at_tok(&self, token: &Tok) -> bool {
+        self.peek() == Some(token)
+    }
+
+    fn at_op(&self, op: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Op(value)) if value == op)
+    }
+
+    fn at_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Tok::Name(value)) if value == keyword)
+    }
+
+    fn eat_tok(&mut self, token: &Tok) -> bool {
+        let matched = self.at_tok(token);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn eat_op(&mut self, op: &str) -> bool {
+        let matched = self.at_op(op);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        let matched = self.at_keyword(keyword);
+        if matched {
+            self.pos += 1;
+        }
+        matched
+    }
+
+    fn expect_op(&mut self, op: &str) -> ParseResult<()> {
+        if self.eat_op(op) {
+            Ok(())
+        } else {
+            Err(format!("expected `{op}`, found `{}`", self.describe()))
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            Some(Tok::Name(value) | Tok::Number(value) | Tok::Op(value)) => value.clone(),
+            Some(Tok::Str(..)) => "string".to_string(),
+            Some(Tok::Newline) => "end of line".to_string(),
+            Some(Tok::Indent) => "indent".to_string(),
+            Some(Tok::Dedent) => "dedent".to_string(),
+            None => "end of file".to_string(),
+        }
+    }
+
+    fn name(&mut self) -> ParseResult<String> {
+        match self.peek() {
+            Some(Tok::Name(value)) => {
+                let value = value.clone();
+                self.pos += 1;
+                Ok(value)
+            }
+            _ => Err(format!("expected a name, found `{}`", self.describe())),
+        }
+    }
+
+    /// Разбирает оператор; при ошибке пропускает его вместе с вложенным блоком.
+    fn statement(&mut self) -> Vec<Stmt> {
+        let start = self.pos;
+        let line = self.line();
+        match self.compound_or_simple() {
+            Ok(statements) => statements,
+            Err(reason) => {
+                self.pos = start;
+                self.skip_statement();
+                vec![Stmt {
+                    line,
+                    kind: StmtKind::Unsupported(reason),
+                }]
+            }
+        }
+    }
+
+    fn skip_statement(&mut self) {
+        while !self.at_end() && !self.at_tok(&Tok::Newline) {
+            self.pos += 1;
+        }
+        self.eat_tok(&Tok::Newline);
+        if self.at_tok(&Tok::Indent) {
+            let mut depth = 0usize;
+            while let Some(token) = self.peek() {
+                match token {
+                    Tok::Indent => depth += 1,
+                    Tok::Dedent => {
+                        depth -= 1;
+                        if depth == 0 {
+                            self.pos += 1;
+                            break;
+                        }
+                    }
+                    _ => {}
+                }
+                self.pos += 1;
+            }
+        }
+    }
+
+    fn compound_or_simple(&mut self) -> ParseResult<Vec<Stmt>> {
+        let line = self.line();
+        let kind = match self.peek() {
+            Some(Tok::Name(keyword)) => match keyword.as_str() {
+                "if" => self.if_statement()?,
+                "for" => self.for_statement()?,
+                "def" => self.def_statement(false)?,
+                "class" => self.class_statement()?,
+                "with" => {
+                    self.skip_to_colon()?;
+                    StmtKind::Block(self.suite()?)
+                }
+                "try" => self.try_statement()?,
+                "while" => {
+                    self.skip_to_colon()?;
+                    self.suite()?;
+                    StmtKind::Unsupported("`while` loops are not supported".to_string())
+                }
+                _ => return self.simple_statements(),
+            },
+            Some(Tok::Op(op)) if op == "@" => self.decorated()?,
+            _ => return self.simple_statements(),
+        };
+        Ok(vec![Stmt { line, kind }])
+    }
+
+    fn skip_to_colon(&mut self) -> ParseResult<()> {
+        let mut depth = 0usize;
+        loop {
+            match self.peek() {
+                None | Some(Tok::Newline) => return Err("expected `:`".to_string()),
+                Some(Tok::Op(op)) => match op.as_str() {
+                    "(" | "[" | "{" => depth += 1,
+                    ")" | "]" | "}" => depth = depth.saturating_sub(1),
+                    ":" if depth == 0 => {
+                        self.pos += 1;
+                        return Ok(());
+                    }
+                    _ => {}
+                },
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn suite(&mut self) -> ParseResult<Vec<Stmt>> {
+        if !self.eat_tok(&Tok::Newline) {
+            return self.simple_statements();
+        }
+        if !self.eat_tok(&Tok::Indent) {
+            return Err("expected an indented block".to_string());
+        }
+        let mut body = Vec::new();
+        while !self.at_end() && !self.at_tok(&Tok::Dedent) {
+            if self.eat_tok(&Tok::Newline) {
+                continue;
+            }
+            body.extend(self.statement());
+        }
+        self.eat_tok(&Tok::Dedent);
+        Ok(body)
+    }
+
+    fn if_statement(&mut self) -> ParseResult<StmtKind> {
+        self.pos += 1;
+        let mut branches = Vec::new();
+        let condition = self.expr()?;
+        self.expect_op(":")?;
+        branches.push((condition, self.suite()?));
+        let mut orelse = Vec::new();
+        loop {
+            if self.eat_keyword("elif") {
+                let condition = self.expr()?;
+                self.expect_op(":")?;
+                branches.push((condition, self.suite()?));
+            } else if self.eat_keyword("else") {
+                self.expect_op(":")?;
+                orelse = self.suite()?;
+                break;
+            } else {
+                break;
+            }
+        }
+        Ok(StmtKind::If(branches, orelse))
+    }
+
+    fn for_statement(&mut self) -> ParseResult<StmtKind> {
+        self.pos += 1;
+        let targets = self.target_names()?;
+        if !self.eat_keyword("in") {
+            return Err("expected `in`".to_string());
+        }
+        let iter = self.expr_list()?;
+        self.expect_op(":")?;
+        let body = self.suite()?;
+        if self.eat_keyword("else") {
+            self.expect_op(":")?;
+            self.suite()?;
+        }
+        Ok(StmtKind::For(targets, iter, body))
+    }
+
+    fn target_names(&mut self) -> ParseResult<Vec<String>> {
+        let parenthesized = self.eat_op("(");
+        let mut names = vec![self.name()?];
+        while self.eat_op(",") {
+            if self.at_keyword("in") || self.at_op(")") {
+                break;
+            }
+            names.push(self.name()?);
+        }
+        if parenthesized {
+            self.expect_op(")")?;
+        }
+        Ok(names)
+    }
+
+    fn decorated(&mut self) -> ParseResult<StmtKind> {
+        let mut property = false;
+        while self.eat_op("@") {
+            let decorator = self.expr()?;
+            property |= decorator == Expr::Name("property".to_string());
+            if !self.eat_tok(&Tok::Newline) {
+                return Err("expected end of line after a decorator".to_string());
+            }
+        }
+        if self.at_keyword("def") {
+            self.def_statement(property)
+        } else if self.at_keyword("class") {
+            self.class_statement()
+        } else {
+            Err("expected `def` or `class` after a decorator".to_string())
+        }
+    }
+
+    fn def_statement(&mut self, property: bool) -> ParseResult<StmtKind> {
+        self.pos += 1;
+        let name = self.name()?;
+        self.expect_op("(")?;
+        let mut params = Vec::new();
+        while !self.eat_op(")") {
+            if self.eat_op("*") || self.eat_op("**") {
+                if self.at_op(",") {
+                    self.pos += 1;
+                    continue;
+                }
+                self.name()?;
+                if self.eat_op(":") {
+                    self.expr()?;
+                }
+            } else if self.eat_op("/") {
+            } else {
+                let param = self.name()?;
+                if self.eat_op(":") {
+                    self.expr()?;
+                }
+                let default = if self.eat_op("=") {
+                    Some(self.expr()?)
+                } else {
+                    None
+                };
+                params.push((param, default));
+            }
+            if !self.eat_op(",") {
+                self.expect_op(")")?;
+                break;
+            }
+        }
+        if self.eat_op("->") {
+            self.expr()?;
+        }
+        self.expect_op(":")?;
+        let body = self.suite()?;
+        Ok(StmtKind::Def(
+            name,
+            Rc::new(FunctionDef {
+                params,
+                property,
+                body,
+            }),
+        ))
+    }
+
+    fn class_statement(&mut self) -> ParseResult<StmtKind> {
+        self.pos += 1;
+        self.name()?;
+        self.skip_to_colon()?;
+        Ok(StmtKind::Class(self.suite()?))
+    }
+
+    fn try_statement(&mut self) -> ParseResult<StmtKind> {
+        self.pos += 1;
+        self.expect_op(":")?;
+        let body = self.suite()?;
+        while self.at_keyword("except") || self.at_keyword("else") || self.at_keyword("finally") {
+            self.pos += 1;
+            self.skip_to_colon()?;
+            self.suite()?;
+        }
+        Ok(StmtKind::Block(body))
+    }
+
+    fn simple_statements(&mut self) -> ParseResult<Vec<Stmt>> {
+        let mut statements = Vec::new();
+        loop {
+            let line = self.line();
+            let kind = self.small_statement()?;
+            statements.push(Stmt { line, kind });
+            if !self.eat_op(";") || self.at_tok(&Tok::Newline) {
+                break;
+            }
+        }
+        if !self.eat_tok(&Tok::Newline) && !self.at_end() {
+            return Err(format!("unexpected `{}`", self.describe()));
+        }
+        Ok(statements)
+    }
+
+    fn skip_simple_statement(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.peek() {
+            match token {
+                Tok::Newline => break,
+                Tok::Op(op) => match op.as_str() {
+                    "(" | "[" | "{" => depth += 1,
+                    ")" | "]" | "}" => depth = depth.saturating_sub(1),
+                    ";" if depth == 0 => break,
+                    _ => {}
+                },
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn small_statement(&mut self) -> ParseResult<StmtKind> {
+        if let Some(Tok::Name(keyword)) = self.peek() {
+            match keyword.as_str() {
+                "pass" | "import" | "from" | "global" | "nonlocal" | "del" | "assert" => {
+                    self.skip_simple_statement();
+                    return Ok(StmtKind::Pass);
+                }
+                "return" => {
+                    self.pos += 1;
+                    if self.at_tok(&Tok::Newline) || self.at_op(";") || self.at_end() {
+                        return Ok(StmtKind::Return(None));
+                    }
+                    return Ok(StmtKind::Return(Some(self.expr_list()?)));
+                }
+                "raise" => {
+                    self.skip_simple_statement();
+                    return Ok(StmtKind::Return(None));
+                }
+                "break" | "continue" => {
+                    let keyword = keyword.clone();
+                    self.pos += 1;
+                    return Ok(StmtKind::Unsupported(format!(
+                        "`{keyword}` is not supported"
+                    )));
+                }
+                "yield" | "lambda" | "async" | "await" => {
+                    return Err(format!("`{keyword}` is not supported"));
+                }
+                _ => {}
+            }
+        }
+
+        let first = self.expr_list()?;
+        if self.at_op("=") {
+            let mut targets = vec![first];
+            while self.eat_op("=") {
+                targets.push(self.expr_list()?);
+            }
+            let value = targets.pop().unwrap_or(Expr::Const(Value::None));
+            return Ok(StmtKind::Assign(targets, value));
+        }
+        if self.eat_op(":") {
+            self.expr()?;
+            if self.eat_op("=") {
+                return Ok(StmtKind::Assign(vec![first], self.expr_list()?));
+            }
+            return Ok(StmtKind::Pass);
+        }
+        if let Some(Tok::Op(op)) = self.peek()
+            && op.len() >= 2
+            && op.ends_with('=')
+            && !matches!(op.as_str(), "==" | "!=" | "<=" | ">=")
+        {
+            let op = op.trim_end_matches('=').to_string();
+            self.pos += 1;
+            return Ok(StmtKind::AugAssign(first, op, self.expr_list()?));
+        }
+        Ok(StmtKind::Expr(first))
+    }
+
+    fn starts_expr(&self) -> bool {
+        match self.peek() {
+            Some(Tok::Name(name)) => !matches!(
+                name.as_str(),
+                "in" | "if" | "else" | "for" | "and" | "or" | "is"
+            ),
+            Some(Tok::Number(_) | Tok::Str(..)) => true,
+            Some(Tok::Op(op)) => matches!(op.as_str(), "(" | "[" | "{" | "-" | "+" | "~"),
+            _ => false,
+        }
+    }
+
+    /// Выражение или кортеж без скобок.
+    fn expr_list(&mut self) -> ParseResult<Expr> {
+        let first = self.expr()?;
+        if !self.at_op(",") {
+            return Ok(first);
+        }
+        let mut items = vec![first];
+        while self.eat_op(",") {
+            if !self.starts_expr() {
+                break;
+            }
+            items.push(self.expr()?);
+        }
+        Ok(Expr::List(items))
+    }
+
+    fn expr(&mut self) -> ParseResult<Expr> {
+        if self.at_keyword("lambda") {
+            return Err("`lambda` is not supported".to_string());
+        }
+        let value = self.or_test()?;
+        if self.eat_keyword("if") {
+            let condition = self.or_test()?;
+            if !self.eat_keyword("else") {
+                return Err("expected `else`".to_string());
+            }
+            let otherwise = self.expr()?;
+            return Ok(Expr::IfElse(
+                Box::new(condition),
+                Box::new(value),
+                Box::new(otherwise),
+            ));
+        }
+        Ok(value)
+    }
+
+    fn or_test(&mut self) -> ParseResult<Expr> {
+        let mut left = self.and_test()?;
+        while self.eat_keyword("or") {
+            left = Expr::Or(Box::new(left), Box::new(self.and_test()?));
+        }
+        Ok(left)
+    }
+
+    fn and_test(&mut self) -> ParseResult<Expr> {
+        let mut left = self.not_test()?;
+        while self.eat_keyword("and") {
+            left = Expr::And(Box::new(left), Box::new(self.not_test()?));
+        }
+        Ok(left)
+    }
+
+    fn not_test(&mut self) -> ParseResult<Expr> {
+        if self.eat_keyword("not") {
+            return Ok(Expr::Not(Box::new(self.not_test()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> ParseResult<Expr> {
+        let mut left = self.arith()?;
+        loop {
+            let op = match self.peek() {
+                Some(Tok::Op(op))
+                    if matches!(op.as_str(), "==" | "!=" | "<" | ">" | "<=" | ">=") =>
+                {
+                    op.clone()
+                }
+                Some(Tok::Name(name)) if name == "in" => "in".to_string(),
+                Some(Tok::Name(name)) if name == "is" => {
+                    self.pos += 1;
+                    let op = if self.eat_keyword("not") {
+                        "is not"
+                    } else {
+                        "is"
+                    };
+                    left = Expr::Binary(op.to_string(), Box::new(left), Box::new(self.arith()?));
+                    continue;
+                }
+                Some(Tok::Name(name))
+                    if name == "not"
+                        && matches!(self.tokens.get(self.pos + 1), Some((Tok::Name(next), _)) if next == "in") =>
+                {
+                    self.pos += 1;
+                    "not in".to_string()
+                }
+                _ => break,
+            };
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.arith()?));
+        }
+        Ok(left)
+    }
+
+    fn arith(&mut self) -> ParseResult<Expr> {
+        let mut left = self.term()?;
+        while let Some(Tok::Op(op)) = self.peek()
+            && matches!(op.as_str(), "+" | "-" | "|" | "&" | "^")
+        {
+            let op = op.clone();
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.term()?));
+        }
+        Ok(left)
+    }
+
+    fn term(&mut self) -> ParseResult<Expr> {
+        let mut left = self.factor()?;
+        while let Some(Tok::Op(op)) = self.peek()
+            && matches!(op.as_str(), "*" | "/" | "//" | "%")
+        {
+            let op = op.clone();
+            self.pos += 1;
+            left = Expr::Binary(op, Box::new(left), Box::new(self.factor()?));
+        }
+        Ok(left)
+    }
+
+    fn factor(&mut self) -> ParseResult<Expr> {
+        if self.eat_op("-") {
+            return Ok(Expr::Neg(Box::new(self.factor()?)));
+        }
+        if self.eat_op("+") {
+            return self.factor();
+        }
+        if self.eat_op("~") {
+            self.factor()?;
+            return Ok(Expr::Unsupported(
+                "operator `~` is not supported".to_string(),
+            ));
+        }
+        let base = self.primary()?;
+        if self.eat_op("**") {
+            let exponent = self.factor()?;
+            return Ok(Expr::Binary(
+                "**".to_string(),
+                Box::new(base),
+                Box::new(exponent),
+            ));
+        }
+        Ok(base)
+    }
+
+    fn primary(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.atom()?;
+        loop {
+            if self.eat_op(".") {
+                expr = Expr::Attr(Box::new(expr), self.name()?);
+            } else if self.eat_op("(") {
+                let (args, kwargs) = self.call_args()?;
+                expr = Expr::Call(Box::new(expr), args, kwargs);
+            } else if self.eat_op("[") {
+                let index = self.subscript()?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn subscript(&mut self) -> ParseResult<Expr> {
+        if !self.at_op(":") {
+            let index = self.expr_list()?;
+            if self.eat_op("]") {
+                return Ok(index);
+            }
+        }
+        let mut depth = 1usize;
+        while let Some(token) = self.peek() {
+            if let Tok::Op(op) = token {
+                match op.as_str() {
+                    "(" | "[" | "{" => depth += 1,
+                    ")" | "]" | "}" => {
+                        depth -= 1;
+                        if depth == 0 {
+                            self.pos += 1;
+                            return Ok(Expr::Unsupported("slices are not supported".to_string()));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            self.pos += 1;
+        }
+        Err("expected `]`".to_string())
+    }
+
+    fn call_args(&mut self) -> ParseResult<CallArgs> {
+        let mut args = Vec::new();
+        let mut kwargs = Vec::new();
+        while !self.eat_op(")") {
+            if self.eat_op("*") || self.eat_op("**") {
+                self.expr()?;
+                args.push(Expr::Unsupported(
+                    "argument unpacking is not supported".to_string(),
+                ));
+            } else if let Some(Tok::Name(name)) = self.peek()
+                && matches!(self.tokens.get(self.pos + 1), Some((Tok::Op(op), _)) if op == "=")
+            {
+                let name = name.clone();
+                self.pos += 2;
+                kwargs.push((name, self.expr()?));
+            } else {
+                let arg = self.expr()?;
+                if self.at_keyword("for") {
+                    args.push(self.comprehension(arg)?);
+                } else {
+                    args.push(arg);
+                }
+            }
+            if !self.eat_op(",") {
+                self.expect_op(")")?;
+                break;
+            }
+        }
+        Ok((args, kwargs))
+    }
+
+    fn comprehension(&mut self, element: Expr) -> ParseResult<Expr> {
+        self.pos += 1;
+        let targets = self.target_names()?;
+        if !self.eat_keyword("in") {
+            return Err("expected `in`".to_string());
+        }
+        let iter = self.or_test()?;
+        let mut conditions = Vec::new();
+        while self.eat_keyword("if") {
+            conditions.push(self.or_test()?);
+        }
+        if self.at_keyword("for") {
+            return Err("nested comprehensions are not supported".to_string());
+        }
+        Ok(Expr::Comprehension {
+            element: Box::new(element),
+            targets,
+            iter: Box::new(iter),
+            conditions,
+        })
+    }
+
+    fn atom(&mut self) -> ParseResult<Expr> {
+        let Some(token) = self.peek().cloned() else {
+            return Err("unexpected end of file".to_string());
+        };
+        self.pos += 1;
+        match token {
+            Tok::Name(name) => Ok(match name.as_str() {
+                "True" => Expr::Const(Value::Bool(true)),
+                "False" => Expr::Const(Value::Bool(false)),
+                "None" => Expr::Const(Value::None),
+                _ => Expr::Name(name),
+            }),
+            Tok::Number(number) => Ok(match number.parse::<i64>() {
+                Ok(value) => Expr::Const(Value::Int(value)),
+                Err(_) => Expr::Unsupported(format!("number `{number}` is not supported")),
+            }),
+            Tok::Str(value, fstring) => {
+                let mut parts = vec![(value, fstring)];
+                while let Some(Tok::Str(value, fstring)) = self.peek() {
+                    parts.push((value.clone(), *fstring));
+                    self.pos += 1;
+                }
+                Ok(Expr::Str(parts))
+            }
+            Tok::Op(op) => match op.as_str() {
+                "(" => {
+                    if self.eat_op(")") {
+                        return Ok(Expr::List(Vec::new()));
+                    }
+                    let first = self.expr()?;
+                    if self.at_keyword("for") {
+                        let comprehension = self.comprehension(first)?;
+                        self.expect_op(")")?;
+                        return Ok(comprehension);
+                    }
+                    if self.eat_op(")") {
+                        return Ok(first);
+                    }
+                    let items = self.sequence_tail(first, ")")?;
+                    Ok(Expr::List(items))
+                }
+                "[" => {
+                    if self.eat_op("]") {
+                        return Ok(Expr::List(Vec::new()));
+                    }
+                    let first = self.expr()?;
+                    if self.at_keyword("for") {
+                        let comprehension = self.comprehension(first)?;
+                        self.expect_op("]")?;
+                        return Ok(comprehension);
+                    }
+                    if self.eat_op("]") {
+                        return Ok(Expr::List(vec![first]));
+                    }
+                    Ok(Expr::List(self.sequence_tail(first, "]")?))
+                }
+                "{" => self.dict_or_set(),
+                "..." => Ok(Expr::Const(Value::None)),
+                _ => Err(format!("unexpected `{op}`")),
+            },
+            _ => {
+                self.pos -= 1;
+                Err(format!("unexpected `{}`", self.describe()))
+            }
+        }
+    }
+
+    fn sequence_tail(&mut self, first: Expr, close: &str) -> ParseResult<Vec<Expr>> {
+        let mut items = vec![first];
+        while self.eat_op(",") {
+            if self.at_op(close) {
+                break;
+            }
+            items.push(self.expr()?);
+        }
+        self.expect_op(close)?;
+        Ok(items)
+    }
+
+    fn dict_or_set(&mut self) -> ParseResult<Expr> {
+        if self.eat_op("}") {
+            return Ok(Expr::Dict(Vec::new()));
+        }
+        if self.at_op("**") {
+            return Err("dict unpacking is not supported".to_string());
+        }
+        let first = self.expr()?;
+        if !self.eat_op(":") {
+            if self.at_keyword("for") {
+                let comprehension = self.comprehension(first)?;
+                self.expect_op("}")?;
+                return Ok(comprehension);
+            }
+            if self.eat_op("}") {
+                return Ok(Expr::List(vec![first]));
+            }
+            return Ok(Expr::List(self.sequence_tail(first, "}")?));
+        }
+        let value = self.expr()?;
+        if self.at_keyword("for") {
+            return Err("dict comprehensions are not supported".to_string());
+        }
+        let mut pairs = vec![(first, value)];
+        while self.eat_op(",") {
+            if self.at_op("}") {
+                break;
+            }
+            if self.at_op("**") {
+                return Err("dict unpacking is not supported".to_string());
+            }
+            let key = self.expr()?;
+            self.expect_op(":")?;
+            pairs.push((key, self.expr()?));
+        }
+        self.expect_op("}")?;
+        Ok(Expr::Dict(pairs))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Вычисление
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    None,
+    Bool(bool),
+    Int(i64),
+    Str(String),
+    List(Vec<Value>),
+    Dict(Vec<(Value, Value)>),
+    /// `self` рецепта.
+    Recipe,
+    Module(String),
+    Builtin(String),
+    /// Функция модуля или `self.<метод>` рецепта.
+    Function(String),
+    /// `self.settings.<key>`; пустой ключ — сам `self.settings`.
+    Setting(String),
+    /// `self.options.<name>`; пустое имя — сам `self.options`.
+    Opt(String),
+    /// `self.cpp_info` или его компонент.
+    CppInfo(Option<String>),
+    Components,
+    CppField(Option<String>, String),
+    BoundMethod(Box<Value>, String),
+    /// Значение, которое не влияет на метаданные (`self.output`, `self.runenv_info`, ...).
+    Ignored,
+    /// Не удалось вычислить, с причиной.
+    Unknown(String),
+}
+
+enum Flow {
+    Next,
+    Return(Value),
+}
+
+#[derive(Debug, Default)]
+struct CppInfoFields {
+    lists: BTreeMap<String, Vec<String>>,
+    properties: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default)]
+struct CppInfoState {
+    root: CppInfoFields,
+    components: Vec<(String, CppInfoFields)>,
+}
+
+impl CppInfoState {
+    fn fields(&mut self, component: &Option<String>) -> &mut CppInfoFields {
+        let Some(name) = component else {
+            return &mut self.root;
+        };
+        let index = match self.components.iter().position(|(item, _)| item == name) {
+            Some(index) => index,
+            None => {
+                self.components
+                    .push((name.clone(), CppInfoFields::default()));
+                self.components.len() - 1
+            }
+        };
+        &mut self.components[index].1
+    }
+
+    fn list(&mut self, component: &Option<String>, field: &str) -> &mut Vec<String> {
+        self.fields(component)
+            .lists
+            .entry(field.to_string())
+            .or_insert_with(|| {
+                default_dirs(field)
+                    .iter()
+                    .map(|dir| dir.to_string())
+                    .collect()
+            })
+    }
+}
+
+fn default_dirs(field: &str) -> &'static [&'static str] {
+    match field {
+        "includedirs" => CppBuildInfo::DEFAULT_INCLUDEDIRS,
+        "libdirs" => CppBuildInfo::DEFAULT_LIBDIRS,
+        "bindirs" => CppBuildInfo::DEFAULT_BINDIRS,
+        _ => &[],
+    }
+}
+
+struct Walker<'a> {
+    lines: Vec<&'a str>,
+    profile: &'a RecipeProfile,
+    package_name: &'a str,
+    globals: BTreeMap<String, Value>,
+    class_attrs: BTreeMap<String, Value>,
+    functions: BTreeMap<String, Rc<FunctionDef>>,
+    methods: BTreeMap<String, Rc<FunctionDef>>,
+    /// Объявленные options и их значения; `None` — значение не задано.
+    options: BTreeMap<String, Option<String>>,
+    frames: Vec<BTreeMap<String, Value>>,
+    cpp_info: CppInfoState,
+    requires: Vec<String>,
+    unsupported: BTreeMap<usize, String>,
+    line: usize,
+    quiet: bool,
+}
+
+impl<'a> Walker<'a> {
+    fn new(package_name: &'a str, conanfile: &'a str, profile: &'a RecipeProfile) -> Self {
+        Self {
+            lines: conanfile.lines().collect(),
+            profile,
+            package_name,
+            globals: BTreeMap::new(),
+            class_attrs: BTreeMap::new(),
+            functions: BTreeMap::new(),
+            methods: BTreeMap::new(),
+            options: BTreeMap::new(),
+            frames: Vec::new(),
+            cpp_info: CppInfoState::default(),
+            requires: Vec::new(),
+            unsupported: BTreeMap::new(),
+            line: 0,
+            quiet: false,
+        }
+    }
+
+    fn note(&mut self, reason: String) {
+        if !self.quiet {
+            self.unsupported.entry(self.line).or_insert(reason);
+        }
+    }
+
+    /// Собирает функции, константы модуля и атрибуты класса рецепта, не сообщая об ошибках.
+    fn load_module(&mut self, module: &[Stmt]) {
+        self.quiet = true;
+        for stmt in module {
+            self.line = stmt.line;
+            match &stmt.kind {
+                StmtKind::Assign(..) => {
+                    self.exec(stmt);
+                }
+                StmtKind::Def(name, function) => {
+                    self.functions.insert(name.clone(), function.clone());
+                }
+                StmtKind::Class(body) => self.load_class(body),
+                _ => {}
+            }
+        }
+
+        if let Some(Value::Dict(declared)) = self.class_attrs.get("options").cloned() {
+            for (key, _) in declared {
+                if let Value::Str(key) = key {
+                    self.options.insert(key, None);
+                }
+            }
+        }
+        if let Some(Value::Dict(defaults)) = self.class_attrs.get("default_options").cloned() {
+            for (key, value) in defaults {
+                if let (Value::Str(key), Ok(value)) = (key, self.str_form(&value))
+                    && !key.contains(':')
+                {
+                    self.options.insert(key, Some(value));
+                }
+            }
+        }
+        for (key, value) in &self.profile.options {
+            self.options.insert(key.clone(), Some(value.clone()));
+        }
+        self.quiet = false;
+    }
+
+    fn load_class(&mut self, body: &[Stmt]) {
+        for stmt in body {
+            self.line = stmt.line;
+            match &stmt.kind {
+                StmtKind::Assign(targets, value) => {
+                    let value = self.eval(value);
+                    for target in targets {
+                        if let Expr::Name(name) = target {
+                            self.class_attrs.insert(name.clone(), value.clone());
+                        }
+                    }
+                }
+                StmtKind::Def(name, function) => {
+                    self.methods.insert(name.clone(), function.clone());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Строки атрибута класса: `attr = "x"` или `attr = ("x", "y")`.
+    fn class_strings(&self, name: &str) -> Vec<String> {
+        match self.class_attrs.get(name) {
+            Some(Value::Str(value)) => vec![value.clone()],
+            Some(Value::List(values)) => values
+                .iter()
+                .filter_map(|value| match value {
+                    Value::Str(value) => Some(value.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Атрибут класса `requires = "a/1.0", "b/2.0"`.
+    fn collect_class_requires(&mut self) {
+        for item in self.class_strings("requires") {
+            if !self.requires.contains(&item) {
+                self.requires.push(item);
+            }
+        }
+    }
+
+    fn run_method(&mut self, name: &str) {
+        let function = self
+            .methods
+            .get(name)
+            .or_else(|| self.functions.get(name))
+            .cloned();
+        if let Some(function) = function {
+            self.invoke(&function, true, Vec::new(), Vec::new());
+        }
+    }
+
+    fn finish(self) -> RecipeInfo {
+        let license = self.class_strings("license");
+        let Walker {
+            lines,
+            package_name,
+            class_attrs,
+            cpp_info,
+            requires,
+            unsupported,
+            ..
+        } = self;
+
+        let mut root = cpp_info.root;
+        let mut info = PackageCppInfo {
+            package_name: package_name.to_string(),
+            is_header_only: class_attrs.get("package_type")
+                == Some(&Value::Str("header-library".to_string())),
+            libs: root.lists.remove("libs").unwrap_or_default(),
+            system_libs: root.lists.remove("system_libs").unwrap_or_default(),
+            pkg_config_name: root.properties.remove("pkg_config_name"),
+            cmake_file_name: root.properties.remove("cmake_file_name"),
+            cmake_target_name: root.properties.remove("cmake_target_name"),
+            build_info: build_info(&mut root),
+            components: Vec::new(),
+        };
+        for (name, mut fields) in cpp_info.components {
+            info.components.push(ComponentInfo {
+                name,
+                libs: fields.lists.remove("libs").unwrap_or_default(),
+                system_libs: fields.lists.remove("system_libs").unwrap_or_default(),
+                requires: fields.lists.remove("requires").unwrap_or_default(),
+                pkg_config_name: fields.properties.remove("pkg_config_name"),
+                cmake_target_name: fields.properties.remove("cmake_target_name"),
+                build_info: build_info(&mut fields),
+            });
+        }
+
+        let unsupported = unsupported
+            .into_iter()
+            .map(|(line, reason)| {
+                let source = lines
+                    .get(line.wrapping_sub(1))
+                    .map_or("", |text| text.trim());
+                format!("line {line}: {reason}: {source}")
+            })
+            .collect();
+        RecipeInfo {
+            cpp_info: info,
+            requires,
+            license,
+            unsupported,
+        }
+    }
+
+    fn exec_block(&mut self, body: &[Stmt]) -> Flow {
+        for stmt in body {
+            if let Flow::Return(value) = self.exec(stmt) {
+                return Flow::Return(value);
+            }
+        }
+        Flow::Next
+    }
+
+    fn exec(&mut self, stmt: &Stmt) -> Flow {
+        self.line = stmt.line;
+        match &stmt.kind {
+            StmtKind::Pass | StmtKind::Class(_) => {}
+            StmtKind::Unsupported(reason) => self.note(reason.clone()),
+            StmtKind::Expr(expr) => {
+                if let Value::Unknown(reason) = self.eval(expr) {
+                    self.note(reason);
+                }
+            }
+            StmtKind::Assign(targets, value) => {
+                let value = self.eval(value);
+                for target in targets {
+                    self.assign(target, value.clone());
+                }
+            }
+            StmtKind::AugAssign(target, op, value) => {
+                let value = self.eval(value);
+                self.aug_assign(target, op, value);
+            }
+            StmtKind::If(branches, orelse) => return self.exec_if(branches, orelse),
+            StmtKind::For(targets, iter, body) => {
+                let iter = self.eval(iter);
+                let items = match self.iterate(iter) {
+                    Ok(items) => items,
+                    Err(reason) => {
+                        self.note(format!("loop was skipped, {reason}"));
+                        return Flow::Next;
+                    }
+                };
+                for item in items {
+                    self.bind(targets, item);
+                    if let Flow::Return(value) = self.exec_block(body) {
+                        return Flow::Return(value);
+                    }
+                }
+            }
+            StmtKind::Return(value) => {
+                let value = value.as_ref().map_or(Value::None, |value| self.eval(value));
+                return Flow::Return(value);
+            }
+            StmtKind::Block(body) => return self.exec_block(body),
+            StmtKind::Def(name, function) => {
+                self.functions.insert(name.clone(), function.clone());
+            }
+        }
+        Flow::Next
+    }
+
+    fn exec_if(&mut self, branches: &[(Expr, Vec<Stmt>)], orelse: &[Stmt]) -> Flow {
+        for (index, (condition, body)) in branches.iter().enumerate() {
+            let value = self.eval(condition);
+            match self.truth(&value) {
+                Ok(true) => return self.exec_block(body),
+                Ok(false) => {}
+                Err(reason) => {
+                    self.note(format!(
+                        "condition is unknown for the profile ({reason}), all branches were taken"
+                    ));
+                    for (_, body) in &branches[index..] {
+                        if let Flow::Return(value) = self.exec_block(body) {
+                            return Flow::Return(value);
+                        }
+                    }
+                    return self.exec_block(orelse);
+                }
+            }
+        }
+        self.exec_block(orelse)
+    }
+
+    fn bind(&mut self, targets: &[String], value: Value) {
+        if let [target] = targets {
+            self.set_name(target, value);
+            return;
+        }
+        match value {
+            Value::List(items) if items.len() == targets.len() => {
+                for (target, item) in targets.iter().zip(items) {
+                    self.set_name(target, item);
+                }
+            }
+            _ => {
+                for target in targets {
+                    self.set_name(
+                        target,
+                        Value::Unknown("cannot unpack the value".to_string()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn set_name(&mut self, name: &str, value: Value) {
+        match self.frames.last_mut() {
+            Some(frame) => {
+                frame.insert(name.to_string(), value);
+            }
+            None => {
+                self.globals.insert(name.to_string(), value);
+            }
+        }
+    }
+
+    fn assign(&mut self, target: &Expr, value: Value) {
+        match target {
+            Expr::Name(name) => self.set_name(name, value),
+            Expr::List(items) => {
+                let names: Option<Vec<String>> = items
+                    .iter()
+                    .map(|item| match item {
+                        Expr::Name(name) => Some(name.clone()),
+                        _ => None,
+                    })
+                    .collect();
+                match names {
+                    Some(names) => self.bind(&names, value),
+                    None => self.note("unsupported assignment target".to_string()),
+                }
+            }
+            Expr::Attr(object, field) => match self.eval(object) {
+                Value::CppInfo(component) if CPP_INFO_LISTS.contains(&field.as_str()) => {
+                    match self.strings(value) {
+                        Ok(values) => {
+                            let list = self.cpp_info.list(&component, field);
+                            list.clear();
+                            push_unique(list, values);
+                        }
+                        Err(reason) => self.note(format!("`{field}` was not changed, {reason}")),
+                    }
+                }
+                Value::Recipe => {
+                    self.class_attrs.insert(field.clone(), value);
+                }
+                Value::Ignored | Value::CppInfo(_) => {}
+                Value::Unknown(reason) => self.note(reason),
+                _ => self.note(format!("unsupported assignment to `{field}`")),
+            },
+            Expr::Index(object, key) => {
+                if let Expr::Name(name) = object.as_ref()
+                    && let Some(Value::Dict(_)) = self.lookup_local(name)
+                {
+                    let key = self.eval(key);
+                    let key = self.scalar(key);
+                    if let Some(Value::Dict(pairs)) = self.lookup_local_mut(name) {
+                        match pairs.iter_mut().find(|(item, _)| item == &key) {
+                            Some(pair) => pair.1 = value,
+                            None => pairs.push((key, value)),
+                        }
+                    }
+                    return;
+                }
+                match self.eval(object) {
+                    Value::Ignored | Value::CppField(..) => {}
+                    Value::Unknown(reason) => self.note(reason),
+                    _ => self.note("unsupported item assignment".to_string()),
+                }
+            }
+            _ => self.note("unsupported assignment target".to_string()),
+        }
+    }
+
+    fn aug_assign(&mut self, target: &Expr, op: &str, value: Value) {
+        if let Expr::Attr(object, field) = target
+            && op == "+"
+            && let Value::CppInfo(component) = self.eval(object)
+            && CPP_INFO_LISTS.contains(&field.as_str())
+        {
+            match self.strings(value) {
+                Ok(values) => push_unique(self.cpp_info.list(&component, field), values),
+                Err(reason) => self.note(format!("`{field}` was not changed, {reason}")),
+            }
+            return;
+        }
+        let current = self.eval(target);
+        let result = self.binary(op, current, value);
+        self.assign(target, result);
+    }
+
+    fn lookup_local(&self, name: &str) -> Option<&Value> {
+        match self.frames.last() {
+            Some(frame) => frame.get(name),
+            None => self.globals.get(name),
+        }
+    }
+
+    fn lookup_local_mut(&mut self, name: &str) -> Option<&mut Value> {
+        match self.frames.last_mut() {
+            Some(frame) => frame.get_mut(name),
+            None => self.globals.get_mut(name),
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        if let Some(value) = self.lookup_local(name).or_else(|| self.globals.get(name)) {
+            return value.clone();
+        }
+        if self.functions.contains_key(name) {
+            return Value::Function(name.to_string());
+        }
+        if name == "os" {
+            return Value::Module(name.to_string());
+        }
+        if BUILTINS.contains(&name) {
+            return Value::Builtin(name.to_string());
+        }
+        Value::Unknown(format!("unknown name `{name}`"))
+    }
+
+    fn eval(&mut self, expr: &Expr) -> Value {
+        match expr {
+            Expr::Const(value) => value.clone(),
+            Expr::Str(parts) => {
+                let mut text = String::new();
+                for (part, fstring) in parts {
+                    if *fstring {
+                        match self.format_fstring(part) {
+                            Ok(value) => text.push_str(&value),
+                            Err(reason) => return Value::Unknown(reason),
+                        }
+                    } else {
+                        text.push_str(part);
+                    }
+                }
+                Value::Str(text)
+            }
+            Expr::Name(name) => self.lookup(name),
+            Expr::Attr(object, name) => {
+                let object = self.eval(object);
+                self.attr(object, name)
+            }
+            Expr::Index(object, key) => {
+                let object = self.eval(object);
+                let key = self.eval(key);
+                let key = self.scalar(key);
+                self.index(object, key)
+            }
+            Expr::Call(function, args, kwargs) => self.eval_call(function, args, kwargs),
+            Expr::List(items) => Value::List(
+                items
+                    .iter()
+                    .map(|item| {
+                        let value = self.eval(item);
+                        self.scalar(value)
+                    })
+                    .collect(),
+            ),
+            Expr::Dict(pairs) => Value::Dict(
+                pairs
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = self.eval(key);
+                        let value = self.eval(value);
+                        (self.scalar(key), self.scalar(value))
+                    })
+                    .collect(),
+            ),
+            Expr::Comprehension {
+                element,
+                targets,
+                iter,
+                conditions,
+            } => {
+                let iter = self.eval(iter);
+                let items = match self.iterate(iter) {
+                    Ok(items) => items,
+                    Err(reason) => return Value::Unknown(reason),
+                };
+                let mut result = Vec::new();
+                'items: for item in items {
+                    self.bind(targets, item);
+                    for condition in conditions {
+                        let value = self.eval(condition);
+                        match self.truth(&value) {
+                            Ok(true) => {}
+                            Ok(false) => continue 'items,
+                            Err(reason) => return Value::Unknown(reason),
+                        }
+                    }
+                    let value = self.eval(element);
+                    result.push(self.scalar(value));
+                }
+                Value::List(result)
+            }
+            Expr::Not(inner) => {
+                let value = self.eval(inner);
+                match self.truth(&value) {
+                    Ok(value) => Value::Bool(!value),
+                    Err(reason) => Value::Unknown(reason),
+                }
+            }
+            Expr::Neg(inner) => match self.eval(inner) {
+                Value::Int(value) => Value::Int(-value),
+                Value::Unknown(reason) => Value::Unknown(reason),
+                _ => Value::Unknown("unsupported operand of `-`".to_string()),
+            },
+            Expr::Binary(op, left, right) => {
+                let left = self.eval(left);
+                let right = self.eval(right);
+                self.binary(op, left, right)
+            }
+            Expr::And(left, right) => {
+                let left = self.eval(left);
+                match self.truth(&left) {
+                    Ok(false) => left,
+                    Ok(true) => self.eval(right),
+                    Err(reason) => {
+                        let right = self.eval(right);
+                        match self.truth(&right) {
+                            Ok(false) => right,
+                            _ => Value::Unknown(reason),
+                        }
+                    }
+                }
+            }
+            Expr::Or(left, right) => {
+                let left = self.eval(left);
+                match self.truth(&left) {
+                    Ok(true) => left,
+                    Ok(false) => self.eval(right),
+                    Err(reason) => {
+                        let right = self.eval(right);
+                        match self.truth(&right) {
+                            Ok(true) => right,
+                            _ => Value::Unknown(reason),
+                        }
+                    }
+                }
+            }
+            Expr::IfElse(condition, value, otherwise) => {
+                let condition = self.eval(condition);
+                match self.truth(&condition) {
+                    Ok(true) => self.eval(value),
+                    Ok(false) => self.eval(otherwise),
+                    Err(reason) => Value::Unknown(reason),
+                }
+            }
+            Expr::Unsupported(reason) => Value::Unknown(reason.clone()),
+        }
+    }
+
+    fn format_fstring(&mut self, text: &str) -> Result<String, String> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::new();
+        let mut i = 0usize;
+        while i < chars.len() {
+            match chars[i] {
+                '{' if chars.get(i + 1) == Some(&'{') => {
+                    out.push('{');
+                    i += 2;
+                }
+                '}' if chars.get(i + 1) == Some(&'}') => {
+                    out.push('}');
+                    i += 2;
+                }
+                '{' => {
+                    let start = i + 1;
+                    let mut depth = 0usize;
+                    let mut end = None;
+                    let mut cut = None;
+                    let mut quote: Option<char> = None;
+                    for (offset, &ch) in chars[start..].iter().enumerate() {
+                        let position = start + offset;
+                        if let Some(open) = quote {
+                            if ch == open {
+                                quote = None;
+                            }
+                            continue;
+                        }
+                        match ch {
+                            '\'' | '"' => quote = Some(ch),
+                            '(' | '[' | '{' => depth += 1,
+                            ')' | ']' => depth = depth.saturating_sub(1),
+                            '}' if depth > 0 => depth -= 1,
+                            '}' => {
+                                end = Some(position);
+                                break;
+                            }
+                            ':' if depth == 0 && cut.is_none() => cut = Some(position),
+                            '!' if depth == 0
+                                && cut.is_none()
+                                && chars.get(position + 1) != Some(&'=') =>
+                            {
+                                cut = Some(position)
+                            }
+                            _ => {}
+                        }
+                    }
+                    let end = end.ok_or_else(|| "unterminated f-string field".to_string())?;
+                    let inner: String = chars[start..cut.unwrap_or(end)].iter().collect();
+                    let expr = parse_expression_text(inner.trim())?;
+                    let value = self.eval(&expr);
+                    out.push_str(&self.str_form(&value)?);
+                    i = end + 1;
+                }
+                ch => {
+                    out.push(ch);
+                    i += 1;
+                }
+            }
+        }
+        Ok(out)
+    }
+
+    fn attr(&mut self, object: Value, name: &str) -> Value {
+        match object {
+            Value::Recipe => match name {
+                "cpp_info" => Value::CppInfo(None),
+                "settings" => Value::Setting(String::new()),
+                "options" => Value::Opt(String::new()),
+                "name" => Value::Str(self.package_name.to_string()),
+                "requires" => Value::Builtin("self.requires".to_string()),
+                _ if IGNORED_RECIPE_ATTRS.contains(&name) => Value::Ignored,
+                _ => {
+                    if let Some(method) = self.methods.get(name).cloned() {
+                        if method.property {
+                            return self.invoke(&method, true, Vec::new(), Vec::new());
+                        }
+                        return Value::Function(format!("self.{name}"));
+                    }
+                    match self.class_attrs.get(name) {
+                        Some(value) => value.clone(),
+                        None => Value::Unknown(format!("unknown attribute `self.{name}`")),
+                    }
+                }
+            },
+            Value::Setting(key) if name == "get_safe" => {
+                Value::BoundMethod(Box::new(Value::Setting(key)), name.to_string())
+            }
+            Value::Setting(key) => Value::Setting(join_key(&key, name)),
+            Value::Opt(key) if key.is_empty() => match name {
+                "get_safe" => Value::BoundMethod(Box::new(Value::Opt(key)), name.to_string()),
+                _ => Value::Opt(name.to_string()),
+            },
+            Value::CppInfo(component) => match name {
+                "components" if component.is_none() => Value::Components,
+                "set_property" | "get_property" => {
+                    Value::BoundMethod(Box::new(Value::CppInfo(component)), name.to_string())
+                }
+                _ if CPP_INFO_LISTS.contains(&name) => Value::CppField(component, name.to_string()),
+                "names" | "filenames" | "build_modules" => Value::Ignored,
+                _ => Value::Unknown(format!("unknown cpp_info attribute `{name}`")),
+            },
+            Value::CppField(component, field) => match name {
+                "append" | "extend" | "insert" | "remove" => Value::BoundMethod(
+                    Box::new(Value::CppField(component, field)),
+                    name.to_string(),
+                ),
+                _ => Value::Unknown(format!("unsupported list method `{name}`")),
+            },
+            Value::Module(module) => {
+                if module == "os" && name == "path" {
+                    Value::Module("os.path".to_string())
+                } else {
+                    Value::Builtin(format!("{module}.{name}"))
+                }
+            }
+            Value::Ignored => Value::Ignored,
+            Value::Unknown(reason) => Value::Unknown(reason),
+            value @ (Value::Str(_) | Value::List(_) | Value::Dict(_)) => {
+                Value::BoundMethod(Box::new(value), name.to_string())
+            }
+            _ => Value::Unknown(format!("unsupported attribute `{name}`")),
+        }
+    }
+
+    fn index(&mut self, object: Value, key: Value) -> Value {
+        if let Value::Unknown(reason) = key {
+            return Value::Unknown(reason);
+        }
+        match object {
+            Value::Components => match key {
+                Value::Str(name) => {
+                    self.cpp_info.fields(&Some(name.clone()));
+                    Value::CppInfo(Some(name))
+                }
+                _ => Value::Unknown("component name is not a string".to_string()),
+            },
+            Value::Opt(prefix) if prefix.is_empty() => match key {
+                Value::Str(name) => Value::Opt(name),
+                _ => Value::Unknown("option name is not a string".to_string()),
+            },
+            Value::Dict(pairs) => pairs
+                .into_iter()
+                .find(|(item, _)| item == &key)
+                .map_or_else(
+                    || Value::Unknown("missing dict key".to_string()),
+                    |(_, value)| value,
+                ),
+            Value::Ignored => Value::Ignored,
+            Value::Unknown(reason) => Value::Unknown(reason),
+            object => match (self.scalar(object), key) {
+                (Value::List(items), Value::Int(index)) => {
+                    let index = if index < 0 {
+                        items.len() as i64 + index
+                    } else {
+                        index
+                    };
+                    usize::try_from(index)
+                        .ok()
+                        .and_then(|index| items.get(index).cloned())
+                        .unwrap_or_else(|| Value::Unknown("index out of range".to_string()))
+                }
+                (Value::Unknown(reason), _) => Value::Unknown(reason),
+                _ => Value::Unknown("unsupported subscript".to_string()),
+            },
+        }
+    }
+
+    fn eval_call(&mut self, function: &Expr, args: &[Expr], kwargs: &[(String, Expr)]) -> Value {
+        // Мутации локальных списков: `libs.append("x")`.
+        if let Expr::Attr(object, method) = function
+            && let Expr::Name(name) = object.as_ref()
+            && matches!(method.as_str(), "append" | "extend")
+            && let Some(Value::List(_)) = self.lookup_local(name)
+        {
+            let arg = args.first().map_or(Value::None, |arg| self.eval(arg));
+            let arg = self.scalar(arg);
+            let added = if method == "append" {
+                Ok(vec![arg])
+            } else {
+                self.iterate(arg)
+            };
+            return match added {
+                Ok(added) => {
+                    if let Some(Value::List(items)) = self.lookup_local_mut(name) {
+                        items.extend(added);
+                    }
+                    Value::None
+                }
+                Err(reason) => Value::Unknown(reason),
+            };
+        }
+
+        let function = self.eval(function);
+        let args: Vec<Value> = args
+            .iter()
+            .map(|arg| {
+                let value = self.eval(arg);
+                self.scalar(value)
+            })
+            .collect();
+        let kwargs: Vec<(String, Value)> = kwargs
+            .iter()
+            .map(|(name, value)| {
+                let value = self.eval(value);
+                (name.clone(), self.scalar(value))
+            })
+            .collect();
+        self.call(function, args, kwargs)
+    }
+
+    fn call(&mut self, function: Value, args: Vec<Value>, kwargs: Vec<(String, Value)>) -> Value {
+        match function {
+            Value::Builtin(name) => self.call_builtin(&name, args),
+            Value::BoundMethod(target, method) => self.call_method(*target, &method, args),
+            Value::Function(name) => {
+                let (function, is_method) = match name.strip_prefix("self.") {
+                    Some(method) => (self.methods.get(method).cloned(), true),
+                    None => (self.functions.get(&name).cloned(), false),
+                };
+                match function {
+                    Some(function) => self.invoke(&function, is_method, args, kwargs),
+                    None => Value::Unknown(format!("unknown function `{name}`")),
+                }
+            }
+            Value::Ignored => Value::Ignored,
+            Value::Unknown(reason) => Value::Unknown(reason),
+            _ => Value::Unknown("call of a non-callable value".to_string()),
+        }
+    }
+
+    fn invoke(
+        &mut self,
+        function: &FunctionDef,
+        is_method: bool,
+        args: Vec<Value>,
+        kwargs: Vec<(String, Value)>,
+    ) -> Value {
+        if self.frames.len() >= MAX_CALL_DEPTH {
+            return Value::Unknown("calls are nested too deep".to_string());
+        }
+        let mut params = function.params.iter();
+        let mut frame = BTreeMap::new();
+        if is_method && let Some((this, _)) = params.next() {
+            frame.insert(this.clone(), Value::Recipe);
+        }
+        let mut args = args.into_iter();
+        for (param, default) in params {
+            let value = match args.next() {
+                Some(value) => value,
+                None => match kwargs.iter().find(|(name, _)| name == param) {
+                    Some((_, value)) => value.clone(),
+                    None => match default {
+                        Some(default) => self.eval(default),
+                        None => Value::Unknown(format!("missing argument `{param}`")),
+                    },
+                },
+            };
+            frame.insert(param.clone(), value);
+        }
+
+        let line = self.line;
+        self.frames.push(frame);
+        let result = match self.exec_block(&function.body) {
+            Flow::Return(value) => value,
+            Flow::Next => Value::None,
+        };
+        self.frames.pop();
+        self.line = line;
+        result
+    }
+
+    fn call_builtin(&mut self, name: &str, args: Vec<Value>) -> Value {
+        let first = args.first().cloned().unwrap_or(Value::None);
+        match name {
+            "self.requires" => match self.str_form(&first) {
+                Ok(reference) => {
+                    push_unique(&mut self.requires, vec![reference]);
+                    Value::None
+                }
+                Err(reason) => Value::Unknown(format!("requirement was skipped, {reason}")),
+            },
+            "os.path.join" => {
+                let parts: Result<Vec<String>, String> =
+                    args.iter().map(|arg| self.str_form(arg)).collect();
+                match parts {
+                    Ok(parts) => Value::Str(parts.join("/")),
+                    Err(reason) => Value::Unknown(reason),
+                }
+            }
+            "str" => match self.str_form(&first) {
+                Ok(value) => Value::Str(value),
+                Err(reason) => Value::Unknown(reason),
+            },
+            "len" => match self.iterate(first) {
+                Ok(items) => Value::Int(items.len() as i64),
+                Err(reason) => Value::Unknown(reason),
+            },
+            "list" | "tuple" | "set" | "sorted" => match self.iterate(first) {
+                Ok(mut items) => {
+                    if name == "sorted" {
+                        items.sort_by_key(|item| self.str_form(item).unwrap_or_default());
+                    }
+                    if name == "set" {
+                        let mut unique = Vec::new();
+                        for item in items {
+                            if !unique.contains(&item) {
+                                unique.push(item);
+                            }
+                        }
+                        items = unique;
+                    }
+                    Value::List(items)
+                }
+                Err(reason) => Value::Unknown(reason),
+            },
+            "bool" => match self.truth(&first) {
+                Ok(value) => Value::Bool(value),
+                Err(reason) => Value::Unknown(reason),
+            },
+            "int" => match self.str_form(&first).map(|value| value.parse::<i64>()) {
+                Ok(Ok(value)) => Value::Int(value),
+                _ => Value::Unknown("cannot convert to int".to_string()),
+            },
+            "is_msvc" | "is_msvc_static_runtime" => {
+                self.setting_in("compiler", &["msvc", "Visual Studio"])
+            }
+            "is_apple_os" => {
+                self.setting_in("os", &["Macos", "iOS", "watchOS", "tvOS", "visionOS"])
+            }
+            "stdcpp_library" => match self
+                .profile
+                .settings
+                .get("compiler.libcxx")
+                .map(String::as_str)
+            {
+                Some("libstdc++" | "libstdc++11") => Value::Str("stdc++".to_string()),
+                Some("libc++") => Value::Str("c++".to_string()),
+                _ => Value::Unknown("`compiler.libcxx` is not set in the profile".to_string()),
+            },
+            _ => Value::Unknown(format!("unknown function `{name}`")),
+        }
+    }
+
+    fn setting_in(&self, key: &str, values: &[&str]) -> Value {
+        match self.profile.settings.get(key) {
+            Some(value) => Value::Bool(values.contains(&value.as_str())),
+            None => Value::Unknown(format!("setting `{key}` is not set in the profile")),
+        }
+    }
+
+    fn call_method(&mut self, target: Value, method: &str, args: Vec<Value>) -> Value {
+        let first = args.first().cloned().unwrap_or(Value::None);
+        match target {
+            Value::CppField(component, field) => {
+                let values = match method {
+                    "append" => self.strings(Value::List(vec![first])),
+                    "insert" => {
+                        self.strings(Value::List(args.get(1).cloned().into_iter().collect()))
+                    }
+                    "extend" => self.strings(first),
+                    _ => match self.str_form(&first) {
+                        Ok(value) => {
+                            self.cpp_info
+                                .list(&component, &field)
+                                .retain(|item| item != &value);
+                            return Value::None;
+                        }
+                        Err(reason) => Err(reason),
+                    },
+                };
+                match values {
+                    Ok(values) => {
+                        push_unique(self.cpp_info.list(&component, &field), values);
+                        Value::None
+                    }
+                    Err(reason) => Value::Unknown(format!("`{field}` was not changed, {reason}")),
+                }
+            }
+            Value::CppInfo(component) => {
+                let Value::Str(property) = first else {
+                    return Value::Unknown("property name is not a string".to_string());
+                };
+                let fields = self.cpp_info.fields(&component);
+                if method == "get_property" {
+                    return fields
+                        .properties
+                        .get(&property)
+                        .map_or(Value::None, |value| Value::Str(value.clone()));
+                }
+                match args.get(1) {
+                    Some(Value::Str(value)) => {
+                        fields.properties.insert(property, value.clone());
+                        Value::None
+                    }
+                    Some(Value::Unknown(reason)) => Value::Unknown(reason.clone()),
+                    // Списки вроде `cmake_build_modules` для .pc и CMake-конфигов не нужны.
+                    _ => Value::None,
+                }
+            }
+            Value::Setting(prefix) => {
+                let Value::Str(name) = first else {
+                    return Value::Unknown("setting name is not a string".to_string());
+                };
+                let key = join_key(&prefix, &name);
+                match self.profile.settings.get(&key) {
+                    Some(value) => Value::Str(value.clone()),
+                    None => Value::Unknown(format!("setting `{key}` is not set in the profile")),
+                }
+            }
+            Value::Opt(_) => {
+                let Value::Str(name) = first else {
+                    return Value::Unknown("option name is not a string".to_string());
+                };
+                match self.options.get(&name) {
+                    Some(Some(value)) => option_value(value),
+                    Some(None) => Value::Unknown(format!("option `{name}` has no default value")),
+                    None => args.get(1).cloned().unwrap_or(Value::None),
+                }
+            }
+            Value::Str(text) => self.call_str_method(&text, method, &args),
+            Value::Dict(pairs) => match method {
+                "get" => pairs
+                    .into_iter()
+                    .find(|(key, _)| key == &first)
+                    .map_or_else(
+                        || args.get(1).cloned().unwrap_or(Value::None),
+                        |(_, value)| value,
+                    ),
+                "keys" => Value::List(pairs.into_iter().map(|(key, _)| key).collect()),
+                "values" => Value::List(pairs.into_iter().map(|(_, value)| value).collect()),
+                "items" => Value::List(
+                    pairs
+                        .into_iter()
+                        .map(|(key, value)| Value::List(vec![key, value]))
+                        .collect(),
+                ),
+                _ => Value::Unknown(format!("unsupported dict method `{method}`")),
+            },
+            _ => Value::Unknown(format!("unsupported method `{method}`")),
+        }
+    }
+
+    fn call_str_method(&mut self, text: &str, method: &str, args: &[Value]) -> Value {
+        let strings: Result<Vec<String>, String> =
+            args.iter().map(|arg| self.str_form(arg)).collect();
+        let strings = match strings {
+            Ok(strings) => strings,
+            Err(reason) => return Value::Unknown(reason),
+        };
+        let arg = |index: usize| strings.get(index).map(String::as_str).unwrap_or_default();
+        match method {
+            "lower" => Value::Str(text.to_lowercase()),
+            "upper" => Value::Str(text.to_uppercase()),
+            "strip" => Value::Str(text.trim().to_string()),
+            "lstrip" => Value::Str(text.trim_start().to_string()),
+            "rstrip" => Value::Str(text.trim_end().to_string()),
+            "replace" => Value::Str(text.replace(arg(0), arg(1))),
+            "startswith" => Value::Bool(text.starts_with(arg(0))),
+            "endswith" => Value::Bool(text.ends_with(arg(0))),
+            "split" if !arg(0).is_empty() => Value::List(
+                text.split(arg(0))
+                    .map(|part| Value::Str(part.to_string()))
+                    .collect(),
+            ),
+            "split" => Value::List(
+                text.split_whitespace()
+                    .map(|part| Value::Str(part.to_string()))
+                    .collect(),
+            ),
+            "format" => {
+                let mut out = String::new();
+                let mut positional = strings.iter();
+                let mut rest = text;
+                while let Some(start) = rest.find("{}") {
+                    out.push_str(&rest[..start]);
+                    match positional.next() {
+                        Some(value) => out.push_str(value),
+                        None => return Value::Unknown("unsupported str.format".to_string()),
+                    }
+                    rest = &rest[start + 2..];
+                }
+                out.push_str(rest);
+                if out.contains('{') {
+                    return Value::Unknown("unsupported str.format".to_string());
+                }
+                Value::Str(out)
+            }
+            "join" => match args.first().cloned().map(|value| self.strings(value)) {
+                Some(Ok(items)) => Value::Str(items.join(text)),
+                Some(Err(reason)) => Value::Unknown(reason),
+                None => Value::Unknown("missing argument of str.join".to_string()),
+            },
+            _ => Value::Unknown(format!("unsupported str method `{method}`")),
+        }
+    }
+
+    fn binary(&mut self, op: &str, left: Value, right: Value) -> Value {
+        let left = self.scalar(left);
+        let right = self.scalar(right);
+        if let Value::Unknown(reason) = &left {
+            return Value::Unknown(reason.clone());
+        }
+        if let Value::Unknown(reason) = &right {
+            return Value::Unknown(reason.clone());
+        }
+        match op {
+            "==" => Value::Bool(values_equal(&left, &right)),
+            "!=" => Value::Bool(!values_equal(&left, &right)),
+            "is" => Value::Bool(left == right),
+            "is not" => Value::Bool(left != right),
+            "in" | "not in" => {
+                let contained = match (&left, &right) {
+                    (Value::Str(needle), Value::Str(haystack)) => {
+                        Ok(haystack.contains(needle.as_str()))
+                    }
+                    _ => self
+                        .iterate(right.clone())
+                        .map(|items| items.iter().any(|item| values_equal(&left, item))),
+                };
+                match contained {
+                    Ok(contained) => Value::Bool(contained == (op == "in")),
+                    Err(reason) => Value::Unknown(reason),
+                }
+            }
+            "<" | ">" | "<=" | ">=" => match (&left, &right) {
+                (Value::Int(a), Value::Int(b)) => Value::Bool(match op {
+                    "<" => a < b,
+                    ">" => a > b,
+                    "<=" => a <= b,
+                    _ => a >= b,
+                }),
+                _ => Value::Unknown(format!(
+                    "comparison `{op}` is not supported for these values"
+                )),
+            },
+            "+" => match (left, right) {
+                (Value::Str(a), Value::Str(b)) => Value::Str(a + &b),
+                (Value::List(mut a), Value::List(b)) => {
+                    a.extend(b);
+                    Value::List(a)
+                }
+                (Value::Int(a), Value::Int(b)) => Value::Int(a + b),
+                _ => Value::Unknown("unsupported operands of `+`".to_string()),
+            },
+            "-" => match (left, right) {
+                (Value::Int(a), Value::Int(b)) => Value::Int(a - b),
+                _ => Value::Unknown("unsupported operands of `-`".to_string()),
+            },
+            "*" => match (left, right) {
+                (Value::Int(a), Value::Int(b)) => Value::Int(a * b),
+                _ => Value::Unknown("unsupported operands of `*`".to_string()),
+            },
+            _ => Value::Unknown(format!("operator `{op}` is not supported")),
+        }
+    }
+
+    /// Раскрывает ссылки на settings, options и поля `cpp_info` в обычные значения.
+    fn scalar(&mut self, value: Value) -> Value {
+        match value {
+            Value::Setting(key) if !key.is_empty() => match self.profile.settings.get(&key) {
+                Some(value) => Value::Str(value.clone()),
+                None => Value::Unknown(format!("setting `{key}` is not set in the profile")),
+            },
+            Value::Opt(name) if !name.is_empty() => match self.options.get(&name) {
+                Some(Some(value)) => option_value(value),
+                Some(None) => Value::Unknown(format!("option `{name}` has no default value")),
+                None => Value::Unknown(format!("option `{name}` is not declared")),
+            },
+            Value::CppField(component, field) => Value::List(
+                self.cpp_info
+                    .list(&component, &field)
+                    .iter()
+                    .map(|item| Value::Str(item.clone()))
+                    .collect(),
+            ),
+            value => value,
+        }
+    }
+
+    fn truth(&mut self, value: &Value) -> Result<bool, String> {
+        match self.scalar(value.clone()) {
+            Value::None => Ok(false),
+            Value::Bool(value) => Ok(value),
+            Value::Int(value) => Ok(value != 0),
+            Value::Str(value) => Ok(!value.is_empty()),
+            Value::List(items) => Ok(!items.is_empty()),
+            Value::Dict(pairs) => Ok(!pairs.is_empty()),
+            Value::Unknown(reason) => Err(reason),
+            Value::Ignored => Err("value is not tracked".to_string()),
+            _ => Ok(true),
+        }
+    }
+
+    fn str_form(&mut self, value: &Value) -> Result<String, String> {
+        match self.scalar(value.clone()) {
+            Value::Str(value) => Ok(value),
+            Value::Int(value) => Ok(value.to_string()),
+            Value::Bool(true) => Ok("True".to_string()),
+            Value::Bool(false) => Ok("False".to_string()),
+            Value::None => Ok("None".to_string()),
+            Value::Unknown(reason) => Err(reason),
+            _ => Err("value is not a string".to_string()),
+        }
+    }
+
+    fn strings(&mut self, value: Value) -> Result<Vec<String>, String> {
+        let items = self.iterate(value)?;
+        items.iter().map(|item| self.str_form(item)).collect()
+    }
+
+    fn iterate(&mut self, value: Value) -> Result<Vec<Value>, String> {
+        match self.scalar(value) {
+            Value::List(items) => Ok(items),
+            Value::Dict(pairs) => Ok(pairs.into_iter().map(|(key, _)| key).collect()),
+            Value::Unknown(reason) => Err(reason),
+            _ => Err("value is not iterable".to_string()),
+        }
+    }
+}
+
+fn push_unique(list: &mut Vec<String>, values: Vec<String>) {
+    for value in values {
+        if !value.is_empty() && !list.contains(&value) {
+            list.push(value);
+        }
+    }
+}
+
+fn join_key(prefix: &str, name: &str) -> String {
+    if prefix.is_empty() {
+        name.to_string()
+    } else {
+        format!("{prefix}.{name}")
+    }
+}
+
+/// Значения options сравниваются как строки, но `True`/`False`/`None` ведут себя как в Conan.
+fn option_value(value: &str) -> Value {
+    match value {
+        "True" => Value::Bool(true),
+        "False" => Value::Bool(false),
+        "None" => Value::None,
+        _ => Value::Str(value.to_string()),
+    }
+}
+
+fn values_equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Bool(flag), Value::Str(text)) | (Value::Str(text), Value::Bool(flag)) => {
+            text == if *flag { "True" } else { "False" }
+        }
+        (Value::List(a), Value::List(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| values_equal(a, b))
+        }
+        _ => left == right,
+    }
+}
+
+fn build_info(fields: &mut CppInfoFields) -> CppBuildInfo {
+    let mut take = |field: &str| fields.lists.remove(field);
+    CppBuildInfo {
+        includedirs: take("includedirs"),
+        libdirs: take("libdirs"),
+        bindirs: take("bindirs"),
+        defines: take("defines").unwrap_or_default(),
+        cflags: take("cflags").unwrap_or_default(),
+        cxxflags: take("cxxflags").unwrap_or_default(),
+        sharedlinkflags: take("sharedlinkflags").unwrap_or_default(),
+        exelinkflags: take("exelinkflags").unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RecipeProfile, Tok, analyze_recipe, tokenize};
+
+    const RECIPE: &str = r#"
+import os
+from conan import ConanFile
+from conan.tools.build import stdcpp_library
+from conan.tools.files import collect_libs
+
+COMPONENTS = ["core", "net"]
+
+
+class DemoConan(ConanFile):
+    name = "demo"
+    package_type = "library"
+    options = {"shared": [True, False], "with_ssl": [True, False]}
+    default_options = {"shared": False, "with_ssl": True, "zlib/*:shared": True}
+
+    @property
+    def _lib_prefix(self):
+        return "demo"
+
+    def requirements(self):
+        self.requires("zlib/1.3.1")
+        if self.options.with_ssl:
+            self.requires("openssl/3.2.0")
+        if self.settings.os == "Windows":
+            self.requires("winapi/1.0")
+
+    def _add_component(self, name, requires=None):
+        component = self.cpp_info.components[name]
+        component.libs = [f"{self._lib_prefix}_{name}"]
+        component.requires = requires or []
+        component.set_property("pkg_config_name", f"{self.name}-{name}")
+        return component
+
+    def package_info(self):
+        for name in COMPONENTS:
+            self._add_component(name)
+        self.cpp_info.components["net"].requires.append("core")
+        self.cpp_info.components["core"].includedirs = [os.path.join("include", "demo")]
+        if not self.options.shared:
+            self.cpp_info.components["core"].defines.append("DEMO_STATIC")
+        if self.settings.os in ["Linux", "FreeBSD"]:
+            self.cpp_info.components["core"].system_libs.extend(["pthread", "m"])
+            self.cpp_info.components["core"].system_libs.append(stdcpp_library(self))
+        elif self.settings.os == "Windows":
+            self.cpp_info.components["core"].system_libs = ["ws2_32"]
+        if self.settings.arch == "armv7":
+            self.cpp_info.components["core"].system_libs.append("atomic")
+        self.cpp_info.components["net"].system_libs = [
+            lib for lib in ["dl", "rt"] if lib != "rt"
+        ]
+"#;
+
+    #[test]
+    fn evaluates_package_info_for_profile() {
+        let recipe = analyze_recipe("demo", RECIPE, &RecipeProfile::aurora(Some("armv7")));
+        let info = recipe.cpp_info;
+        assert!(!info.is_header_only);
+        assert_eq!(info.components.len(), 2);
+
+        let core = &info.components[0];
+        assert_eq!(core.name, "core");
+        assert_eq!(core.libs, vec!["demo_core"]);
+        assert_eq!(core.pkg_config_name.as_deref(), Some("demo-core"));
+        assert_eq!(core.system_libs, vec!["pthread", "m", "stdc++", "atomic"]);
+        assert_eq!(
+            core.build_info.includedirs,
+            Some(vec!["include/demo".to_string()])
+        );
+        assert_eq!(core.build_info.defines, vec!["DEMO_STATIC"]);
+
+        let net = &info.components[1];
+        assert_eq!(net.requires, vec!["core"]);
+        assert_eq!(net.system_libs, vec!["dl"]);
+        assert!(recipe.unsupported.is_empty(), "{:?}", recipe.unsupported);
+    }
+
+    #[test]
+    fn options_from_profile_override_defaults() {
+        let mut profile = RecipeProfile::aurora(Some("armv8"));
+        profile
+            .options
+            .insert("with_ssl".to_string(), "False".to_string());
+        profile
+            .options
+            .insert("shared".to_string(), "True".to_string());
+
+        let recipe = analyze_recipe("demo", RECIPE, &profile);
+        assert_eq!(recipe.requires, vec!["zlib/1.3.1"]);
+        let core = &recipe.cpp_info.components[0];
+        assert!(core.build_info.defines.is_empty());
+        assert_eq!(core.system_libs, vec!["pthread", "m", "stdc++"]);
+    }
+
+    #[test]
+    fn reports_constructs_it_cannot_evaluate() {
+        let conanfile = r#"
+class Demo(ConanFile):
+    def package_info(self):
+        self.cpp_info.libs = collect_libs(self)
+        if self.settings.arch == "armv8":
+            self.cpp_info.system_libs = ["atomic"]
+        else:
+            self.cpp_info.system_libs.append("m")
+        while True:
+            pass
+"#;
+        let recipe = analyze_recipe("demo", conanfile, &RecipeProfile::aurora(None));
+        assert!(recipe.cpp_info.libs.is_empty());
+        // Неизвестное условие: учитываются все ветки.
+        assert_eq!(recipe.cpp_info.system_libs, vec!["atomic", "m"]);
+        assert_eq!(
+            recipe.unsupported,
+            vec![
+                "line 4: `libs` was not changed, unknown name `collect_libs`: self.cpp_info.libs = collect_libs(self)",
+                "line 5: condition is unknown for the profile (setting `arch` is not set in the profile), all branches were taken: if self.settings.arch == \"armv8\":",
+                "line 9: `while` loops are not supported: while True:",
+            ]
+        );
+    }
+
+    #[test]
+    fn collects_class_requires_and_tolerates_syntax_outside_walked_methods() {
+        let conanfile = r#"
+class Demo(ConanFile):
+    requires = "zlib/1.3.1", "bzip2/1.0.8"
+    license = ("MIT", "BSL-1.0")
+    package_type = "header-library"
+
+    def build(self):
+        with chdir(self, self.source_folder):
+            run = lambda cmd: self.run(cmd)
+        data = {k: v for k, v in items}
+
+    def package_info(self):
+        self.cpp_info.bindirs = []
+        self.cpp_info.libdirs = []
+"#;
+        let recipe = analyze_recipe("demo", conanfile, &RecipeProfile::aurora(None));
+        assert_eq!(recipe.requires, vec!["zlib/1.3.1", "bzip2/1.0.8"]);
+        assert_eq!(recipe.license, vec!["MIT", "BSL-1.0"]);
+        assert!(recipe.cpp_info.is_header_only);
+        assert_eq!(recipe.cpp_info.build_info.libdirs, Some(Vec::new()));
+        assert!(recipe.unsupported.is_empty(), "{:?}", recipe.unsupported);
+    }
+
+    #[test]
+    fn tokenizes_triple_quoted_and_continued_strings() {
+        let tokens = tokenize("x = '''a\n\"b\"\n'''\ny = 1\n").expect("valid source");
+        assert_eq!(tokens[2], (Tok::Str("a\n\"b\"\n".to_string(), false), 1));
+        assert_eq!(tokens[4], (Tok::Name("y".to_string()), 4));
+
+        let conanfile = r#"
+class Demo(ConanFile):
+    description = """Demo library.
+
+    Spans several lines."""
+
+    def package_info(self):
+        self.cpp_info.libs = ["demo" \
+            "_core", 'it\'s', r"c\d"]
+        name = "a" \
+               "b"
+        self.cpp_info.system_libs = [name, f"{name}" "c"]
+        while True:
+            pass
+"#;
+        let recipe = analyze_recipe("demo", conanfile, &RecipeProfile::aurora(None));
+        assert_eq!(recipe.cpp_info.libs, vec!["demo_core", "it's", "c\\d"]);
+        assert_eq!(recipe.cpp_info.system_libs, vec!["ab", "abc"]);
+        // Номера строк учитывают переводы строк внутри литералов и продолжения `\`
+        assert_eq!(
+            recipe.unsupported,
+            vec!["line 13: `while` loops are not supported: while True:"]
+        );
+    }
+
+    #[test]
+    fn evaluates_dict_literals_and_comprehensions() {
+        let conanfile = r#"
+LIBS = {
+    "core": "demo_core",
+    "net": "demo_net",
+}
+
+class Demo(ConanFile):
+    def package_info(self):
+        deps = {"net": ["core"]}
+        for name, lib in LIBS.items():
+            self.cpp_info.components[name].libs = [lib]
+            self.cpp_info.components[name].requires = deps.get(name, [])
+        self.cpp_info.system_libs = [lib for lib in ("m", "dl", "rt") if lib != "dl"]
+        self.cpp_info.defines = [f"{k.upper()}={v}" for k, v in {"a": "1"}.items()]
+"#;
+        let recipe = analyze_recipe("demo", conanfile, &RecipeProfile::aurora(None));
+        let components = &recipe.cpp_info.components;
+        assert_eq!(components.len(), 2);
+        assert_eq!(components[0].libs, vec!["demo_core"]);
+        assert!(components[0].requires.is_empty());
+        assert_eq!(components[1].libs, vec!["demo_net"]);
+        assert_eq!(components[1].requires, vec!["core"]);
+        assert_eq!(recipe.cpp_info.system_libs, vec!["m", "rt"]);
+        assert_eq!(recipe.cpp_info.build_info.defines, vec!["A=1"]);
+        assert!(recipe.unsupported.is_empty(), "{:?}", recipe.unsupported);
+    }
+
+    #[test]
+    fn runs_try_with_and_property_bodies() {
+        let conanfile = r#"
+class Demo(ConanFile):
+    @property
+    def _system_libs(self):
+        libs = ["m"]
+        if self.settings.os == "Linux":
+            libs.append("pthread")
+        return libs
+
+    def package_info(self):
+        try:
+            self.cpp_info.libs = ["demo"]
+        except ConanException:
+            self.cpp_info.libs = ["fallback"]
+        finally:
+            pass
+        with chdir(self, self.package_folder):
+            self.cpp_info.system_libs = self._system_libs
+"#;
+        let recipe = analyze_recipe("demo", conanfile, &RecipeProfile::aurora(None));
+        assert_eq!(recipe.cpp_info.libs, vec!["demo"]);
+        assert_eq!(recipe.cpp_info.system_libs, vec!["m", "pthread"]);
+        assert!(recipe.unsupported.is_empty(), "{:?}", recipe.unsupported);
+    }
+
+    #[test]
+    fn skips_unparsable_statement_with_its_block() {
+        let conanfile = r#"
+class Demo(ConanFile):
+    def package_info(self):
+        async def helper():
+            self.cpp_info.libs = ["never"]
+            if True:
+                self.cpp_info.libs = ["nested"]
+        self.cpp_info.libs = ["demo"]
+        yield self.cpp_info
+        self.cpp_info.system_libs = ["m"]
+"#;
+        let recipe = analyze_recipe("demo", conanfile, &RecipeProfile::aurora(None));
+        assert_eq!(recipe.cpp_info.libs, vec!["demo"]);
+        assert_eq!(recipe.cpp_info.system_libs, vec!["m"]);
+        assert_eq!(
+            recipe.unsupported,
+            vec![
+                "line 4: `async` is not supported: async def helper():",
+                "line 9: `yield` is not supported: yield self.cpp_info",
+            ]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_empty_info_on_unsupported_source() {
+        // Экранированные переводы строк (`\n` внутри одной строки) — не Python:
+        // разбор не падает, а сообщает о первой непонятной позиции
+        let escaped = r#"def requirements(self):\n    self.requires("x/1.0.0@aurora")\n"#;
+        let recipe = analyze_recipe("demo", escaped, &RecipeProfile::aurora(None));
+        assert!(recipe.requires.is_empty());
+        assert_eq!(recipe.cpp_info.package_name, "demo");
+        assert_eq!(recipe.unsupported.len(), 1);
+        assert!(
+            recipe.unsupported[0].starts_with("line 1: unexpected character `\\`"),
+            "{:?}",
+            recipe.unsupported
+        );
+
+        for source in ["x = 'unterminated\n", "class Demo:\n    x = 1\n  y = 2\n"] {
+            let recipe = analyze_recipe("demo", source, &RecipeProfile::aurora(None));
+            assert_eq!(recipe.unsupported.len(), 1, "{source:?}");
+            assert!(recipe.cpp_info.libs.is_empty());
+        }
+    }
+}
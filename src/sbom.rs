@@ -0,0 +1,455 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+
+use crate::licenses::NOASSERTION;
+use crate::model::LockedPackage;
+
+const TOOL_NAME: &str = "aurora-conan-cli";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SbomFormat {
+    CycloneDx,
+    Spdx,
+}
+
+impl SbomFormat {
+    pub fn parse(value: &str) -> Option<SbomFormat> {
+        match value {
+            "cyclonedx" => Some(SbomFormat::CycloneDx),
+            "spdx" => Some(SbomFormat::Spdx),
+            _ => None,
+        }
+    }
+}
+
+/// Приложение, для которого строится SBOM, и его прямые зависимости.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SbomSubject {
+    pub name: String,
+    pub version: String,
+    /// Прямые зависимости в виде `name/version`.
+    pub requires: Vec<String>,
+}
+
+/// SBOM в JSON: граф пакетов с лицензиями, бинарниками и рёбрами зависимостей.
+pub fn render(
+    format: SbomFormat,
+    subject: &SbomSubject,
+    packages: &[LockedPackage],
+    created: &str,
+) -> String {
+    let document = match format {
+        SbomFormat::CycloneDx => cyclonedx(subject, packages, created),
+        SbomFormat::Spdx => spdx(subject, packages, created),
+    };
+    let mut text = serde_json::to_string_pretty(&document).unwrap_or_default();
+    text.push('\n');
+    text
+}
+
+/// Время создания документа: `SOURCE_DATE_EPOCH` для воспроизводимых сборок, иначе текущее.
+pub fn creation_timestamp() -> String {
+    let seconds = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|duration| duration.as_secs())
+                .unwrap_or_default()
+        });
+    format_timestamp(seconds)
+}
+
+/// RFC 3339 в UTC по числу секунд от эпохи.
+fn format_timestamp(seconds: u64) -> String {
+    let days = (seconds / 86_400) as i64;
+    let rest = seconds % 86_400;
+    // Алгоритм civil_from_days (Howard Hinnant)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+fn label(package: &LockedPackage) -> String {
+    format!("{}/{}", package.name, package.version)
+}
+
+fn recipe_revision(package: &LockedPackage) -> Option<&str> {
+    package
+        .binaries
+        .iter()
+        .find_map(|binary| binary.origin.recipe_revision.as_deref())
+}
+
+/// `pkg:conan/<name>@<version>?user=<user>&rrev=<rrev>`.
+fn purl(package: &LockedPackage) -> String {
+    let mut qualifiers = Vec::new();
+    if !package.user.is_empty() {
+        qualifiers.push(format!("user={}", package.user));
+    }
+    if let Some(rrev) = recipe_revision(package) {
+        qualifiers.push(format!("rrev={rrev}"));
+    }
+    let mut purl = format!("pkg:conan/{}@{}", package.name, package.version);
+    if !qualifiers.is_empty() {
+        purl.push('?');
+        purl.push_str(&qualifiers.join("&"));
+    }
+    purl
+}
+
+fn cyclonedx(subject: &SbomSubject, packages: &[LockedPackage], created: &str) -> Value {
+    let components: Vec<Value> = packages
+        .iter()
+        .map(|package| {
+            let mut component = json!({
+                "type": "library",
+                "bom-ref": label(package),
+                "name": package.name,
+                "version": package.version,
+                "purl": purl(package),
+            });
+            if package.license != NOASSERTION {
+                component["licenses"] = json!([{ "expression": package.license }]);
+            }
+            let mut properties = Vec::new();
+            if let Some(rrev) = recipe_revision(package) {
+                properties.push(json!({ "name": "conan:recipe_revision", "value": rrev }));
+            }
+            let mut references = Vec::new();
+            for binary in &package.binaries {
+                if let Some(package_id) = &binary.origin.package_id {
+                    properties.push(json!({
+                        "name": format!("conan:package_id:{}", binary.arch),
+                        "value": package_id,
+                    }));
+                }
+                if let Some(prev) = &binary.origin.package_revision {
+                    properties.push(json!({
+                        "name": format!("conan:package_revision:{}", binary.arch),
+                        "value": prev,
+                    }));
+                }
+                if let Some(url) = &binary.origin.url {
+                    references.push(json!({
+                        "type": "distribution",
+                        "url": url,
+                        "comment": binary.arch,
+                        "hashes": [{ "alg": "SHA-256", "content": binary.sha256 }],
+                    }));
+                }
+            }
+            if !properties.is_empty() {
+                component["properties"] = Value::Array(properties);
+            }
+            if !references.is_empty() {
+                component["externalReferences"] = Value::Array(references);
+            }
+            component
+        })
+        .collect();
+
+    let mut dependencies = vec![json!({ "ref": subject.name, "dependsOn": subject.requires })];
+    dependencies.extend(
+        packages
+            .iter()
+            .map(|package| json!({ "ref": label(package), "dependsOn": package.requires })),
+    );
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": created,
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": TOOL_NAME,
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+            "component": {
+                "type": "application",
+                "bom-ref": subject.name,
+                "name": subject.name,
+                "version": subject.version,
+            },
+        },
+        "components": components,
+        "dependencies": dependencies,
+    })
+}
+
+/// Идентификатор элемента SPDX: буквы, цифры, `.` и `-`.
+fn spdx_id(parts: &[&str]) -> String {
+    let raw = parts.join("-");
+    let sanitized: String = raw
+        .chars()
+        .map(|ch| {
+            if ch.is_ascii_alphanumeric() || ch == '.' || ch == '-' {
+                ch
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-Package-{sanitized}")
+}
+
+fn spdx(subject: &SbomSubject, packages: &[LockedPackage], created: &str) -> Value {
+    let subject_id = spdx_id(&[&subject.name]);
+    let package_id = |reference: &str| {
+        let (name, version) = reference.split_once('/').unwrap_or((reference, ""));
+        spdx_id(&[name, version])
+    };
+
+    let mut elements = vec![json!({
+        "SPDXID": subject_id,
+        "name": subject.name,
+        "versionInfo": subject.version,
+        "downloadLocation": NOASSERTION,
+        "filesAnalyzed": false,
+        "licenseConcluded": NOASSERTION,
+        "licenseDeclared": NOASSERTION,
+        "copyrightText": NOASSERTION,
+        "primaryPackagePurpose": "APPLICATION",
+    })];
+    let mut relationships = vec![json!({
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": subject_id,
+    })];
+    for reference in &subject.requires {
+        relationships.push(json!({
+            "spdxElementId": subject_id,
+            "relationshipType": "DEPENDS_ON",
+            "relatedSpdxElement": package_id(reference),
+        }));
+    }
+
+    // Рецепт — отдельный пакет, бинарник под каждую архитектуру — его вариант со ссылкой и хешем
+    for package in packages {
+        let id = package_id(&label(package));
+        let mut element = json!({
+            "SPDXID": id,
+            "name": package.name,
+            "versionInfo": package.version,
+            "downloadLocation": NOASSERTION,
+            "filesAnalyzed": false,
+            "licenseConcluded": NOASSERTION,
+            "licenseDeclared": package.license,
+            "copyrightText": NOASSERTION,
+            "primaryPackagePurpose": "LIBRARY",
+            "externalRefs": [{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": purl(package),
+            }],
+        });
+        if let Some(rrev) = recipe_revision(package) {
+            element["comment"] = json!(format!("Conan recipe revision {rrev}"));
+        }
+        elements.push(element);
+        for reference in &package.requires {
+            relationships.push(json!({
+                "spdxElementId": id,
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": package_id(reference),
+            }));
+        }
+
+        for binary in &package.binaries {
+            let binary_id = spdx_id(&[&package.name, &package.version, &binary.arch]);
+            let mut comment = vec![format!("arch {}", binary.arch)];
+            if let Some(package_id) = &binary.origin.package_id {
+                comment.push(format!("package_id {package_id}"));
+            }
+            if let Some(prev) = &binary.origin.package_revision {
+                comment.push(format!("package revision {prev}"));
+            }
+            elements.push(json!({
+                "SPDXID": binary_id,
+                "name": format!("{} ({})", package.name, binary.arch),
+                "versionInfo": package.version,
+                "downloadLocation": binary.origin.url.as_deref().unwrap_or(NOASSERTION),
+                "filesAnalyzed": false,
+                "checksums": [{ "algorithm": "SHA256", "checksumValue": binary.sha256 }],
+                "licenseConcluded": NOASSERTION,
+                "licenseDeclared": package.license,
+                "copyrightText": NOASSERTION,
+                "primaryPackagePurpose": "ARCHIVE",
+                "comment": comment.join(", "),
+            }));
+            relationships.push(json!({
+                "spdxElementId": binary_id,
+                "relationshipType": "VARIANT_OF",
+                "relatedSpdxElement": id,
+            }));
+        }
+    }
+
+    // Пространство имён документа однозначно определяется графом пакетов
+    let mut digest = Sha256::new();
+    for package in packages {
+        digest.update(purl(package).as_bytes());
+        for binary in &package.binaries {
+            digest.update(binary.sha256.as_bytes());
+        }
+    }
+    let fingerprint: String = digest
+        .finalize()
+        .iter()
+        .take(8)
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": format!("{}-{}", subject.name, subject.version),
+        "documentNamespace": format!(
+            "https://spdx.org/spdxdocs/{}-{}-{}",
+            subject.name, subject.version, fingerprint
+        ),
+        "creationInfo": {
+            "created": created,
+            "creators": [format!("Tool: {}-{}", TOOL_NAME, env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": elements,
+        "relationships": relationships,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::{SbomFormat, SbomSubject, format_timestamp, render};
+    use crate::model::{BinaryOrigin, LockedBinary, LockedPackage};
+
+    fn packages() -> Vec<LockedPackage> {
+        vec![
+            LockedPackage {
+                name: "openssl".to_string(),
+                version: "3.2.0".to_string(),
+                user: "aurora".to_string(),
+                license: "Apache-2.0".to_string(),
+                requires: vec!["zlib/1.3.1".to_string()],
+                binaries: vec![LockedBinary {
+                    arch: "armv8".to_string(),
+                    shared: Some(true),
+                    sha256: "ab".repeat(32),
+                    origin: BinaryOrigin::from_url(
+                        "https://conan.omp.ru/artifactory/public/openssl/3.2.0/_/r1/package/p1/v1/conan_package.tgz",
+                    ),
+                }],
+            },
+            LockedPackage {
+                name: "zlib".to_string(),
+                version: "1.3.1".to_string(),
+                user: "aurora".to_string(),
+                license: "NOASSERTION".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn subject() -> SbomSubject {
+        SbomSubject {
+            name: "ru.auroraos.TestApp".to_string(),
+            version: "0.1".to_string(),
+            requires: vec!["openssl/3.2.0".to_string()],
+        }
+    }
+
+    #[test]
+    fn formats_rfc3339_timestamps() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_709_251_199), "2024-02-29T23:59:59Z");
+    }
+
+    #[test]
+    fn renders_cyclonedx_components_and_dependencies() {
+        let text = render(
+            SbomFormat::CycloneDx,
+            &subject(),
+            &packages(),
+            "2024-01-01T00:00:00Z",
+        );
+        let bom: Value = serde_json::from_str(&text).expect("valid JSON");
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        let openssl = &bom["components"][0];
+        assert_eq!(
+            openssl["purl"],
+            "pkg:conan/openssl@3.2.0?user=aurora&rrev=r1"
+        );
+        assert_eq!(openssl["licenses"][0]["expression"], "Apache-2.0");
+        assert_eq!(
+            openssl["externalReferences"][0]["hashes"][0]["content"],
+            "ab".repeat(32)
+        );
+        assert_eq!(openssl["properties"][1]["name"], "conan:package_id:armv8");
+        assert_eq!(openssl["properties"][1]["value"], "p1");
+        assert!(bom["components"][1].get("licenses").is_none());
+        assert_eq!(bom["dependencies"][0]["dependsOn"][0], "openssl/3.2.0");
+        assert_eq!(bom["dependencies"][1]["dependsOn"][0], "zlib/1.3.1");
+    }
+
+    #[test]
+    fn renders_spdx_packages_binaries_and_relationships() {
+        let text = render(
+            SbomFormat::Spdx,
+            &subject(),
+            &packages(),
+            "2024-01-01T00:00:00Z",
+        );
+        let document: Value = serde_json::from_str(&text).expect("valid JSON");
+        assert_eq!(document["spdxVersion"], "SPDX-2.3");
+        let binary = document["packages"]
+            .as_array()
+            .and_then(|items| {
+                items
+                    .iter()
+                    .find(|item| item["SPDXID"] == "SPDXRef-Package-openssl-3.2.0-armv8")
+            })
+            .expect("binary package");
+        assert_eq!(binary["checksums"][0]["checksumValue"], "ab".repeat(32));
+        assert!(
+            binary["downloadLocation"]
+                .as_str()
+                .is_some_and(|url| url.ends_with("/p1/v1/conan_package.tgz"))
+        );
+        let relationships = document["relationships"].as_array().expect("relationships");
+        assert!(relationships.iter().any(|item| {
+            item["spdxElementId"] == "SPDXRef-Package-openssl-3.2.0"
+                && item["relationshipType"] == "DEPENDS_ON"
+                && item["relatedSpdxElement"] == "SPDXRef-Package-zlib-1.3.1"
+        }));
+        assert!(relationships.iter().any(|item| {
+            item["spdxElementId"] == "SPDXRef-Package-ru.auroraos.TestApp"
+                && item["relatedSpdxElement"] == "SPDXRef-Package-openssl-3.2.0"
+        }));
+    }
+}
@@ -0,0 +1,390 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow};
+use regex::Regex;
+
+use crate::clear_store;
+use crate::elf;
+use crate::files;
+use crate::model::{SpecConfig, dependency_closure};
+
+const BUILTIN_ALLOWED_LIBRARIES: &str = include_str!("../data/allowed-libraries.txt");
+const PROJECT_ALLOWED_LIBRARIES: &str = "allowed-libraries.txt";
+const PROVIDES_EXCLUDE_DIR: &str = "%{_datadir}/%{name}/lib";
+
+/// Нарушение правил валидатора магазина для поставляемых библиотек.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub arch: Option<String>,
+    pub message: String,
+}
+
+impl Violation {
+    fn global(message: String) -> Self {
+        Self {
+            arch: None,
+            message,
+        }
+    }
+
+    fn for_arch(arch: &str, message: String) -> Self {
+        Self {
+            arch: Some(arch.to_string()),
+            message,
+        }
+    }
+}
+
+/// Загружает список разрешённых библиотек: явный файл, затем файл проекта, затем встроенный список.
+pub fn load_allowed_libraries(project_root: &Path, explicit: Option<&Path>) -> Result<String> {
+    let project_file = clear_store::thirdparty_root(project_root).join(PROJECT_ALLOWED_LIBRARIES);
+    let path = match explicit {
+        Some(path) => path.to_path_buf(),
+        None if project_file.exists() => project_file,
+        None => return Ok(BUILTIN_ALLOWED_LIBRARIES.to_string()),
+    };
+
+    fs::read_to_string(&path).with_context(|| {
+        format!(
+            "Не удалось прочитать список разрешённых библиотек {}",
+            path.display()
+        )
+    })
+}
+
+/// Проверяет поставляемые `.so` и `%define` в .spec по правилам валидатора магазина.
+///
+/// `__requires_exclude` каждого .spec сверяется только с библиотеками его пакетов и их
+/// транзитивных зависимостей (`dependencies`), как их отбирает `update_spec_clear`.
+pub fn validate_project(
+    project_root: &Path,
+    specs: &[SpecConfig],
+    dependencies: &BTreeMap<String, Vec<String>>,
+    allowed: &str,
+) -> Result<Vec<Violation>> {
+    let mut violations = Vec::new();
+    // .spec с корректным __requires_exclude: префикс сообщений, пакеты (пусто — все) и regex
+    let mut requires_exclude = Vec::new();
+
+    for spec in specs {
+        let spec_path = project_root.join(&spec.path);
+        // Для одного .spec сообщения остаются без префикса.
+        let prefix = if specs.len() > 1 {
+            format!("{}: ", spec.path.display())
+        } else {
+            String::new()
+        };
+
+        let provides_exclude_from = files::read_spec_define(&spec_path, "__provides_exclude_from")?;
+        match provides_exclude_from.as_deref() {
+            None => violations.push(Violation::global(format!(
+                "{prefix}В .spec не задан %define __provides_exclude_from: RPM будет предоставлять поставляемые библиотеки"
+            ))),
+            Some(value) if !value.contains(PROVIDES_EXCLUDE_DIR) => {
+                violations.push(Violation::global(format!(
+                    "{prefix}__provides_exclude_from '{}' не покрывает каталог {}",
+                    value, PROVIDES_EXCLUDE_DIR
+                )))
+            }
+            Some(_) => {}
+        }
+
+        match files::read_spec_define(&spec_path, "__requires_exclude")? {
+            Some(value) => match compile_rpm_regex(&value) {
+                Ok(re) => requires_exclude.push((
+                    prefix,
+                    dependency_closure(dependencies, &spec.packages),
+                    re,
+                )),
+                Err(error) => violations.push(Violation::global(format!(
+                    "{prefix}__requires_exclude '{}' не является корректным регулярным выражением: {}",
+                    value, error
+                ))),
+            },
+            None => violations.push(Violation::global(format!(
+                "{prefix}В .spec не задан %define __requires_exclude"
+            ))),
+        }
+    }
+
+    let mut checked_any = false;
+    for arch in clear_store::supported_arches() {
+        let packages = clear_store::installed_packages(project_root, arch)?;
+        if packages.is_empty() {
+            continue;
+        }
+        checked_any = true;
+
+        // Один SONAME могут поставлять несколько пакетов, поэтому храним все пути
+        let mut bundled: BTreeMap<String, Vec<(PathBuf, String)>> = BTreeMap::new();
+        let mut needed_by: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (name, version, prefix) in &packages {
+            for library in elf::scan_shared_libraries(prefix)? {
+                let soname = library.provided_name();
+                for needed in &library.info.needed {
+                    needed_by
+                        .entry(needed.clone())
+                        .or_default()
+                        .insert(format!("{} ({}/{})", soname, name, version));
+                }
+                bundled
+                    .entry(soname)
+                    .or_default()
+                    .push((library.path, name.clone()));
+            }
+        }
+
+        for (soname, shipped) in &bundled {
+            if elf::library_list_matches(allowed, soname) {
+                for (path, _) in shipped {
+                    violations.push(Violation::for_arch(
+                        arch,
+                        format!(
+                            "Поставляется системная библиотека {} ({}): её нужно брать из системы",
+                            soname,
+                            path.display()
+                        ),
+                    ));
+                }
+            }
+
+            for (prefix, spec_packages, re) in &requires_exclude {
+                if !spec_packages.is_empty()
+                    && !shipped
+                        .iter()
+                        .any(|(_, package)| spec_packages.contains(package))
+                {
+                    continue;
+                }
+                if !rpm_dependency_names(soname)
+                    .iter()
+                    .any(|dep| re.is_match(dep))
+                {
+                    violations.push(Violation::for_arch(
+                        arch,
+                        format!(
+                            "{prefix}__requires_exclude не исключает поставляемую библиотеку {}",
+                            soname
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (needed, consumers) in &needed_by {
+            if bundled.contains_key(needed) || elf::library_list_matches(allowed, needed) {
+                continue;
+            }
+            violations.push(Violation::for_arch(
+                arch,
+                format!(
+                    "Линковка с неразрешённой библиотекой {}: {}",
+                    needed,
+                    consumers.iter().cloned().collect::<Vec<_>>().join(", ")
+                ),
+            ));
+        }
+    }
+
+    if !checked_any {
+        return Err(anyhow!(
+            "В {} нет поставляемых пакетов. Команда validate работает в режиме init-clear после add",
+            clear_store::thirdparty_root(project_root).display()
+        ));
+    }
+
+    Ok(violations)
+}
+
+/// Компилирует regex из макроса RPM, где `\\` в теле макроса означает один `\`.
+fn compile_rpm_regex(value: &str) -> Result<Regex> {
+    Regex::new(&value.replace("\\\\", "\\")).map_err(|error| anyhow!(error))
+}
+
+/// Формы автоматических зависимостей RPM для SONAME на 32- и 64-битных архитектурах.
+fn rpm_dependency_names(soname: &str) -> Vec<String> {
+    vec![soname.to_string(), format!("{soname}()(64bit)")]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use anyhow::Result;
+    use tempfile::tempdir;
+
+    use super::{load_allowed_libraries, validate_project};
+    use crate::elf::tests::{EM_AARCH64, build_elf64};
+    use crate::model::SpecConfig;
+
+    fn spec(path: &str, packages: &[&str]) -> SpecConfig {
+        SpecConfig {
+            path: PathBuf::from(path),
+            packages: packages.iter().map(|name| name.to_string()).collect(),
+        }
+    }
+
+    fn write_project(root: &Path, defines: &str) -> Result<()> {
+        fs::create_dir_all(root.join("rpm"))?;
+        fs::write(
+            root.join("rpm/app.spec"),
+            format!("{defines}Name: app\n\n%description\napp\n"),
+        )?;
+
+        let lib_dir = root.join("thirdparty/aurora/armv8/packages/openssl/3.2.0/lib");
+        fs::create_dir_all(&lib_dir)?;
+        fs::write(
+            lib_dir.join("libssl.so.3"),
+            build_elf64(
+                EM_AARCH64,
+                Some("libssl.so.3"),
+                &["libcrypto.so.3", "libc.so.6", "libkrb5.so.3"],
+            ),
+        )?;
+        fs::write(
+            lib_dir.join("libcrypto.so.3"),
+            build_elf64(EM_AARCH64, Some("libcrypto.so.3"), &["libc.so.6"]),
+        )?;
+        Ok(())
+    }
+
+    #[test]
+    fn reports_unallowed_links_and_missing_excludes() -> Result<()> {
+        let dir = tempdir()?;
+        write_project(
+            dir.path(),
+            "%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$\n\
+             %define __requires_exclude ^(libssl[.]so[.]3([(].*)?)$\n",
+        )?;
+
+        let allowed = load_allowed_libraries(dir.path(), None)?;
+        let violations = validate_project(
+            dir.path(),
+            &[spec("rpm/app.spec", &[])],
+            &BTreeMap::new(),
+            &allowed,
+        )?;
+        let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+
+        assert_eq!(violations.len(), 2, "{messages:?}");
+        assert!(messages.iter().any(|m| m.contains("libkrb5.so.3")));
+        assert!(
+            messages
+                .iter()
+                .any(|m| m.contains("не исключает") && m.contains("libcrypto.so.3"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn uses_project_allowed_list_and_checks_provides_exclude() -> Result<()> {
+        let dir = tempdir()?;
+        write_project(
+            dir.path(),
+            "%define __requires_exclude ^(libssl[.]so[.]3([(].*)?|libcrypto[.]so[.]3([(].*)?)$\n",
+        )?;
+        fs::write(
+            dir.path().join("thirdparty/aurora/allowed-libraries.txt"),
+            "libc.so.6\nlibkrb5.so.*\n",
+        )?;
+
+        let allowed = load_allowed_libraries(dir.path(), None)?;
+        let violations = validate_project(
+            dir.path(),
+            &[spec("rpm/app.spec", &[])],
+            &BTreeMap::new(),
+            &allowed,
+        )?;
+
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("__provides_exclude_from"));
+        Ok(())
+    }
+
+    #[test]
+    fn library_must_be_excluded_by_the_spec_that_ships_it() -> Result<()> {
+        let dir = tempdir()?;
+        write_project(
+            dir.path(),
+            "%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$\n\
+             %define __requires_exclude ^(libssl[.]so[.]3([(].*)?)$\n",
+        )?;
+        let tests_spec = dir.path().join("rpm/app-tests.spec");
+        fs::write(
+            &tests_spec,
+            "%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$\n\
+             %define __requires_exclude ^(libcrypto[.]so[.]3([(].*)?)$\n\
+             Name: app-tests\n",
+        )?;
+        fs::write(
+            dir.path().join("thirdparty/aurora/allowed-libraries.txt"),
+            "libc.so.6\nlibkrb5.so.*\n",
+        )?;
+
+        // openssl попадает в app.spec транзитивно через curl; исключение libcrypto
+        // в app-tests.spec, который openssl не упаковывает, его не покрывает
+        let dependencies = BTreeMap::from([("curl".to_string(), vec!["openssl".to_string()])]);
+        let allowed = load_allowed_libraries(dir.path(), None)?;
+        let violations = validate_project(
+            dir.path(),
+            &[
+                spec("rpm/app.spec", &["curl"]),
+                spec("rpm/app-tests.spec", &["gtest"]),
+            ],
+            &dependencies,
+            &allowed,
+        )?;
+        let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "rpm/app.spec: __requires_exclude не исключает поставляемую библиотеку libcrypto.so.3"
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn reports_system_soname_from_every_package_that_ships_it() -> Result<()> {
+        let dir = tempdir()?;
+        write_project(
+            dir.path(),
+            "%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$\n\
+             %define __requires_exclude ^(libssl[.]so[.]3([(].*)?|libcrypto[.]so[.]3([(].*)?|libz[.]so[.]1([(].*)?)$\n",
+        )?;
+        for package in ["zlib/1.3.1", "minizip/1.3.1"] {
+            let lib_dir = dir
+                .path()
+                .join("thirdparty/aurora/armv8/packages")
+                .join(package)
+                .join("lib");
+            fs::create_dir_all(&lib_dir)?;
+            fs::write(
+                lib_dir.join("libz.so.1"),
+                build_elf64(EM_AARCH64, Some("libz.so.1"), &["libc.so.6"]),
+            )?;
+        }
+        fs::write(
+            dir.path().join("thirdparty/aurora/allowed-libraries.txt"),
+            "libc.so.6\nlibkrb5.so.*\nlibz.so.1\n",
+        )?;
+
+        let allowed = load_allowed_libraries(dir.path(), None)?;
+        let violations = validate_project(
+            dir.path(),
+            &[spec("rpm/app.spec", &[])],
+            &BTreeMap::new(),
+            &allowed,
+        )?;
+        let messages: Vec<&str> = violations.iter().map(|v| v.message.as_str()).collect();
+
+        assert_eq!(violations.len(), 2, "{messages:?}");
+        assert!(messages.iter().any(|m| m.contains("packages/zlib/")));
+        assert!(messages.iter().any(|m| m.contains("packages/minizip/")));
+        Ok(())
+    }
+}
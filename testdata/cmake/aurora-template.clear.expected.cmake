@@ -0,0 +1,71 @@
+cmake_minimum_required(VERSION 3.5)
+
+project(ru.auroraos.TestApp CXX)
+
+# >>> aurora-conan-cli:rpath:begin
+set(CMAKE_SKIP_RPATH FALSE)
+set(CMAKE_BUILD_WITH_INSTALL_RPATH TRUE)
+set(CMAKE_INSTALL_RPATH "${CMAKE_INSTALL_PREFIX}/share/${PROJECT_NAME}/lib")
+# <<< aurora-conan-cli:rpath:end
+
+# >>> aurora-conan-cli:clear-arch:begin
+if(CMAKE_SYSTEM_PROCESSOR MATCHES "^(aarch64|arm64)$")
+  set(AURORA_TP_ARCH "armv8")
+elseif(CMAKE_SYSTEM_PROCESSOR MATCHES "^(armv7|armv7hl)$")
+  set(AURORA_TP_ARCH "armv7")
+elseif(CMAKE_SYSTEM_PROCESSOR MATCHES "^(x86_64|amd64)$")
+  set(AURORA_TP_ARCH "x86_64")
+else()
+  message(FATAL_ERROR "Unsupported architecture: ${CMAKE_SYSTEM_PROCESSOR}")
+endif()
+
+set(AURORA_TP_ROOT "${CMAKE_CURRENT_SOURCE_DIR}/thirdparty/aurora")
+set(AURORA_TP_PKGCONFIG_DIR "${AURORA_TP_ROOT}/${AURORA_TP_ARCH}/pkgconfig")
+if(DEFINED ENV{PKG_CONFIG_PATH} AND NOT "$ENV{PKG_CONFIG_PATH}" STREQUAL "")
+  set(ENV{PKG_CONFIG_PATH} "${AURORA_TP_PKGCONFIG_DIR}:$ENV{PKG_CONFIG_PATH}")
+else()
+  set(ENV{PKG_CONFIG_PATH} "${AURORA_TP_PKGCONFIG_DIR}")
+endif()
+set(CMAKE_PREFIX_PATH "${AURORA_TP_ROOT}/${AURORA_TP_ARCH}/cmake" ${CMAKE_PREFIX_PATH})
+# <<< aurora-conan-cli:clear-arch:end
+
+find_package(AuroraCompilerFlags REQUIRED)
+include(AuroraCompilerFlags)
+
+set(CMAKE_AUTOMOC ON)
+set(CMAKE_CXX_STANDARD 17)
+set(CMAKE_CXX_STANDARD_REQUIRED ON)
+
+find_package(Qt5 COMPONENTS Core Quick REQUIRED)
+find_package(PkgConfig REQUIRED)
+
+pkg_search_module(AURORAAPP auroraapp REQUIRED)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+target_compile_definitions(${PROJECT_NAME} PRIVATE
+    $<$<OR:$<CONFIG:Debug>,$<CONFIG:RelWithDebInfo>>:QT_QML_DEBUG>
+)
+target_include_directories(${PROJECT_NAME} PRIVATE
+    $<BUILD_INTERFACE:
+    ${AURORAAPP_INCLUDE_DIRS}
+>)
+target_link_libraries(${PROJECT_NAME}
+    Qt5::Quick
+    ${AURORAAPP_LIBRARIES}
+)
+
+install(TARGETS ${PROJECT_NAME}
+    RUNTIME DESTINATION bin
+)
+
+# >>> aurora-conan-cli:pkgconfig:begin
+pkg_check_modules(OPENSSL REQUIRED IMPORTED_TARGET openssl)
+# <<< aurora-conan-cli:pkgconfig:end
+
+# >>> aurora-conan-cli:targets:begin
+if(TARGET ${PROJECT_NAME})
+  target_link_libraries(${PROJECT_NAME}
+    PkgConfig::OPENSSL
+  )
+endif()
+# <<< aurora-conan-cli:targets:end
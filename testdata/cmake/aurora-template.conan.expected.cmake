@@ -0,0 +1,52 @@
+cmake_minimum_required(VERSION 3.5)
+
+project(ru.auroraos.TestApp CXX)
+
+# >>> aurora-conan-cli:rpath:begin
+set(CMAKE_SKIP_RPATH FALSE)
+set(CMAKE_BUILD_WITH_INSTALL_RPATH TRUE)
+set(CMAKE_INSTALL_RPATH "${CMAKE_INSTALL_PREFIX}/share/${PROJECT_NAME}/lib")
+# <<< aurora-conan-cli:rpath:end
+
+find_package(AuroraCompilerFlags REQUIRED)
+include(AuroraCompilerFlags)
+
+set(CMAKE_AUTOMOC ON)
+set(CMAKE_CXX_STANDARD 17)
+set(CMAKE_CXX_STANDARD_REQUIRED ON)
+
+find_package(Qt5 COMPONENTS Core Quick REQUIRED)
+find_package(PkgConfig REQUIRED)
+
+pkg_search_module(AURORAAPP auroraapp REQUIRED)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+target_compile_definitions(${PROJECT_NAME} PRIVATE
+    $<$<OR:$<CONFIG:Debug>,$<CONFIG:RelWithDebInfo>>:QT_QML_DEBUG>
+)
+target_include_directories(${PROJECT_NAME} PRIVATE
+    $<BUILD_INTERFACE:
+    ${AURORAAPP_INCLUDE_DIRS}
+>)
+target_link_libraries(${PROJECT_NAME}
+    Qt5::Quick
+    ${AURORAAPP_LIBRARIES}
+)
+
+install(TARGETS ${PROJECT_NAME}
+    RUNTIME DESTINATION bin
+)
+
+# >>> aurora-conan-cli:pkgconfig:begin
+pkg_check_modules(FFMPEG REQUIRED IMPORTED_TARGET ffmpeg)
+pkg_check_modules(LIBCURL REQUIRED IMPORTED_TARGET libcurl)
+# <<< aurora-conan-cli:pkgconfig:end
+
+# >>> aurora-conan-cli:targets:begin
+if(TARGET ${PROJECT_NAME})
+  target_link_libraries(${PROJECT_NAME}
+    PkgConfig::FFMPEG
+    PkgConfig::LIBCURL
+  )
+endif()
+# <<< aurora-conan-cli:targets:end
@@ -0,0 +1,32 @@
+cmake_minimum_required(VERSION 3.5)
+
+project(ru.auroraos.TestApp CXX)
+
+find_package(AuroraCompilerFlags REQUIRED)
+include(AuroraCompilerFlags)
+
+set(CMAKE_AUTOMOC ON)
+set(CMAKE_CXX_STANDARD 17)
+set(CMAKE_CXX_STANDARD_REQUIRED ON)
+
+find_package(Qt5 COMPONENTS Core Quick REQUIRED)
+find_package(PkgConfig REQUIRED)
+
+pkg_search_module(AURORAAPP auroraapp REQUIRED)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+target_compile_definitions(${PROJECT_NAME} PRIVATE
+    $<$<OR:$<CONFIG:Debug>,$<CONFIG:RelWithDebInfo>>:QT_QML_DEBUG>
+)
+target_include_directories(${PROJECT_NAME} PRIVATE
+    $<BUILD_INTERFACE:
+    ${AURORAAPP_INCLUDE_DIRS}
+>)
+target_link_libraries(${PROJECT_NAME}
+    Qt5::Quick
+    ${AURORAAPP_LIBRARIES}
+)
+
+install(TARGETS ${PROJECT_NAME}
+    RUNTIME DESTINATION bin
+)
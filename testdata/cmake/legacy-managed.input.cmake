@@ -0,0 +1,29 @@
+cmake_minimum_required(VERSION 3.5)
+project(ru.auroraos.Legacy CXX)
+find_package(PkgConfig REQUIRED)
+
+# >>> aurora-conan-cli:rpath:begin
+set(CMAKE_SKIP_RPATH FALSE)
+set(CMAKE_BUILD_WITH_INSTALL_RPATH TRUE)
+set(CMAKE_INSTALL_RPATH "${CMAKE_INSTALL_PREFIX}/share/${PROJECT_NAME}/lib")
+# <<< aurora-conan-cli:rpath:end
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+
+# >>> aurora-conan-cli:pkgconfig:begin
+pkg_check_modules(FFMPEG REQUIRED IMPORTED_TARGET ffmpeg)
+# <<< aurora-conan-cli:pkgconfig:end
+
+# >>> aurora-conan-cli:targets:begin
+if(TARGET ${PROJECT_NAME})
+  target_include_directories(${PROJECT_NAME} PRIVATE
+    $<BUILD_INTERFACE:
+      PkgConfig::FFMPEG
+    >
+  )
+
+  target_link_libraries(${PROJECT_NAME} PRIVATE
+      PkgConfig::FFMPEG
+  )
+endif()
+# <<< aurora-conan-cli:targets:end
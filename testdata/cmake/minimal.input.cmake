@@ -0,0 +1,4 @@
+cmake_minimum_required(VERSION 3.5)
+project(ru.auroraos.Minimal CXX)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
@@ -0,0 +1,52 @@
+cmake_minimum_required(VERSION 3.5)
+project(ru.auroraos.MultiTarget CXX)
+
+# >>> aurora-conan-cli:rpath:begin
+set(CMAKE_SKIP_RPATH FALSE)
+set(CMAKE_BUILD_WITH_INSTALL_RPATH TRUE)
+set(CMAKE_INSTALL_RPATH "${CMAKE_INSTALL_PREFIX}/share/${PROJECT_NAME}/lib")
+# <<< aurora-conan-cli:rpath:end
+
+find_package(PkgConfig REQUIRED)
+
+add_library(core STATIC src/core.cpp)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+target_link_libraries(${PROJECT_NAME} PRIVATE core)
+
+add_executable(tests tests/main.cpp)
+target_link_libraries(tests PRIVATE core)
+
+# >>> aurora-conan-cli:pkgconfig:begin
+pkg_check_modules(FFMPEG REQUIRED IMPORTED_TARGET ffmpeg)
+pkg_check_modules(GTEST REQUIRED IMPORTED_TARGET gtest)
+pkg_check_modules(LIBCRYPTO REQUIRED IMPORTED_TARGET libcrypto)
+pkg_check_modules(LIBSSL REQUIRED IMPORTED_TARGET libssl)
+# <<< aurora-conan-cli:pkgconfig:end
+
+# >>> aurora-conan-cli:targets:begin
+if(TARGET ${PROJECT_NAME})
+  target_link_libraries(${PROJECT_NAME} PRIVATE
+    PkgConfig::FFMPEG
+  )
+endif()
+# <<< aurora-conan-cli:targets:end
+
+# >>> aurora-conan-cli:targets-core:begin
+if(TARGET core)
+  target_link_libraries(core PUBLIC
+    PkgConfig::LIBCRYPTO
+    PkgConfig::LIBSSL
+  )
+endif()
+# <<< aurora-conan-cli:targets-core:end
+
+# >>> aurora-conan-cli:targets-tests:begin
+if(TARGET tests)
+  target_link_libraries(tests PRIVATE
+    PkgConfig::GTEST
+    PkgConfig::LIBCRYPTO
+    PkgConfig::LIBSSL
+  )
+endif()
+# <<< aurora-conan-cli:targets-tests:end
@@ -0,0 +1,10 @@
+cmake_minimum_required(VERSION 3.5)
+project(ru.auroraos.MultiTarget CXX)
+
+add_library(core STATIC src/core.cpp)
+
+add_executable(${PROJECT_NAME} src/main.cpp)
+target_link_libraries(${PROJECT_NAME} PRIVATE core)
+
+add_executable(tests tests/main.cpp)
+target_link_libraries(tests PRIVATE core)
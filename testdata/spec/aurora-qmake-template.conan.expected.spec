@@ -0,0 +1,53 @@
+%define _cmake_skip_rpath %{nil}
+%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$
+%define __requires_exclude ^(libavcodec.*)$
+Name:       ru.auroraos.Application
+Summary:    My Aurora OS Application
+Version:    0.1
+Release:    1
+License:    BSD-3-Clause
+URL:        https://auroraos.ru
+Source0:    %{name}-%{version}.tar.bz2
+
+Requires:   sailfishsilica-qt5 >= 0.10.9
+BuildRequires:  pkgconfig(auroraapp)
+BuildRequires:  pkgconfig(Qt5Core)
+BuildRequires:  pkgconfig(Qt5Qml)
+BuildRequires:  pkgconfig(Qt5Quick)
+BuildRequires:  conan
+
+%description
+Short description of my Aurora OS Application
+
+%prep
+%autosetup
+
+%build
+# >>> aurora-conan-cli:build-snippet:begin
+CONAN_LIB_DIR="%{_builddir}/conan-libs/"
+%{set_build_flags}
+conan-install-if-modified --source-folder="%{_sourcedir}/.." --output-folder="$CONAN_LIB_DIR" -vwarning
+PKG_CONFIG_PATH="$CONAN_LIB_DIR:$PKG_CONFIG_PATH"
+export PKG_CONFIG_PATH
+# <<< aurora-conan-cli:build-snippet:end
+%qmake5
+%make_build
+
+%install
+%make_install
+
+# >>> aurora-conan-cli:install-snippet:begin
+EXECUTABLE="%{buildroot}/%{_bindir}/%{name}"
+CONAN_LIB_DIR="%{_builddir}/conan-libs/"
+SHARED_LIBRARIES="%{buildroot}/%{_datadir}/%{name}/lib"
+mkdir -p "$SHARED_LIBRARIES"
+conan-deploy-libraries "$EXECUTABLE" "$CONAN_LIB_DIR" "$SHARED_LIBRARIES"
+# <<< aurora-conan-cli:install-snippet:end
+
+%files
+%defattr(-,root,root,-)
+%{_bindir}/%{name}
+%defattr(644,root,root,-)
+%{_datadir}/%{name}
+%{_datadir}/applications/%{name}.desktop
+%{_datadir}/icons/hicolor/*/apps/%{name}.png
@@ -0,0 +1,128 @@
+%global build_tests 1
+%global __requires_exclude ^$
+
+%define _cmake_skip_rpath %{nil}
+%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$
+Name:       ru.auroraos.Complex
+Summary:    Application with subpackages
+Version:    1.2.0
+Release:    1
+License:    BSD-3-Clause
+URL:        https://auroraos.ru
+Source0:    %{name}-%{version}.tar.bz2
+
+Requires:   sailfishsilica-qt5 >= 0.10.9
+BuildRequires:  cmake >= 3.16, ninja
+BuildRequires:  pkgconfig(auroraapp)
+%if 0%{?build_tests}
+BuildRequires:  pkgconfig(gtest)
+%endif
+%ifarch aarch64
+%define _arch_suffix arm64
+%else
+%define _arch_suffix other
+%endif
+
+# >>> aurora-conan-cli:requires:begin
+# glibc: m, pthread
+Requires:       libstdc++
+# <<< aurora-conan-cli:requires:end
+
+%description
+Application with development and test subpackages.
+
+%package devel
+Summary:    Development files for %{name}
+Requires:   %{name} = %{version}-%{release}
+BuildRequires:  doxygen
+
+%description devel
+Headers and CMake files.
+
+%package -n ru.auroraos.Complex-tests
+Summary:    Tests for %{name}
+
+%description -n ru.auroraos.Complex-tests
+Unit tests.
+
+%prep
+%autosetup -n %{name}-%{version}
+
+%build -v
+# >>> aurora-conan-cli:build-snippet:begin
+THIRDPARTY_ROOT="%{_sourcedir}/../thirdparty/aurora"
+case "%{_arch}" in
+  aarch64) AURORA_TP_ARCH="armv8" ;;
+  armv7hl) AURORA_TP_ARCH="armv7" ;;
+  x86_64) AURORA_TP_ARCH="x86_64" ;;
+  *) echo "Unsupported arch: %{_arch}" >&2; exit 1 ;;
+esac
+THIRDPARTY_ARCH_DIR="$THIRDPARTY_ROOT/$AURORA_TP_ARCH"
+PKG_CONFIG_PATH="$THIRDPARTY_ARCH_DIR/pkgconfig:$PKG_CONFIG_PATH"
+export PKG_CONFIG_PATH
+# <<< aurora-conan-cli:build-snippet:end
+%cmake -GNinja \
+%if 0%{?build_tests}
+    -DBUILD_TESTS=ON \
+%endif
+    -DCMAKE_BUILD_TYPE=Release
+%ninja_build
+
+%install
+%ninja_install
+%if 0%{?build_tests}
+install -m 755 build/tests %{buildroot}%{_bindir}/%{name}-tests
+%endif
+
+# >>> aurora-conan-cli:install-snippet:begin
+THIRDPARTY_ROOT="%{_sourcedir}/../thirdparty/aurora"
+case "%{_arch}" in
+  aarch64) AURORA_TP_ARCH="armv8" ;;
+  armv7hl) AURORA_TP_ARCH="armv7" ;;
+  x86_64) AURORA_TP_ARCH="x86_64" ;;
+  *) echo "Unsupported arch: %{_arch}" >&2; exit 1 ;;
+esac
+THIRDPARTY_ARCH_DIR="$THIRDPARTY_ROOT/$AURORA_TP_ARCH"
+SHARED_LIBRARIES="%{buildroot}/%{_datadir}/%{name}/lib"
+mkdir -p "$SHARED_LIBRARIES"
+INSTALL_MANIFEST="$THIRDPARTY_ARCH_DIR/install-manifest.txt"
+[ -f "$INSTALL_MANIFEST" ] || INSTALL_MANIFEST=/dev/null
+TAB="$(printf '\t')"
+while IFS="$TAB" read -r kind package source name; do
+  case "$kind" in
+    file) cp -p "$THIRDPARTY_ARCH_DIR/packages/$source" "$SHARED_LIBRARIES/$name" ;;
+    link) ln -sfn "$source" "$SHARED_LIBRARIES/$name" ;;
+  esac
+done < "$INSTALL_MANIFEST"
+LICENSES="%{buildroot}/%{_datadir}/%{name}/licenses"
+mkdir -p "$LICENSES"
+NOTICES="$THIRDPARTY_ROOT/THIRD-PARTY-NOTICES.txt"
+if [ -f "$NOTICES" ]; then install -m 0644 "$NOTICES" "$LICENSES/"; fi
+for license_dir in "$THIRDPARTY_ARCH_DIR/packages"/*/*/licenses; do
+  [ -d "$license_dir" ] || continue
+  package_name="$(basename "$(dirname "$(dirname "$license_dir")")")"
+  mkdir -p "$LICENSES/$package_name"
+  cp -a "$license_dir/." "$LICENSES/$package_name/"
+done
+# <<< aurora-conan-cli:install-snippet:end
+
+%files
+%defattr(-,root,root,-)
+%{_bindir}/%{name}
+%{_datadir}/%{name}/qml
+%{_datadir}/applications/%{name}.desktop
+
+# >>> aurora-conan-cli:files:begin
+%{_datadir}/%{name}/lib
+%{_datadir}/%{name}/licenses
+# <<< aurora-conan-cli:files:end
+
+%files devel
+%{_includedir}/*
+
+%files -n ru.auroraos.Complex-tests
+%{_bindir}/%{name}-tests
+
+%changelog
+* Mon Jan 15 2024 Developer <dev@example.com> - 1.2.0-1
+- Fix %%build flags
@@ -0,0 +1,94 @@
+%global build_tests 1
+%global __requires_exclude ^(libssl[.]so[.]3([(].*)?)$
+
+%define _cmake_skip_rpath %{nil}
+%define __provides_exclude_from ^%{_datadir}/%{name}/lib/.*$
+Name:       ru.auroraos.Complex
+Summary:    Application with subpackages
+Version:    1.2.0
+Release:    1
+License:    BSD-3-Clause
+URL:        https://auroraos.ru
+Source0:    %{name}-%{version}.tar.bz2
+
+Requires:   sailfishsilica-qt5 >= 0.10.9
+BuildRequires:  cmake >= 3.16, ninja
+BuildRequires:  pkgconfig(auroraapp)
+BuildRequires:  conan
+%if 0%{?build_tests}
+BuildRequires:  pkgconfig(gtest)
+%endif
+%ifarch aarch64
+%define _arch_suffix arm64
+%else
+%define _arch_suffix other
+%endif
+
+%description
+Application with development and test subpackages.
+
+%package devel
+Summary:    Development files for %{name}
+Requires:   %{name} = %{version}-%{release}
+BuildRequires:  doxygen
+
+%description devel
+Headers and CMake files.
+
+%package -n ru.auroraos.Complex-tests
+Summary:    Tests for %{name}
+
+%description -n ru.auroraos.Complex-tests
+Unit tests.
+
+%prep
+%autosetup -n %{name}-%{version}
+
+%build -v
+# >>> aurora-conan-cli:build-snippet:begin
+CONAN_LIB_DIR="%{_builddir}/conan-libs/"
+%{set_build_flags}
+conan-install-if-modified --source-folder="%{_sourcedir}/.." --output-folder="$CONAN_LIB_DIR" -vwarning
+PKG_CONFIG_PATH="$CONAN_LIB_DIR:$PKG_CONFIG_PATH"
+export PKG_CONFIG_PATH
+# <<< aurora-conan-cli:build-snippet:end
+%cmake -GNinja \
+%if 0%{?build_tests}
+    -DBUILD_TESTS=ON \
+%endif
+    -DCMAKE_BUILD_TYPE=Release
+%ninja_build
+
+%install
+%ninja_install
+%if 0%{?build_tests}
+install -m 755 build/tests %{buildroot}%{_bindir}/%{name}-tests
+%endif
+
+# >>> aurora-conan-cli:install-snippet:begin
+EXECUTABLE="%{buildroot}/%{_bindir}/%{name}"
+CONAN_LIB_DIR="%{_builddir}/conan-libs/"
+SHARED_LIBRARIES="%{buildroot}/%{_datadir}/%{name}/lib"
+mkdir -p "$SHARED_LIBRARIES"
+conan-deploy-libraries "$EXECUTABLE" "$CONAN_LIB_DIR" "$SHARED_LIBRARIES"
+# <<< aurora-conan-cli:install-snippet:end
+
+%files
+%defattr(-,root,root,-)
+%{_bindir}/%{name}
+%{_datadir}/%{name}/qml
+%{_datadir}/applications/%{name}.desktop
+
+# >>> aurora-conan-cli:files:begin
+%{_datadir}/%{name}/lib
+# <<< aurora-conan-cli:files:end
+
+%files devel
+%{_includedir}/*
+
+%files -n ru.auroraos.Complex-tests
+%{_bindir}/%{name}-tests
+
+%changelog
+* Mon Jan 15 2024 Developer <dev@example.com> - 1.2.0-1
+- Fix %%build flags
@@ -0,0 +1,71 @@
+%global build_tests 1
+%global __requires_exclude ^libfoo[.]so.*$
+
+Name:       ru.auroraos.Complex
+Summary:    Application with subpackages
+Version:    1.2.0
+Release:    1
+License:    BSD-3-Clause
+URL:        https://auroraos.ru
+Source0:    %{name}-%{version}.tar.bz2
+
+Requires:   sailfishsilica-qt5 >= 0.10.9
+BuildRequires:  cmake >= 3.16, ninja
+BuildRequires:  pkgconfig(auroraapp)
+%if 0%{?build_tests}
+BuildRequires:  pkgconfig(gtest)
+%endif
+%ifarch aarch64
+%define _arch_suffix arm64
+%else
+%define _arch_suffix other
+%endif
+
+%description
+Application with development and test subpackages.
+
+%package devel
+Summary:    Development files for %{name}
+Requires:   %{name} = %{version}-%{release}
+BuildRequires:  doxygen
+
+%description devel
+Headers and CMake files.
+
+%package -n ru.auroraos.Complex-tests
+Summary:    Tests for %{name}
+
+%description -n ru.auroraos.Complex-tests
+Unit tests.
+
+%prep
+%autosetup -n %{name}-%{version}
+
+%build -v
+%cmake -GNinja \
+%if 0%{?build_tests}
+    -DBUILD_TESTS=ON \
+%endif
+    -DCMAKE_BUILD_TYPE=Release
+%ninja_build
+
+%install
+%ninja_install
+%if 0%{?build_tests}
+install -m 755 build/tests %{buildroot}%{_bindir}/%{name}-tests
+%endif
+%files
+%defattr(-,root,root,-)
+%{_bindir}/%{name}
+%{_datadir}/%{name}/qml
+%{_datadir}/applications/%{name}.desktop
+
+%files devel
+%{_includedir}/*
+
+%files -n ru.auroraos.Complex-tests
+%{_bindir}/%{name}-tests
+
+%changelog
+* Mon Jan 15 2024 Developer <dev@example.com> - 1.2.0-1
+- Fix %%build flags